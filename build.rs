@@ -0,0 +1,5 @@
+fn main() {
+    // The gRPC service is generated only with the `grpc` feature, which requires `protoc`.
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/halo2_regex.proto").unwrap();
+}
@@ -0,0 +1,10 @@
+#![no_main]
+use halo2_regex::fuzzing::{check_circuit, shrink, FuzzCase};
+use libfuzzer_sys::fuzz_target;
+
+// Compare the circuit checked by MockProver with the `regex` crate.
+fuzz_target!(|case: FuzzCase| {
+    if let Some((case, discrepancy)) = shrink(case, check_circuit) {
+        panic!("{}\nshrunk case: {:?}", discrepancy, case);
+    }
+});
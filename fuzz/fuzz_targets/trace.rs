@@ -0,0 +1,10 @@
+#![no_main]
+use halo2_regex::fuzzing::{check_trace, shrink, FuzzCase};
+use libfuzzer_sys::fuzz_target;
+
+// Compare the DFA of the compiled regex definitions with the `regex` crate.
+fuzz_target!(|case: FuzzCase| {
+    if let Some((case, discrepancy)) = shrink(case, check_trace) {
+        panic!("{}\nshrunk case: {:?}", discrepancy, case);
+    }
+});
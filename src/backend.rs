@@ -0,0 +1,103 @@
+#[cfg(feature = "backend-axiom")]
+use halo2_base::halo2_proofs::plonk::Assigned;
+use halo2_base::halo2_proofs::{
+    circuit::{AssignedCell, Cell, Region, Value},
+    plonk::{Advice, Column, Error},
+};
+use halo2_base::utils::PrimeField;
+
+/// The halo2 fork selected by the backend feature, which a larger circuit should import instead of depending on it directly.
+pub use halo2_base::halo2_proofs;
+
+#[cfg(not(any(feature = "backend-pse", feature = "backend-axiom")))]
+compile_error!(
+    "the `circuit` feature requires either the `backend-pse` or the `backend-axiom` feature"
+);
+#[cfg(all(feature = "backend-pse", feature = "backend-axiom"))]
+compile_error!("the `backend-pse` and `backend-axiom` features are mutually exclusive");
+
+/// The name of the backend the crate is built with.
+#[cfg(feature = "backend-pse")]
+pub const BACKEND: &str = "pse";
+/// The name of the backend the crate is built with.
+#[cfg(feature = "backend-axiom")]
+pub const BACKEND: &str = "axiom";
+
+/// An advice cell assigned by [`assign_advice`] in a region of lifetime `'v`.
+#[cfg(feature = "backend-pse")]
+pub type AssignedAdvice<'v, F> = AssignedCell<F, F>;
+/// An advice cell assigned by [`assign_advice`] in a region of lifetime `'v`.
+#[cfg(feature = "backend-axiom")]
+pub type AssignedAdvice<'v, F> = AssignedCell<&'v Assigned<F>, F>;
+
+/// Assign `value` to the advice cell of `column` at `offset` in `region`.
+///
+/// # Arguments
+/// * `region` - a region in which the cell is assigned.
+/// * `annotation` - the annotation of the cell, which is ignored by the Axiom backend.
+/// * `column` - an advice column of the cell.
+/// * `offset` - a row offset of the cell in `region`.
+/// * `value` - the value of the cell.
+///
+/// # Return values
+/// Return the assigned cell as [`AssignedAdvice`].
+#[cfg(feature = "backend-pse")]
+pub fn assign_advice<'v, F: PrimeField, A: Fn() -> AR, AR: Into<String>>(
+    region: &mut Region<'v, F>,
+    annotation: A,
+    column: Column<Advice>,
+    offset: usize,
+    value: Value<F>,
+) -> Result<AssignedAdvice<'v, F>, Error> {
+    region.assign_advice(annotation, column, offset, || value)
+}
+
+/// Assign `value` to the advice cell of `column` at `offset` in `region`.
+///
+/// # Arguments
+/// * `region` - a region in which the cell is assigned.
+/// * `annotation` - the annotation of the cell, which is ignored by the Axiom backend.
+/// * `column` - an advice column of the cell.
+/// * `offset` - a row offset of the cell in `region`.
+/// * `value` - the value of the cell.
+///
+/// # Return values
+/// Return the assigned cell as [`AssignedAdvice`].
+#[cfg(feature = "backend-axiom")]
+pub fn assign_advice<'v, F: PrimeField, A: Fn() -> AR, AR: Into<String>>(
+    region: &mut Region<'v, F>,
+    _annotation: A,
+    column: Column<Advice>,
+    offset: usize,
+    value: Value<F>,
+) -> Result<AssignedAdvice<'v, F>, Error> {
+    Ok(region.assign_advice(column, offset, value))
+}
+
+/// Return the value of `cell` assigned by [`assign_advice`].
+pub fn advice_value<F: PrimeField>(cell: &AssignedAdvice<'_, F>) -> Value<F> {
+    #[cfg(feature = "backend-pse")]
+    return cell.value().map(|v| *v);
+    #[cfg(feature = "backend-axiom")]
+    return cell.value().map(|v| v.evaluate());
+}
+
+/// Constrain the cells `a` and `b` of `region` to be equal.
+///
+/// # Arguments
+/// * `region` - a region in which the equality is constrained.
+/// * `a` - a cell in a column with the equality enabled.
+/// * `b` - another cell in a column with the equality enabled.
+pub fn constrain_equal<F: PrimeField>(
+    region: &mut Region<'_, F>,
+    a: Cell,
+    b: Cell,
+) -> Result<(), Error> {
+    #[cfg(feature = "backend-pse")]
+    return region.constrain_equal(a, b);
+    #[cfg(feature = "backend-axiom")]
+    {
+        region.constrain_equal(&a, &b);
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use crate::gadgets::sha256::Sha256Config;
+use crate::table::RegexTableCache;
+use crate::{new_region_context, AssignedRegexResult, RegexDefs, RegexVerifyConfig, RegexWitness};
+use halo2_base::halo2_proofs::{
+    circuit::{Cell, Layouter},
+    plonk::{Column, ConstraintSystem, Error, Instance},
+};
+use halo2_base::SKIP_FIRST_PASS;
+use halo2_base::{gates::flex_gate::FlexGateConfig, utils::PrimeField, AssignedValue, Context};
+
+/// Configuration of [`RegexVerifyConfig`] and [`Sha256Config`] chained over the same assigned characters, whose digest is exposed in the instance column.
+///
+/// It proves that the regexes match the exact message whose SHA-256 digest is `H`, where the instances are [`digest_instances`] of `H`.
+#[derive(Debug, Clone)]
+pub struct Sha256RegexConfig<F: PrimeField> {
+    /// The regex verification of the message.
+    pub regex: RegexVerifyConfig<F>,
+    /// The SHA-256 hash of the characters assigned by `regex`.
+    pub sha256: Sha256Config<F>,
+    /// The instance column for the bytes of the digest.
+    pub instance: Column<Instance>,
+}
+
+impl<F: PrimeField> Sha256RegexConfig<F> {
+    /// Configure a new [`Sha256RegexConfig`] whose chips share `gate`, e.g., that of a larger circuit.
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `max_chars_size` - the maximum length of the message.
+    /// * `gate` - a configuration for [`FlexGateConfig`].
+    /// * `regex_defs` - a vector of regex definitions applied to the message.
+    /// * `cache` - the tables configured so far.
+    ///
+    /// # Return values
+    /// Return a new [`Sha256RegexConfig`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        max_chars_size: usize,
+        gate: &FlexGateConfig<F>,
+        regex_defs: Vec<RegexDefs>,
+        cache: &mut RegexTableCache<F>,
+    ) -> Self {
+        let regex =
+            RegexVerifyConfig::configure_with_gate(meta, max_chars_size, gate, regex_defs, cache);
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        Self {
+            regex,
+            sha256: Sha256Config::new(gate.clone()),
+            instance,
+        }
+    }
+
+    /// Load the lookup tables of the regex definitions.
+    ///
+    /// # Arguments
+    /// * `layouter` - a [`Layouter`] in which the lookup tables are loaded.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.regex.load(layouter)
+    }
+
+    /// Match the message of `witness` and hash the same assigned characters.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `witness` - the output of [`RegexVerifyConfig::derive_witness`] for the message.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedRegexResult`] and the assigned bytes of the digest.
+    pub fn match_and_digest<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        witness: &RegexWitness,
+    ) -> Result<(AssignedRegexResult<'a, F>, Vec<AssignedValue<'a, F>>), Error> {
+        let result = self.regex.match_substrs_with_witness(ctx, witness)?;
+        let digest = self.sha256.digest_regex_input(ctx, &result)?;
+        Ok((result, digest))
+    }
+
+    /// Constrain the bytes of the digest to be equal to the instances from the first row.
+    ///
+    /// # Arguments
+    /// * `layouter` - a [`Layouter`] of the circuit.
+    /// * `digest` - the cells of the digest returned by [`Sha256RegexConfig::match_and_digest`].
+    pub fn expose_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        digest: &[Cell],
+    ) -> Result<(), Error> {
+        for (row, cell) in digest.iter().enumerate() {
+            layouter.constrain_instance(*cell, self.instance, row)?;
+        }
+        Ok(())
+    }
+
+    /// Match and hash `message` in its own region and expose the digest, which is the whole synthesis of a circuit made only of this configuration.
+    ///
+    /// # Arguments
+    /// * `layouter` - a [`Layouter`] of the circuit.
+    /// * `message` - the bytes of the message.
+    pub fn synthesize(&self, layouter: &mut impl Layouter<F>, message: &[u8]) -> Result<(), Error> {
+        self.load(layouter)?;
+        let witness = self.regex.derive_witness(message)?;
+        let gate = self.regex.gate();
+        let mut first_pass = SKIP_FIRST_PASS;
+        let mut digest_cells = vec![];
+        layouter.assign_region(
+            || "sha256 regex",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = new_region_context(region, &gate);
+                let ctx = &mut aux;
+                let (_, digest) = self.match_and_digest(ctx, &witness)?;
+                digest_cells = digest.iter().map(|byte| byte.cell()).collect();
+                Ok(())
+            },
+        )?;
+        self.expose_digest(layouter, &digest_cells)
+    }
+}
+
+/// Return the instances of [`Sha256RegexConfig`] for `digest`, i.e., one field element per byte.
+///
+/// # Arguments
+/// * `digest` - the bytes of the SHA-256 digest of the message, e.g., computed by [`sha256`](crate::gadgets::sha256::sha256).
+pub fn digest_instances<F: PrimeField>(digest: &[u8]) -> Vec<F> {
+    digest.iter().map(|byte| F::from(*byte as u64)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::sha256::sha256;
+    use crate::{AllstrRegexDef, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+    use std::marker::PhantomData;
+
+    const K: usize = 17;
+    const MAX_CHARS_SIZE: usize = 32;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestCircuit<F: PrimeField> {
+        message: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCircuit<F> {
+        type Config = Sha256RegexConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                message: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[5],
+                1,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+                substrs: vec![SubstrRegexDef::read_from_text(
+                    "./test_regexes/substr4_test_lookup.txt",
+                )],
+            }];
+            Sha256RegexConfig::configure(
+                meta,
+                MAX_CHARS_SIZE,
+                &gate,
+                regex_defs,
+                &mut RegexTableCache::new(),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.synthesize(&mut layouter, &self.message)
+        }
+    }
+
+    #[test]
+    fn test_sha256_regex() {
+        let message = b"amount=1000;".to_vec();
+        let instances = digest_instances::<Fr>(&sha256(&message));
+        let circuit = TestCircuit::<Fr> {
+            message,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![instances]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_sha256_regex_other_digest() {
+        // The digest of another message matched by the same regex is rejected.
+        let instances = digest_instances::<Fr>(&sha256(b"amount=2000;"));
+        let circuit = TestCircuit::<Fr> {
+            message: b"amount=1000;".to_vec(),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![instances]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,2369 @@
+use crate::backend::{advice_value, assign_advice, constrain_equal, AssignedAdvice};
+use crate::table::{
+    check_universal_bounds, regex_defs_digest, substr_limit_rows, RegexTableCache,
+    RegexTableConfig, RegexTableKey, UniversalBoundsError, UniversalRegexTableConfig,
+    UniversalTableBounds,
+};
+use crate::{AllstrRegexDef, RegexDefs, SubstrRegexDef};
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Assigned, Circuit, Column, ConstraintSystem, Constraints, Error, Expression,
+        Instance, Selector, TableColumn, VirtualCells,
+    },
+    poly::Rotation,
+};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::{bigint_to_fe, biguint_to_fe, fe_to_biguint, modulus, PrimeField},
+    AssignedValue, Context, ContextParams, QuantumCell,
+};
+use rayon::prelude::*;
+use std::{
+    collections::HashSet,
+    fmt::format,
+    fs::File,
+    io::{BufRead, BufReader},
+    marker::PhantomData,
+};
+
+/// Construct a [`Context`] of `region` whose cells are assigned by `gate`.
+///
+/// It is the only place where the circuits of this crate construct [`ContextParams`].
+///
+/// # Arguments
+/// * `region` - a region passed to the closure of [`Layouter::assign_region`].
+/// * `gate` - a configuration for [`FlexGateConfig`].
+///
+/// # Return values
+/// Return a new [`Context`] with one context id.
+pub fn new_region_context<'a, F: PrimeField>(
+    region: Region<'a, F>,
+    gate: &FlexGateConfig<F>,
+) -> Context<'a, F> {
+    Context::new(
+        region,
+        ContextParams {
+            max_rows: gate.max_rows,
+            num_context_ids: 1,
+            fixed_columns: gate.constants.clone(),
+        },
+    )
+}
+
+/// Output type definition of [`RegexVerifyConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct AssignedRegexResult<'a, F: PrimeField> {
+    /// The assigned bits of `enable_flag` that indicates whether each character of the input string is a padded byte or not. (`enable_flag=true` iff the character is not padded one.)
+    /// The length is equal to `max_chars_size`.
+    pub all_enable_flags: Vec<AssignedValue<'a, F>>,
+    /// The assigned character (byte) of the input string, which is `padding_byte` beyond the input string.
+    /// The length is equal to `max_chars_size`.
+    pub all_characters: Vec<AssignedValue<'a, F>>,
+    /// The assigned substring id of characters in the input string, which is constrained to be zero beyond the input string.
+    /// The length is equal to `max_chars_size`.    
+    pub all_substr_ids: Vec<AssignedValue<'a, F>>,
+    /// The masked version of `all_characters`.
+    /// Each character in `all_characters` is turned to zero in `masked_characters` iff its `substr_id` is zero, i.e., it belongs to no substring, which holds for every character beyond the input string.
+    /// The length is equal to `max_chars_size`.    
+    pub masked_characters: Vec<AssignedValue<'a, F>>,
+    /// The assigned values of the lookup tables in the universal mode, i.e., [`universal_table_values`](crate::table::universal_table_values) of each regex definition.
+    /// It is empty if the tables are fixed.
+    pub table_values: Vec<AssignedValue<'a, F>>,
+    /// The number of the advice cells of [`FlexGateConfig`] assigned for each regex definition.
+    pub def_advice_cells: Vec<usize>,
+    /// The byte of the padded characters, i.e., [`RegexVerifyConfig::padding_byte`].
+    pub padding_byte: u8,
+}
+
+/// A character of an input string without the transition from the current state in the DFA of a regex definition, which is found by [`RegexVerifyConfig::derive_witness`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("The transition from {state} by {char} at the position {position} is invalid")]
+pub struct InvalidTransitionError {
+    pub position: usize,
+    pub char: u8,
+    pub state: u64,
+}
+
+impl From<InvalidTransitionError> for Error {
+    fn from(error: InvalidTransitionError) -> Self {
+        tracing::error!("{}", error);
+        Error::Synthesis
+    }
+}
+
+/// Error definitions of [`RegexVerifyConfig::derive_universal_witness`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum UniversalWitnessError {
+    #[error(
+        "{actual} regex definitions are given to the universal mode configured for {expected}"
+    )]
+    NumRegexDefs { expected: usize, actual: usize },
+    #[error("The regex definition {def_idx} does not fit in the bounds: {error}")]
+    Bounds {
+        def_idx: usize,
+        error: UniversalBoundsError,
+    },
+    #[error(transparent)]
+    Transition(#[from] InvalidTransitionError),
+}
+
+impl From<UniversalWitnessError> for Error {
+    fn from(error: UniversalWitnessError) -> Self {
+        tracing::error!("{}", error);
+        Error::Synthesis
+    }
+}
+
+/// Witnesses of [`RegexVerifyConfig::match_substrs`] derived off-circuit.
+#[derive(Debug, Clone, Default)]
+pub struct RegexWitness {
+    /// The characters of the input string.
+    pub characters: Vec<u8>,
+    states: Vec<Vec<u64>>,
+    substr_ids: Vec<Vec<usize>>,
+    is_starts: Vec<Vec<bool>>,
+    is_ends: Vec<Vec<bool>>,
+    /// The regex definitions given by [`RegexVerifyConfig::derive_universal_witness`], which are empty for the fixed lookup tables.
+    regex_defs: Vec<RegexDefs>,
+}
+
+/// Configuration to 1) verify that the input string satisfies the specified regexes and 2) extracts the specified substrings from the input string.
+#[derive(Debug, Clone)]
+pub struct RegexVerifyConfig<F: PrimeField> {
+    characters: Column<Advice>,
+    char_enable: Column<Advice>,
+    states_array: Vec<Column<Advice>>,
+    substr_ids_array: Vec<Column<Advice>>,
+    is_start_array: Vec<Column<Advice>>,
+    is_end_array: Vec<Column<Advice>>,
+    table_array: Vec<RegexTableConfig<F>>,
+    table_keys: Vec<RegexTableKey>,
+    universal_table_array: Vec<UniversalRegexTableConfig<F>>,
+    q_first: Selector,
+    not_q_first: Selector,
+    max_chars_size: usize,
+    padding_byte: u8,
+    gate: FlexGateConfig<F>,
+    /// A vector of regex definitions applied to the input string, which is empty in the universal mode, where they are given to [`RegexVerifyConfig::derive_universal_witness`] instead.
+    pub regex_defs: Vec<RegexDefs>,
+}
+
+impl<F: PrimeField> RegexVerifyConfig<F> {
+    /// Configure a new [`RegexVerifyConfig`].
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `max_chars_size` - the maximum length of the input string.
+    /// * `gate` - a configuration for [`FlexGateConfig`].
+    /// * `regex_defs` - a vector of regex definitions applied to the input string.
+    ///
+    /// # Return values
+    /// Return a new [`RegexVerifyConfig`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        max_chars_size: usize,
+        gate: FlexGateConfig<F>,
+        regex_defs: Vec<RegexDefs>,
+    ) -> Self {
+        Self::configure_with_cache(
+            meta,
+            max_chars_size,
+            gate,
+            regex_defs,
+            &mut RegexTableCache::new(),
+        )
+    }
+
+    /// Configure a new [`RegexVerifyConfig`] whose lookup tables are shared with the other configurations in `cache` if their contents are identical.
+    ///
+    /// The regex definitions of one configuration also share the identical tables, which reduces the fixed rows and the table columns.
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `max_chars_size` - the maximum length of the input string.
+    /// * `gate` - a configuration for [`FlexGateConfig`].
+    /// * `regex_defs` - a vector of regex definitions applied to the input string.
+    /// * `cache` - the tables configured so far.
+    ///
+    /// # Return values
+    /// Return a new [`RegexVerifyConfig`].
+    pub fn configure_with_cache(
+        meta: &mut ConstraintSystem<F>,
+        max_chars_size: usize,
+        gate: FlexGateConfig<F>,
+        regex_defs: Vec<RegexDefs>,
+        cache: &mut RegexTableCache<F>,
+    ) -> Self {
+        Self::configure_with_padding(meta, max_chars_size, gate, regex_defs, None, 0, cache)
+    }
+
+    /// Configure a new [`RegexVerifyConfig`] as a sub-chip of a larger circuit, e.g., an email verification circuit, which owns `gate` and shares it with its other chips.
+    ///
+    /// The sub-chip allocates its own advice columns and lookup tables, while all its arithmetic is laid out in the columns of `gate`.
+    /// The caller must:
+    /// 1. load the lookup tables by [`RegexVerifyConfig::load_shared`] (or [`RegexVerifyConfig::load`] if `cache` is not shared) in its `synthesize`;
+    /// 2. call [`RegexVerifyConfig::match_substrs_with_cells`] once per region with a [`Context`] of the same `gate`, since the sub-chip assigns its own columns from the first row of the region.
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `max_chars_size` - the maximum length of the input string.
+    /// * `gate` - the [`FlexGateConfig`] owned by the larger circuit.
+    /// * `regex_defs` - a vector of regex definitions applied to the input string.
+    /// * `cache` - the tables configured so far, e.g., by the other sub-chips of the larger circuit.
+    ///
+    /// # Return values
+    /// Return a new [`RegexVerifyConfig`].
+    pub fn configure_with_gate(
+        meta: &mut ConstraintSystem<F>,
+        max_chars_size: usize,
+        gate: &FlexGateConfig<F>,
+        regex_defs: Vec<RegexDefs>,
+        cache: &mut RegexTableCache<F>,
+    ) -> Self {
+        Self::configure_with_cache(meta, max_chars_size, gate.clone(), regex_defs, cache)
+    }
+
+    /// Configure a new [`RegexVerifyConfig`] in the universal mode.
+    ///
+    /// In the universal mode, the regex definitions are not fixed at the configuration but given to [`RegexVerifyConfig::derive_universal_witness`] at the synthesis.
+    /// Their lookup tables and substring limits are assigned as witnesses in [`RegexVerifyConfig::match_substrs_with_witness`] instead of fixed columns,
+    /// so that one proving/verifying key serves any regex definitions within `bounds`.
+    /// The assigned values are returned in `table_values` of [`AssignedRegexResult`], whose hash must be exposed as an instance and compared with [`universal_table_digest`](crate::table::universal_table_digest) by the verifier.
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `max_chars_size` - the maximum length of the input string.
+    /// * `gate` - a configuration for [`FlexGateConfig`].
+    /// * `num_regex_defs` - the number of the regex definitions applied to the input string.
+    /// * `bounds` - size bounds of the lookup tables.
+    ///
+    /// # Return values
+    /// Return a new [`RegexVerifyConfig`].
+    pub fn configure_universal(
+        meta: &mut ConstraintSystem<F>,
+        max_chars_size: usize,
+        gate: FlexGateConfig<F>,
+        num_regex_defs: usize,
+        bounds: UniversalTableBounds,
+    ) -> Self {
+        Self::configure_with_padding(
+            meta,
+            max_chars_size,
+            gate,
+            vec![RegexDefs::default(); num_regex_defs],
+            Some(bounds),
+            0,
+            &mut RegexTableCache::new(),
+        )
+    }
+
+    /// Configure a new [`RegexVerifyConfig`] whose characters beyond the input string are constrained to be `padding_byte`.
+    ///
+    /// The other constructors pad the input string with zeros, which can be replaced, e.g., by `0x80` or a space, if the regexes match `0x00` and the padded characters are consumed by the other gadgets.
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `max_chars_size` - the maximum length of the input string.
+    /// * `gate` - a configuration for [`FlexGateConfig`].
+    /// * `regex_defs` - a vector of regex definitions applied to the input string. In the universal mode, only its length is used.
+    /// * `universal_bounds` - size bounds of the lookup tables in the universal mode, or `None` to fix the tables.
+    /// * `padding_byte` - the byte of the padded characters.
+    /// * `cache` - the tables configured so far, which is not used in the universal mode.
+    ///
+    /// # Return values
+    /// Return a new [`RegexVerifyConfig`].
+    pub fn configure_with_padding(
+        meta: &mut ConstraintSystem<F>,
+        max_chars_size: usize,
+        gate: FlexGateConfig<F>,
+        regex_defs: Vec<RegexDefs>,
+        universal_bounds: Option<UniversalTableBounds>,
+        padding_byte: u8,
+        cache: &mut RegexTableCache<F>,
+    ) -> Self {
+        let num_regex_def = regex_defs.len();
+        let characters = meta.advice_column();
+        let char_enable = meta.advice_column();
+        let states_array = (0..num_regex_def)
+            .map(|_| {
+                let column = meta.advice_column();
+                meta.enable_equality(column);
+                column
+            })
+            .collect::<Vec<Column<Advice>>>();
+        let substr_ids_array = (0..num_regex_def)
+            .map(|_| {
+                let column = meta.advice_column();
+                meta.enable_equality(column);
+                column
+            })
+            .collect::<Vec<Column<Advice>>>();
+        let is_start_array = (0..num_regex_def)
+            .map(|_| {
+                let column = meta.advice_column();
+                meta.enable_equality(column);
+                column
+            })
+            .collect::<Vec<Column<Advice>>>();
+        let is_end_array = (0..num_regex_def)
+            .map(|_| {
+                let column = meta.advice_column();
+                meta.enable_equality(column);
+                column
+            })
+            .collect::<Vec<Column<Advice>>>();
+        let q_first = meta.complex_selector();
+        let not_q_first = meta.complex_selector();
+        let table_keys = match universal_bounds {
+            Some(_) => vec![],
+            None => {
+                let mut substr_id_offset = 1;
+                regex_defs
+                    .iter()
+                    .map(|defs| {
+                        let key = RegexTableKey::new(defs, substr_id_offset);
+                        substr_id_offset += defs.substrs.len();
+                        key
+                    })
+                    .collect::<Vec<RegexTableKey>>()
+            }
+        };
+        let table_array = table_keys
+            .iter()
+            .map(|key| cache.get_or_configure(meta, key))
+            .collect::<Vec<RegexTableConfig<F>>>();
+        let universal_table_array = match universal_bounds {
+            Some(bounds) => (0..num_regex_def)
+                .map(|_| UniversalRegexTableConfig::configure(meta, bounds))
+                .collect::<Vec<UniversalRegexTableConfig<F>>>(),
+            None => vec![],
+        };
+        meta.enable_equality(characters);
+        meta.enable_equality(char_enable);
+
+        meta.create_gate("The state must start from the first state value", |meta| {
+            let q_frist = meta.query_selector(q_first);
+            let cur_enable = meta.query_advice(char_enable, Rotation::cur());
+            let not_cur_enable = Expression::Constant(F::from(1)) - cur_enable.clone();
+            let mut constraints =
+                vec![q_frist.clone() * cur_enable.clone() * not_cur_enable.clone()];
+            // In the universal mode, the first state value is a witness constrained in `match_substrs`.
+            // The first state is constrained even for the empty input string, which is accepted iff the first state is the accepted state.
+            if universal_bounds.is_none() {
+                for (idx, states) in states_array.iter().enumerate() {
+                    let cur_state = meta.query_advice(*states, Rotation::cur());
+                    constraints.push(
+                        q_frist.clone()
+                            * (cur_state
+                                - Expression::Constant(F::from(
+                                    regex_defs[idx].allstr.first_state_val,
+                                ))),
+                    );
+                }
+            }
+            constraints
+        });
+
+        meta.create_gate("The padded characters must be the padding byte", |meta| {
+            let q_char = meta.query_selector(q_first) + meta.query_selector(not_q_first);
+            let cur_enable = meta.query_advice(char_enable, Rotation::cur());
+            let not_cur_enable = Expression::Constant(F::from(1)) - cur_enable;
+            let cur_char = meta.query_advice(characters, Rotation::cur());
+            vec![
+                q_char
+                    * not_cur_enable
+                    * (cur_char - Expression::Constant(F::from(padding_byte as u64))),
+            ]
+        });
+
+        meta.create_gate("The padded characters must have no substring id", |meta| {
+            let q_char = meta.query_selector(q_first) + meta.query_selector(not_q_first);
+            let cur_enable = meta.query_advice(char_enable, Rotation::cur());
+            let not_cur_enable = Expression::Constant(F::from(1)) - cur_enable;
+            substr_ids_array
+                .iter()
+                .map(|substr_ids| {
+                    let substr_id = meta.query_advice(*substr_ids, Rotation::cur());
+                    q_char.clone() * not_cur_enable.clone() * substr_id
+                })
+                .collect::<Vec<Expression<F>>>()
+        });
+
+        meta.create_gate("The transition of enable flags", |meta| {
+            let not_q_frist = meta.query_selector(not_q_first);
+            let cur_enable = meta.query_advice(char_enable, Rotation::cur());
+            let not_cur_enable = Expression::Constant(F::from(1)) - cur_enable.clone();
+            let prev_enable = meta.query_advice(char_enable, Rotation::prev());
+            let enable_change = prev_enable.clone() - cur_enable.clone();
+            let not_enable_change = Expression::Constant(F::from(1)) - enable_change.clone();
+            vec![
+                not_q_frist.clone() * enable_change * not_enable_change,
+                not_q_frist * cur_enable * not_cur_enable,
+            ]
+        });
+
+        for (idx, defs) in regex_defs.iter().enumerate() {
+            let states = states_array[idx];
+            let substr_ids = substr_ids_array[idx];
+            let is_starts = is_start_array[idx];
+            let is_ends = is_end_array[idx];
+            match universal_bounds {
+                None => {
+                    let dummy_state = defs.allstr.largest_state_val + 1;
+                    let table = &table_array[idx];
+                    meta.lookup("lookup characters and their state", |meta| {
+                        let inputs = transition_lookup_inputs(
+                            meta,
+                            char_enable,
+                            characters,
+                            states,
+                            substr_ids,
+                            dummy_state,
+                        );
+                        inputs
+                            .into_iter()
+                            .zip([
+                                table.characters,
+                                table.cur_states,
+                                table.next_states,
+                                table.substr_ids,
+                            ])
+                            .collect()
+                    });
+
+                    meta.lookup("lookup start_state of substring", |meta| {
+                        let inputs = start_lookup_inputs(
+                            meta,
+                            char_enable,
+                            states,
+                            substr_ids,
+                            is_starts,
+                            dummy_state,
+                        );
+                        inputs
+                            .into_iter()
+                            .zip([
+                                table.endpoints_substr_ids,
+                                table.start_states,
+                                table.end_states,
+                            ])
+                            .collect()
+                    });
+
+                    meta.lookup("lookup end_state of substring", |meta| {
+                        let inputs = end_lookup_inputs(
+                            meta,
+                            char_enable,
+                            states,
+                            substr_ids,
+                            is_ends,
+                            dummy_state,
+                        );
+                        inputs
+                            .into_iter()
+                            .zip([
+                                table.endpoints_substr_ids,
+                                table.start_states,
+                                table.end_states,
+                            ])
+                            .collect()
+                    });
+                }
+                Some(bounds) => {
+                    let dummy_state = bounds.dummy_state;
+                    let table = &universal_table_array[idx];
+                    meta.lookup_any("lookup characters and their state", |meta| {
+                        let inputs = transition_lookup_inputs(
+                            meta,
+                            char_enable,
+                            characters,
+                            states,
+                            substr_ids,
+                            dummy_state,
+                        );
+                        let table_exprs = universal_table_exprs(
+                            meta,
+                            table.q_transition,
+                            &[
+                                table.characters,
+                                table.cur_states,
+                                table.next_states,
+                                table.substr_ids,
+                            ],
+                            &[0, dummy_state, dummy_state, 0],
+                        );
+                        inputs.into_iter().zip(table_exprs).collect()
+                    });
+
+                    meta.lookup_any("lookup start_state of substring", |meta| {
+                        let inputs = start_lookup_inputs(
+                            meta,
+                            char_enable,
+                            states,
+                            substr_ids,
+                            is_starts,
+                            dummy_state,
+                        );
+                        let table_exprs = universal_table_exprs(
+                            meta,
+                            table.q_endpoint,
+                            &[
+                                table.endpoints_substr_ids,
+                                table.start_states,
+                                table.end_states,
+                            ],
+                            &[0, dummy_state, dummy_state],
+                        );
+                        inputs.into_iter().zip(table_exprs).collect()
+                    });
+
+                    meta.lookup_any("lookup end_state of substring", |meta| {
+                        let inputs = end_lookup_inputs(
+                            meta,
+                            char_enable,
+                            states,
+                            substr_ids,
+                            is_ends,
+                            dummy_state,
+                        );
+                        let table_exprs = universal_table_exprs(
+                            meta,
+                            table.q_endpoint,
+                            &[
+                                table.endpoints_substr_ids,
+                                table.start_states,
+                                table.end_states,
+                            ],
+                            &[0, dummy_state, dummy_state],
+                        );
+                        inputs.into_iter().zip(table_exprs).collect()
+                    });
+                }
+            }
+        }
+
+        Self {
+            characters,
+            char_enable,
+            states_array,
+            substr_ids_array,
+            is_start_array,
+            is_end_array,
+            table_array,
+            table_keys,
+            universal_table_array,
+            q_first,
+            not_q_first,
+            max_chars_size,
+            padding_byte,
+            gate,
+            // The universal mode keeps no regex definition, which are given at the synthesis.
+            regex_defs: match universal_bounds {
+                Some(_) => vec![],
+                None => regex_defs,
+            },
+        }
+    }
+
+    /// Return the byte of the characters beyond the input string.
+    pub fn padding_byte(&self) -> u8 {
+        self.padding_byte
+    }
+
+    /// Verify that the input string `characters` satisfies each regex of [`AllstrRegexDef`] in `regex_defs` and extracts its strings that match any of [`SubstrRegexDef`] in `regex_defs`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `characters` - bytes of the input string.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedRegexResult`], or [`Error::Synthesis`] if `characters` does not match a regex.
+    pub fn match_substrs<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        characters: &[u8],
+    ) -> Result<AssignedRegexResult<'a, F>, Error> {
+        let witness = self.derive_witness(characters)?;
+        self.match_substrs_with_witness(ctx, &witness)
+    }
+
+    /// Derive the witnesses of [`RegexVerifyConfig::match_substrs`] off-circuit, which runs the DFA of each regex definition in parallel.
+    ///
+    /// It can be called outside the region closure, e.g., for multiple input strings in parallel.
+    ///
+    /// # Arguments
+    /// * `characters` - bytes of the input string.
+    ///
+    /// In the universal mode, the witnesses must be derived by [`RegexVerifyConfig::derive_universal_witness`] instead.
+    ///
+    /// # Return values
+    /// Return the derived witnesses as [`RegexWitness`], or [`InvalidTransitionError`] of the first invalid character if `characters` does not match a regex, which converts into [`Error::Synthesis`].
+    pub fn derive_witness(
+        &self,
+        characters: &[u8],
+    ) -> Result<RegexWitness, InvalidTransitionError> {
+        derive_witness_of(&self.regex_defs, characters)
+    }
+
+    /// Derive the witnesses of the universal mode, whose regex definitions are given as witnesses and committed by the digest of their lookup tables.
+    ///
+    /// # Arguments
+    /// * `regex_defs` - the regex definitions applied to the input string, whose number must be that of [`RegexVerifyConfig::configure_universal`].
+    /// * `characters` - bytes of the input string.
+    ///
+    /// # Return values
+    /// Return the derived witnesses as [`RegexWitness`], or [`UniversalWitnessError`] if the configuration is not universal, `regex_defs` does not fit in its bounds, or `characters` does not match a regex, which converts into [`Error::Synthesis`].
+    pub fn derive_universal_witness(
+        &self,
+        regex_defs: &[RegexDefs],
+        characters: &[u8],
+    ) -> Result<RegexWitness, UniversalWitnessError> {
+        if regex_defs.len() != self.universal_table_array.len() || regex_defs.is_empty() {
+            return Err(UniversalWitnessError::NumRegexDefs {
+                expected: self.universal_table_array.len(),
+                actual: regex_defs.len(),
+            });
+        }
+        for (def_idx, (defs, table)) in regex_defs
+            .iter()
+            .zip(self.universal_table_array.iter())
+            .enumerate()
+        {
+            check_universal_bounds(defs, &table.bounds)
+                .map_err(|error| UniversalWitnessError::Bounds { def_idx, error })?;
+        }
+        let mut witness = derive_witness_of(regex_defs, characters)?;
+        witness.regex_defs = regex_defs.to_vec();
+        Ok(witness)
+    }
+
+    /// Return the regex definitions of `witness`, i.e., those given to [`RegexVerifyConfig::derive_universal_witness`] in the universal mode, or `regex_defs` otherwise.
+    pub(crate) fn defs_of<'w>(&'w self, witness: &'w RegexWitness) -> &'w [RegexDefs] {
+        if self.universal_table_array.is_empty() {
+            &self.regex_defs
+        } else {
+            &witness.regex_defs
+        }
+    }
+
+    /// Same as [`RegexVerifyConfig::match_substrs`] except that the witnesses are derived in advance by [`RegexVerifyConfig::derive_witness`].
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `witness` - the output of [`RegexVerifyConfig::derive_witness`].
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedRegexResult`].
+    pub fn match_substrs_with_witness<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        witness: &RegexWitness,
+    ) -> Result<AssignedRegexResult<'a, F>, Error> {
+        let regex_defs = self.defs_of(witness);
+        // Each regex definition has its own columns, which the witness must fill.
+        if regex_defs.len() != self.states_array.len() || witness.states.len() != regex_defs.len() {
+            tracing::error!(
+                "the witness of {} regex definitions is given to the configuration of {}",
+                witness.states.len(),
+                self.states_array.len()
+            );
+            return Err(Error::Synthesis);
+        }
+        let characters = &witness.characters[..];
+        let states = &witness.states;
+        let substr_ids = &witness.substr_ids;
+        let (is_starts, is_ends) = (&witness.is_starts, &witness.is_ends);
+        // for d_idx in 0..self.regex_defs.len() {
+        //     for idx in 0..characters.len() {
+        //         println!(
+        //             "d_idx {}, idx {}, char {}, state {}, substr_id {}, is_start {}, is_end {}",
+        //             d_idx,
+        //             idx,
+        //             characters[idx] as char,
+        //             states[d_idx][idx],
+        //             substr_ids[d_idx][idx],
+        //             is_starts[d_idx][idx],
+        //             is_ends[d_idx][idx]
+        //         );
+        //     }
+        // }
+
+        self.q_first.enable(&mut ctx.region, 0)?;
+        for idx in 1..self.max_chars_size {
+            self.not_q_first.enable(&mut ctx.region, idx)?;
+        }
+
+        let mut enable_values = vec![];
+        let mut character_values = vec![];
+        for char in characters.iter() {
+            enable_values.push(Value::known(F::from(1)));
+            character_values.push(Value::known(F::from(*char as u64)));
+        }
+        for _ in characters.len()..self.max_chars_size {
+            enable_values.push(Value::known(F::from(0)));
+            character_values.push(Value::known(F::from(self.padding_byte as u64)));
+        }
+        let assigned_enables = enable_values
+            .into_iter()
+            .enumerate()
+            .map(|(idx, val)| {
+                let assigned = assign_advice(
+                    &mut ctx.region,
+                    || format!("enable at {}", idx),
+                    self.char_enable,
+                    idx,
+                    val,
+                )?;
+                self.assigned_cell2value(ctx, &assigned)
+            })
+            .collect::<Result<Vec<AssignedValue<F>>, Error>>()?;
+        let assigned_characters = character_values
+            .into_iter()
+            .enumerate()
+            .map(|(idx, val)| {
+                let assigned = assign_advice(
+                    &mut ctx.region,
+                    || format!("character at {}", idx),
+                    self.characters,
+                    idx,
+                    val,
+                )?;
+                self.assigned_cell2value(ctx, &assigned)
+            })
+            .collect::<Result<Vec<AssignedValue<F>>, Error>>()?;
+
+        let gate = self.gate();
+        let mut assigned_substr_ids = (0..self.max_chars_size)
+            .map(|_| gate.load_zero(ctx))
+            .collect::<Vec<AssignedValue<F>>>();
+        let mut assigned_is_start = (0..self.max_chars_size + 1)
+            .map(|_| gate.load_zero(ctx))
+            .collect::<Vec<AssignedValue<F>>>();
+        let mut assigned_is_end = (0..self.max_chars_size + 1)
+            .map(|_| gate.load_zero(ctx))
+            .collect::<Vec<AssignedValue<F>>>();
+
+        let mut table_values = vec![];
+        let mut def_advice_cells = vec![];
+        // The substring id and the maximum length of each substring slot of each regex definition in the universal mode.
+        let mut substr_limits = vec![];
+        let mut substr_id_offset = 1;
+        for (d_idx, defs) in regex_defs.iter().enumerate() {
+            let pre_total_advice = ctx.total_advice;
+            // In the universal mode, the lookup tables, the first and accepted state values and the substring limits are witnesses.
+            let universal_states = match self.universal_table_array.get(d_idx) {
+                Some(table) => {
+                    let first_state =
+                        gate.load_witness(ctx, Value::known(F::from(defs.allstr.first_state_val)));
+                    let accepted_state = gate
+                        .load_witness(ctx, Value::known(F::from(defs.allstr.accepted_state_val)));
+                    table_values.push(first_state.clone());
+                    table_values.push(accepted_state.clone());
+                    for cell in table.assign(ctx, defs, substr_id_offset)?.iter() {
+                        table_values.push(self.assigned_cell2value(ctx, cell)?);
+                    }
+                    let mut limits = vec![];
+                    for (substr_id, max_length) in
+                        substr_limit_rows(defs, substr_id_offset, &table.bounds)
+                    {
+                        let substr_id = gate.load_witness(ctx, Value::known(F::from(substr_id)));
+                        let max_length = gate.load_witness(ctx, Value::known(F::from(max_length)));
+                        table_values.push(substr_id.clone());
+                        table_values.push(max_length.clone());
+                        limits.push((substr_id, max_length));
+                    }
+                    substr_limits.push(limits);
+                    Some((first_state, accepted_state))
+                }
+                None => None,
+            };
+            substr_id_offset += defs.substrs.len();
+            let mut state_values = states[d_idx][0..characters.len()]
+                .iter()
+                .map(|state| Value::known(F::from(*state)))
+                .collect::<Vec<Value<F>>>();
+            let mut substr_id_values = substr_ids[d_idx]
+                .iter()
+                .map(|substr_id| Value::known(F::from(*substr_id as u64)))
+                .collect::<Vec<Value<F>>>();
+            let mut is_start_values = is_starts[d_idx][0..characters.len()]
+                .iter()
+                .map(|flag| Value::known(F::from(*flag)))
+                .collect::<Vec<Value<F>>>();
+            let mut is_end_values = is_ends[d_idx][0..characters.len()]
+                .iter()
+                .map(|flag| Value::known(F::from(*flag)))
+                .collect::<Vec<Value<F>>>();
+            for idx in characters.len()..self.max_chars_size {
+                substr_id_values.push(Value::known(F::from(0)));
+                let (state_val, is_start, is_end) = if idx == characters.len() {
+                    (
+                        states[d_idx][idx],
+                        is_starts[d_idx][idx],
+                        is_ends[d_idx][idx],
+                    )
+                } else {
+                    (defs.allstr.largest_state_val + 1, false, false)
+                };
+                state_values.push(Value::known(F::from(state_val)));
+                is_start_values.push(Value::known(F::from(is_start)));
+                is_end_values.push(Value::known(F::from(is_end)));
+            }
+            for (s_idx, state) in state_values.into_iter().enumerate() {
+                let assigned_cell = assign_advice(
+                    &mut ctx.region,
+                    || format!("state at {} of def {}", s_idx, d_idx),
+                    self.states_array[d_idx],
+                    s_idx,
+                    state,
+                )?;
+                let assigned_value = self.assigned_cell2value(ctx, &assigned_cell)?;
+                if let (0, Some((first_state, _))) = (s_idx, &universal_states) {
+                    gate.assert_equal(
+                        ctx,
+                        QuantumCell::Existing(&assigned_value),
+                        QuantumCell::Existing(first_state),
+                    );
+                }
+                let pre_flag = if s_idx == 0 {
+                    gate.load_constant(ctx, F::from(1))
+                } else {
+                    assigned_enables[s_idx - 1].clone()
+                };
+                let cur_flag = if s_idx == self.max_chars_size {
+                    gate.load_constant(ctx, F::from(0))
+                } else {
+                    assigned_enables[s_idx].clone()
+                };
+                let flag_change = gate.sub(
+                    ctx,
+                    QuantumCell::Existing(&pre_flag),
+                    QuantumCell::Existing(&cur_flag),
+                );
+                let accepted_state = match &universal_states {
+                    Some((_, accepted_state)) => QuantumCell::Existing(accepted_state),
+                    None => QuantumCell::Constant(F::from(defs.allstr.accepted_state_val)),
+                };
+                let is_state_eq =
+                    gate.is_equal(ctx, QuantumCell::Existing(&assigned_value), accepted_state);
+                let is_accepted = gate.select(
+                    ctx,
+                    QuantumCell::Existing(&is_state_eq),
+                    QuantumCell::Constant(F::from(1)),
+                    QuantumCell::Existing(&flag_change),
+                );
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(&is_accepted),
+                    QuantumCell::Constant(F::from(1)),
+                );
+            }
+            for (s_idx, substr_id) in substr_id_values.into_iter().enumerate() {
+                let assigned_cell = assign_advice(
+                    &mut ctx.region,
+                    || format!("substr_id at {} of def {}", s_idx, d_idx),
+                    self.substr_ids_array[d_idx],
+                    s_idx,
+                    substr_id,
+                )?;
+                let assigned_value = self.assigned_cell2value(ctx, &assigned_cell)?;
+                assigned_substr_ids[s_idx] = gate.add(
+                    ctx,
+                    QuantumCell::Existing(&assigned_substr_ids[s_idx]),
+                    QuantumCell::Existing(&assigned_value),
+                );
+            }
+            for (idx, (is_start, is_end)) in is_start_values
+                .into_iter()
+                .zip(is_end_values.into_iter())
+                .enumerate()
+            {
+                {
+                    let assigned_cell = assign_advice(
+                        &mut ctx.region,
+                        || format!("is_start at {} of def {}", idx, d_idx),
+                        self.is_start_array[d_idx],
+                        idx,
+                        is_start,
+                    )?;
+                    let assigned_value = self.assigned_cell2value(ctx, &assigned_cell)?;
+                    assigned_is_start[idx] = gate.add(
+                        ctx,
+                        QuantumCell::Existing(&assigned_is_start[idx]),
+                        QuantumCell::Existing(&assigned_value),
+                    );
+                }
+                {
+                    let assigned_cell = assign_advice(
+                        &mut ctx.region,
+                        || format!("is_end at {} of def {}", idx, d_idx),
+                        self.is_end_array[d_idx],
+                        idx,
+                        is_end,
+                    )?;
+                    let assigned_value = self.assigned_cell2value(ctx, &assigned_cell)?;
+                    assigned_is_end[idx] = gate.add(
+                        ctx,
+                        QuantumCell::Existing(&assigned_is_end[idx]),
+                        QuantumCell::Existing(&assigned_value),
+                    );
+                }
+            }
+            def_advice_cells.push(ctx.total_advice - pre_total_advice);
+        }
+        debug_assert_eq!(assigned_enables.len(), assigned_characters.len());
+
+        let mut masked_characters = vec![];
+        let mut masked_substr_ids = vec![];
+        let mut start_mask = vec![];
+        let mut end_mask = vec![];
+
+        let mut last_start_mask = gate.load_zero(ctx);
+        for idx in 0..self.max_chars_size {
+            let is_changed = {
+                let pre_substr_id = if idx == 0 {
+                    gate.load_zero(ctx)
+                } else {
+                    assigned_substr_ids[idx - 1].clone()
+                };
+                let is_eq = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&pre_substr_id),
+                    QuantumCell::Existing(&assigned_substr_ids[idx]),
+                );
+                gate.not(ctx, QuantumCell::Existing(&is_eq))
+            };
+            let is_set = gate.and(
+                ctx,
+                QuantumCell::Existing(&assigned_is_start[idx]),
+                QuantumCell::Existing(&is_changed),
+            );
+            let is_reset = {
+                let not = gate.not(ctx, QuantumCell::Existing(&assigned_is_start[idx]));
+                let and = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&not),
+                    QuantumCell::Existing(&assigned_is_end[idx]),
+                );
+                gate.and(
+                    ctx,
+                    QuantumCell::Existing(&and),
+                    QuantumCell::Existing(&is_changed),
+                )
+            };
+            let mut new_mask = gate.select(
+                ctx,
+                QuantumCell::Constant(F::from(1u64)),
+                QuantumCell::Existing(&last_start_mask),
+                QuantumCell::Existing(&is_set),
+            );
+            new_mask = gate.select(
+                ctx,
+                QuantumCell::Constant(F::from(0u64)),
+                QuantumCell::Existing(&new_mask),
+                QuantumCell::Existing(&is_reset),
+            );
+            start_mask.push(new_mask.clone());
+            last_start_mask = new_mask;
+        }
+        // for (is_start, is_end) in assigned_is_start.iter().zip(assigned_is_end.iter()) {
+        //     let new_mask = {
+        //         let not_end = gate.not(ctx, QuantumCell::Existing(&is_end));
+        //         let and = gate.and(
+        //             ctx,
+        //             QuantumCell::Existing(&not_end),
+        //             QuantumCell::Existing(&last_start_mask),
+        //         );
+        //         gate.or(
+        //             ctx,
+        //             QuantumCell::Existing(&is_start),
+        //             QuantumCell::Existing(&and),
+        //         )
+        //     };
+        //     start_mask.push(new_mask.clone());
+        //     last_start_mask = new_mask;
+        // }
+        let mut last_end_mask = gate.load_zero(ctx);
+        for idx in 0..self.max_chars_size {
+            let is_changed = {
+                let pre_substr_id = if idx == 0 {
+                    gate.load_zero(ctx)
+                } else {
+                    assigned_substr_ids[self.max_chars_size - idx].clone()
+                };
+                let is_eq = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&pre_substr_id),
+                    QuantumCell::Existing(&assigned_substr_ids[self.max_chars_size - 1 - idx]),
+                );
+                gate.not(ctx, QuantumCell::Existing(&is_eq))
+            };
+            let is_set = gate.and(
+                ctx,
+                QuantumCell::Existing(&assigned_is_end[self.max_chars_size - idx]),
+                QuantumCell::Existing(&is_changed),
+            );
+            let is_reset = {
+                let not = gate.not(
+                    ctx,
+                    QuantumCell::Existing(&assigned_is_end[self.max_chars_size - idx]),
+                );
+                let and = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&not),
+                    QuantumCell::Existing(&assigned_is_start[self.max_chars_size - idx]),
+                );
+                gate.and(
+                    ctx,
+                    QuantumCell::Existing(&and),
+                    QuantumCell::Existing(&is_changed),
+                )
+            };
+            let mut new_mask = gate.select(
+                ctx,
+                QuantumCell::Constant(F::from(1u64)),
+                QuantumCell::Existing(&last_end_mask),
+                QuantumCell::Existing(&is_set),
+            );
+            new_mask = gate.select(
+                ctx,
+                QuantumCell::Constant(F::from(0u64)),
+                QuantumCell::Existing(&new_mask),
+                QuantumCell::Existing(&is_reset),
+            );
+            end_mask.push(new_mask.clone());
+            last_end_mask = new_mask;
+        }
+        end_mask.reverse();
+
+        // for (is_start, is_end) in assigned_is_start
+        //     .iter()
+        //     .rev()
+        //     .zip(assigned_is_end.iter().rev())
+        // {
+        //     let new_mask = {
+        //         let not_start = gate.not(ctx, QuantumCell::Existing(&is_start));
+        //         let and = gate.and(
+        //             ctx,
+        //             QuantumCell::Existing(&not_start),
+        //             QuantumCell::Existing(&last_end_mask),
+        //         );
+        //         gate.or(
+        //             ctx,
+        //             QuantumCell::Existing(&is_end),
+        //             QuantumCell::Existing(&and),
+        //         )
+        //     };
+        //     end_mask.push(new_mask.clone());
+        //     last_end_mask = new_mask;
+        // }
+        // end_mask.reverse();
+        // end_mask = vec![&end_mask[1..], &[gate.load_constant(ctx, F::one())][..]].concat();
+
+        for idx in 0..self.max_chars_size {
+            let mask = gate.and(
+                ctx,
+                QuantumCell::Existing(&start_mask[idx]),
+                QuantumCell::Existing(&end_mask[idx]),
+            );
+            // The characters beyond the input string are never revealed, whatever `is_start` and `is_end` are assigned there.
+            let mask = gate.and(
+                ctx,
+                QuantumCell::Existing(&mask),
+                QuantumCell::Existing(&assigned_enables[idx]),
+            );
+            // println!(
+            //     "idx {} char {} start_mask {:?} end_mask {:?}",
+            //     idx, characters[idx] as char, &start_mask[idx], &end_mask[idx],
+            // );
+            // let is_zero = gate.is_zero(ctx, &assigned_substr_ids[idx]);
+            // let is_not_zero = gate.not(ctx, QuantumCell::Existing(&is_zero));
+            let masked_char = gate.mul(
+                ctx,
+                QuantumCell::Existing(&mask),
+                QuantumCell::Existing(&assigned_characters[idx]),
+            );
+            let masked_substr_id = gate.mul(
+                ctx,
+                QuantumCell::Existing(&mask),
+                QuantumCell::Existing(&assigned_substr_ids[idx]),
+            );
+            masked_characters.push(masked_char);
+            masked_substr_ids.push(masked_substr_id);
+        }
+
+        // Every substring must be at most `max_length` long whatever transitions the lookup tables allow.
+        // In the universal mode, the limits are read from the committed cells of every slot, so that the constraints do not depend on the regex definitions.
+        let mut substr_id_offset = 1;
+        for (d_idx, defs) in regex_defs.iter().enumerate() {
+            let pre_total_advice = ctx.total_advice;
+            match substr_limits.get(d_idx) {
+                Some(limits) => {
+                    for (substr_id, max_length) in limits.iter() {
+                        let is_padded = gate.is_zero(ctx, substr_id);
+                        let is_slot = gate.not(ctx, QuantumCell::Existing(&is_padded));
+                        let over_length = gate.add(
+                            ctx,
+                            QuantumCell::Existing(max_length),
+                            QuantumCell::Constant(F::from(1)),
+                        );
+                        self.assert_max_run_length(
+                            ctx,
+                            &masked_substr_ids,
+                            substr_id,
+                            &over_length,
+                            Some(&is_slot),
+                        );
+                    }
+                }
+                None => {
+                    for (idx, substr_def) in defs.substrs.iter().enumerate() {
+                        let substr_id =
+                            gate.load_constant(ctx, F::from((substr_id_offset + idx) as u64));
+                        let over_length =
+                            gate.load_constant(ctx, F::from(substr_def.max_length as u64 + 1));
+                        self.assert_max_run_length(
+                            ctx,
+                            &masked_substr_ids,
+                            &substr_id,
+                            &over_length,
+                            None,
+                        );
+                    }
+                }
+            }
+            def_advice_cells[d_idx] += ctx.total_advice - pre_total_advice;
+            substr_id_offset += defs.substrs.len();
+        }
+
+        let result = AssignedRegexResult {
+            all_characters: assigned_characters,
+            all_enable_flags: assigned_enables,
+            all_substr_ids: masked_substr_ids,
+            masked_characters,
+            table_values,
+            def_advice_cells,
+            padding_byte: self.padding_byte,
+        };
+        Ok(result)
+    }
+
+    /// Same as [`RegexVerifyConfig::match_substrs_with_witness`] except that the characters are the cells assigned by the larger circuit, e.g., the bytes hashed by its SHA-256 chip.
+    ///
+    /// Input cells: `characters` are exactly `max_chars_size` cells of the input string followed by `padding_byte`, each of which is constrained to be equal to the corresponding character of the regex verification.
+    /// The length of the input string is given by `witness`, derived off-circuit from the same bytes, and is constrained by the enable flags as in [`RegexVerifyConfig::match_substrs`].
+    ///
+    /// Output cells: [`AssignedRegexResult`], whose `all_characters` are copies of `characters` and whose `masked_characters` and `all_substr_ids` can be consumed by the other chips, e.g., exposed as instances or hashed.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context of the [`FlexGateConfig`] passed to [`RegexVerifyConfig::configure_with_gate`].
+    /// * `characters` - the assigned characters of the padded input string.
+    /// * `witness` - the output of [`RegexVerifyConfig::derive_witness`] for the bytes of `characters` without the padding.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedRegexResult`], or [`Error::Synthesis`] if the number of `characters` is not `max_chars_size`.
+    pub fn match_substrs_with_cells<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        characters: &[AssignedValue<'a, F>],
+        witness: &RegexWitness,
+    ) -> Result<AssignedRegexResult<'a, F>, Error> {
+        if characters.len() != self.max_chars_size {
+            return Err(Error::Synthesis);
+        }
+        let result = self.match_substrs_with_witness(ctx, witness)?;
+        let gate = self.gate();
+        for (input, assigned) in characters.iter().zip(result.all_characters.iter()) {
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(input),
+                QuantumCell::Existing(assigned),
+            );
+        }
+        Ok(result)
+    }
+
+    /// Load looup tables of each [`RegexDefs`] in `regex_defs`.
+    ///
+    /// # Arguments
+    /// * `layouter` - a [`Layouter`] in which the lookup tables are loaded.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.load_shared(layouter, &mut HashSet::new())
+    }
+
+    /// Load looup tables of each [`RegexDefs`] in `regex_defs` except those already loaded, which is required if the tables are shared by [`RegexVerifyConfig::configure_with_cache`].
+    ///
+    /// # Arguments
+    /// * `layouter` - a [`Layouter`] in which the lookup tables are loaded.
+    /// * `loaded` - the contents of the tables loaded so far, to which those loaded by this call are added.
+    pub fn load_shared(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        loaded: &mut HashSet<RegexTableKey>,
+    ) -> Result<(), Error> {
+        let mut substr_id_offset = 1;
+        for (idx, table) in self.table_array.iter().enumerate() {
+            let defs = &self.regex_defs[idx];
+            if loaded.insert(self.table_keys[idx].clone()) {
+                table.load(layouter, defs, substr_id_offset)?;
+            }
+            substr_id_offset += defs.substrs.len();
+        }
+        Ok(())
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        &self.gate
+    }
+
+    /// Return the canonical digest of `regex_defs` computed by [`regex_defs_digest`].
+    pub fn regex_commitment(&self) -> F {
+        regex_defs_digest(&self.regex_defs)
+    }
+
+    /// Constrain that every run of the characters whose substring id is `substr_id` is shorter than `over_length`, i.e., at most `max_length = over_length - 1` long.
+    ///
+    /// If `is_slot` is given, the runs are counted only if it is one, e.g., to skip the padded substring slots of the universal mode.
+    fn assert_max_run_length<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        substr_ids: &[AssignedValue<'a, F>],
+        substr_id: &AssignedValue<'a, F>,
+        over_length: &AssignedValue<'a, F>,
+        is_slot: Option<&AssignedValue<'a, F>>,
+    ) {
+        let gate = self.gate();
+        let mut run_length = gate.load_zero(ctx);
+        for id in substr_ids.iter() {
+            let mut is_target = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(id),
+                QuantumCell::Existing(substr_id),
+            );
+            if let Some(is_slot) = is_slot {
+                is_target = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_target),
+                    QuantumCell::Existing(is_slot),
+                );
+            }
+            run_length = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_target),
+                QuantumCell::Existing(&run_length),
+                QuantumCell::Existing(&is_target),
+            );
+            // The counter increases at most by one per character, so it exceeds `max_length` iff it reaches `max_length + 1`.
+            let is_over = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(&run_length),
+                QuantumCell::Existing(over_length),
+            );
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(&is_over),
+                QuantumCell::Constant(F::from(0)),
+            );
+        }
+    }
+
+    fn assigned_cell2value<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        assigned_cell: &AssignedAdvice<'v, F>,
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        let gate = self.gate();
+        let assigned_value = gate.load_witness(ctx, advice_value(assigned_cell));
+        constrain_equal(&mut ctx.region, assigned_cell.cell(), assigned_value.cell())?;
+        Ok(assigned_value)
+    }
+}
+
+/// Derive the witnesses of `characters` for `regex_defs` as [`RegexVerifyConfig::derive_witness`].
+fn derive_witness_of(
+    regex_defs: &[RegexDefs],
+    characters: &[u8],
+) -> Result<RegexWitness, InvalidTransitionError> {
+    let states = derive_states(regex_defs, characters)?;
+    let substr_ids = derive_substr_ids(regex_defs, &states);
+    let (is_starts, is_ends) = derive_is_start_end(regex_defs, &states, &substr_ids);
+    Ok(RegexWitness {
+        characters: characters.to_vec(),
+        states,
+        substr_ids,
+        is_starts,
+        is_ends,
+        regex_defs: vec![],
+    })
+}
+
+/// Return the `substr_id` of the first substring of each regex definition.
+fn substr_id_offsets(regex_defs: &[RegexDefs]) -> Vec<usize> {
+    let mut offsets = vec![];
+    let mut substr_id_offset = 1;
+    for defs in regex_defs.iter() {
+        offsets.push(substr_id_offset);
+        substr_id_offset += defs.substrs.len();
+    }
+    offsets
+}
+
+fn derive_states(
+    regex_defs: &[RegexDefs],
+    characters: &[u8],
+) -> Result<Vec<Vec<u64>>, InvalidTransitionError> {
+    regex_defs
+        .par_iter()
+        .map(|defs| {
+            let mut states = vec![defs.allstr.first_state_val];
+            for (c_idx, char) in characters.iter().enumerate() {
+                let state = states[c_idx];
+                match defs.allstr.state_lookup.get(&(*char, state)) {
+                    Some((_, s)) => states.push(*s),
+                    None => {
+                        return Err(InvalidTransitionError {
+                            position: c_idx,
+                            char: *char,
+                            state,
+                        })
+                    }
+                }
+            }
+            Ok(states)
+        })
+        .collect()
+}
+
+fn derive_substr_ids(regex_defs: &[RegexDefs], states: &[Vec<u64>]) -> Vec<Vec<usize>> {
+    regex_defs
+        .par_iter()
+        .zip(substr_id_offsets(regex_defs))
+        .zip(states.par_iter())
+        .map(|((defs, substr_id_offset), states)| {
+            states
+                .par_windows(2)
+                .map(|transition| {
+                    for (substr_idx, substr_def) in defs.substrs.iter().enumerate() {
+                        if substr_def
+                            .valid_state_transitions
+                            .get(&(transition[0], transition[1]))
+                            .is_some()
+                        {
+                            return substr_id_offset + substr_idx;
+                        }
+                    }
+                    0
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn derive_is_start_end(
+    regex_defs: &[RegexDefs],
+    states: &[Vec<u64>],
+    substr_ids: &[Vec<usize>],
+) -> (Vec<Vec<bool>>, Vec<Vec<bool>>) {
+    let (is_starts_array, is_ends_array): (Vec<Vec<bool>>, Vec<Vec<bool>>) = regex_defs
+        .par_iter()
+        .zip(substr_id_offsets(regex_defs))
+        .enumerate()
+        .map(|(d_idx, (defs, substr_id_offset))| {
+            let state_len = states[d_idx].len();
+            let mut is_starts = states[d_idx][0..state_len - 1]
+                .par_iter()
+                .zip(substr_ids[d_idx].par_iter())
+                .map(|(state, substr_id)| {
+                    if *substr_id == 0 {
+                        return false;
+                    }
+                    let substr_idx = *substr_id - substr_id_offset;
+                    let valid_start_states = &defs.substrs[substr_idx].start_states;
+                    valid_start_states.contains(state)
+                })
+                .collect::<Vec<bool>>();
+            is_starts.push(false);
+            let is_ends = states[d_idx][1..]
+                .par_iter()
+                .zip(substr_ids[d_idx].par_iter())
+                .map(|(state, substr_id)| {
+                    if *substr_id == 0 {
+                        return false;
+                    }
+                    let substr_idx: usize = *substr_id - substr_id_offset;
+                    let valid_end_states = &defs.substrs[substr_idx].end_states;
+                    valid_end_states.contains(state)
+                })
+                .collect::<Vec<bool>>();
+            let is_ends = vec![&vec![false][..], &is_ends].concat();
+            (is_starts, is_ends)
+        })
+        .unzip();
+    (is_starts_array, is_ends_array)
+}
+
+/// Return the inputs of the lookup of characters and their state transitions.
+fn transition_lookup_inputs<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    char_enable: Column<Advice>,
+    characters: Column<Advice>,
+    states: Column<Advice>,
+    substr_ids: Column<Advice>,
+    dummy_state: u64,
+) -> Vec<Expression<F>> {
+    let enable = meta.query_advice(char_enable, Rotation::cur());
+    let not_enable = Expression::Constant(F::from(1)) - enable.clone();
+    let character = meta.query_advice(characters, Rotation::cur());
+    let cur_state = meta.query_advice(states, Rotation::cur());
+    let next_state = meta.query_advice(states, Rotation::next());
+    let substr_id = meta.query_advice(substr_ids, Rotation::cur());
+    let dummy_state_val = Expression::Constant(F::from(dummy_state));
+    vec![
+        enable.clone() * character,
+        enable.clone() * cur_state + not_enable.clone() * dummy_state_val.clone(),
+        enable.clone() * next_state + not_enable * dummy_state_val,
+        enable * substr_id,
+    ]
+}
+
+/// Return the inputs of the lookup of start states of substrings.
+fn start_lookup_inputs<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    char_enable: Column<Advice>,
+    states: Column<Advice>,
+    substr_ids: Column<Advice>,
+    is_starts: Column<Advice>,
+    dummy_state: u64,
+) -> Vec<Expression<F>> {
+    let enable = meta.query_advice(char_enable, Rotation::cur());
+    let cur_state = meta.query_advice(states, Rotation::cur());
+    let substr_id = meta.query_advice(substr_ids, Rotation::cur());
+    let is_start = meta.query_advice(is_starts, Rotation::cur());
+    let dummy_state_val = Expression::Constant(F::from(dummy_state));
+    let flag = enable * is_start;
+    let not_flag = Expression::Constant(F::from(1)) - flag.clone();
+    vec![
+        flag.clone() * substr_id,
+        flag * cur_state + not_flag * dummy_state_val.clone(),
+        dummy_state_val,
+    ]
+}
+
+/// Return the inputs of the lookup of end states of substrings.
+fn end_lookup_inputs<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    char_enable: Column<Advice>,
+    states: Column<Advice>,
+    substr_ids: Column<Advice>,
+    is_ends: Column<Advice>,
+    dummy_state: u64,
+) -> Vec<Expression<F>> {
+    let enable = meta.query_advice(char_enable, Rotation::cur());
+    let next_state = meta.query_advice(states, Rotation::next());
+    let substr_id = meta.query_advice(substr_ids, Rotation::cur());
+    let next_is_end = meta.query_advice(is_ends, Rotation::next());
+    let dummy_state_val = Expression::Constant(F::from(dummy_state));
+    let flag = enable * next_is_end;
+    let not_flag = Expression::Constant(F::from(1)) - flag.clone();
+    vec![
+        flag.clone() * substr_id,
+        dummy_state_val.clone(),
+        flag * next_state + not_flag * dummy_state_val,
+    ]
+}
+
+/// Return the expressions of a lookup table assigned as witnesses, which are equal to `dummies` in the rows where `selector` is disabled.
+fn universal_table_exprs<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    selector: Selector,
+    columns: &[Column<Advice>],
+    dummies: &[u64],
+) -> Vec<Expression<F>> {
+    let q = meta.query_selector(selector);
+    let not_q = Expression::Constant(F::from(1)) - q.clone();
+    columns
+        .iter()
+        .zip(dummies.iter())
+        .map(|(column, dummy)| {
+            q.clone() * meta.query_advice(*column, Rotation::cur())
+                + not_q.clone() * Expression::Constant(F::from(*dummy))
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "compiler"))]
+mod test {
+    use halo2_base::halo2_proofs::{
+        dev::{CircuitCost, FailureLocation, MockProver, VerifyFailure},
+        halo2curves::bn256::{Bn256, Fr, G1Affine, G1},
+        plonk::{Any, Circuit},
+    };
+    use halo2_base::{gates::range::RangeStrategy::Vertical, SKIP_FIRST_PASS};
+
+    use super::*;
+    use crate::{
+        defs::{AllstrRegexDef, SubstrRegexDef},
+        vrm::DecomposedRegexConfig,
+    };
+
+    use halo2_base::halo2_proofs::plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, ConstraintSystem,
+    };
+    use halo2_base::halo2_proofs::poly::commitment::{Params, ParamsProver, ParamsVerifier};
+    use halo2_base::halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+    use halo2_base::halo2_proofs::poly::kzg::multiopen::{ProverGWC, VerifierGWC};
+    use halo2_base::halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use halo2_base::halo2_proofs::transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    };
+    use rand::rngs::OsRng;
+    use std::marker::PhantomData;
+    use std::{collections::HashSet, path::Path};
+
+    use halo2_base::halo2_proofs::{
+        circuit::{floor_planner::V1, Cell, SimpleFloorPlanner},
+        plonk::{Column, Instance},
+    };
+    use itertools::Itertools;
+
+    // Checks a regex of string len
+    const MAX_STRING_LEN: usize = 1024;
+    const K: usize = 17;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestCircuit1<F: PrimeField> {
+        // Since this is only relevant for the witness, we can opt to make this whatever convenient type we want
+        characters: Vec<u8>,
+        correct_substrs: Vec<(usize, String)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestCircuit1<F> {
+        const NUM_ADVICE: usize = 2;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCircuit1<F> {
+        type Config = RegexVerifyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        // Circuit without witnesses, called only during key generation
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                correct_substrs: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def1 =
+                AllstrRegexDef::read_from_text("./test_regexes/regex1_test_lookup.txt");
+            let substr_def1 =
+                SubstrRegexDef::read_from_text("./test_regexes/substr1_test_lookup.txt");
+            let all_regex_def2 =
+                AllstrRegexDef::read_from_text("./test_regexes/regex2_test_lookup.txt");
+            let substr_def2 =
+                SubstrRegexDef::read_from_text("./test_regexes/substr2_test_lookup.txt");
+            // let substr_def2 =
+            //     SubstrRegexDef::read_from_text("./test_regexes/substr2_test_lookup.txt");
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![
+                RegexDefs {
+                    allstr: all_regex_def1,
+                    substrs: vec![substr_def1],
+                },
+                RegexDefs {
+                    allstr: all_regex_def2,
+                    substrs: vec![substr_def2],
+                },
+            ];
+            let config = RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs);
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let regex1_decomposed: DecomposedRegexConfig =
+                serde_json::from_reader(File::open("./test_regexes/regex1_test.json").unwrap())
+                    .unwrap();
+            regex1_decomposed
+                .gen_regex_files(
+                    &Path::new("./test_regexes/regex1_test_lookup.txt").to_path_buf(),
+                    &[Path::new("./test_regexes/substr1_test_lookup.txt").to_path_buf()],
+                )
+                .unwrap();
+            let regex2_decomposed: DecomposedRegexConfig =
+                serde_json::from_reader(File::open("./test_regexes/regex2_test.json").unwrap())
+                    .unwrap();
+            regex2_decomposed
+                .gen_regex_files(
+                    &Path::new("./test_regexes/regex2_test_lookup.txt").to_path_buf(),
+                    &[Path::new("./test_regexes/substr2_test_lookup.txt").to_path_buf()],
+                )
+                .unwrap();
+            // test regex: "email was meant for @(a|b|c|d|e|f|g|h|i|j|k|l|m|n|o|p|q|r|s|t|u|v|w|x|y|z|A|B|C|D|E|F|G|H|I|J|K|L|M|N|O|P|Q|R|S|T|U|V|W|X|Y|Z|0|1|2|3|4|5|6|7|8|9|_)+( and (a|b|c|d|e|f|g|h|i|j|k|l|m|n|o|p|q|r|s|t|u|v|w|x|y|z)+)*."
+            config.load(&mut layouter)?;
+
+            // println!("Synthesize being called...");
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            // let mut substr_positions = self.substr_positions.to_vec();
+            // for _ in substr_positions.len()..self.substr_def.max_length {
+            //     substr_positions.push(0);
+            // }
+
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    let mut expected_masked_chars = vec![0; MAX_STRING_LEN];
+                    let mut expected_substr_ids = vec![0; MAX_STRING_LEN];
+
+                    for (substr_idx, (start, chars)) in self.correct_substrs.iter().enumerate() {
+                        for (idx, char) in chars.as_bytes().iter().enumerate() {
+                            expected_masked_chars[start + idx] = *char;
+                            expected_substr_ids[start + idx] = substr_idx + 1;
+                        }
+                    }
+                    for idx in 0..MAX_STRING_LEN {
+                        result.masked_characters[idx]
+                            .value()
+                            .map(|v| assert_eq!(*v, F::from(expected_masked_chars[idx] as u64)));
+                        result.all_substr_ids[idx]
+                            .value()
+                            .map(|v| assert_eq!(*v, F::from(expected_substr_ids[idx] as u64)));
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_substr_pass1() {
+        let characters: Vec<u8> = "email was meant for @y. Also for x."
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit1::<Fr> {
+            characters,
+            correct_substrs: vec![(21, "y".to_string()), (33, "x".to_string())],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit1<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_pass2() {
+        let characters: Vec<u8> = "email was meant for @yajk. Also for swq."
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit1::<Fr> {
+            characters,
+            correct_substrs: vec![(21, "yajk".to_string()), (36, "swq".to_string())],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit1<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_fail1() {
+        // 1. The string does not satisfy the regex.
+        let characters: Vec<u8> = "email was meant for @@".chars().map(|c| c as u8).collect();
+
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit1::<Fr> {
+            characters,
+            correct_substrs: vec![],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        match prover.verify() {
+            Err(_) => {
+                println!("Error successfully achieved!");
+            }
+            _ => assert!(false, "Should be error."),
+        }
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit1<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_invalid_transition() {
+        // No state has a transition by the byte 0xff, whose witnesses cannot be derived.
+        let mut characters = b"email was meant for @y".to_vec();
+        characters.push(0xff);
+        let circuit = TestCircuit1::<Fr> {
+            characters,
+            correct_substrs: vec![],
+            _marker: PhantomData,
+        };
+        assert!(matches!(
+            MockProver::run(K as u32, &circuit, vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn test_substr_pass1_keygen_and_prove() {
+        let characters: Vec<u8> = "email was meant for @y. Also for x."
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+
+        let circuit = TestCircuit1::<Fr> {
+            characters,
+            correct_substrs: vec![(21, "y".to_string()), (33, "x".to_string())],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let emp_circuit = circuit.without_witnesses();
+        let params = ParamsKZG::<Bn256>::setup(K as u32, OsRng);
+        let vk = keygen_vk(&params, &emp_circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &emp_circuit).unwrap();
+        let proof = {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit.clone()],
+                &[&[]],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        };
+        {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            let verifier_params = params.verifier_params();
+            let strategy = SingleStrategy::new(&verifier_params);
+            verify_proof::<_, VerifierGWC<_>, _, _, _>(
+                verifier_params,
+                &vk,
+                strategy,
+                &[&[]],
+                &mut transcript,
+            )
+            .unwrap();
+        }
+    }
+
+    #[derive(Default, Clone, Debug)]
+    struct TestCircuit2<F: PrimeField> {
+        characters: Vec<u8>,
+        correct_substrs: Vec<(usize, String)>,
+        is_success: bool,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestCircuit2<F> {
+        const NUM_ADVICE: usize = 25;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCircuit2<F> {
+        type Config = RegexVerifyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        // Circuit without witnesses, called only during key generation
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                correct_substrs: vec![],
+                is_success: false,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def =
+                AllstrRegexDef::read_from_text("./test_regexes/regex3_test_lookup.txt");
+            let substr_def =
+                SubstrRegexDef::read_from_text("./test_regexes/substr3_test_lookup.txt");
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: all_regex_def,
+                substrs: vec![substr_def],
+            }];
+            let config = RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs);
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let regex_decomposed: DecomposedRegexConfig =
+                serde_json::from_reader(File::open("./test_regexes/regex3_test.json").unwrap())
+                    .unwrap();
+            regex_decomposed
+                .gen_regex_files(
+                    &Path::new("./test_regexes/regex3_test_lookup.txt").to_path_buf(),
+                    &[Path::new("./test_regexes/substr3_test_lookup.txt").to_path_buf()],
+                )
+                .unwrap();
+            // test regex: "email was meant for @(a|b|c|d|e|f|g|h|i|j|k|l|m|n|o|p|q|r|s|t|u|v|w|x|y|z|A|B|C|D|E|F|G|H|I|J|K|L|M|N|O|P|Q|R|S|T|U|V|W|X|Y|Z|0|1|2|3|4|5|6|7|8|9|_)+( and (a|b|c|d|e|f|g|h|i|j|k|l|m|n|o|p|q|r|s|t|u|v|w|x|y|z)+)*."
+            config.load(&mut layouter)?;
+
+            // println!("Synthesize being called...");
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            // let mut substr_positions = self.substr_positions.to_vec();
+            // for _ in substr_positions.len()..self.substr_def.max_length {
+            //     substr_positions.push(0);
+            // }
+
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    let mut expected_masked_chars = vec![0; MAX_STRING_LEN];
+                    let mut expected_substr_ids = vec![0; MAX_STRING_LEN];
+
+                    if self.is_success {
+                        for (substr_idx, (start, chars)) in self.correct_substrs.iter().enumerate()
+                        {
+                            for (idx, char) in chars.as_bytes().iter().enumerate() {
+                                expected_masked_chars[start + idx] = *char;
+                                expected_substr_ids[start + idx] = substr_idx + 1;
+                            }
+                        }
+                        for idx in 0..MAX_STRING_LEN {
+                            result.masked_characters[idx].value().map(|v| {
+                                assert_eq!(*v, F::from(expected_masked_chars[idx] as u64))
+                            });
+                            result.all_substr_ids[idx]
+                                .value()
+                                .map(|v| assert_eq!(*v, F::from(expected_substr_ids[idx] as u64)));
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_substr_pass3() {
+        let characters: Vec<u8> = "from:alice@gmail.com\r\n"
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit2::<Fr> {
+            characters,
+            correct_substrs: vec![(5, "alice@gmail.com".to_string())],
+            is_success: true,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit2<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_pass4() {
+        let characters: Vec<u8> = "dummy\r\nfrom:alice<alice@gmail.com>\r\n"
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit2::<Fr> {
+            characters,
+            correct_substrs: vec![(18, "alice@gmail.com".to_string())],
+            is_success: true,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit2<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_fail2() {
+        let characters: Vec<u8> = "from:alice<alicegmail.com>\r\n"
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit2::<Fr> {
+            characters,
+            correct_substrs: vec![],
+            is_success: false,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        match prover.verify() {
+            Err(_) => {
+                println!("Error successfully achieved!");
+            }
+            _ => panic!("Should be error."),
+        }
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit2<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_fail3() {
+        let characters: Vec<u8> = "from:alice<alice@gmail.com>"
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit2::<Fr> {
+            characters,
+            correct_substrs: vec![],
+            is_success: false,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        match prover.verify() {
+            Err(_) => {
+                println!("Error successfully achieved!");
+            }
+            _ => panic!("Should be error."),
+        }
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit2<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_fail4() {
+        let characters: Vec<u8> = "fromalice<alice@gmail.com>\r\n"
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        // Make a vector of the numbers 1...24
+        // let states = (1..=STRING_LEN as u128).collect::<Vec<u128>>();
+        // assert_eq!(characters.len(), STRING_LEN);
+        // assert_eq!(states.len(), STRING_LEN);
+
+        // Successful cases
+        let circuit = TestCircuit2::<Fr> {
+            characters,
+            correct_substrs: vec![],
+            is_success: false,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        match prover.verify() {
+            Err(_) => {
+                println!("Error successfully achieved!");
+            }
+            _ => panic!("Should be error."),
+        }
+        // CircuitCost::<Eq, RegexCheckCircuit<Fp>>::measure((k as u128).try_into().unwrap(), &circuit)
+        println!(
+            "{:?}",
+            CircuitCost::<G1, TestCircuit2<Fr>>::measure((K as u128).try_into().unwrap(), &circuit)
+        );
+    }
+
+    #[test]
+    fn test_substr_fail_empty() {
+        // The first state of the regex3 is not accepted, so the empty string must be rejected.
+        let circuit = TestCircuit2::<Fr> {
+            characters: vec![],
+            correct_substrs: vec![],
+            is_success: false,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        match prover.verify() {
+            Err(_) => {
+                println!("Error successfully achieved!");
+            }
+            _ => panic!("Should be error."),
+        }
+    }
+
+    #[derive(Default, Clone, Debug)]
+    struct TestCircuit3<F: PrimeField> {
+        characters: Vec<u8>,
+        correct_substrs: Vec<(usize, String)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestCircuit3<F> {
+        const NUM_ADVICE: usize = 25;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCircuit3<F> {
+        type Config = RegexVerifyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        // Circuit without witnesses, called only during key generation
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                correct_substrs: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def =
+                AllstrRegexDef::read_from_text("./test_regexes/regex5_test_lookup.txt");
+            let substr_def =
+                SubstrRegexDef::read_from_text("./test_regexes/substr5_test_lookup.txt");
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: all_regex_def,
+                substrs: vec![substr_def],
+            }];
+            RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let regex_decomposed: DecomposedRegexConfig =
+                serde_json::from_reader(File::open("./test_regexes/regex5_test.json").unwrap())
+                    .unwrap();
+            regex_decomposed
+                .gen_regex_files(
+                    &Path::new("./test_regexes/regex5_test_lookup.txt").to_path_buf(),
+                    &[Path::new("./test_regexes/substr5_test_lookup.txt").to_path_buf()],
+                )
+                .unwrap();
+            // test regex: "(a|b)*", whose first state is the accepted state.
+            config.load(&mut layouter)?;
+
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    let mut expected_masked_chars = vec![0; MAX_STRING_LEN];
+                    let mut expected_substr_ids = vec![0; MAX_STRING_LEN];
+                    for (substr_idx, (start, chars)) in self.correct_substrs.iter().enumerate() {
+                        for (idx, char) in chars.as_bytes().iter().enumerate() {
+                            expected_masked_chars[start + idx] = *char;
+                            expected_substr_ids[start + idx] = substr_idx + 1;
+                        }
+                    }
+                    for idx in 0..MAX_STRING_LEN {
+                        result.masked_characters[idx]
+                            .value()
+                            .map(|v| assert_eq!(*v, F::from(expected_masked_chars[idx] as u64)));
+                        result.all_substr_ids[idx]
+                            .value()
+                            .map(|v| assert_eq!(*v, F::from(expected_substr_ids[idx] as u64)));
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_substr_pass_empty() {
+        // All the characters are the padding, i.e., the empty string matched without any transition.
+        let circuit = TestCircuit3::<Fr> {
+            characters: vec![],
+            correct_substrs: vec![],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_substr_pass_accepting_first_state() {
+        let circuit = TestCircuit3::<Fr> {
+            characters: b"abba".to_vec(),
+            correct_substrs: vec![(0, "abba".to_string())],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_shared_tables() {
+        let regex_defs = vec![RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                "./test_regexes/substr4_test_lookup.txt",
+            )],
+        }];
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let gate = FlexGateConfig::<Fr>::configure(
+            &mut meta,
+            halo2_base::gates::flex_gate::GateStrategy::Vertical,
+            &[2],
+            1,
+            0,
+            K,
+        );
+        let mut cache = RegexTableCache::new();
+        RegexVerifyConfig::configure_with_cache(
+            &mut meta,
+            MAX_STRING_LEN,
+            gate.clone(),
+            regex_defs.clone(),
+            &mut cache,
+        );
+        let num_fixed = meta.num_fixed_columns();
+        RegexVerifyConfig::configure_with_cache(
+            &mut meta,
+            MAX_STRING_LEN,
+            gate,
+            regex_defs,
+            &mut cache,
+        );
+        assert_eq!(cache.len(), 1);
+        assert_eq!(meta.num_fixed_columns(), num_fixed);
+    }
+
+    #[derive(Clone, Debug)]
+    struct SuperCircuitConfig<F: PrimeField> {
+        gate: FlexGateConfig<F>,
+        regex: RegexVerifyConfig<F>,
+    }
+
+    // A larger circuit that assigns the characters by itself and embeds the regex verification as a sub-chip.
+    #[derive(Default, Clone, Debug)]
+    struct SuperCircuit<F: PrimeField> {
+        // The bytes assigned by the larger circuit.
+        assigned: Vec<u8>,
+        // The bytes from which the witnesses of the regex verification are derived.
+        characters: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> SuperCircuit<F> {
+        const MAX_STRING_LEN: usize = 64;
+    }
+
+    impl<F: PrimeField> Circuit<F> for SuperCircuit<F> {
+        type Config = SuperCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                assigned: vec![],
+                characters: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[1],
+                1,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+                substrs: vec![SubstrRegexDef::read_from_text(
+                    "./test_regexes/substr4_test_lookup.txt",
+                )],
+            }];
+            let regex = RegexVerifyConfig::configure_with_gate(
+                meta,
+                Self::MAX_STRING_LEN,
+                &gate,
+                regex_defs,
+                &mut RegexTableCache::new(),
+            );
+            SuperCircuitConfig { gate, regex }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.regex.load(&mut layouter)?;
+            let witness = config.regex.derive_witness(&self.characters)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "super",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &config.gate);
+                    let ctx = &mut aux;
+                    let padding_byte = config.regex.padding_byte();
+                    let characters = (0..Self::MAX_STRING_LEN)
+                        .map(|idx| {
+                            let char = self.assigned.get(idx).copied().unwrap_or(padding_byte);
+                            config
+                                .gate
+                                .load_witness(ctx, Value::known(F::from(char as u64)))
+                        })
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let result =
+                        config
+                            .regex
+                            .match_substrs_with_cells(ctx, &characters, &witness)?;
+                    assert_eq!(result.masked_characters.len(), Self::MAX_STRING_LEN);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_super_circuit() {
+        let circuit = SuperCircuit::<Fr> {
+            assigned: b"amount=1000;".to_vec(),
+            characters: b"amount=1000;".to_vec(),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_super_circuit_other_cells() {
+        // The cells of the larger circuit differ from the bytes of the witnesses.
+        let circuit = SuperCircuit::<Fr> {
+            assigned: b"amount=2000;".to_vec(),
+            characters: b"amount=1000;".to_vec(),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::evm::{accumulation_circuit, NUM_ACCUMULATOR_INSTANCES};
+use crate::helpers::VerificationError;
+use halo2_base::halo2_proofs::arithmetic::CurveAffine;
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine, G2Prepared};
+use halo2_base::halo2_proofs::halo2curves::group::Group;
+use halo2_base::halo2_proofs::halo2curves::pairing::{MillerLoopResult, MultiMillerLoop};
+use halo2_base::halo2_proofs::plonk::{verify_proof, ProvingKey, VerifyingKey};
+use halo2_base::halo2_proofs::poly::commitment::ParamsProver;
+use halo2_base::halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_base::halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+use halo2_base::halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use snark_verifier::loader::native::NativeLoader;
+use snark_verifier::util::arithmetic::fe_from_limbs;
+use snark_verifier_sdk::halo2::{
+    aggregation::PublicAggregationCircuit, gen_snark_shplonk, PoseidonTranscript,
+};
+use snark_verifier_sdk::{Snark, BITS, LIMBS};
+
+/// Construct the wrapper circuit that verifies `snark` and exposes its accumulator followed by the instances of the regex proof.
+///
+/// It is the aggregation circuit of [`gen_aggregation_circuit`](crate::evm::gen_aggregation_circuit) of the single `snark`, whose shape is read from the file of [`AGGREGATION_CONFIG_ENV`](crate::evm::AGGREGATION_CONFIG_ENV), whose `degree` must be that of `wrapper_params`.
+/// A smaller degree and fewer columns than those of the wrapped circuit make the wrapper proof cheaper to verify at the cost of generating it.
+/// The regex proof of many instances, e.g., in the full-transparency mode, should commit them by [`set_config_commit_instances`](crate::helpers::set_config_commit_instances), so that the wrapper proof exposes only one instance after its accumulator.
+///
+/// # Arguments
+/// * `wrapper_params` - the SRS parameters of the wrapper circuit.
+/// * `snark` - a regex proof of [`gen_regex_snark`](crate::evm::gen_regex_snark), or a wrapper proof of [`compress`] to wrap it once more.
+/// * `nested` - true iff `snark` is a wrapper proof, whose accumulator is folded into that of the new wrapper circuit.
+/// * `seed` - the seed of the RNG used to blind the accumulation, or `None` to use a random seed.
+///
+/// # Return values
+/// Return the wrapper circuit, whose proving key is generated by [`gen_aggregation_pk`](crate::evm::gen_aggregation_pk).
+pub fn gen_wrapper_circuit(
+    wrapper_params: &ParamsKZG<Bn256>,
+    snark: Snark,
+    nested: bool,
+    seed: Option<[u8; 32]>,
+) -> PublicAggregationCircuit {
+    accumulation_circuit(wrapper_params, vec![snark], nested, seed)
+}
+
+/// Generate a wrapper proof of `wrapper_circuit`, whose multiopen scheme is SHPLONK and whose transcript is Poseidon, so that it can be wrapped again by [`gen_wrapper_circuit`].
+///
+/// # Arguments
+/// * `wrapper_params` - the SRS parameters of the wrapper circuit.
+/// * `wrapper_pk` - the proving key of the wrapper circuit.
+/// * `wrapper_circuit` - a wrapper circuit of [`gen_wrapper_circuit`].
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+///
+/// # Return values
+/// Return the wrapper proof with its instances and the protocol of its verifying key as [`Snark`].
+pub fn compress(
+    wrapper_params: &ParamsKZG<Bn256>,
+    wrapper_pk: &ProvingKey<G1Affine>,
+    wrapper_circuit: PublicAggregationCircuit,
+    seed: Option<[u8; 32]>,
+) -> Snark {
+    let mut rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    gen_snark_shplonk(
+        wrapper_params,
+        wrapper_pk,
+        wrapper_circuit,
+        &mut rng,
+        None::<&str>,
+    )
+}
+
+/// Return the instances of the regex proof wrapped in `snark` of [`compress`], i.e., those following its accumulator, which is the Poseidon hash of the instances of the regex proof if they are committed.
+pub fn regex_instances(snark: &Snark) -> &[Fr] {
+    &snark.instances[0][NUM_ACCUMULATOR_INSTANCES..]
+}
+
+/// Verify a wrapper proof of [`compress`] natively, i.e., the proof of the wrapper circuit and the pairing check of its accumulator, which together imply the validity of the wrapped regex proof.
+///
+/// # Arguments
+/// * `wrapper_params` - the SRS parameters of the wrapper circuit.
+/// * `wrapper_vk` - the verifying key of the wrapper circuit.
+/// * `snark` - a wrapper proof of [`compress`].
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not, e.g., [`VerificationError::PairingFailed`] if the accumulator is invalid.
+pub fn verify_compressed(
+    wrapper_params: &ParamsKZG<Bn256>,
+    wrapper_vk: &VerifyingKey<G1Affine>,
+    snark: &Snark,
+) -> Result<(), VerificationError> {
+    let instances = &snark.instances[0];
+    if instances.len() < NUM_ACCUMULATOR_INSTANCES {
+        return Err(VerificationError::InstanceCount {
+            expected: NUM_ACCUMULATOR_INSTANCES,
+            found: instances.len(),
+        });
+    }
+    let mut transcript = PoseidonTranscript::<NativeLoader, &[u8]>::new(snark.proof.as_slice());
+    let verifier_params = wrapper_params.verifier_params();
+    let strategy = SingleStrategy::new(verifier_params);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+        verifier_params,
+        wrapper_vk,
+        strategy,
+        &[&[instances]],
+        &mut transcript,
+    )
+    .map_err(|e| {
+        tracing::debug!("the wrapper proof is rejected: {:?}", e);
+        VerificationError::from(e)
+    })?;
+    if decide_accumulator(wrapper_params, &instances[..NUM_ACCUMULATOR_INSTANCES]) {
+        Ok(())
+    } else {
+        tracing::debug!("the accumulator of the wrapper proof is rejected");
+        Err(VerificationError::PairingFailed)
+    }
+}
+
+/// Return true iff the KZG accumulator encoded in `limbs`, i.e., the limbs of the coordinates of its left and right points, passes the pairing check with `params`.
+fn decide_accumulator(params: &ParamsKZG<Bn256>, limbs: &[Fr]) -> bool {
+    let points = limbs
+        .chunks(2 * LIMBS)
+        .map(|coordinates| {
+            let x = fe_from_limbs::<Fr, Fq, LIMBS, BITS>(coordinates[..LIMBS].try_into().unwrap());
+            let y = fe_from_limbs::<Fr, Fq, LIMBS, BITS>(coordinates[LIMBS..].try_into().unwrap());
+            Option::<G1Affine>::from(G1Affine::from_xy(x, y))
+        })
+        .collect::<Option<Vec<_>>>();
+    let (lhs, rhs) = match points.as_deref() {
+        Some([lhs, rhs]) => (*lhs, *rhs),
+        _ => return false,
+    };
+    let g2 = G2Prepared::from(params.g2());
+    let minus_s_g2 = G2Prepared::from(-params.s_g2());
+    bool::from(
+        Bn256::multi_miller_loop(&[(&lhs, &g2), (&rhs, &minus_s_g2)])
+            .final_exponentiation()
+            .is_identity(),
+    )
+}
@@ -4,13 +4,8 @@ use std::{
     marker::PhantomData,
 };
 
-use halo2_base::halo2_proofs::{
-    circuit::{Layouter, Value},
-    plonk::{ConstraintSystem, Error, TableColumn},
-};
-use halo2_base::utils::PrimeField;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+/// The default maximum substring id, i.e., the maximum number of the substr regex definitions of an input string and of the public parts of a decomposed regex.
+pub const DEFAULT_MAX_SUBSTR_ID: usize = 255;
 
 /// Regex definition.
 #[derive(Debug, Clone, Default)]
@@ -21,6 +16,103 @@ pub struct RegexDefs {
     pub substrs: Vec<SubstrRegexDef>,
 }
 
+impl RegexDefs {
+    /// Run the DFA of the regex definitions over `input` off-circuit, e.g., to debug an input string that a regex should match but the circuit does not.
+    ///
+    /// The DFA stops at the first character that has no transition from its state.
+    ///
+    /// # Arguments
+    /// * `input` - the characters matched by the regexes, i.e., the output of the decoding stages if any.
+    ///
+    /// # Return values
+    /// Return [`MatchTrace`] of the characters up to the first failing one.
+    pub fn trace(&self, input: &[u8]) -> MatchTrace {
+        let mut states = vec![self.allstr.first_state_val];
+        let mut substr_ids = vec![];
+        let mut failed_position = None;
+        for (position, char) in input.iter().enumerate() {
+            let state = states[position];
+            let next_state = match self.allstr.state_lookup.get(&(*char, state)) {
+                Some((_, next_state)) => *next_state,
+                None => {
+                    failed_position = Some(position);
+                    break;
+                }
+            };
+            let substr_id = self
+                .substrs
+                .iter()
+                .position(|substr_def| {
+                    substr_def
+                        .valid_state_transitions
+                        .contains(&(state, next_state))
+                })
+                .map_or(0, |substr_idx| substr_idx + 1);
+            states.push(next_state);
+            substr_ids.push(substr_id);
+        }
+        let is_accepted = failed_position.is_none()
+            && states.last().copied() == Some(self.allstr.accepted_state_val);
+        MatchTrace {
+            states,
+            substr_ids,
+            failed_position,
+            is_accepted,
+        }
+    }
+
+    /// Return the pairs of the substr regex definitions tagging the same state transitions, whose substring id in the transition table is ambiguous.
+    ///
+    /// # Return values
+    /// Return [`SubstrOverlap`] of each overlapping pair in ascending order of the indexes, which is empty if no transition is tagged twice.
+    pub fn substr_overlaps(&self) -> Vec<SubstrOverlap> {
+        let mut overlaps = vec![];
+        for (first, first_def) in self.substrs.iter().enumerate() {
+            for (second, second_def) in self.substrs.iter().enumerate().skip(first + 1) {
+                let mut transitions = first_def
+                    .valid_state_transitions
+                    .intersection(&second_def.valid_state_transitions)
+                    .copied()
+                    .collect::<Vec<(u64, u64)>>();
+                if transitions.is_empty() {
+                    continue;
+                }
+                transitions.sort();
+                overlaps.push(SubstrOverlap {
+                    first,
+                    second,
+                    transitions,
+                });
+            }
+        }
+        overlaps
+    }
+}
+
+/// A pair of the substr regex definitions of [`RegexDefs::substr_overlaps`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SubstrOverlap {
+    /// The index of the first substr regex definition.
+    pub first: usize,
+    /// The index of the second substr regex definition.
+    pub second: usize,
+    /// The state transitions `(cur_state, next_state)` tagged by both in ascending order.
+    pub transitions: Vec<(u64, u64)>,
+}
+
+/// The result of [`RegexDefs::trace`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct MatchTrace {
+    /// The state before each traced character followed by the state after the last one, i.e., `states[0]` is the first state.
+    pub states: Vec<u64>,
+    /// The substring id of the transition by each traced character, i.e., the index of its substring definition plus one, or 0 if it is not in any substring.
+    pub substr_ids: Vec<usize>,
+    /// The position of the first character that has no transition from its state, or `None` if every character has.
+    pub failed_position: Option<usize>,
+    /// Whether every character has a transition and the last state is the accepted state.
+    pub is_accepted: bool,
+}
+
 /// Regex that whole of the input string must satisfy.
 #[derive(Debug, Clone, Default)]
 pub struct AllstrRegexDef {
@@ -45,47 +137,91 @@ impl AllstrRegexDef {
     /// Third line: the largest largest state id.
     ///
     /// The following lines: "(current state id in DFA) (next state id) (character)" for each line.
+    /// In [`TextFormatVersion::V2`], the character can be a range "(first character)-(last character)" of the same transition.
+    ///
+    /// The version of the format is detected by [`TextFormatVersion::detect`], so the files written by the older versions of the crate are read into the same definition.
     ///
     /// # Arguments
     /// * `file_path` - a file path of the text file.
     ///
     /// # Return values
     /// Return a new [`AllstrRegexDef`].
+    /// It panics if the file cannot be read or is malformed, which [`AllstrRegexDef::try_read_from_text`] returns as an error instead.
     pub fn read_from_text(file_path: &str) -> Self {
-        let file = File::open(file_path).unwrap();
-        let reader = BufReader::new(file);
+        Self::try_read_from_text(file_path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [`AllstrRegexDef::read_from_text`] except that it returns [`RegexDefError`] instead of panicking.
+    pub fn try_read_from_text(file_path: &str) -> Result<Self, RegexDefError> {
+        Self::try_from_text(&read_text_file(file_path)?)
+    }
+
+    /// Construct [`AllstrRegexDef`] from the content of a text file of [`AllstrRegexDef::read_from_text`], e.g., where no file system is available.
+    ///
+    /// # Arguments
+    /// * `text` - the content of the text file.
+    ///
+    /// # Return values
+    /// Return a new [`AllstrRegexDef`].
+    /// It panics if the text is malformed, which [`AllstrRegexDef::try_from_text`] returns as an error instead.
+    pub fn from_text(text: &str) -> Self {
+        Self::try_from_text(text).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [`AllstrRegexDef::from_text`] except that it returns [`RegexDefError`] instead of panicking.
+    pub fn try_from_text(text: &str) -> Result<Self, RegexDefError> {
+        let (version, lines) = parse_text_lines(text)?;
         let mut state_lookup = HashMap::<(u8, u64), (usize, u64)>::new();
         // let mut array = Vec::new();
         let mut first_state_val = 0;
         let mut accepted_state_val = 0;
         let mut largest_state_val = 0;
+        // The index of each transition, which is the line index in the legacy format.
+        let mut transition_idx = 3;
 
-        for (idx, line) in reader.lines().enumerate() {
-            let line = line.expect(&format!("fail to get {}-th line.", idx));
-            let elements: Vec<u64> = line
-                .split_whitespace()
-                .map(|s| {
-                    s.parse()
-                        .expect(&format!("fail to parse string {} at {}-th line.", s, idx))
-                })
-                .collect();
-            if idx == 0 {
-                first_state_val = elements[0];
-            } else if idx == 1 {
-                accepted_state_val = elements[0];
-            } else if idx == 2 {
-                largest_state_val = elements[0];
-            } else {
-                state_lookup.insert((elements[2] as u8, elements[0]), (idx, elements[1]));
+        for (pos, (idx, line)) in lines.iter().enumerate() {
+            let tokens = line.split_whitespace().collect::<Vec<&str>>();
+            let token = |i: usize| {
+                tokens
+                    .get(i)
+                    .copied()
+                    .ok_or(RegexDefError::MissingElement { line: *idx })
+            };
+            if pos < 3 {
+                let value = parse_text_element(token(0)?, *idx)?;
+                if pos == 0 {
+                    first_state_val = value;
+                } else if pos == 1 {
+                    accepted_state_val = value;
+                } else {
+                    largest_state_val = value;
+                }
+                continue;
+            }
+            let cur_state = parse_text_element(token(0)?, *idx)?;
+            let next_state = parse_text_element(token(1)?, *idx)?;
+            let (first_char, last_char) = match (version, token(2)?.split_once('-')) {
+                (TextFormatVersion::V2, Some((first, last))) => (
+                    parse_text_element(first, *idx)?,
+                    parse_text_element(last, *idx)?,
+                ),
+                _ => {
+                    let char = parse_text_element(token(2)?, *idx)?;
+                    (char, char)
+                }
+            };
+            for char in first_char..=last_char {
+                state_lookup.insert((char as u8, cur_state), (transition_idx, next_state));
+                transition_idx += 1;
             }
             // array.push(elements);
         }
-        Self {
+        Ok(Self {
             state_lookup,
             first_state_val,
             accepted_state_val,
             largest_state_val,
-        }
+        })
     }
 }
 
@@ -93,6 +229,7 @@ impl AllstrRegexDef {
 #[derive(Debug, Clone, Default)]
 pub struct SubstrRegexDef {
     /// Maximum length of the substring.
+    /// [`RegexVerifyConfig`] constrains that every run of the characters of the substring is at most `max_length` long.
     pub max_length: usize,
     /// A minimum position of the first character in the substring.
     /// # Notes
@@ -155,14 +292,38 @@ impl SubstrRegexDef {
     ///
     /// The following lines: `(current_state_id, next_state_id)` for each line. The ids within a line are separated by spaces.
     ///
+    /// The version of the format is detected by [`TextFormatVersion::detect`] as [`AllstrRegexDef::read_from_text`].
+    ///
     /// # Arguments
     /// * `file_path` - a file path of the text file.
     ///
     /// # Return values
     /// Returns a new [`SubstrRegexDef`].
+    /// It panics if the file cannot be read or is malformed, which [`SubstrRegexDef::try_read_from_text`] returns as an error instead.
     pub fn read_from_text(file_path: &str) -> Self {
-        let file = File::open(file_path).unwrap();
-        let reader = BufReader::new(file);
+        Self::try_read_from_text(file_path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [`SubstrRegexDef::read_from_text`] except that it returns [`RegexDefError`] instead of panicking.
+    pub fn try_read_from_text(file_path: &str) -> Result<Self, RegexDefError> {
+        Self::try_from_text(&read_text_file(file_path)?)
+    }
+
+    /// Construct [`SubstrRegexDef`] from the content of a text file of [`SubstrRegexDef::read_from_text`].
+    ///
+    /// # Arguments
+    /// * `text` - the content of the text file.
+    ///
+    /// # Return values
+    /// Returns a new [`SubstrRegexDef`].
+    /// It panics if the text is malformed, which [`SubstrRegexDef::try_from_text`] returns as an error instead.
+    pub fn from_text(text: &str) -> Self {
+        Self::try_from_text(text).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [`SubstrRegexDef::from_text`] except that it returns [`RegexDefError`] instead of panicking.
+    pub fn try_from_text(text: &str) -> Result<Self, RegexDefError> {
+        let (_, lines) = parse_text_lines(text)?;
         let mut valid_state_transitions = HashSet::<(u64, u64)>::new();
         // let mut one_state_path = HashMap::<u64, u64>::new();
         let mut max_length = 0;
@@ -171,27 +332,29 @@ impl SubstrRegexDef {
         let mut start_states = vec![];
         let mut end_states = vec![];
 
-        for (idx, line) in reader.lines().enumerate() {
-            let line = line.expect(&format!("fail to get {}-th line.", idx));
-            let elements: Vec<u64> = line
+        for (pos, (idx, line)) in lines.iter().enumerate() {
+            let elements = line
                 .split_whitespace()
-                .map(|s| {
-                    s.parse()
-                        .expect(&format!("fail to parse string {} at {}-th line.", s, idx))
-                })
-                .collect();
-            if idx == 0 {
-                max_length = elements[0] as usize;
-            } else if idx == 1 {
-                min_position = elements[0];
-            } else if idx == 2 {
-                max_position = elements[0];
-            } else if idx == 3 {
+                .map(|s| parse_text_element(s, *idx))
+                .collect::<Result<Vec<u64>, RegexDefError>>()?;
+            let element = |i: usize| {
+                elements
+                    .get(i)
+                    .copied()
+                    .ok_or(RegexDefError::MissingElement { line: *idx })
+            };
+            if pos == 0 {
+                max_length = element(0)? as usize;
+            } else if pos == 1 {
+                min_position = element(0)?;
+            } else if pos == 2 {
+                max_position = element(0)?;
+            } else if pos == 3 {
                 start_states = elements;
-            } else if idx == 4 {
+            } else if pos == 4 {
                 end_states = elements;
             } else {
-                valid_state_transitions.insert((elements[0], elements[1]));
+                valid_state_transitions.insert((element(0)?, element(1)?));
                 // if elements[0] < start_state {
                 //     start_state = elements[0];
                 // }
@@ -210,13 +373,202 @@ impl SubstrRegexDef {
         //     start_state, end_state, valid_state_transitions
         // );
 
-        Self {
+        Ok(Self {
             max_length,
             min_position,
             max_position,
             valid_state_transitions,
             start_states,
             end_states,
+        })
+    }
+}
+
+/// The version of the text formats of [`AllstrRegexDef`] and [`SubstrRegexDef`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFormatVersion {
+    /// The format without any header, which is written by [`DecomposedRegexConfig::gen_regex_files`](crate::vrm::DecomposedRegexConfig::gen_regex_files).
+    Legacy,
+    /// The format whose first line is [`TextFormatVersion::V2_HEADER`], after which the lines starting with `#` are comments.
+    V2,
+}
+
+impl TextFormatVersion {
+    /// The first line of the files of [`TextFormatVersion::V2`].
+    pub const V2_HEADER: &'static str = "# halo2-regex v2";
+
+    /// Detect the version of a text file from its first line.
+    ///
+    /// It panics if the line is the header of an unknown version, e.g., written by a newer version of the crate, which [`TextFormatVersion::try_detect`] returns as an error instead.
+    ///
+    /// # Arguments
+    /// * `first_line` - the first line of the text file.
+    ///
+    /// # Return values
+    /// Return the detected [`TextFormatVersion`].
+    pub fn detect(first_line: &str) -> Self {
+        Self::try_detect(first_line).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [`TextFormatVersion::detect`] except that it returns [`RegexDefError::UnsupportedFormat`] instead of panicking.
+    pub fn try_detect(first_line: &str) -> Result<Self, RegexDefError> {
+        let line = first_line.trim();
+        if !line.starts_with("# halo2-regex") {
+            Ok(Self::Legacy)
+        } else if line == Self::V2_HEADER {
+            Ok(Self::V2)
+        } else {
+            Err(RegexDefError::UnsupportedFormat(line.to_string()))
+        }
+    }
+}
+
+/// Error definitions of reading the text files of [`AllstrRegexDef`] and [`SubstrRegexDef`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum RegexDefError {
+    #[error("fail to read {path}: {error}")]
+    Unreadable { path: String, error: String },
+    #[error("unsupported text format {0}; upgrade the crate to read it.")]
+    UnsupportedFormat(String),
+    #[error("fail to parse string {element} at {line}-th line.")]
+    InvalidElement { element: String, line: usize },
+    #[error("too few elements at {line}-th line.")]
+    MissingElement { line: usize },
+}
+
+fn read_text_file(file_path: &str) -> Result<String, RegexDefError> {
+    std::fs::read_to_string(file_path).map_err(|e| RegexDefError::Unreadable {
+        path: file_path.to_string(),
+        error: e.to_string(),
+    })
+}
+
+/// Split the content of a text file of [`AllstrRegexDef`] or [`SubstrRegexDef`] into the lines paired with their indexes, skipping the header and the comments of its [`TextFormatVersion`].
+fn parse_text_lines(
+    text: &str,
+) -> Result<(TextFormatVersion, Vec<(usize, String)>), RegexDefError> {
+    let mut version = TextFormatVersion::Legacy;
+    let mut lines = vec![];
+    for (idx, line) in text.lines().enumerate() {
+        if idx == 0 {
+            version = TextFormatVersion::try_detect(line)?;
+        }
+        // The legacy format has no comment, and a line of no state id is an empty set of states.
+        if version == TextFormatVersion::V2 && line.trim_start().starts_with('#') {
+            continue;
         }
+        lines.push((idx, line.to_string()));
+    }
+    Ok((version, lines))
+}
+
+fn parse_text_element(element: &str, idx: usize) -> Result<u64, RegexDefError> {
+    element.parse().map_err(|_| RegexDefError::InvalidElement {
+        element: element.to_string(),
+        line: idx,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_trace() {
+        let regex_defs = RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                "./test_regexes/substr4_test_lookup.txt",
+            )],
+        };
+        let trace = regex_defs.trace(b"amount=1000;");
+        assert_eq!(trace.failed_position, None);
+        assert!(trace.is_accepted);
+        assert_eq!(trace.states.len(), 13);
+        assert_eq!(trace.substr_ids, vec![0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 0]);
+
+        let trace = regex_defs.trace(b"amount=x;");
+        assert_eq!(trace.failed_position, Some(7));
+        assert!(!trace.is_accepted);
+        assert_eq!(trace.states.len(), 8);
+        assert_eq!(trace.substr_ids.len(), 7);
+    }
+
+    #[test]
+    fn test_substr_overlaps() {
+        let substr_def = SubstrRegexDef::read_from_text("./test_regexes/substr4_test_lookup.txt");
+        let mut regex_defs = RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+            substrs: vec![substr_def.clone()],
+        };
+        assert!(regex_defs.substr_overlaps().is_empty());
+
+        regex_defs.substrs.push(substr_def);
+        let overlaps = regex_defs.substr_overlaps();
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!((overlaps[0].first, overlaps[0].second), (0, 1));
+        assert_eq!(overlaps[0].transitions, vec![(7, 8), (8, 8)]);
+    }
+
+    #[test]
+    fn test_read_text_formats() {
+        let dir = std::env::temp_dir();
+        let write = |file_name: &str, text: &str| {
+            let path = dir.join(file_name);
+            std::fs::write(&path, text).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let legacy = AllstrRegexDef::read_from_text(&write(
+            "halo2_regex_test_allstr_legacy.txt",
+            "0\n2\n2\n0 1 97\n0 1 98\n0 1 99\n1 2 59\n",
+        ));
+        let v2 = AllstrRegexDef::read_from_text(&write(
+            "halo2_regex_test_allstr_v2.txt",
+            "# halo2-regex v2\n# a, b or c followed by ;\n0\n2\n2\n0 1 97-99\n1 2 59\n",
+        ));
+        assert_eq!(v2.state_lookup, legacy.state_lookup);
+        assert_eq!(
+            (
+                v2.first_state_val,
+                v2.accepted_state_val,
+                v2.largest_state_val
+            ),
+            (0, 2, 2)
+        );
+
+        let legacy = SubstrRegexDef::read_from_text(&write(
+            "halo2_regex_test_substr_legacy.txt",
+            "8\n0\n63\n0 \n1 \n0 1\n",
+        ));
+        let v2 = SubstrRegexDef::read_from_text(&write(
+            "halo2_regex_test_substr_v2.txt",
+            "# halo2-regex v2\n8\n0\n63\n# the start states and the end states\n0 \n1 \n0 1\n",
+        ));
+        assert_eq!(v2.valid_state_transitions, legacy.valid_state_transitions);
+        assert_eq!(v2.start_states, legacy.start_states);
+        assert_eq!(v2.end_states, legacy.end_states);
+        assert_eq!(
+            (v2.max_length, v2.min_position, v2.max_position),
+            (8, 0, 63)
+        );
+    }
+
+    #[test]
+    fn test_from_text() {
+        let path = "./test_regexes/regex4_test_lookup.txt";
+        let from_file = AllstrRegexDef::read_from_text(path);
+        let from_text = AllstrRegexDef::from_text(&std::fs::read_to_string(path).unwrap());
+        assert_eq!(from_text.state_lookup, from_file.state_lookup);
+        assert_eq!(from_text.accepted_state_val, from_file.accepted_state_val);
+
+        let path = "./test_regexes/substr4_test_lookup.txt";
+        let from_file = SubstrRegexDef::read_from_text(path);
+        let from_text = SubstrRegexDef::from_text(&std::fs::read_to_string(path).unwrap());
+        assert_eq!(
+            from_text.valid_state_transitions,
+            from_file.valid_state_transitions
+        );
+        assert_eq!(from_text.start_states, from_file.start_states);
+        assert_eq!(from_text.end_states, from_file.end_states);
     }
 }
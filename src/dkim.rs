@@ -0,0 +1,235 @@
+use crate::gadgets::sha256::Sha256Config;
+use crate::gadgets::substr::SubstrCompactConfig;
+use crate::table::RegexTableCache;
+use crate::{AssignedRegexResult, RegexDefs, RegexVerifyConfig, RegexWitness};
+use halo2_base::halo2_proofs::plonk::{ConstraintSystem, Error};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// The number of the base64 characters of a SHA-256 body hash in the `bh=` tag of a DKIM signature.
+pub const BODY_HASH_BASE64_SIZE: usize = 44;
+
+/// Output type definition of [`DkimHookConfig`], i.e., the cells over which a DKIM chip of the parent circuit, e.g., of halo2-rsa, constrains the signature.
+#[derive(Debug, Clone)]
+pub struct AssignedDkimHooks<'a, F: PrimeField> {
+    /// The assigned bytes of the canonicalized header, which is `padding_byte` beyond the header.
+    /// The length is equal to `max_header_size`.
+    pub header: Vec<AssignedValue<'a, F>>,
+    /// The assigned enable flags of `header`, which are one iff the byte is a part of the header.
+    /// The length is equal to `max_header_size`.
+    pub header_enable_flags: Vec<AssignedValue<'a, F>>,
+    /// The assigned length of the header.
+    pub header_len: AssignedValue<'a, F>,
+    /// The assigned bytes of the SHA-256 digest of the header, which are signed by the RSA signature of DKIM.
+    /// The length is equal to [`SHA256_DIGEST_SIZE`](crate::gadgets::sha256::SHA256_DIGEST_SIZE).
+    pub header_hash: Vec<AssignedValue<'a, F>>,
+    /// The assigned base64 characters of the body hash extracted from the `bh=` tag, padded with zeros.
+    /// The length is equal to [`BODY_HASH_BASE64_SIZE`].
+    pub body_hash: Vec<AssignedValue<'a, F>>,
+    /// All the outputs of the regex verification of the header, e.g., for the other substrings.
+    pub regex_result: AssignedRegexResult<'a, F>,
+}
+
+/// Configuration to verify the regexes of an email header and expose the cells of the header and its body hash to a DKIM chip.
+///
+/// The regex definitions must extract the base64 body hash of the `bh=` tag of the `DKIM-Signature` header as the substring of `body_hash_substr_id`.
+/// The parent circuit then constrains the RSA signature over `header_hash` and the SHA-256 digest of the body to be equal to the decoded `body_hash`.
+#[derive(Debug, Clone)]
+pub struct DkimHookConfig<F: PrimeField> {
+    /// The regex verification of the header.
+    pub regex: RegexVerifyConfig<F>,
+    sha256: Sha256Config<F>,
+    compact: SubstrCompactConfig<F>,
+    body_hash_substr_id: usize,
+}
+
+impl<F: PrimeField> DkimHookConfig<F> {
+    /// Configure a new [`DkimHookConfig`] whose chips share `gate` with the DKIM chip of the parent circuit.
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `max_header_size` - the maximum length of the canonicalized header.
+    /// * `gate` - the [`FlexGateConfig`] owned by the parent circuit.
+    /// * `regex_defs` - a vector of regex definitions applied to the header.
+    /// * `body_hash_substr_id` - the substring id of the body hash in `regex_defs`.
+    /// * `cache` - the tables configured so far.
+    ///
+    /// # Return values
+    /// Return a new [`DkimHookConfig`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        max_header_size: usize,
+        gate: &FlexGateConfig<F>,
+        regex_defs: Vec<RegexDefs>,
+        body_hash_substr_id: usize,
+        cache: &mut RegexTableCache<F>,
+    ) -> Self {
+        let regex =
+            RegexVerifyConfig::configure_with_gate(meta, max_header_size, gate, regex_defs, cache);
+        Self {
+            regex,
+            sha256: Sha256Config::new(gate.clone()),
+            compact: SubstrCompactConfig::new(gate.clone()),
+            body_hash_substr_id,
+        }
+    }
+
+    /// Verify the regexes of the header of `witness` and assign the cells for the DKIM chip.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context of the [`FlexGateConfig`] passed to [`DkimHookConfig::configure`].
+    /// * `witness` - the output of [`RegexVerifyConfig::derive_witness`] for the canonicalized header.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedDkimHooks`].
+    pub fn assign_header<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        witness: &RegexWitness,
+    ) -> Result<AssignedDkimHooks<'a, F>, Error> {
+        let regex_result = self.regex.match_substrs_with_witness(ctx, witness)?;
+        let gate = self.regex.gate();
+        let mut header_len = gate.load_zero(ctx);
+        for flag in regex_result.all_enable_flags.iter() {
+            header_len = gate.add(
+                ctx,
+                QuantumCell::Existing(&header_len),
+                QuantumCell::Existing(flag),
+            );
+        }
+        let header_hash = self.sha256.digest_regex_input(ctx, &regex_result)?;
+        let body_hash = self.compact.compact(
+            ctx,
+            &regex_result,
+            self.body_hash_substr_id,
+            BODY_HASH_BASE64_SIZE,
+        )?;
+        Ok(AssignedDkimHooks {
+            header: regex_result.all_characters.clone(),
+            header_enable_flags: regex_result.all_enable_flags.clone(),
+            header_len,
+            header_hash,
+            body_hash,
+            regex_result,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::sha256::sha256;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::Circuit,
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const K: usize = 17;
+    const MAX_HEADER_SIZE: usize = 32;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestCircuit<F: PrimeField> {
+        header: Vec<u8>,
+        body_hash: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCircuit<F> {
+        type Config = DkimHookConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                header: vec![],
+                body_hash: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[6],
+                1,
+                0,
+                K,
+            );
+            // The digits stand in for the body hash.
+            let regex_defs = vec![RegexDefs {
+                allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+                substrs: vec![SubstrRegexDef::read_from_text(
+                    "./test_regexes/substr4_test_lookup.txt",
+                )],
+            }];
+            DkimHookConfig::configure(
+                meta,
+                MAX_HEADER_SIZE,
+                &gate,
+                regex_defs,
+                1,
+                &mut RegexTableCache::new(),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.regex.load(&mut layouter)?;
+            let witness = config.regex.derive_witness(&self.header)?;
+            let gate = config.regex.gate().clone();
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "dkim",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let hooks = config.assign_header(ctx, &witness)?;
+                    assert_eq!(hooks.header.len(), MAX_HEADER_SIZE);
+                    hooks
+                        .header_len
+                        .value()
+                        .map(|v| assert_eq!(*v, F::from(self.header.len() as u64)));
+                    for (assigned, expected) in hooks.header_hash.iter().zip(sha256(&self.header)) {
+                        assigned
+                            .value()
+                            .map(|v| assert_eq!(*v, F::from(expected as u64)));
+                    }
+                    for (idx, assigned) in hooks.body_hash.iter().enumerate() {
+                        let expected = self.body_hash.get(idx).copied().unwrap_or(0);
+                        assigned
+                            .value()
+                            .map(|v| assert_eq!(*v, F::from(expected as u64)));
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_dkim_hooks() {
+        let circuit = TestCircuit::<Fr> {
+            header: b"amount=1000;".to_vec(),
+            body_hash: b"1000".to_vec(),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
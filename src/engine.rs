@@ -0,0 +1,241 @@
+use halo2_base::halo2_proofs::{
+    arithmetic::CurveAffine,
+    halo2curves::{bn256::Bn256, pairing::Engine},
+    plonk::{verify_proof, Circuit, Error, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{commitment::ParamsKZG, multiopen::VerifierGWC, strategy::SingleStrategy},
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
+    SerdeFormat,
+};
+#[cfg(feature = "prover")]
+use halo2_base::halo2_proofs::{
+    plonk::{create_proof, keygen_pk, keygen_vk, ProvingKey},
+    poly::kzg::{commitment::KZGCommitmentScheme, multiopen::ProverGWC},
+    transcript::{Blake2bWrite, TranscriptWriterBuffer},
+};
+use halo2_base::utils::PrimeField;
+#[cfg(feature = "prover")]
+use rand::RngCore;
+use std::fmt::Debug;
+use std::io::{Read, Write};
+
+/// A pairing-friendly curve over which the regex verification circuit is proven with the KZG commitment scheme and the GWC multiopen scheme.
+///
+/// The bounds of halo2 on the serialization of the KZG parameters and the keys are not nameable outside halo2, so that each operation is implemented for a concrete curve by `impl_proving_engine!`.
+/// It is implemented for [`Bn256`], whose proofs are verifiable on the EVM.
+pub trait ProvingEngine: Engine + Debug + Sized + 'static {
+    /// The scalar field of the circuit.
+    type Fr: PrimeField;
+    /// The affine points of G1, to which the keys and the proofs commit.
+    type Affine: CurveAffine<ScalarExt = Self::Fr>;
+
+    /// Generate the KZG parameters of 2^`k` rows from the toxic waste drawn from `rng`.
+    #[cfg(feature = "prover")]
+    fn setup<R: RngCore>(k: u32, rng: R) -> ParamsKZG<Self>;
+
+    /// Read the KZG parameters from `reader`.
+    fn read_params<R: Read>(reader: &mut R) -> Result<ParamsKZG<Self>, std::io::Error>;
+
+    /// Write `params` to `writer`.
+    fn write_params<W: Write>(
+        params: &ParamsKZG<Self>,
+        writer: &mut W,
+    ) -> Result<(), std::io::Error>;
+
+    /// Return the degree of the number of rows of `params`.
+    fn params_k(params: &ParamsKZG<Self>) -> u32;
+
+    /// Downsize `params` to 2^`k` rows.
+    fn downsize_params(params: &mut ParamsKZG<Self>, k: u32);
+
+    /// Generate the verifying key of `circuit`.
+    #[cfg(feature = "prover")]
+    fn keygen_vk<C: Circuit<Self::Fr>>(
+        params: &ParamsKZG<Self>,
+        circuit: &C,
+    ) -> Result<VerifyingKey<Self::Affine>, Error>;
+
+    /// Generate the proving key of `circuit` from its verifying key `vk`.
+    #[cfg(feature = "prover")]
+    fn keygen_pk<C: Circuit<Self::Fr>>(
+        params: &ParamsKZG<Self>,
+        vk: VerifyingKey<Self::Affine>,
+        circuit: &C,
+    ) -> Result<ProvingKey<Self::Affine>, Error>;
+
+    /// Read the verifying key of the circuit `C` in `format` from `reader`.
+    fn read_vk<C: Circuit<Self::Fr>, R: Read>(
+        reader: &mut R,
+        format: SerdeFormat,
+    ) -> Result<VerifyingKey<Self::Affine>, std::io::Error>;
+
+    /// Write `vk` in `format` to `writer`.
+    fn write_vk<W: Write>(
+        vk: &VerifyingKey<Self::Affine>,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> Result<(), std::io::Error>;
+
+    /// Read the proving key of the circuit `C` in `format` from `reader`.
+    #[cfg(feature = "prover")]
+    fn read_pk<C: Circuit<Self::Fr>, R: Read>(
+        reader: &mut R,
+        format: SerdeFormat,
+    ) -> Result<ProvingKey<Self::Affine>, std::io::Error>;
+
+    /// Write `pk` in `format` to `writer`.
+    #[cfg(feature = "prover")]
+    fn write_pk<W: Write>(
+        pk: &ProvingKey<Self::Affine>,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> Result<(), std::io::Error>;
+
+    /// Generate one proof of `circuits` with the Blake2b transcript, where `instances` are the instances of each circuit.
+    #[cfg(feature = "prover")]
+    fn create_proof<C: Circuit<Self::Fr>, R: RngCore>(
+        params: &ParamsKZG<Self>,
+        pk: &ProvingKey<Self::Affine>,
+        circuits: &[C],
+        instances: &[&[&[Self::Fr]]],
+        rng: R,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Verify `proof` generated by [`ProvingEngine::create_proof`], where `instances` are the instances of each circuit.
+    fn verify_proof(
+        params: &ParamsKZG<Self>,
+        vk: &VerifyingKey<Self::Affine>,
+        proof: &[u8],
+        instances: &[&[&[Self::Fr]]],
+    ) -> Result<(), Error>;
+}
+
+/// Implement [`ProvingEngine`] for a pairing-friendly curve of halo2curves with its scalar field and its affine points of G1.
+macro_rules! impl_proving_engine {
+    ($engine:ty, $fr:ty, $g1_affine:ty) => {
+        impl ProvingEngine for $engine {
+            type Fr = $fr;
+            type Affine = $g1_affine;
+
+            #[cfg(feature = "prover")]
+            fn setup<R: RngCore>(k: u32, rng: R) -> ParamsKZG<Self> {
+                ParamsKZG::<Self>::setup(k, rng)
+            }
+
+            fn read_params<R: Read>(reader: &mut R) -> Result<ParamsKZG<Self>, std::io::Error> {
+                ParamsKZG::<Self>::read(reader)
+            }
+
+            fn write_params<W: Write>(
+                params: &ParamsKZG<Self>,
+                writer: &mut W,
+            ) -> Result<(), std::io::Error> {
+                params.write(writer)
+            }
+
+            fn params_k(params: &ParamsKZG<Self>) -> u32 {
+                params.k()
+            }
+
+            fn downsize_params(params: &mut ParamsKZG<Self>, k: u32) {
+                params.downsize(k)
+            }
+
+            #[cfg(feature = "prover")]
+            fn keygen_vk<C: Circuit<Self::Fr>>(
+                params: &ParamsKZG<Self>,
+                circuit: &C,
+            ) -> Result<VerifyingKey<Self::Affine>, Error> {
+                keygen_vk(params, circuit)
+            }
+
+            #[cfg(feature = "prover")]
+            fn keygen_pk<C: Circuit<Self::Fr>>(
+                params: &ParamsKZG<Self>,
+                vk: VerifyingKey<Self::Affine>,
+                circuit: &C,
+            ) -> Result<ProvingKey<Self::Affine>, Error> {
+                keygen_pk(params, vk, circuit)
+            }
+
+            fn read_vk<C: Circuit<Self::Fr>, R: Read>(
+                reader: &mut R,
+                format: SerdeFormat,
+            ) -> Result<VerifyingKey<Self::Affine>, std::io::Error> {
+                VerifyingKey::<Self::Affine>::read::<_, C>(reader, format)
+            }
+
+            fn write_vk<W: Write>(
+                vk: &VerifyingKey<Self::Affine>,
+                writer: &mut W,
+                format: SerdeFormat,
+            ) -> Result<(), std::io::Error> {
+                vk.write(writer, format)
+            }
+
+            #[cfg(feature = "prover")]
+            fn read_pk<C: Circuit<Self::Fr>, R: Read>(
+                reader: &mut R,
+                format: SerdeFormat,
+            ) -> Result<ProvingKey<Self::Affine>, std::io::Error> {
+                ProvingKey::<Self::Affine>::read::<_, C>(reader, format)
+            }
+
+            #[cfg(feature = "prover")]
+            fn write_pk<W: Write>(
+                pk: &ProvingKey<Self::Affine>,
+                writer: &mut W,
+                format: SerdeFormat,
+            ) -> Result<(), std::io::Error> {
+                pk.write(writer, format)
+            }
+
+            #[cfg(feature = "prover")]
+            fn create_proof<C: Circuit<Self::Fr>, R: RngCore>(
+                params: &ParamsKZG<Self>,
+                pk: &ProvingKey<Self::Affine>,
+                circuits: &[C],
+                instances: &[&[&[Self::Fr]]],
+                rng: R,
+            ) -> Result<Vec<u8>, Error> {
+                let mut transcript = Blake2bWrite::<_, Self::Affine, Challenge255<_>>::init(vec![]);
+                create_proof::<KZGCommitmentScheme<Self>, ProverGWC<_>, _, _, _, _>(
+                    params,
+                    pk,
+                    circuits,
+                    instances,
+                    rng,
+                    &mut transcript,
+                )?;
+                Ok(transcript.finalize())
+            }
+
+            fn verify_proof(
+                params: &ParamsKZG<Self>,
+                vk: &VerifyingKey<Self::Affine>,
+                proof: &[u8],
+                instances: &[&[&[Self::Fr]]],
+            ) -> Result<(), Error> {
+                let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+                let verifier_params = params.verifier_params();
+                let strategy = SingleStrategy::new(verifier_params);
+                verify_proof::<_, VerifierGWC<_>, _, _, _>(
+                    verifier_params,
+                    vk,
+                    strategy,
+                    instances,
+                    &mut transcript,
+                )?;
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_proving_engine!(
+    Bn256,
+    halo2_base::halo2_proofs::halo2curves::bn256::Fr,
+    halo2_base::halo2_proofs::halo2curves::bn256::G1Affine
+);
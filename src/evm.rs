@@ -0,0 +1,476 @@
+use crate::helpers::{
+    open_key_file, read_params, write_proof_metadata, KeySerdeFormat, ProofMetadata, ProveError,
+    RegexCircuit,
+};
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use halo2_base::halo2_proofs::plonk::{create_proof, Circuit, ProvingKey, VerifyingKey};
+use halo2_base::halo2_proofs::poly::commitment::{Params, ParamsProver};
+use halo2_base::halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_base::halo2_proofs::poly::kzg::multiopen::ProverGWC;
+use halo2_base::halo2_proofs::transcript::TranscriptWriterBuffer;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use snark_verifier::loader::evm::{self, EvmLoader};
+use snark_verifier::loader::native::NativeLoader;
+use snark_verifier::pcs::kzg::{Gwc19, KzgAs};
+use snark_verifier::system::halo2::{compile, transcript::evm::EvmTranscript, Config};
+use snark_verifier::verifier::{self, SnarkVerifier};
+use snark_verifier_sdk::evm::{gen_evm_proof_shplonk, gen_evm_verifier_shplonk};
+use snark_verifier_sdk::halo2::{aggregation::PublicAggregationCircuit, gen_snark_shplonk};
+use snark_verifier_sdk::{gen_pk, CircuitExt, Snark, LIMBS};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::rc::Rc;
+
+/// The verifier of the proofs with the GWC multiopen scheme, which is that of [`prove`](crate::helpers::prove).
+type PlonkVerifier = verifier::plonk::PlonkVerifier<KzgAs<Bn256, Gwc19>>;
+
+/// The name of the transcript of the proofs generated by [`prove_evm`] recorded in [`ProofMetadata`].
+pub const EVM_TRANSCRIPT: &str = "keccak256";
+
+/// The environment variable of the file path of the json configuration of the aggregation circuit, i.e., its `degree`, `num_advice`, `num_lookup_advice`, `num_fixed` and `lookup_bits`, which is read by snark-verifier when the circuit is configured.
+pub const AGGREGATION_CONFIG_ENV: &str = "VERIFY_CONFIG";
+
+/// The number of the instances of the accumulator of the aggregation circuit, which precede the instances of the regex proof.
+pub const NUM_ACCUMULATOR_INSTANCES: usize = 4 * LIMBS;
+
+/// Generate the Yul code of the verifier contract of the proofs generated by [`prove_evm`].
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `vk` - the verifying key.
+/// * `num_instance` - the number of the instances, e.g., the length of the output of [`RegexCircuit::instances`](crate::helpers::RegexCircuit::instances).
+///
+/// # Return values
+/// Return the Yul code.
+pub fn gen_evm_verifier_yul(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: usize,
+) -> String {
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg().with_num_instance(vec![num_instance]),
+    );
+    let vk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+    let instances = transcript.load_instances(vec![num_instance]);
+    let proof = PlonkVerifier::read_proof(&vk, &protocol, &instances, &mut transcript).unwrap();
+    PlonkVerifier::verify(&vk, &protocol, &instances, &proof).unwrap();
+    loader.yul_code()
+}
+
+/// Compile the Yul code generated by [`gen_evm_verifier_yul`] to the deployment code of the verifier contract, which requires `solc`.
+pub fn gen_evm_verifier(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: usize,
+) -> Vec<u8> {
+    evm::compile_yul(&gen_evm_verifier_yul(params, vk, num_instance))
+}
+
+/// Generate a proof verified by the contract of [`gen_evm_verifier`], whose transcript is Keccak256 instead of Blake2b.
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `pk` - the proving key.
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+/// * `circuit` - a regex verification circuit.
+/// * `instances` - the instances of the circuit.
+///
+/// # Return values
+/// Return the bytes of the proof.
+pub fn prove_evm_with_keys<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    seed: Option<[u8; 32]>,
+    circuit: C,
+    instances: &[Fr],
+) -> Result<Vec<u8>, ProveError> {
+    let rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    let mut transcript =
+        EvmTranscript::<G1Affine, NativeLoader, Vec<u8>, Vec<u8>>::init(Vec::new());
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[instances]],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Generate a proof verified by the contract of [`gen_evm_verifier`] and write it with its metadata, as [`prove`](crate::helpers::prove) does.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `pk_path` - a file path of the proving key.
+/// * `serde_format` - the serialization format of the proving key.
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+/// * `proof_path` - a file path of the output proof.
+/// * `circuit` - a regex verification circuit.
+/// * `instances` - the instances of the circuit.
+pub fn prove_evm<C: Circuit<Fr>>(
+    params_path: &str,
+    pk_path: &str,
+    serde_format: KeySerdeFormat,
+    seed: Option<[u8; 32]>,
+    proof_path: &str,
+    circuit: C,
+    instances: &[Fr],
+) -> Result<(), ProveError> {
+    let params = read_params::<Bn256>(params_path)?;
+    let pk = {
+        let mut reader = open_key_file(pk_path)?;
+        ProvingKey::<G1Affine>::read::<_, C>(&mut reader, serde_format.into())
+            .map_err(ProveError::InvalidProvingKey)?
+    };
+    let proof = prove_evm_with_keys(&params, &pk, seed, circuit, instances)?;
+    let mut writer = BufWriter::new(File::create(proof_path)?);
+    writer.write_all(&proof)?;
+    writer.flush()?;
+    let mut metadata = ProofMetadata::new(params.k(), instances);
+    metadata.transcript = EVM_TRANSCRIPT.to_string();
+    write_proof_metadata(proof_path, &metadata)?;
+    Ok(())
+}
+
+/// Encode the calldata of the verifier contract, i.e., the 32-byte big-endian words of `instances` followed by `proof`.
+pub fn encode_calldata(instances: &[Fr], proof: &[u8]) -> Vec<u8> {
+    evm::encode_calldata(&[instances.to_vec()], proof)
+}
+
+/// Deploy the verifier contract of `deployment_code` in a local EVM and call it with `calldata`.
+///
+/// # Return values
+/// Return the gas used by the call, or the error if the contract reverts, i.e., the proof is invalid.
+pub fn evm_verify(deployment_code: Vec<u8>, calldata: Vec<u8>) -> Result<u64, String> {
+    evm::deploy_and_call(deployment_code, calldata)
+}
+
+/// The gas of every transaction before its calldata and its execution.
+pub const TX_BASE_GAS: u64 = 21000;
+
+/// The gas of a zero byte of calldata.
+pub const CALLDATA_ZERO_BYTE_GAS: u64 = 4;
+
+/// The gas of a non-zero byte of calldata.
+pub const CALLDATA_NONZERO_BYTE_GAS: u64 = 16;
+
+/// The head of a call of a precompile in the Yul code of [`gen_evm_verifier_yul`], which is followed by the address of the precompile, e.g., `0x8` of the pairing.
+const PAIRING_CALL: &str = "staticcall(gas(),";
+
+/// The Yul function replacing the pairing precompile in [`stub_pairing`], which takes the arguments of `staticcall` except for the address and writes the successful result without checking anything.
+const PAIRING_STUB: &str = "function stub_pairing(g, a, b, c, d) -> ok { mstore(c, 1) ok := 1 }";
+
+/// The gas used by a call to the verifier contract of [`gen_evm_verifier`], broken down by its parts.
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvmGasReport {
+    /// The gas used by the call measured in a local EVM.
+    pub total: u64,
+    /// The base gas of the transaction, i.e., [`TX_BASE_GAS`].
+    pub base: u64,
+    /// The gas of the calldata, i.e., the instances and the proof, measured by calling an empty contract with the same calldata.
+    pub calldata: u64,
+    /// The gas of the pairing check, measured as the difference from the call to the contract whose pairing precompile is stubbed by [`stub_pairing`].
+    pub pairing: u64,
+    /// The rest of [`EvmGasReport::total`], e.g., the elliptic curve operations and the field arithmetic of the verifier.
+    pub execution: u64,
+}
+
+/// Return the gas of `calldata` charged before the execution of the transaction.
+pub fn calldata_gas(calldata: &[u8]) -> u64 {
+    calldata
+        .iter()
+        .map(|byte| {
+            if *byte == 0 {
+                CALLDATA_ZERO_BYTE_GAS
+            } else {
+                CALLDATA_NONZERO_BYTE_GAS
+            }
+        })
+        .sum()
+}
+
+/// Replace every call of the pairing precompile in `yul` of [`gen_evm_verifier_yul`] with a function that always succeeds, whose contract costs the same as the original one except for the pairing check.
+///
+/// # Return values
+/// Return the modified Yul code, or the error if `yul` has no call of the pairing precompile or no runtime code.
+pub fn stub_pairing(yul: &str) -> Result<String, String> {
+    let mut stubbed = String::with_capacity(yul.len());
+    let mut rest = yul;
+    let mut num_calls = 0;
+    while let Some(idx) = rest.find(PAIRING_CALL) {
+        let (head, tail) = rest.split_at(idx);
+        stubbed.push_str(head);
+        let args = &tail[PAIRING_CALL.len()..];
+        let (address, after_address) = args.split_once(',').unwrap_or((args, ""));
+        let address = address.trim();
+        let is_pairing = u64::from_str_radix(address.trim_start_matches("0x"), 16) == Ok(8);
+        if is_pairing {
+            stubbed.push_str("stub_pairing(gas(),");
+            rest = after_address;
+            num_calls += 1;
+        } else {
+            stubbed.push_str(PAIRING_CALL);
+            rest = args;
+        }
+    }
+    stubbed.push_str(rest);
+    if num_calls == 0 {
+        return Err("the Yul code does not call the pairing precompile".to_string());
+    }
+    let runtime = stubbed
+        .find("object \"Runtime\"")
+        .ok_or_else(|| "the Yul code has no runtime object".to_string())?;
+    let code = stubbed[runtime..]
+        .find("code {")
+        .map(|idx| runtime + idx + "code {".len())
+        .ok_or_else(|| "the runtime object of the Yul code has no code".to_string())?;
+    stubbed.insert_str(code, &format!("\n{}\n", PAIRING_STUB));
+    Ok(stubbed)
+}
+
+/// Deploy the verifier contract of `yul` in a local EVM, call it with `calldata` as [`evm_verify`] does and break down the gas used by the call, which requires `solc`.
+///
+/// Each part is measured by another call, i.e., the transaction base and the calldata by a call to an empty contract and the pairing check by a call to the contract of [`stub_pairing`], and the execution is the rest.
+///
+/// # Arguments
+/// * `yul` - the Yul code of [`gen_evm_verifier_yul`].
+/// * `calldata` - the calldata of [`encode_calldata`] of a sample proof.
+///
+/// # Return values
+/// Return [`EvmGasReport`] of the call, or the error if the contract reverts, i.e., the proof is invalid.
+pub fn estimate_evm_gas(yul: &str, calldata: Vec<u8>) -> Result<EvmGasReport, String> {
+    let total = evm_verify(evm::compile_yul(yul), calldata.clone())?;
+    let without_pairing = evm_verify(evm::compile_yul(&stub_pairing(yul)?), calldata.clone())?;
+    // The deployment code `STOP` deploys a contract without code, whose call costs only the transaction base and the calldata.
+    let intrinsic = evm_verify(vec![0x00], calldata)?;
+    let pairing = total.saturating_sub(without_pairing);
+    Ok(EvmGasReport {
+        total,
+        base: TX_BASE_GAS,
+        calldata: intrinsic.saturating_sub(TX_BASE_GAS),
+        pairing,
+        execution: total.saturating_sub(intrinsic + pairing),
+    })
+}
+
+impl CircuitExt<Fr> for RegexCircuit<Fr> {
+    fn num_instance(&self) -> Vec<usize> {
+        vec![RegexCircuit::instances(self).len()]
+    }
+
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![RegexCircuit::instances(self)]
+    }
+}
+
+/// Generate a regex proof aggregated by [`prove_aggregation_evm`], whose multiopen scheme is SHPLONK and whose transcript is Poseidon, as snark-verifier verifies it in the aggregation circuit.
+///
+/// The proving key of the regex verification circuit is the same as that for [`prove`](crate::helpers::prove), since it does not depend on the multiopen scheme.
+///
+/// # Arguments
+/// * `params` - the SRS parameters of the regex verification circuit.
+/// * `pk` - the proving key of the regex verification circuit.
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+/// * `circuit` - a regex verification circuit.
+///
+/// # Return values
+/// Return the proof with its instances and the protocol of its verifying key as [`Snark`].
+pub fn gen_regex_snark(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    seed: Option<[u8; 32]>,
+    circuit: RegexCircuit<Fr>,
+) -> Snark {
+    let mut rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    gen_snark_shplonk(params, pk, circuit, &mut rng, None::<&str>)
+}
+
+/// Construct the single-layer aggregation circuit that verifies `snarks` and exposes their accumulator followed by their instances in their order.
+///
+/// The shape of the circuit is read from the file of [`AGGREGATION_CONFIG_ENV`], whose `degree` must be that of `agg_params`.
+///
+/// # Arguments
+/// * `agg_params` - the SRS parameters of the aggregation circuit.
+/// * `snarks` - the outputs of [`gen_regex_snark`], e.g., of the regex proofs of different strings.
+/// * `seed` - the seed of the RNG used to blind the accumulation, or `None` to use a random seed.
+///
+/// # Return values
+/// Return the aggregation circuit, whose proving key depends on the number of `snarks` and on their verifying keys.
+pub fn gen_aggregation_circuit(
+    agg_params: &ParamsKZG<Bn256>,
+    snarks: Vec<Snark>,
+    seed: Option<[u8; 32]>,
+) -> PublicAggregationCircuit {
+    accumulation_circuit(agg_params, snarks, false, seed)
+}
+
+/// Construct the aggregation circuit of [`gen_aggregation_circuit`], which also folds the accumulator of the first of `snarks` if `has_prev_accumulator`, i.e., it is a proof of another aggregation circuit.
+pub(crate) fn accumulation_circuit(
+    agg_params: &ParamsKZG<Bn256>,
+    snarks: Vec<Snark>,
+    has_prev_accumulator: bool,
+    seed: Option<[u8; 32]>,
+) -> PublicAggregationCircuit {
+    let mut rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    PublicAggregationCircuit::new(agg_params, snarks, has_prev_accumulator, &mut rng)
+}
+
+/// Generate the proving key of the aggregation circuit, which depends only on the verifying key of the regex verification circuit and not on the aggregated proof.
+///
+/// # Arguments
+/// * `agg_params` - the SRS parameters of the aggregation circuit.
+/// * `agg_circuit` - an aggregation circuit of [`gen_aggregation_circuit`].
+///
+/// # Return values
+/// Return the proving key of the aggregation circuit.
+pub fn gen_aggregation_pk(
+    agg_params: &ParamsKZG<Bn256>,
+    agg_circuit: &PublicAggregationCircuit,
+) -> ProvingKey<G1Affine> {
+    gen_pk(agg_params, agg_circuit, None)
+}
+
+/// Generate the deployment code of the fixed verifier contract of the aggregation circuit, which requires `solc`.
+///
+/// Unlike the contract of [`gen_evm_verifier`], it verifies only the accumulator and a proof of the aggregation circuit, which is cheaper for large regex verification circuits.
+///
+/// # Arguments
+/// * `agg_params` - the SRS parameters of the aggregation circuit.
+/// * `agg_vk` - the verifying key of the aggregation circuit.
+/// * `num_instance` - the total number of the instances of the aggregated regex proofs.
+///
+/// # Return values
+/// Return the deployment code.
+pub fn gen_aggregation_evm_verifier(
+    agg_params: &ParamsKZG<Bn256>,
+    agg_vk: &VerifyingKey<G1Affine>,
+    num_instance: usize,
+) -> Vec<u8> {
+    gen_evm_verifier_shplonk::<PublicAggregationCircuit>(
+        agg_params,
+        agg_vk,
+        vec![NUM_ACCUMULATOR_INSTANCES + num_instance],
+        None,
+    )
+}
+
+/// Generate a proof of the aggregation circuit verified by the contract of [`gen_aggregation_evm_verifier`].
+///
+/// # Arguments
+/// * `agg_params` - the SRS parameters of the aggregation circuit.
+/// * `agg_pk` - the proving key of the aggregation circuit.
+/// * `agg_circuit` - an aggregation circuit of [`gen_aggregation_circuit`].
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+///
+/// # Return values
+/// Return the instances of the aggregation circuit and the bytes of the proof, which are encoded into the calldata by [`encode_calldata`].
+pub fn prove_aggregation_evm(
+    agg_params: &ParamsKZG<Bn256>,
+    agg_pk: &ProvingKey<G1Affine>,
+    agg_circuit: PublicAggregationCircuit,
+    seed: Option<[u8; 32]>,
+) -> (Vec<Fr>, Vec<u8>) {
+    let mut rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    let instances = agg_circuit.instances();
+    let proof = gen_evm_proof_shplonk(agg_params, agg_pk, agg_circuit, instances.clone(), &mut rng);
+    (instances[0].clone(), proof)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helpers::{set_config_k, set_config_max_chars_size, set_config_params};
+
+    const YUL: &str = r#"object "plonk_verifier" {
+    code {
+        datacopy(0, dataoffset("Runtime"), datasize("Runtime"))
+        return(0, datasize("Runtime"))
+    }
+    object "Runtime" {
+        code {
+            let success:bool := true
+            success := and(success, staticcall(gas(), 0x7, 0x00, 0x60, 0x00, 0x40))
+            success := and(success, staticcall(gas(), 0x8, 0x00, 0x180, 0x00, 0x20))
+            success := and(success, mload(0x00))
+        }
+    }
+}"#;
+
+    #[test]
+    fn test_stub_pairing() {
+        let stubbed = stub_pairing(YUL).unwrap();
+        assert!(stubbed.contains("staticcall(gas(), 0x7, 0x00, 0x60, 0x00, 0x40)"));
+        assert!(stubbed.contains("stub_pairing(gas(), 0x00, 0x180, 0x00, 0x20)"));
+        assert!(!stubbed.contains("0x8,"));
+        let runtime = stubbed.find("object \"Runtime\"").unwrap();
+        assert!(stubbed.find(PAIRING_STUB).unwrap() > runtime);
+        assert!(stub_pairing(&YUL.replace("0x8", "0x6")).is_err());
+    }
+
+    #[test]
+    fn test_empty_contract_gas() {
+        let calldata = vec![0, 1, 0, 0, 255];
+        let gas = evm_verify(vec![0x00], calldata.clone()).unwrap();
+        assert_eq!(
+            gas,
+            TX_BASE_GAS + 2 * CALLDATA_NONZERO_BYTE_GAS + 3 * CALLDATA_ZERO_BYTE_GAS
+        );
+        assert_eq!(gas, TX_BASE_GAS + calldata_gas(&calldata));
+    }
+
+    #[test]
+    #[ignore = "requires solc and the minutes and the memory of the aggregation circuit of 2^21 rows"]
+    fn test_aggregate_two_regex_snarks() {
+        set_config_params(
+            "./test_regexes/regex4_test_lookup.txt".to_string(),
+            "./test_regexes/substr4_test_lookup.txt".to_string(),
+        );
+        set_config_max_chars_size(64);
+        set_config_k(13);
+        let config_path = std::env::temp_dir().join("halo2_regex_test_aggregation_config.json");
+        std::fs::write(
+            &config_path,
+            r#"{"degree": 21, "num_advice": 12, "num_lookup_advice": 2, "num_fixed": 1, "lookup_bits": 20}"#,
+        )
+        .unwrap();
+        std::env::set_var(AGGREGATION_CONFIG_ENV, &config_path);
+
+        let agg_params = ParamsKZG::<Bn256>::setup(21, ChaCha20Rng::from_seed([0; 32]));
+        let mut params = agg_params.clone();
+        params.downsize(13);
+        let circuit = |amount: &str| RegexCircuit::<Fr> {
+            characters: format!("amount={};", amount).into_bytes(),
+            correct_substrs: vec![(7, amount.to_string())],
+            is_success: true,
+            ..Default::default()
+        };
+        let pk = gen_pk(&params, &circuit("1000"), None);
+        let snarks = vec![
+            gen_regex_snark(&params, &pk, Some([1; 32]), circuit("1000")),
+            gen_regex_snark(&params, &pk, Some([2; 32]), circuit("42")),
+        ];
+        let regex_instances = snarks
+            .iter()
+            .flat_map(|snark| snark.instances[0].clone())
+            .collect::<Vec<_>>();
+
+        let agg_circuit = gen_aggregation_circuit(&agg_params, snarks, Some([3; 32]));
+        let agg_pk = gen_aggregation_pk(&agg_params, &agg_circuit);
+        let deployment_code =
+            gen_aggregation_evm_verifier(&agg_params, agg_pk.get_vk(), regex_instances.len());
+        let (instances, proof) =
+            prove_aggregation_evm(&agg_params, &agg_pk, agg_circuit, Some([4; 32]));
+        assert_eq!(
+            &instances[NUM_ACCUMULATOR_INSTANCES..],
+            &regex_instances[..]
+        );
+        assert!(evm_verify(deployment_code, encode_calldata(&instances, &proof)).is_ok());
+    }
+}
@@ -0,0 +1,432 @@
+use crate::helpers::{
+    derive_correct_substrs, mock_run, set_config_max_chars_size, set_config_params, RegexCircuit,
+};
+use crate::vrm::{DecomposedRegexConfig, RegexPartConfig};
+use crate::{AllstrRegexDef, RegexDefs, SubstrRegexDef, DEFAULT_MAX_SUBSTR_ID};
+use arbitrary::Arbitrary;
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+
+/// The maximum length of the input strings of [`FuzzCase`].
+pub const MAX_CHARS_SIZE: usize = 16;
+/// The degree of the number of the rows of the circuit checked by [`check_circuit`].
+pub const K: u32 = 12;
+/// The maximum depth of [`FuzzRegex`] rendered into a regex, beyond which each expression is cut to its first character.
+pub const MAX_DEPTH: usize = 4;
+
+/// A character of the alphabet of [`FuzzRegex`].
+#[derive(Arbitrary, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuzzChar {
+    A,
+    B,
+    C,
+}
+
+impl FuzzChar {
+    /// Return the byte of the character.
+    pub fn byte(&self) -> u8 {
+        match self {
+            Self::A => b'a',
+            Self::B => b'b',
+            Self::C => b'c',
+        }
+    }
+}
+
+/// A regex over [`FuzzChar`] built from the operators supported by the compiler of [`DecomposedRegexConfig`].
+#[derive(Arbitrary, Debug, Clone, PartialEq, Eq)]
+pub enum FuzzRegex {
+    Char(FuzzChar),
+    Concat(Box<FuzzRegex>, Box<FuzzRegex>),
+    Alternation(Box<FuzzRegex>, Box<FuzzRegex>),
+    Plus(Box<FuzzRegex>),
+    Star(Box<FuzzRegex>),
+}
+
+impl FuzzRegex {
+    /// Return the regex string, whose expressions deeper than [`MAX_DEPTH`] are cut to their first characters.
+    pub fn to_regex_str(&self) -> String {
+        self.to_regex_str_at(0)
+    }
+
+    fn to_regex_str_at(&self, depth: usize) -> String {
+        if depth >= MAX_DEPTH {
+            return (self.first_char().byte() as char).to_string();
+        }
+        match self {
+            Self::Char(char) => (char.byte() as char).to_string(),
+            Self::Concat(lhs, rhs) => {
+                lhs.to_regex_str_at(depth + 1) + &rhs.to_regex_str_at(depth + 1)
+            }
+            Self::Alternation(lhs, rhs) => format!(
+                "({}|{})",
+                lhs.to_regex_str_at(depth + 1),
+                rhs.to_regex_str_at(depth + 1)
+            ),
+            Self::Plus(inner) => format!("({})+", inner.to_regex_str_at(depth + 1)),
+            Self::Star(inner) => format!("({})*", inner.to_regex_str_at(depth + 1)),
+        }
+    }
+
+    fn first_char(&self) -> FuzzChar {
+        match self {
+            Self::Char(char) => *char,
+            Self::Concat(lhs, _) | Self::Alternation(lhs, _) => lhs.first_char(),
+            Self::Plus(inner) | Self::Star(inner) => inner.first_char(),
+        }
+    }
+
+    /// Return the sub-expressions replacing `self` in [`shrink`], i.e., the direct children and the same expression with a shrunk child.
+    fn shrunk(&self) -> Vec<Self> {
+        match self {
+            Self::Char(char) if *char != FuzzChar::A => vec![Self::Char(FuzzChar::A)],
+            Self::Char(_) => vec![],
+            Self::Concat(lhs, rhs) | Self::Alternation(lhs, rhs) => {
+                let rebuild = |lhs: Self, rhs: Self| match self {
+                    Self::Concat(..) => Self::Concat(Box::new(lhs), Box::new(rhs)),
+                    _ => Self::Alternation(Box::new(lhs), Box::new(rhs)),
+                };
+                let mut shrunk = vec![lhs.as_ref().clone(), rhs.as_ref().clone()];
+                shrunk.extend(
+                    lhs.shrunk()
+                        .into_iter()
+                        .map(|lhs| rebuild(lhs, rhs.as_ref().clone())),
+                );
+                shrunk.extend(
+                    rhs.shrunk()
+                        .into_iter()
+                        .map(|rhs| rebuild(lhs.as_ref().clone(), rhs)),
+                );
+                shrunk
+            }
+            Self::Plus(inner) | Self::Star(inner) => {
+                let mut shrunk = vec![inner.as_ref().clone()];
+                shrunk.extend(inner.shrunk().into_iter().map(|inner| match self {
+                    Self::Plus(_) => Self::Plus(Box::new(inner)),
+                    _ => Self::Star(Box::new(inner)),
+                }));
+                shrunk
+            }
+        }
+    }
+}
+
+/// A decomposed regex of a private prefix, a public part and a private suffix, and an input string to match.
+#[derive(Arbitrary, Debug, Clone, PartialEq, Eq)]
+pub struct FuzzCase {
+    pub prefix: FuzzRegex,
+    pub public: FuzzRegex,
+    pub suffix: FuzzRegex,
+    /// The characters of the input string, e.g., `d` is out of the alphabet of the regex.
+    pub input: Vec<FuzzInputChar>,
+}
+
+/// A character of the input string of [`FuzzCase`].
+#[derive(Arbitrary, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuzzInputChar {
+    Regex(FuzzChar),
+    Other,
+}
+
+impl FuzzCase {
+    /// Return the whole regex string of the three parts.
+    pub fn regex_str(&self) -> String {
+        self.part_strs().concat()
+    }
+
+    /// Return the bytes of the input string cut to [`MAX_CHARS_SIZE`].
+    pub fn input_bytes(&self) -> Vec<u8> {
+        self.input
+            .iter()
+            .take(MAX_CHARS_SIZE)
+            .map(|char| match char {
+                FuzzInputChar::Regex(char) => char.byte(),
+                FuzzInputChar::Other => b'd',
+            })
+            .collect()
+    }
+
+    fn part_strs(&self) -> [String; 3] {
+        [
+            self.prefix.to_regex_str(),
+            self.public.to_regex_str(),
+            self.suffix.to_regex_str(),
+        ]
+    }
+
+    fn decomposed_regex(&self) -> DecomposedRegexConfig {
+        let [prefix, public, suffix] = self.part_strs();
+        let part = |is_public: bool, regex_def: String| RegexPartConfig {
+            is_public,
+            regex_def,
+            max_size: MAX_CHARS_SIZE,
+            solidity: None,
+        };
+        DecomposedRegexConfig {
+            max_byte_size: MAX_CHARS_SIZE,
+            parts: vec![part(false, prefix), part(true, public), part(false, suffix)],
+            max_substr_id: DEFAULT_MAX_SUBSTR_ID,
+        }
+    }
+}
+
+/// A difference between the regex definitions compiled from [`FuzzCase`] and the `regex` crate.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    #[error("The regex {regex} could not be compiled: {error}")]
+    Compile { regex: String, error: String },
+    #[error("The check of {input:?} for the regex {regex} failed: {message}")]
+    Failed {
+        regex: String,
+        input: String,
+        message: String,
+    },
+    #[error("{input:?} is rejected, though the regex {regex} matches it")]
+    Rejected { regex: String, input: String },
+    #[error("{input:?} is accepted, though the regex {regex} does not match it")]
+    Accepted { regex: String, input: String },
+    #[error("The substring {substr:?} at {pos} of {input:?} is not the public part of any match of the regex {regex}")]
+    InvalidSubstr {
+        regex: String,
+        input: String,
+        pos: usize,
+        substr: String,
+    },
+}
+
+/// Check that [`RegexDefs::trace`] of the regex definitions compiled from `case` matches its input string iff the `regex` crate does, and that the substring is the public part of a match.
+///
+/// It needs no circuit, so it covers the compiler much faster than [`check_circuit`].
+pub fn check_trace(case: &FuzzCase) -> Result<(), Discrepancy> {
+    check_with(case, |defs, _, input| {
+        let trace = defs.trace(input);
+        if !trace.is_accepted {
+            return Ok(None);
+        }
+        let positions = trace
+            .substr_ids
+            .iter()
+            .enumerate()
+            .filter(|(_, substr_id)| **substr_id == 1)
+            .map(|(pos, _)| pos)
+            .collect::<Vec<usize>>();
+        let pos = positions.first().copied().unwrap_or(0);
+        let substr = positions.iter().map(|idx| input[*idx]).collect();
+        Ok(Some((pos, substr)))
+    })
+}
+
+/// Check that the circuit of the regex definitions compiled from `case` is satisfied by [`MockProver`](halo2_base::halo2_proofs::dev::MockProver) iff the `regex` crate matches its input string, and that the revealed substring is the public part of a match.
+///
+/// The global configuration of [`RegexCircuit`] is overwritten, and a panic holding it poisons it for the following checks, so shrink such a case by [`check_trace`] if it also fails.
+pub fn check_circuit(case: &FuzzCase) -> Result<(), Discrepancy> {
+    check_with(case, |_, (allstr_path, substr_path), input| {
+        set_config_params(
+            allstr_path.to_string_lossy().to_string(),
+            substr_path.to_string_lossy().to_string(),
+        );
+        set_config_max_chars_size(MAX_CHARS_SIZE);
+        let correct_substrs = match derive_correct_substrs(0, input) {
+            Ok(correct_substrs) => correct_substrs,
+            Err(_) => return Ok(None),
+        };
+        let circuit = RegexCircuit::<Fr> {
+            characters: input.to_vec(),
+            correct_substrs,
+            ..Default::default()
+        };
+        let report = mock_run(K, &circuit).map_err(|e| e.to_string())?;
+        if report.verify_result.is_err() {
+            return Ok(None);
+        }
+        let output = &report.outputs[0];
+        let positions = output
+            .substr_ids
+            .iter()
+            .enumerate()
+            .filter(|(_, substr_id)| **substr_id == 1)
+            .map(|(pos, _)| pos)
+            .collect::<Vec<usize>>();
+        let pos = positions.first().copied().unwrap_or(0);
+        let substr = positions
+            .iter()
+            .map(|idx| output.masked_characters[*idx])
+            .collect();
+        Ok(Some((pos, substr)))
+    })
+}
+
+/// Compile `case` and compare the result of `run`, i.e., the position and the characters of the substring if the input string is accepted, with the `regex` crate.
+fn check_with<R>(case: &FuzzCase, run: R) -> Result<(), Discrepancy>
+where
+    R: Fn(&RegexDefs, (&PathBuf, &PathBuf), &[u8]) -> Result<Option<(usize, Vec<u8>)>, String>,
+{
+    let regex = case.regex_str();
+    let input = case.input_bytes();
+    let input_str = String::from_utf8_lossy(&input).to_string();
+    let dir = std::env::temp_dir().join(format!("halo2_regex_fuzz_{}", std::process::id()));
+    let allstr_path = dir.join("allstr.txt");
+    let substr_path = dir.join("substr0.txt");
+    std::fs::create_dir_all(&dir).expect("fail to create the directory of the regex files");
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        case.decomposed_regex()
+            .gen_regex_files(&allstr_path, &[substr_path.clone()])
+            .map_err(|e| e.to_string())?;
+        let defs = RegexDefs {
+            allstr: AllstrRegexDef::read_from_text(&allstr_path.to_string_lossy()),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                &substr_path.to_string_lossy(),
+            )],
+        };
+        Ok::<_, String>(run(&defs, (&allstr_path, &substr_path), &input))
+    }));
+    let matched = match result {
+        Ok(Ok(Ok(matched))) => matched,
+        Ok(Ok(Err(error))) => {
+            return Err(Discrepancy::Failed {
+                regex,
+                input: input_str,
+                message: error,
+            })
+        }
+        Ok(Err(error)) => return Err(Discrepancy::Compile { regex, error }),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default();
+            return Err(Discrepancy::Failed {
+                regex,
+                input: input_str,
+                message,
+            });
+        }
+    };
+    let [prefix, public, suffix] = case.part_strs();
+    let full_match = |pattern: &str, chars: &[u8]| {
+        regex::bytes::Regex::new(&format!("^(?:{})$", pattern))
+            .unwrap()
+            .is_match(chars)
+    };
+    let is_match = full_match(&regex, &input);
+    match matched {
+        None if is_match => Err(Discrepancy::Rejected {
+            regex,
+            input: input_str,
+        }),
+        None => Ok(()),
+        Some(_) if !is_match => Err(Discrepancy::Accepted {
+            regex,
+            input: input_str,
+        }),
+        Some((pos, substr)) => {
+            // The split of the input string is ambiguous in general, so the substring only has to be the public part of one of the matches.
+            let end = pos + substr.len();
+            let is_valid = end <= input.len()
+                && input[pos..end] == substr[..]
+                && full_match(&prefix, &input[..pos])
+                && full_match(&public, &substr)
+                && full_match(&suffix, &input[end..]);
+            if is_valid {
+                Ok(())
+            } else {
+                Err(Discrepancy::InvalidSubstr {
+                    regex,
+                    input: input_str,
+                    pos,
+                    substr: String::from_utf8_lossy(&substr).to_string(),
+                })
+            }
+        }
+    }
+}
+
+/// Shrink `case` failing `check` to a smaller case failing it, i.e., with fewer input characters and smaller regexes, by removing one of them at a time while the check still fails.
+///
+/// # Arguments
+/// * `case` - a case for which `check` returns [`Discrepancy`].
+/// * `check` - [`check_trace`] or [`check_circuit`].
+///
+/// # Return values
+/// Return the shrunk case and its [`Discrepancy`], or `None` if `check` does not fail on `case`.
+pub fn shrink<C>(case: FuzzCase, check: C) -> Option<(FuzzCase, Discrepancy)>
+where
+    C: Fn(&FuzzCase) -> Result<(), Discrepancy>,
+{
+    let mut discrepancy = check(&case).err()?;
+    let mut case = case;
+    case.input.truncate(MAX_CHARS_SIZE);
+    loop {
+        let mut candidates = vec![];
+        for idx in 0..case.input.len() {
+            let mut candidate = case.clone();
+            candidate.input.remove(idx);
+            candidates.push(candidate);
+        }
+        for prefix in case.prefix.shrunk() {
+            candidates.push(FuzzCase {
+                prefix,
+                ..case.clone()
+            });
+        }
+        for public in case.public.shrunk() {
+            candidates.push(FuzzCase {
+                public,
+                ..case.clone()
+            });
+        }
+        for suffix in case.suffix.shrunk() {
+            candidates.push(FuzzCase {
+                suffix,
+                ..case.clone()
+            });
+        }
+        let shrunk = candidates
+            .into_iter()
+            .find_map(|candidate| check(&candidate).err().map(|error| (candidate, error)));
+        match shrunk {
+            Some((candidate, error)) => {
+                case = candidate;
+                discrepancy = error;
+            }
+            None => return Some((case, discrepancy)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn char(char: FuzzChar) -> Box<FuzzRegex> {
+        Box::new(FuzzRegex::Char(char))
+    }
+
+    #[test]
+    fn test_check_trace() {
+        // "a" "(b|c)+" "a"
+        let case = FuzzCase {
+            prefix: FuzzRegex::Char(FuzzChar::A),
+            public: FuzzRegex::Plus(Box::new(FuzzRegex::Alternation(
+                char(FuzzChar::B),
+                char(FuzzChar::C),
+            ))),
+            suffix: FuzzRegex::Char(FuzzChar::A),
+            input: vec![
+                FuzzInputChar::Regex(FuzzChar::A),
+                FuzzInputChar::Regex(FuzzChar::B),
+                FuzzInputChar::Regex(FuzzChar::C),
+                FuzzInputChar::Regex(FuzzChar::A),
+            ],
+        };
+        assert_eq!(case.regex_str(), "a((b|c))+a");
+        assert_eq!(check_trace(&case), Ok(()));
+        assert_eq!(shrink(case.clone(), check_trace), None);
+
+        let mut rejected = case;
+        rejected.input.push(FuzzInputChar::Other);
+        assert_eq!(check_trace(&rejected), Ok(()));
+    }
+}
@@ -0,0 +1,550 @@
+use crate::gadgets::input::assert_regex_input;
+use crate::gadgets::numeric::DecimalConfig;
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Error definitions of [`Base64DecodeConfig::new`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Base64ConfigError {
+    #[error(
+        "The maximum length {max_encoded_size} of the encoded string is not a multiple of four"
+    )]
+    UnalignedSize { max_encoded_size: usize },
+}
+
+/// Output type definition of [`Base64DecodeConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct AssignedBase64Result<'a, F: PrimeField> {
+    /// The assigned characters of the base64-encoded string padded with zeros.
+    /// The length is equal to `max_encoded_size`.
+    /// These cells can be passed to other chips, e.g., a hash chip, to link the encoded string with upstream checks.
+    pub encoded: Vec<AssignedValue<'a, F>>,
+    /// The assigned bytes of the decoded string padded with zeros.
+    /// The length is equal to `max_encoded_size / 4 * 3`.
+    pub decoded: Vec<AssignedValue<'a, F>>,
+    /// The assigned length of the decoded string.
+    pub decoded_len: AssignedValue<'a, F>,
+}
+
+/// Configuration to decode a base64-encoded string, e.g., an email body with `Content-Transfer-Encoding: base64`, before it is matched by [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+///
+/// The encoded string must be padded with `=` to a multiple of four characters and must not contain line breaks.
+#[derive(Debug, Clone)]
+pub struct Base64DecodeConfig<F: PrimeField> {
+    decimal: DecimalConfig<F>,
+    /// The maximum length of the encoded string, which must be a multiple of four.
+    pub max_encoded_size: usize,
+}
+
+impl<F: PrimeField> Base64DecodeConfig<F> {
+    /// Construct a new [`Base64DecodeConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    /// * `max_encoded_size` - the maximum length of the encoded string, which must be a multiple of four.
+    ///
+    /// # Return values
+    /// Return a new [`Base64DecodeConfig`], or [`Base64ConfigError`] if `max_encoded_size` is not a multiple of four.
+    pub fn new(
+        gate: FlexGateConfig<F>,
+        max_encoded_size: usize,
+    ) -> Result<Self, Base64ConfigError> {
+        if max_encoded_size % 4 != 0 {
+            return Err(Base64ConfigError::UnalignedSize { max_encoded_size });
+        }
+        Ok(Self {
+            decimal: DecimalConfig::new_unchecked(gate, 3),
+            max_encoded_size,
+        })
+    }
+
+    /// Decode the base64-encoded string `encoded` in the circuit.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `encoded` - characters of the base64-encoded string.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedBase64Result`], or [`Error::Synthesis`] if `encoded` is longer than `max_encoded_size`.
+    pub fn decode<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        encoded: &[u8],
+    ) -> Result<AssignedBase64Result<'a, F>, Error> {
+        if encoded.len() > self.max_encoded_size {
+            return Err(Error::Synthesis);
+        }
+        let gate = self.gate();
+        let assigned_encoded = (0..self.max_encoded_size)
+            .map(|idx| {
+                let char = encoded.get(idx).copied().unwrap_or(0);
+                gate.load_witness(ctx, Value::known(F::from(char as u64)))
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+
+        let mut decoded = vec![];
+        let mut decoded_len = gate.load_zero(ctx);
+        let mut pre_is_end = gate.load_zero(ctx);
+        let mut pre_is_last_pad = gate.load_zero(ctx);
+        for group in assigned_encoded.chunks(4) {
+            let mut sextets = vec![];
+            let mut is_zeros = vec![];
+            let mut is_pads = vec![];
+            for char in group.iter() {
+                let (sextet, is_zero, is_pad) = self.char_to_sextet(ctx, char);
+                sextets.push(sextet);
+                is_zeros.push(is_zero);
+                is_pads.push(is_pad);
+            }
+            // The four characters in a group are either all zero (after the end of the string) or all non-zero.
+            for is_zero in is_zeros[1..].iter() {
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(&is_zeros[0]),
+                    QuantumCell::Existing(is_zero),
+                );
+            }
+            // Once the string ends, including by padding, no group follows.
+            let is_started = gate.not(ctx, QuantumCell::Existing(&is_zeros[0]));
+            let is_ended = gate.or(
+                ctx,
+                QuantumCell::Existing(&pre_is_end),
+                QuantumCell::Existing(&pre_is_last_pad),
+            );
+            let is_invalid = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_ended),
+                QuantumCell::Existing(&is_started),
+            );
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(&is_invalid),
+                QuantumCell::Constant(F::from(0)),
+            );
+            // `=` can appear only at the third and fourth characters, and the third one implies the fourth one.
+            for is_pad in is_pads[0..2].iter() {
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(is_pad),
+                    QuantumCell::Constant(F::from(0)),
+                );
+            }
+            let not_fourth_pad = gate.not(ctx, QuantumCell::Existing(&is_pads[3]));
+            let is_invalid_pad = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_pads[2]),
+                QuantumCell::Existing(&not_fourth_pad),
+            );
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(&is_invalid_pad),
+                QuantumCell::Constant(F::from(0)),
+            );
+
+            let mut packed = gate.load_zero(ctx);
+            for sextet in sextets.iter() {
+                packed = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&packed),
+                    QuantumCell::Constant(F::from(64)),
+                    QuantumCell::Existing(sextet),
+                );
+            }
+            let bits = gate.num_to_bits(ctx, &packed, 24);
+            // `bits` are little-endian, while the decoded bytes are big-endian in `packed`.
+            let bytes = (0..3)
+                .map(|byte_idx| {
+                    let start = 8 * (2 - byte_idx);
+                    let mut byte = gate.load_zero(ctx);
+                    for bit in bits[start..start + 8].iter().rev() {
+                        byte = gate.mul_add(
+                            ctx,
+                            QuantumCell::Existing(&byte),
+                            QuantumCell::Constant(F::from(2)),
+                            QuantumCell::Existing(bit),
+                        );
+                    }
+                    byte
+                })
+                .collect::<Vec<AssignedValue<F>>>();
+            // The bytes covered by `=` are not a part of the decoded string.
+            let keeps = [
+                gate.load_constant(ctx, F::from(1)),
+                gate.not(ctx, QuantumCell::Existing(&is_pads[2])),
+                not_fourth_pad,
+            ];
+            for (byte, keep) in bytes.iter().zip(keeps.iter()) {
+                let kept = gate.mul(
+                    ctx,
+                    QuantumCell::Existing(byte),
+                    QuantumCell::Existing(keep),
+                );
+                decoded.push(kept);
+            }
+            let group_len = gate.mul(
+                ctx,
+                QuantumCell::Existing(&is_started),
+                QuantumCell::Constant(F::from(3)),
+            );
+            let group_len = gate.sub(
+                ctx,
+                QuantumCell::Existing(&group_len),
+                QuantumCell::Existing(&is_pads[2]),
+            );
+            let group_len = gate.sub(
+                ctx,
+                QuantumCell::Existing(&group_len),
+                QuantumCell::Existing(&is_pads[3]),
+            );
+            decoded_len = gate.add(
+                ctx,
+                QuantumCell::Existing(&decoded_len),
+                QuantumCell::Existing(&group_len),
+            );
+            pre_is_end = is_zeros[0].clone();
+            pre_is_last_pad = is_pads[3].clone();
+        }
+
+        Ok(AssignedBase64Result {
+            encoded: assigned_encoded,
+            decoded,
+            decoded_len,
+        })
+    }
+
+    /// Constrain that the input string of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) is equal to the decoded string.
+    ///
+    /// `match_substrs` must be called with the bytes returned by [`decode_base64`].
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `decoded` - the output of [`Base64DecodeConfig::decode`].
+    /// * `regex_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    pub fn assert_regex_input<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        decoded: &AssignedBase64Result<'a, F>,
+        regex_result: &AssignedRegexResult<'a, F>,
+    ) {
+        assert_regex_input(
+            self.gate(),
+            ctx,
+            &decoded.decoded,
+            &decoded.decoded_len,
+            regex_result,
+        );
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.decimal.gate()
+    }
+
+    /// Convert a base64 character into its 6-bit value.
+    ///
+    /// Return the assigned 6-bit value, a flag indicating the character is zero (padded byte), and a flag indicating the character is `=`.
+    fn char_to_sextet<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        char: &AssignedValue<'a, F>,
+    ) -> (
+        AssignedValue<'a, F>,
+        AssignedValue<'a, F>,
+        AssignedValue<'a, F>,
+    ) {
+        let gate = self.gate();
+        gate.num_to_bits(ctx, char, 8);
+        let is_upper = self.is_in_range(ctx, char, b'A', b'Z');
+        let is_lower = self.is_in_range(ctx, char, b'a', b'z');
+        let is_digit = self.is_in_range(ctx, char, b'0', b'9');
+        let [is_plus, is_slash, is_pad, is_zero] = [b'+', b'/', b'=', 0].map(|byte| {
+            gate.is_equal(
+                ctx,
+                QuantumCell::Existing(char),
+                QuantumCell::Constant(F::from(byte as u64)),
+            )
+        });
+        let mut is_valid = gate.load_zero(ctx);
+        for flag in [
+            &is_upper, &is_lower, &is_digit, &is_plus, &is_slash, &is_pad, &is_zero,
+        ] {
+            is_valid = gate.add(
+                ctx,
+                QuantumCell::Existing(&is_valid),
+                QuantumCell::Existing(flag),
+            );
+        }
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_valid),
+            QuantumCell::Constant(F::from(1)),
+        );
+
+        let mut sextet = gate.load_zero(ctx);
+        // (flag, value of the sextet minus the character)
+        let offsets = [
+            (&is_upper, -F::from(b'A' as u64)),
+            (&is_lower, F::from(26) - F::from(b'a' as u64)),
+            (&is_digit, F::from(52) - F::from(b'0' as u64)),
+        ];
+        for (flag, offset) in offsets {
+            let shifted = gate.add(
+                ctx,
+                QuantumCell::Existing(char),
+                QuantumCell::Constant(offset),
+            );
+            sextet = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(flag),
+                QuantumCell::Existing(&shifted),
+                QuantumCell::Existing(&sextet),
+            );
+        }
+        for (flag, value) in [(&is_plus, 62), (&is_slash, 63)] {
+            sextet = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(flag),
+                QuantumCell::Constant(F::from(value)),
+                QuantumCell::Existing(&sextet),
+            );
+        }
+        (sextet, is_zero, is_pad)
+    }
+
+    /// Return an assigned bit that is one iff `min <= char <= max`, where `char` must be less than 256.
+    fn is_in_range<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        char: &AssignedValue<'a, F>,
+        min: u8,
+        max: u8,
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let min = gate.load_constant(ctx, F::from(min as u64));
+        let max_plus_one = gate.load_constant(ctx, F::from(max as u64 + 1));
+        let is_below = self.decimal.is_less_than(ctx, char, &min, 9);
+        let is_not_above = self.decimal.is_less_than(ctx, char, &max_plus_one, 9);
+        let not_below = gate.not(ctx, QuantumCell::Existing(&is_below));
+        gate.and(
+            ctx,
+            QuantumCell::Existing(&not_below),
+            QuantumCell::Existing(&is_not_above),
+        )
+    }
+}
+
+/// Decode a base64-encoded string off-circuit, i.e., compute the bytes passed to [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) along with [`Base64DecodeConfig`].
+///
+/// # Arguments
+/// * `encoded` - characters of the base64-encoded string padded with `=`.
+///
+/// # Return values
+/// Return the decoded bytes, or `None` if `encoded` is not a valid base64 string.
+pub fn decode_base64(encoded: &[u8]) -> Option<Vec<u8>> {
+    if encoded.len() % 4 != 0 {
+        return None;
+    }
+    let mut decoded = vec![];
+    for (group_idx, group) in encoded.chunks(4).enumerate() {
+        let is_last = group_idx == encoded.len() / 4 - 1;
+        let mut packed = 0u32;
+        let mut num_pads = 0;
+        for (idx, char) in group.iter().enumerate() {
+            let sextet = match char {
+                b'A'..=b'Z' => char - b'A',
+                b'a'..=b'z' => char - b'a' + 26,
+                b'0'..=b'9' => char - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                b'=' if is_last && idx >= 2 => {
+                    num_pads += 1;
+                    0
+                }
+                _ => return None,
+            };
+            if num_pads > 0 && *char != b'=' {
+                return None;
+            }
+            packed = (packed << 6) | sextet as u32;
+        }
+        let bytes = packed.to_be_bytes();
+        decoded.extend_from_slice(&bytes[1..(4 - num_pads)]);
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 64;
+    const K: usize = 13;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestBase64Circuit<F: PrimeField> {
+        encoded: Vec<u8>,
+        /// The characters matched by the regexes, which are given apart from `encoded` as a malicious prover would assign them.
+        characters: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestBase64Circuit<F> {
+        const NUM_ADVICE: usize = 8;
+        const NUM_FIXED: usize = 1;
+
+        fn new(encoded: &[u8], characters: &[u8]) -> Self {
+            Self {
+                encoded: encoded.to_vec(),
+                characters: characters.to_vec(),
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestBase64Circuit<F> {
+        type Config = RegexVerifyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                encoded: vec![],
+                characters: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def =
+                AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt");
+            let substr_def =
+                SubstrRegexDef::read_from_text("./test_regexes/substr4_test_lookup.txt");
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: all_regex_def,
+                substrs: vec![substr_def],
+            }];
+            RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let base64 = Base64DecodeConfig::new(gate.clone(), MAX_STRING_LEN).unwrap();
+                    let decoded = base64.decode(ctx, &self.encoded)?;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    base64.assert_regex_input(ctx, &decoded, &result);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_decode_valid_base64() {
+        for (encoded, characters) in [
+            (&b"YW1vdW50PTEwMDA7"[..], &b"amount=1000;"[..]),
+            (b"YW1vdW50PTEwOw==", b"amount=10;"),
+        ] {
+            let circuit = TestBase64Circuit::<Fr>::new(encoded, characters);
+            let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_character() {
+        let circuit = TestBase64Circuit::<Fr>::new(b"YW1vdW50PTEwO!==", b"amount=10;");
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_decode_bad_padding() {
+        // `=` at the third character without the fourth one, and a group after the padding.
+        for encoded in [&b"YW1vdW50PTEwOw=A"[..], b"YW1vdW50PQ==MTA7"] {
+            let circuit = TestBase64Circuit::<Fr>::new(encoded, b"amount=10;");
+            let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_mismatched_characters() {
+        let circuit = TestBase64Circuit::<Fr>::new(b"YW1vdW50PTEwOw==", b"amount=11;");
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_unaligned_size() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let gate = FlexGateConfig::<Fr>::configure(
+            &mut meta,
+            halo2_base::gates::flex_gate::GateStrategy::Vertical,
+            &[1],
+            1,
+            0,
+            K,
+        );
+        assert!(Base64DecodeConfig::new(gate.clone(), 8).is_ok());
+        assert_eq!(
+            Base64DecodeConfig::new(gate, 10).unwrap_err(),
+            Base64ConfigError::UnalignedSize {
+                max_encoded_size: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_too_long() {
+        let circuit = TestBase64Circuit::<Fr>::new(&[b'A'; MAX_STRING_LEN + 4], b"amount=10;");
+        assert!(MockProver::run(K as u32, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64(b"aGVsbG8="), Some(b"hello".to_vec()));
+        assert_eq!(decode_base64(b"aGk="), Some(b"hi".to_vec()));
+        assert_eq!(decode_base64(b"YWJj"), Some(b"abc".to_vec()));
+        assert_eq!(decode_base64(b"YQ=="), Some(b"a".to_vec()));
+        assert_eq!(decode_base64(b"YQ="), None);
+        assert_eq!(decode_base64(b"Y=Q="), None);
+    }
+}
@@ -0,0 +1,826 @@
+use crate::gadgets::numeric::DecimalConfig;
+use crate::gadgets::substr::SubstrCompactConfig;
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Cumulative days before each month in a non-leap year.
+const CUMULATIVE_DAYS: [u64; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+/// Days of each month in a non-leap year.
+const DAYS_IN_MONTH: [u64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+/// Month names used in RFC 2822.
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+/// The number of leap days from year 0 to year 1969.
+const LEAP_DAYS_BEFORE_1970: u64 = 477;
+/// The maximum bit length of the year.
+const YEAR_BITS: usize = 14;
+/// The maximum bit length of the Unix timestamp of a date before 10000.
+const TIMESTAMP_BITS: usize = 40;
+
+/// Format of the date substring.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFormat {
+    /// `YYYY-MM-DD`, e.g., `2023-04-01`. The time of the day is treated as 00:00:00 UTC.
+    Iso8601Date,
+    /// `DD Mon YYYY HH:MM:SS +HHMM` defined in RFC 2822 without the optional day of the week, e.g., `01 Apr 2023 12:34:56 +0900`.
+    /// The day must be zero-padded to two digits.
+    Rfc2822,
+}
+
+impl DateFormat {
+    /// Return the length of the date substring in this format.
+    pub fn substr_len(&self) -> usize {
+        match self {
+            DateFormat::Iso8601Date => 10,
+            DateFormat::Rfc2822 => 26,
+        }
+    }
+}
+
+/// Configuration to convert a date substring extracted by [`RegexVerifyConfig`](crate::RegexVerifyConfig) into a Unix timestamp.
+#[derive(Debug, Clone)]
+pub struct DateConfig<F: PrimeField> {
+    compact: SubstrCompactConfig<F>,
+    decimal: DecimalConfig<F>,
+    /// The format of the date substring.
+    pub format: DateFormat,
+}
+
+impl<F: PrimeField> DateConfig<F> {
+    /// Construct a new [`DateConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    /// * `format` - the format of the date substring.
+    ///
+    /// # Return values
+    /// Return a new [`DateConfig`].
+    pub fn new(gate: FlexGateConfig<F>, format: DateFormat) -> Self {
+        Self {
+            compact: SubstrCompactConfig::new(gate.clone()),
+            decimal: DecimalConfig::new_unchecked(gate, 4),
+            format,
+        }
+    }
+
+    /// Convert the substring whose substring id is `substr_id` into a Unix timestamp.
+    ///
+    /// The year, month, day, hour, minute, second, and timezone offset are constrained to be a valid date in or after 1970, and the timestamp adjusted by the timezone offset is constrained to be non-negative, e.g., `01 Jan 1970 00:00:00 +0100` is rejected.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    /// * `substr_id` - the substring id of the date substring.
+    ///
+    /// # Return values
+    /// Return the assigned Unix timestamp in seconds.
+    pub fn parse_timestamp<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &AssignedRegexResult<'a, F>,
+        substr_id: usize,
+    ) -> Result<AssignedValue<'a, F>, Error> {
+        let chars = self
+            .compact
+            .compact(ctx, result, substr_id, self.format.substr_len())?;
+        let gate = self.gate();
+        let timestamp = match self.format {
+            DateFormat::Iso8601Date => {
+                self.assert_char(ctx, &chars[4], b'-');
+                self.assert_char(ctx, &chars[7], b'-');
+                let year = self.parse_digits(ctx, &chars[0..4]);
+                let month = self.parse_digits(ctx, &chars[5..7]);
+                let day = self.parse_digits(ctx, &chars[8..10]);
+                let days = self.days_from_epoch(ctx, &year, &month, &day);
+                gate.mul(
+                    ctx,
+                    QuantumCell::Existing(&days),
+                    QuantumCell::Constant(F::from(86400)),
+                )
+            }
+            DateFormat::Rfc2822 => {
+                for idx in [2, 6, 11, 20] {
+                    self.assert_char(ctx, &chars[idx], b' ');
+                }
+                self.assert_char(ctx, &chars[14], b':');
+                self.assert_char(ctx, &chars[17], b':');
+                let day = self.parse_digits(ctx, &chars[0..2]);
+                let month = self.parse_month_name(ctx, &chars[3..6]);
+                let year = self.parse_digits(ctx, &chars[7..11]);
+                let hour = self.parse_digits(ctx, &chars[12..14]);
+                let minute = self.parse_digits(ctx, &chars[15..17]);
+                let second = self.parse_digits(ctx, &chars[18..20]);
+                let tz_hour = self.parse_digits(ctx, &chars[22..24]);
+                let tz_minute = self.parse_digits(ctx, &chars[24..26]);
+                self.assert_less_than_const(ctx, &hour, 24, 5);
+                self.assert_less_than_const(ctx, &minute, 60, 6);
+                // A leap second is allowed.
+                self.assert_less_than_const(ctx, &second, 61, 6);
+                self.assert_less_than_const(ctx, &tz_hour, 24, 5);
+                self.assert_less_than_const(ctx, &tz_minute, 60, 6);
+                let days = self.days_from_epoch(ctx, &year, &month, &day);
+                let mut seconds = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&days),
+                    QuantumCell::Constant(F::from(86400)),
+                    QuantumCell::Existing(&second),
+                );
+                seconds = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&hour),
+                    QuantumCell::Constant(F::from(3600)),
+                    QuantumCell::Existing(&seconds),
+                );
+                seconds = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&minute),
+                    QuantumCell::Constant(F::from(60)),
+                    QuantumCell::Existing(&seconds),
+                );
+                let offset = {
+                    let tz_seconds = gate.mul(
+                        ctx,
+                        QuantumCell::Existing(&tz_minute),
+                        QuantumCell::Constant(F::from(60)),
+                    );
+                    gate.mul_add(
+                        ctx,
+                        QuantumCell::Existing(&tz_hour),
+                        QuantumCell::Constant(F::from(3600)),
+                        QuantumCell::Existing(&tz_seconds),
+                    )
+                };
+                let is_plus = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&chars[21]),
+                    QuantumCell::Constant(F::from(b'+' as u64)),
+                );
+                let is_minus = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&chars[21]),
+                    QuantumCell::Constant(F::from(b'-' as u64)),
+                );
+                let is_sign = gate.add(
+                    ctx,
+                    QuantumCell::Existing(&is_plus),
+                    QuantumCell::Existing(&is_minus),
+                );
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(&is_sign),
+                    QuantumCell::Constant(F::from(1)),
+                );
+                // The local time is ahead of UTC by `offset` iff the sign is `+`.
+                let sign = gate.select(
+                    ctx,
+                    QuantumCell::Constant(-F::from(1)),
+                    QuantumCell::Constant(F::from(1)),
+                    QuantumCell::Existing(&is_plus),
+                );
+                gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&sign),
+                    QuantumCell::Existing(&offset),
+                    QuantumCell::Existing(&seconds),
+                )
+            }
+        };
+        // A negative timestamp wraps around to the top of the field, which does not fit in `TIMESTAMP_BITS`.
+        gate.num_to_bits(ctx, &timestamp, TIMESTAMP_BITS);
+        Ok(timestamp)
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.compact.gate()
+    }
+
+    /// Compute the number of days from 1970-01-01 to the given date, constraining that the date is valid.
+    fn days_from_epoch<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        year: &AssignedValue<'a, F>,
+        month: &AssignedValue<'a, F>,
+        day: &AssignedValue<'a, F>,
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let min_year = gate.load_constant(ctx, F::from(1970));
+        let is_before_epoch = self.decimal.is_less_than(ctx, year, &min_year, YEAR_BITS);
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_before_epoch),
+            QuantumCell::Constant(F::from(0)),
+        );
+
+        let (_, rem4) = self.div_mod_const(ctx, year, 4);
+        let (_, rem100) = self.div_mod_const(ctx, year, 100);
+        let (_, rem400) = self.div_mod_const(ctx, year, 400);
+        let is_leap = {
+            let div4 = gate.is_zero(ctx, &rem4);
+            let div100 = gate.is_zero(ctx, &rem100);
+            let div400 = gate.is_zero(ctx, &rem400);
+            let not_div100 = gate.not(ctx, QuantumCell::Existing(&div100));
+            let and = gate.and(
+                ctx,
+                QuantumCell::Existing(&div4),
+                QuantumCell::Existing(&not_div100),
+            );
+            gate.or(
+                ctx,
+                QuantumCell::Existing(&and),
+                QuantumCell::Existing(&div400),
+            )
+        };
+
+        // Cumulative days and days of the month selected by `month`, which must be one of 1..=12.
+        let mut is_month = gate.load_zero(ctx);
+        let mut cumulative_days = gate.load_zero(ctx);
+        let mut days_in_month = gate.load_zero(ctx);
+        let mut is_after_feb = gate.load_zero(ctx);
+        let mut is_feb = gate.load_zero(ctx);
+        for idx in 0..12 {
+            let is_eq = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(month),
+                QuantumCell::Constant(F::from(idx as u64 + 1)),
+            );
+            is_month = gate.add(
+                ctx,
+                QuantumCell::Existing(&is_month),
+                QuantumCell::Existing(&is_eq),
+            );
+            cumulative_days = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_eq),
+                QuantumCell::Constant(F::from(CUMULATIVE_DAYS[idx])),
+                QuantumCell::Existing(&cumulative_days),
+            );
+            days_in_month = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_eq),
+                QuantumCell::Constant(F::from(DAYS_IN_MONTH[idx])),
+                QuantumCell::Existing(&days_in_month),
+            );
+            if idx == 1 {
+                is_feb = is_eq;
+            } else if idx > 1 {
+                is_after_feb = gate.add(
+                    ctx,
+                    QuantumCell::Existing(&is_after_feb),
+                    QuantumCell::Existing(&is_eq),
+                );
+            }
+        }
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_month),
+            QuantumCell::Constant(F::from(1)),
+        );
+        let leap_feb = gate.and(
+            ctx,
+            QuantumCell::Existing(&is_leap),
+            QuantumCell::Existing(&is_feb),
+        );
+        days_in_month = gate.add(
+            ctx,
+            QuantumCell::Existing(&days_in_month),
+            QuantumCell::Existing(&leap_feb),
+        );
+        let leap_after_feb = gate.and(
+            ctx,
+            QuantumCell::Existing(&is_leap),
+            QuantumCell::Existing(&is_after_feb),
+        );
+        cumulative_days = gate.add(
+            ctx,
+            QuantumCell::Existing(&cumulative_days),
+            QuantumCell::Existing(&leap_after_feb),
+        );
+
+        // 1 <= day <= days_in_month.
+        let is_zero_day = gate.is_zero(ctx, day);
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_zero_day),
+            QuantumCell::Constant(F::from(0)),
+        );
+        let is_over = self.decimal.is_less_than(ctx, &days_in_month, day, 7);
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_over),
+            QuantumCell::Constant(F::from(0)),
+        );
+
+        // Leap days in [1970, year) = leap days in [0, year) - leap days in [0, 1970).
+        let prev_year = gate.sub(
+            ctx,
+            QuantumCell::Existing(year),
+            QuantumCell::Constant(F::from(1)),
+        );
+        let (q4, _) = self.div_mod_const(ctx, &prev_year, 4);
+        let (q100, _) = self.div_mod_const(ctx, &prev_year, 100);
+        let (q400, _) = self.div_mod_const(ctx, &prev_year, 400);
+        let leap_days = {
+            let sum = gate.sub(
+                ctx,
+                QuantumCell::Existing(&q4),
+                QuantumCell::Existing(&q100),
+            );
+            let sum = gate.add(
+                ctx,
+                QuantumCell::Existing(&sum),
+                QuantumCell::Existing(&q400),
+            );
+            gate.sub(
+                ctx,
+                QuantumCell::Existing(&sum),
+                QuantumCell::Constant(F::from(LEAP_DAYS_BEFORE_1970)),
+            )
+        };
+        let years = gate.sub(
+            ctx,
+            QuantumCell::Existing(year),
+            QuantumCell::Constant(F::from(1970)),
+        );
+        let days = gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&years),
+            QuantumCell::Constant(F::from(365)),
+            QuantumCell::Existing(&leap_days),
+        );
+        let days = gate.add(
+            ctx,
+            QuantumCell::Existing(&days),
+            QuantumCell::Existing(&cumulative_days),
+        );
+        let days = gate.add(
+            ctx,
+            QuantumCell::Existing(&days),
+            QuantumCell::Existing(day),
+        );
+        gate.sub(
+            ctx,
+            QuantumCell::Existing(&days),
+            QuantumCell::Constant(F::from(1)),
+        )
+    }
+
+    /// Parse fixed-length decimal digits, constraining that every character is one of `0`-`9`.
+    fn parse_digits<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        chars: &[AssignedValue<'a, F>],
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let mut value = gate.load_zero(ctx);
+        for char in chars.iter() {
+            let digit = gate.sub(
+                ctx,
+                QuantumCell::Existing(char),
+                QuantumCell::Constant(F::from(b'0' as u64)),
+            );
+            self.assert_less_than_const(ctx, &digit, 10, 4);
+            value = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&value),
+                QuantumCell::Constant(F::from(10)),
+                QuantumCell::Existing(&digit),
+            );
+        }
+        value
+    }
+
+    /// Parse a three-letter month name into one of 1..=12.
+    fn parse_month_name<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        chars: &[AssignedValue<'a, F>],
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let mut packed = gate.load_zero(ctx);
+        for char in chars.iter() {
+            packed = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&packed),
+                QuantumCell::Constant(F::from(256)),
+                QuantumCell::Existing(char),
+            );
+        }
+        let mut month = gate.load_zero(ctx);
+        let mut is_month = gate.load_zero(ctx);
+        for (idx, name) in MONTH_NAMES.iter().enumerate() {
+            let name_val = name
+                .as_bytes()
+                .iter()
+                .fold(0u64, |acc, byte| acc * 256 + *byte as u64);
+            let is_eq = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(&packed),
+                QuantumCell::Constant(F::from(name_val)),
+            );
+            month = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_eq),
+                QuantumCell::Constant(F::from(idx as u64 + 1)),
+                QuantumCell::Existing(&month),
+            );
+            is_month = gate.add(
+                ctx,
+                QuantumCell::Existing(&is_month),
+                QuantumCell::Existing(&is_eq),
+            );
+        }
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_month),
+            QuantumCell::Constant(F::from(1)),
+        );
+        month
+    }
+
+    /// Divide `a` by a constant `divisor`, where `a` must be less than `2^YEAR_BITS`.
+    ///
+    /// Return the assigned quotient and remainder.
+    fn div_mod_const<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'a, F>,
+        divisor: u64,
+    ) -> (AssignedValue<'a, F>, AssignedValue<'a, F>) {
+        let gate = self.gate();
+        let a_val = a.value().map(|v| v.get_lower_32() as u64);
+        let quotient = gate.load_witness(ctx, a_val.map(|v| F::from(v / divisor)));
+        let remainder = gate.load_witness(ctx, a_val.map(|v| F::from(v % divisor)));
+        gate.num_to_bits(ctx, &quotient, YEAR_BITS);
+        let divisor_bits = (u64::BITS - divisor.leading_zeros()) as usize;
+        self.assert_less_than_const(ctx, &remainder, divisor, divisor_bits);
+        let recomposed = gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&quotient),
+            QuantumCell::Constant(F::from(divisor)),
+            QuantumCell::Existing(&remainder),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&recomposed),
+            QuantumCell::Existing(a),
+        );
+        (quotient, remainder)
+    }
+
+    fn assert_less_than_const<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'a, F>,
+        bound: u64,
+        num_bits: usize,
+    ) {
+        let gate = self.gate();
+        // `a` itself must fit in `num_bits` so that the comparison does not wrap around.
+        gate.num_to_bits(ctx, a, num_bits);
+        let bound = gate.load_constant(ctx, F::from(bound));
+        let is_less = self.decimal.is_less_than(ctx, a, &bound, num_bits);
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_less),
+            QuantumCell::Constant(F::from(1)),
+        );
+    }
+
+    fn assert_char<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        char: &AssignedValue<'a, F>,
+        expected: u8,
+    ) {
+        self.gate().assert_equal(
+            ctx,
+            QuantumCell::Existing(char),
+            QuantumCell::Constant(F::from(expected as u64)),
+        );
+    }
+}
+
+/// Convert a date substring into a Unix timestamp off-circuit, i.e., compute the output of [`DateConfig::parse_timestamp`].
+///
+/// # Arguments
+/// * `date` - the characters of the date substring.
+/// * `format` - the format of the date substring.
+///
+/// # Return values
+/// Return the Unix timestamp in seconds, or `None` if `date` is not a valid date of `format` or is before the epoch.
+pub fn date_to_timestamp(date: &[u8], format: DateFormat) -> Option<u64> {
+    if date.len() != format.substr_len() {
+        return None;
+    }
+    let digits = |range: std::ops::Range<usize>| {
+        date[range].iter().try_fold(0u64, |value, char| {
+            char.is_ascii_digit()
+                .then(|| value * 10 + (char - b'0') as u64)
+        })
+    };
+    // (year, month, day, seconds of the day, timezone offset in seconds)
+    let (year, month, day, seconds, offset) = match format {
+        DateFormat::Iso8601Date => {
+            if date[4] != b'-' || date[7] != b'-' {
+                return None;
+            }
+            (digits(0..4)?, digits(5..7)?, digits(8..10)?, 0, 0)
+        }
+        DateFormat::Rfc2822 => {
+            if [2, 6, 11, 20].iter().any(|idx| date[*idx] != b' ')
+                || date[14] != b':'
+                || date[17] != b':'
+            {
+                return None;
+            }
+            let month = MONTH_NAMES
+                .iter()
+                .position(|name| name.as_bytes() == &date[3..6])?;
+            let (hour, minute, second) = (digits(12..14)?, digits(15..17)?, digits(18..20)?);
+            let (tz_hour, tz_minute) = (digits(22..24)?, digits(24..26)?);
+            if hour >= 24 || minute >= 60 || second >= 61 || tz_hour >= 24 || tz_minute >= 60 {
+                return None;
+            }
+            let offset = (tz_hour * 3600 + tz_minute * 60) as i64;
+            let offset = match date[21] {
+                b'+' => offset,
+                b'-' => -offset,
+                _ => return None,
+            };
+            let seconds = (hour * 3600 + minute * 60 + second) as i64;
+            (
+                digits(7..11)?,
+                month as u64 + 1,
+                digits(0..2)?,
+                seconds,
+                offset,
+            )
+        }
+    };
+    if year < 1970 || !(1..=12).contains(&month) {
+        return None;
+    }
+    let is_leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let month_idx = month as usize - 1;
+    let days_in_month = DAYS_IN_MONTH[month_idx] + (is_leap && month == 2) as u64;
+    if day == 0 || day > days_in_month {
+        return None;
+    }
+    let leap_days = (year - 1) / 4 - (year - 1) / 100 + (year - 1) / 400 - LEAP_DAYS_BEFORE_1970;
+    let days = (year - 1970) * 365
+        + leap_days
+        + CUMULATIVE_DAYS[month_idx]
+        + (is_leap && month > 2) as u64
+        + day
+        - 1;
+    // The local time is ahead of UTC by `offset`.
+    u64::try_from(days as i64 * 86400 + seconds - offset).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 64;
+    const K: usize = 14;
+    /// The DFA of `d=[0-9A-Za-z :+-]+;`, which accepts invalid dates to be rejected by [`DateConfig`].
+    const ALLSTR_TEXT: &str = "# halo2-regex v2
+0
+4
+4
+0 1 100
+1 2 61
+2 3 48-58
+2 3 65-90
+2 3 97-122
+2 3 32
+2 3 43
+2 3 45
+3 3 48-58
+3 3 65-90
+3 3 97-122
+3 3 32
+3 3 43
+3 3 45
+3 4 59
+";
+    /// The substring `[0-9A-Za-z :+-]+` of [`ALLSTR_TEXT`].
+    const SUBSTR_TEXT: &str = "# halo2-regex v2
+32
+0
+63
+2
+3
+2 3
+3 3
+";
+
+    #[derive(Clone, Debug)]
+    struct TestDateCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        format: DateFormat,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestDateCircuit<F> {
+        const NUM_ADVICE: usize = 8;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestDateCircuit<F> {
+        type Config = (RegexVerifyConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                format: self.format,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: AllstrRegexDef::from_text(ALLSTR_TEXT),
+                substrs: vec![SubstrRegexDef::from_text(SUBSTR_TEXT)],
+            }];
+            let regex = RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (regex, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            let mut timestamp_cell = None;
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    let date = DateConfig::new(gate.clone(), self.format);
+                    timestamp_cell = Some(date.parse_timestamp(ctx, &result, 1)?.cell());
+                    Ok(())
+                },
+            )?;
+            if let Some(cell) = timestamp_cell {
+                layouter.constrain_instance(cell, instance, 0)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Run the circuit over `d={date};` with the timestamp `date_to_timestamp` computes, or zero for an invalid date.
+    fn run(date: &str, format: DateFormat) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = TestDateCircuit::<Fr> {
+            characters: format!("d={};", date).into_bytes(),
+            format,
+            _marker: PhantomData,
+        };
+        let timestamp = date_to_timestamp(date.as_bytes(), format).unwrap_or(0);
+        MockProver::run(K as u32, &circuit, vec![vec![Fr::from(timestamp)]])
+            .unwrap()
+            .verify()
+    }
+
+    #[test]
+    fn test_iso8601_date() {
+        let date = "2023-04-01";
+        assert_eq!(
+            date_to_timestamp(date.as_bytes(), DateFormat::Iso8601Date),
+            Some(1680307200)
+        );
+        assert_eq!(run(date, DateFormat::Iso8601Date), Ok(()));
+    }
+
+    #[test]
+    fn test_rfc2822_date() {
+        let date = "01 Apr 2023 12:34:56 +0900";
+        assert_eq!(
+            date_to_timestamp(date.as_bytes(), DateFormat::Rfc2822),
+            Some(1680320096)
+        );
+        assert_eq!(run(date, DateFormat::Rfc2822), Ok(()));
+    }
+
+    #[test]
+    fn test_wrong_timestamp() {
+        let circuit = TestDateCircuit::<Fr> {
+            characters: b"d=2023-04-01;".to_vec(),
+            format: DateFormat::Iso8601Date,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![vec![Fr::from(1680307201)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_leap_day() {
+        assert_eq!(
+            date_to_timestamp(b"2024-02-29", DateFormat::Iso8601Date),
+            Some(1709164800)
+        );
+        assert_eq!(run("2024-02-29", DateFormat::Iso8601Date), Ok(()));
+        assert_eq!(run("2000-02-29", DateFormat::Iso8601Date), Ok(()));
+        for date in ["2023-02-29", "2100-02-29"] {
+            assert_eq!(
+                date_to_timestamp(date.as_bytes(), DateFormat::Iso8601Date),
+                None
+            );
+            assert!(run(date, DateFormat::Iso8601Date).is_err());
+        }
+    }
+
+    #[test]
+    fn test_invalid_month_and_day() {
+        for date in [
+            "2023-13-01",
+            "2023-00-01",
+            "2023-01-32",
+            "2023-04-31",
+            "2023-04-00",
+        ] {
+            assert_eq!(
+                date_to_timestamp(date.as_bytes(), DateFormat::Iso8601Date),
+                None
+            );
+            assert!(run(date, DateFormat::Iso8601Date).is_err());
+        }
+        let date = "32 Jan 2023 00:00:00 +0000";
+        assert_eq!(
+            date_to_timestamp(date.as_bytes(), DateFormat::Rfc2822),
+            None
+        );
+        assert!(run(date, DateFormat::Rfc2822).is_err());
+    }
+
+    #[test]
+    fn test_year_before_epoch() {
+        let date = "1969-12-31";
+        assert_eq!(
+            date_to_timestamp(date.as_bytes(), DateFormat::Iso8601Date),
+            None
+        );
+        assert!(run(date, DateFormat::Iso8601Date).is_err());
+    }
+
+    #[test]
+    fn test_timezone_offsets() {
+        // The local time behind UTC is later in UTC.
+        let date = "01 Jan 1970 00:00:00 -0100";
+        assert_eq!(
+            date_to_timestamp(date.as_bytes(), DateFormat::Rfc2822),
+            Some(3600)
+        );
+        assert_eq!(run(date, DateFormat::Rfc2822), Ok(()));
+        // The local time ahead of UTC near the epoch is before the epoch in UTC.
+        let date = "01 Jan 1970 00:00:00 +0100";
+        assert_eq!(
+            date_to_timestamp(date.as_bytes(), DateFormat::Rfc2822),
+            None
+        );
+        assert!(run(date, DateFormat::Rfc2822).is_err());
+        let date = "01 Jan 1970 01:00:00 +0100";
+        assert_eq!(
+            date_to_timestamp(date.as_bytes(), DateFormat::Rfc2822),
+            Some(0)
+        );
+        assert_eq!(run(date, DateFormat::Rfc2822), Ok(()));
+    }
+}
@@ -0,0 +1,505 @@
+use crate::gadgets::input::{assert_regex_input, assign_chars, remove_chars};
+use crate::gadgets::numeric::DecimalConfig;
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// How [`FoldingConfig`] normalizes a folding sequence, i.e., `\r\n` followed by one or more spaces or tabs.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum FoldingMode {
+    /// Remove `\r\n` and keep the following whitespace as it is, as RFC 5322 unfolding does.
+    Unfold,
+    /// Remove `\r\n` and all but the first of the following whitespace characters.
+    Collapse,
+}
+
+/// Output type definition of [`FoldingConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct AssignedFoldingResult<'a, F: PrimeField> {
+    /// The assigned characters of the normalized string padded with zeros.
+    /// The length is equal to `max_input_size`.
+    pub normalized: Vec<AssignedValue<'a, F>>,
+    /// The assigned length of the normalized string.
+    pub normalized_len: AssignedValue<'a, F>,
+}
+
+/// Configuration to remove folding sequences, e.g., those splitting a long email header field into multiple lines, before the string is matched by [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+#[derive(Debug, Clone)]
+pub struct FoldingConfig<F: PrimeField> {
+    decimal: DecimalConfig<F>,
+    /// The maximum length of the input string.
+    pub max_input_size: usize,
+    /// The maximum number of the characters removed by the normalization.
+    pub max_removed_size: usize,
+    /// How each folding sequence is normalized.
+    pub mode: FoldingMode,
+}
+
+impl<F: PrimeField> FoldingConfig<F> {
+    /// Construct a new [`FoldingConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    /// * `max_input_size` - the maximum length of the input string.
+    /// * `max_removed_size` - the maximum number of the characters removed by the normalization.
+    /// * `mode` - how each folding sequence is normalized.
+    ///
+    /// # Return values
+    /// Return a new [`FoldingConfig`].
+    pub fn new(
+        gate: FlexGateConfig<F>,
+        max_input_size: usize,
+        max_removed_size: usize,
+        mode: FoldingMode,
+    ) -> Self {
+        Self {
+            decimal: DecimalConfig::new_unchecked(gate, 3),
+            max_input_size,
+            max_removed_size,
+            mode,
+        }
+    }
+
+    /// Assign the characters of the input string and normalize them in the circuit.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `input` - characters of the input string.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedFoldingResult`].
+    pub fn normalize<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        input: &[u8],
+    ) -> Result<AssignedFoldingResult<'a, F>, Error> {
+        let assigned = assign_chars(self.gate(), ctx, input, self.max_input_size)?;
+        self.normalize_assigned(ctx, &assigned)
+    }
+
+    /// Normalize the assigned characters, e.g., the output of another decoding stage, in the circuit.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `input` - the assigned characters of the input string padded with zeros, whose length must be equal to `max_input_size`.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedFoldingResult`], or [`Error::Synthesis`] if the length of `input` is not `max_input_size`.
+    pub fn normalize_assigned<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        input: &[AssignedValue<'a, F>],
+    ) -> Result<AssignedFoldingResult<'a, F>, Error> {
+        if input.len() != self.max_input_size {
+            return Err(Error::Synthesis);
+        }
+        let gate = self.gate();
+        let n = self.max_input_size;
+        let zero = gate.load_zero(ctx);
+        let is_char = |ctx: &mut Context<'v, F>, char: &AssignedValue<'a, F>, byte: u8| {
+            gate.is_equal(
+                ctx,
+                QuantumCell::Existing(char),
+                QuantumCell::Constant(F::from(byte as u64)),
+            )
+        };
+        let mut is_crs = vec![];
+        let mut is_lfs = vec![];
+        let mut is_wsps = vec![];
+        for char in input.iter() {
+            is_crs.push(is_char(ctx, char, b'\r'));
+            is_lfs.push(is_char(ctx, char, b'\n'));
+            let is_space = is_char(ctx, char, b' ');
+            let is_tab = is_char(ctx, char, b'\t');
+            is_wsps.push(gate.or(
+                ctx,
+                QuantumCell::Existing(&is_space),
+                QuantumCell::Existing(&is_tab),
+            ));
+        }
+        let flag_at =
+            |flags: &[AssignedValue<'a, F>], idx: usize| flags.get(idx).unwrap_or(&zero).clone();
+
+        // `is_fold_starts[idx]` indicates a folding sequence starts with `\r` at `idx`.
+        let mut is_fold_starts = vec![];
+        for idx in 0..n {
+            let is_crlf = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_crs[idx]),
+                QuantumCell::Existing(&flag_at(&is_lfs, idx + 1)),
+            );
+            is_fold_starts.push(gate.and(
+                ctx,
+                QuantumCell::Existing(&is_crlf),
+                QuantumCell::Existing(&flag_at(&is_wsps, idx + 2)),
+            ));
+        }
+        let mut is_removeds = vec![];
+        // `in_folds[idx]` indicates the `idx`-th character is whitespace following `\r\n` of a folding sequence.
+        let mut in_folds: Vec<AssignedValue<F>> = vec![];
+        for idx in 0..n {
+            let is_lf_of_fold = if idx >= 1 {
+                is_fold_starts[idx - 1].clone()
+            } else {
+                zero.clone()
+            };
+            let is_crlf = gate.or(
+                ctx,
+                QuantumCell::Existing(&is_fold_starts[idx]),
+                QuantumCell::Existing(&is_lf_of_fold),
+            );
+            let pre_is_lf_of_fold = if idx >= 2 {
+                is_fold_starts[idx - 2].clone()
+            } else {
+                zero.clone()
+            };
+            let pre_in_fold = if idx >= 1 {
+                in_folds[idx - 1].clone()
+            } else {
+                zero.clone()
+            };
+            let continues_fold = gate.or(
+                ctx,
+                QuantumCell::Existing(&pre_is_lf_of_fold),
+                QuantumCell::Existing(&pre_in_fold),
+            );
+            let in_fold = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_wsps[idx]),
+                QuantumCell::Existing(&continues_fold),
+            );
+            let is_removed = match self.mode {
+                FoldingMode::Unfold => is_crlf,
+                FoldingMode::Collapse => {
+                    // Only the first whitespace character of the folding sequence is kept.
+                    let is_extra_wsp = gate.and(
+                        ctx,
+                        QuantumCell::Existing(&in_fold),
+                        QuantumCell::Existing(&pre_in_fold),
+                    );
+                    gate.or(
+                        ctx,
+                        QuantumCell::Existing(&is_crlf),
+                        QuantumCell::Existing(&is_extra_wsp),
+                    )
+                }
+            };
+            is_removeds.push(is_removed);
+            in_folds.push(in_fold);
+        }
+        let (normalized, normalized_len) = remove_chars(
+            &self.decimal,
+            ctx,
+            input,
+            input,
+            &is_removeds,
+            self.max_removed_size,
+        );
+        Ok(AssignedFoldingResult {
+            normalized,
+            normalized_len,
+        })
+    }
+
+    /// Constrain that the input string of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) is equal to the normalized string.
+    ///
+    /// `match_substrs` must be called with the bytes returned by [`normalize_folding`].
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `normalized` - the output of [`FoldingConfig::normalize`] or [`FoldingConfig::normalize_assigned`].
+    /// * `regex_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    pub fn assert_regex_input<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        normalized: &AssignedFoldingResult<'a, F>,
+        regex_result: &AssignedRegexResult<'a, F>,
+    ) {
+        assert_regex_input(
+            self.gate(),
+            ctx,
+            &normalized.normalized,
+            &normalized.normalized_len,
+            regex_result,
+        );
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.decimal.gate()
+    }
+}
+
+/// Remove folding sequences off-circuit, i.e., compute the bytes passed to [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) along with [`FoldingConfig`].
+///
+/// # Arguments
+/// * `input` - characters of the input string.
+/// * `mode` - how each folding sequence is normalized.
+///
+/// # Return values
+/// Return the normalized bytes.
+pub fn normalize_folding(input: &[u8], mode: FoldingMode) -> Vec<u8> {
+    let is_wsp = |byte: &u8| *byte == b' ' || *byte == b'\t';
+    let mut normalized = vec![];
+    let mut idx = 0;
+    while idx < input.len() {
+        let is_fold = input[idx..].starts_with(b"\r\n") && input.get(idx + 2).map_or(false, is_wsp);
+        if !is_fold {
+            normalized.push(input[idx]);
+            idx += 1;
+            continue;
+        }
+        idx += 2;
+        match mode {
+            FoldingMode::Unfold => {}
+            FoldingMode::Collapse => {
+                normalized.push(input[idx]);
+                idx += 1;
+                while input.get(idx).map_or(false, is_wsp) {
+                    idx += 1;
+                }
+            }
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 32;
+    const MAX_REMOVED_SIZE: usize = 6;
+    const K: usize = 13;
+    /// The DFA of `[^;]+;` over `\t`, `\n`, `\r` and the bytes from 32 to 255, which accepts both the input strings and the normalized ones.
+    const ALLSTR_TEXT: &str = "# halo2-regex v2
+0
+2
+2
+0 1 9-10
+0 1 13
+0 1 32-58
+0 1 60-255
+1 1 9-10
+1 1 13
+1 1 32-58
+1 1 60-255
+1 2 59
+";
+    /// The substring `[^;]+` of [`ALLSTR_TEXT`].
+    const SUBSTR_TEXT: &str = "# halo2-regex v2
+32
+0
+31
+0
+1
+0 1
+1 1
+";
+
+    #[derive(Clone, Debug)]
+    struct TestFoldingCircuit<F: PrimeField> {
+        input: Vec<u8>,
+        normalized: Vec<u8>,
+        mode: FoldingMode,
+        /// The number of the characters assigned before [`FoldingConfig::normalize_assigned`], or `None` to call [`FoldingConfig::normalize`].
+        assigned_len: Option<usize>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestFoldingCircuit<F> {
+        const NUM_ADVICE: usize = 4;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestFoldingCircuit<F> {
+        type Config = RegexVerifyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                input: vec![],
+                normalized: vec![],
+                mode: self.mode,
+                assigned_len: self.assigned_len,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: AllstrRegexDef::from_text(ALLSTR_TEXT),
+                substrs: vec![SubstrRegexDef::from_text(SUBSTR_TEXT)],
+            }];
+            RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let folding = FoldingConfig::new(
+                        gate.clone(),
+                        MAX_STRING_LEN,
+                        MAX_REMOVED_SIZE,
+                        self.mode,
+                    );
+                    let normalized = match self.assigned_len {
+                        Some(len) => {
+                            let assigned = assign_chars(&gate, ctx, &self.input, len)?;
+                            folding.normalize_assigned(ctx, &assigned)?
+                        }
+                        None => folding.normalize(ctx, &self.input)?,
+                    };
+                    let result = config.match_substrs(ctx, &self.normalized)?;
+                    folding.assert_regex_input(ctx, &normalized, &result);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Run the circuit normalizing `input` whose regexes match `normalized`.
+    fn run(
+        input: &[u8],
+        normalized: &[u8],
+        mode: FoldingMode,
+        assigned_len: Option<usize>,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = TestFoldingCircuit::<Fr> {
+            input: input.to_vec(),
+            normalized: normalized.to_vec(),
+            mode,
+            assigned_len,
+            _marker: PhantomData,
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .verify()
+    }
+
+    /// Run the circuit by both [`FoldingConfig::normalize`] and [`FoldingConfig::normalize_assigned`] with the output of [`normalize_folding`].
+    fn run_normalized(input: &[u8], mode: FoldingMode) -> Result<(), Vec<VerifyFailure>> {
+        let normalized = normalize_folding(input, mode);
+        run(input, &normalized, mode, None)?;
+        run(input, &normalized, mode, Some(MAX_STRING_LEN))
+    }
+
+    #[test]
+    fn test_normalize_unfold() {
+        assert_eq!(
+            normalize_folding(b"a\r\n \tb;", FoldingMode::Unfold),
+            b"a \tb;".to_vec()
+        );
+        assert_eq!(run_normalized(b"a\r\n \tb;", FoldingMode::Unfold), Ok(()));
+        assert_eq!(
+            run_normalized(b"Subject: a\r\n b\r\n\tc;", FoldingMode::Unfold),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_normalize_collapse() {
+        assert_eq!(
+            normalize_folding(b"a\r\n \tb;", FoldingMode::Collapse),
+            b"a b;".to_vec()
+        );
+        assert_eq!(run_normalized(b"a\r\n \tb;", FoldingMode::Collapse), Ok(()));
+        assert_eq!(
+            run_normalized(b"Subject: a\r\n b\r\n\t  c;", FoldingMode::Collapse),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keep_line_break_without_whitespace() {
+        for mode in [FoldingMode::Unfold, FoldingMode::Collapse] {
+            assert_eq!(normalize_folding(b"a\r\nb;", mode), b"a\r\nb;".to_vec());
+            assert_eq!(run_normalized(b"a\r\nb;", mode), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_normalize_wrong_witness() {
+        for assigned_len in [None, Some(MAX_STRING_LEN)] {
+            // The folding sequence kept.
+            assert!(run(b"a\r\n b;", b"a\r\n b;", FoldingMode::Unfold, assigned_len).is_err());
+            // The whitespace of `Unfold` kept in `Collapse`.
+            assert!(run(b"a\r\n  b;", b"a  b;", FoldingMode::Collapse, assigned_len).is_err());
+            // The whitespace collapsed in `Unfold`.
+            assert!(run(b"a\r\n  b;", b"a b;", FoldingMode::Unfold, assigned_len).is_err());
+        }
+    }
+
+    #[test]
+    fn test_normalize_too_many_removed() {
+        // Each folding sequence removes two characters in `Unfold`, so four of them exceed `MAX_REMOVED_SIZE`.
+        assert_eq!(
+            run_normalized(b"a\r\n b\r\n c\r\n d;", FoldingMode::Unfold),
+            Ok(())
+        );
+        assert!(run_normalized(b"a\r\n b\r\n c\r\n d\r\n e;", FoldingMode::Unfold).is_err());
+    }
+
+    #[test]
+    fn test_normalize_assigned_wrong_length() {
+        let circuit = TestFoldingCircuit::<Fr> {
+            input: b"a\r\n b;".to_vec(),
+            normalized: b"a b;".to_vec(),
+            mode: FoldingMode::Unfold,
+            assigned_len: Some(MAX_STRING_LEN - 1),
+            _marker: PhantomData,
+        };
+        assert!(MockProver::run(K as u32, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_normalize_folding() {
+        let input = b"Subject: a long\r\n \tsubject\r\nFrom: x";
+        assert_eq!(
+            normalize_folding(input, FoldingMode::Unfold),
+            b"Subject: a long \tsubject\r\nFrom: x".to_vec()
+        );
+        assert_eq!(
+            normalize_folding(input, FoldingMode::Collapse),
+            b"Subject: a long subject\r\nFrom: x".to_vec()
+        );
+    }
+}
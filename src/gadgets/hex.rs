@@ -0,0 +1,355 @@
+use crate::gadgets::substr::SubstrCompactConfig;
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// The number of hex characters in an address.
+pub const ADDRESS_HEX_LEN: usize = 40;
+/// The number of bits in the lower limb of a parsed address.
+pub const ADDRESS_LO_BITS: usize = 128;
+
+/// Configuration to parse a `0x[0-9a-fA-F]{40}` substring extracted by [`RegexVerifyConfig`](crate::RegexVerifyConfig) into an address.
+#[derive(Debug, Clone)]
+pub struct HexAddressConfig<F: PrimeField> {
+    compact: SubstrCompactConfig<F>,
+}
+
+impl<F: PrimeField> HexAddressConfig<F> {
+    /// Construct a new [`HexAddressConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    ///
+    /// # Return values
+    /// Return a new [`HexAddressConfig`].
+    pub fn new(gate: FlexGateConfig<F>) -> Self {
+        Self {
+            compact: SubstrCompactConfig::new(gate),
+        }
+    }
+
+    /// Parse the substring whose substring id is `substr_id` as a hex address.
+    ///
+    /// The substring is constrained to be `0x` followed by exactly 40 hex characters (either lower or upper case).
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    /// * `substr_id` - the substring id of the address substring.
+    ///
+    /// # Return values
+    /// Return the assigned limbs `[hi, lo]` of the address, where `lo` is its lower 128 bits and `hi` is its upper 32 bits, i.e., `address = hi * 2^128 + lo`.
+    pub fn parse_address<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &AssignedRegexResult<'a, F>,
+        substr_id: usize,
+    ) -> Result<[AssignedValue<'a, F>; 2], Error> {
+        let chars = self
+            .compact
+            .compact(ctx, result, substr_id, ADDRESS_HEX_LEN + 2)?;
+        let gate = self.gate();
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&chars[0]),
+            QuantumCell::Constant(F::from(b'0' as u64)),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&chars[1]),
+            QuantumCell::Constant(F::from(b'x' as u64)),
+        );
+        let nibbles = chars[2..]
+            .iter()
+            .map(|char| self.hex_char_to_nibble(ctx, char))
+            .collect::<Vec<AssignedValue<F>>>();
+        let lo_start = ADDRESS_HEX_LEN - ADDRESS_LO_BITS / 4;
+        let hi = self.pack_nibbles(ctx, &nibbles[0..lo_start]);
+        let lo = self.pack_nibbles(ctx, &nibbles[lo_start..]);
+        Ok([hi, lo])
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.compact.gate()
+    }
+
+    /// Convert a hex character into its 4-bit value, constraining that the character is one of `0-9`, `a-f`, and `A-F`.
+    fn hex_char_to_nibble<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        char: &AssignedValue<'a, F>,
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let (nibble, is_hex) = hex_char_to_nibble_unchecked(gate, ctx, char);
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_hex),
+            QuantumCell::Constant(F::from(1)),
+        );
+        nibble
+    }
+
+    /// Pack big-endian nibbles into a field element.
+    fn pack_nibbles<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        nibbles: &[AssignedValue<'a, F>],
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let mut packed = gate.load_zero(ctx);
+        for nibble in nibbles.iter() {
+            packed = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&packed),
+                QuantumCell::Constant(F::from(16)),
+                QuantumCell::Existing(nibble),
+            );
+        }
+        packed
+    }
+}
+
+/// Convert a hex character into its 4-bit value without constraining that the character is a hex character.
+///
+/// Return the assigned 4-bit value, which is zero for a non-hex character, and a flag indicating the character is one of `0-9`, `a-f`, and `A-F`.
+pub(crate) fn hex_char_to_nibble_unchecked<'v: 'a, 'a, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    char: &AssignedValue<'a, F>,
+) -> (AssignedValue<'a, F>, AssignedValue<'a, F>) {
+    let mut nibble = gate.load_zero(ctx);
+    let mut is_hex = gate.load_zero(ctx);
+    for (byte, value) in hex_alphabet() {
+        let is_eq = gate.is_equal(
+            ctx,
+            QuantumCell::Existing(char),
+            QuantumCell::Constant(F::from(byte as u64)),
+        );
+        nibble = gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&is_eq),
+            QuantumCell::Constant(F::from(value as u64)),
+            QuantumCell::Existing(&nibble),
+        );
+        is_hex = gate.add(
+            ctx,
+            QuantumCell::Existing(&is_hex),
+            QuantumCell::Existing(&is_eq),
+        );
+    }
+    (nibble, is_hex)
+}
+
+/// Return pairs of (hex character, its 4-bit value).
+fn hex_alphabet() -> Vec<(u8, u8)> {
+    let digits = (b'0'..=b'9').zip(0..10);
+    let lowers = (b'a'..=b'f').zip(10..16);
+    let uppers = (b'A'..=b'F').zip(10..16);
+    digits.chain(lowers).chain(uppers).collect()
+}
+
+/// Compute the limbs `[hi, lo]` output by [`HexAddressConfig::parse_address`] off-circuit, e.g., to expose them as instances.
+///
+/// # Arguments
+/// * `substr` - the characters of the address substring.
+///
+/// # Return values
+/// Return the limbs, or `None` if `substr` is not `0x` followed by exactly 40 hex characters.
+pub fn address_limbs<F: PrimeField>(substr: &[u8]) -> Option<[F; 2]> {
+    let hex = substr.strip_prefix(b"0x")?;
+    if hex.len() != ADDRESS_HEX_LEN {
+        return None;
+    }
+    let alphabet = hex_alphabet();
+    let nibbles = hex
+        .iter()
+        .map(|char| {
+            alphabet
+                .iter()
+                .find(|(byte, _)| byte == char)
+                .map(|(_, value)| *value)
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    let pack = |nibbles: &[u8]| {
+        nibbles.iter().fold(F::zero(), |packed, nibble| {
+            packed * F::from(16) + F::from(*nibble as u64)
+        })
+    };
+    let lo_start = ADDRESS_HEX_LEN - ADDRESS_LO_BITS / 4;
+    Some([pack(&nibbles[0..lo_start]), pack(&nibbles[lo_start..])])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 64;
+    const K: usize = 14;
+    /// The DFA of `to=[0-9A-Za-z]+;`, which accepts non-hex substrings to be rejected by [`HexAddressConfig`].
+    const ALLSTR_TEXT: &str = "# halo2-regex v2
+0
+5
+5
+0 1 116
+1 2 111
+2 3 61
+3 4 48-57
+3 4 65-90
+3 4 97-122
+4 4 48-57
+4 4 65-90
+4 4 97-122
+4 5 59
+";
+    /// The substring `[0-9A-Za-z]+` of [`ALLSTR_TEXT`].
+    const SUBSTR_TEXT: &str = "# halo2-regex v2
+48
+0
+63
+3
+4
+3 4
+4 4
+";
+
+    #[derive(Default, Clone, Debug)]
+    struct TestAddressCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestAddressCircuit<F> {
+        // Compacting the address takes about 16 cells for each pair of a character and a position.
+        const NUM_ADVICE: usize = 8;
+        const NUM_FIXED: usize = 1;
+
+        fn new(characters: &str) -> Self {
+            Self {
+                characters: characters.as_bytes().to_vec(),
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestAddressCircuit<F> {
+        type Config = (RegexVerifyConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: AllstrRegexDef::from_text(ALLSTR_TEXT),
+                substrs: vec![SubstrRegexDef::from_text(SUBSTR_TEXT)],
+            }];
+            let regex = RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (regex, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            let mut limb_cells = vec![];
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    let hex = HexAddressConfig::new(gate.clone());
+                    limb_cells = hex
+                        .parse_address(ctx, &result, 1)?
+                        .iter()
+                        .map(|limb| limb.cell())
+                        .collect();
+                    Ok(())
+                },
+            )?;
+            for (row, cell) in limb_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run(characters: &str, limbs: [Fr; 2]) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = TestAddressCircuit::<Fr>::new(characters);
+        MockProver::run(K as u32, &circuit, vec![limbs.to_vec()])
+            .unwrap()
+            .verify()
+    }
+
+    #[test]
+    fn test_parse_valid_address() {
+        let address = "0x1234567800000000000000000000000000000abc";
+        let limbs = address_limbs::<Fr>(address.as_bytes()).unwrap();
+        assert_eq!(limbs, [Fr::from(0x12345678), Fr::from(0xabc)]);
+        assert_eq!(run(&format!("to={};", address), limbs), Ok(()));
+        // The limbs are bound to the instances.
+        assert!(run(&format!("to={};", address), [limbs[0], Fr::from(0xabd)]).is_err());
+    }
+
+    #[test]
+    fn test_parse_mixed_case_address() {
+        let address = "0xAbCdEf7800000000000000000000000000000aBc";
+        let limbs = address_limbs::<Fr>(address.as_bytes()).unwrap();
+        assert_eq!(
+            Some(limbs),
+            address_limbs(address.to_lowercase().as_bytes())
+        );
+        assert_eq!(run(&format!("to={};", address), limbs), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_non_hex_address() {
+        let address = "0x1234567800000000000000000000000000000abg";
+        assert_eq!(address_limbs::<Fr>(address.as_bytes()), None);
+        assert!(run(&format!("to={};", address), [Fr::from(0); 2]).is_err());
+    }
+
+    #[test]
+    fn test_parse_address_without_prefix() {
+        let address = "001234567800000000000000000000000000000abc";
+        assert_eq!(address_limbs::<Fr>(address.as_bytes()), None);
+        assert!(run(&format!("to={};", address), [Fr::from(0); 2]).is_err());
+    }
+}
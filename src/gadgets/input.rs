@@ -0,0 +1,400 @@
+use crate::gadgets::numeric::DecimalConfig;
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::circuit::Value;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Constrain that the input string of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) is equal to `decoded`, the output of a decoding stage.
+///
+/// # Arguments
+/// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+/// * `ctx` - a region context.
+/// * `decoded` - the assigned bytes of the decoded string padded with zeros, which are compared with the padding byte of `regex_result` beyond the decoded string.
+/// * `decoded_len` - the assigned length of the decoded string.
+/// * `regex_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+pub fn assert_regex_input<'v: 'a, 'a, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    decoded: &[AssignedValue<'a, F>],
+    decoded_len: &AssignedValue<'a, F>,
+    regex_result: &AssignedRegexResult<'a, F>,
+) {
+    let padding_byte = F::from(regex_result.padding_byte as u64);
+    for (idx, (char, enable)) in regex_result
+        .all_characters
+        .iter()
+        .zip(regex_result.all_enable_flags.iter())
+        .enumerate()
+    {
+        // The padded characters of the regexes are the padding byte, while the decoded bytes beyond the decoded string are zero.
+        let expected = match (decoded.get(idx), regex_result.padding_byte) {
+            (Some(byte), 0) => byte.clone(),
+            (Some(byte), _) => {
+                let not_enable = gate.not(ctx, QuantumCell::Existing(enable));
+                gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&not_enable),
+                    QuantumCell::Constant(padding_byte),
+                    QuantumCell::Existing(byte),
+                )
+            }
+            (None, _) => {
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(char),
+                    QuantumCell::Constant(padding_byte),
+                );
+                continue;
+            }
+        };
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(char),
+            QuantumCell::Existing(&expected),
+        );
+    }
+    // The decoded bytes beyond the input string must be zero.
+    for byte in decoded[regex_result.all_characters.len().min(decoded.len())..].iter() {
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(byte),
+            QuantumCell::Constant(F::from(0)),
+        );
+    }
+    let mut input_len = gate.load_zero(ctx);
+    for enable in regex_result.all_enable_flags.iter() {
+        input_len = gate.add(
+            ctx,
+            QuantumCell::Existing(&input_len),
+            QuantumCell::Existing(enable),
+        );
+    }
+    gate.assert_equal(
+        ctx,
+        QuantumCell::Existing(&input_len),
+        QuantumCell::Existing(decoded_len),
+    );
+}
+
+/// Same as [`assert_regex_input`] except that the character cells of `regex_result` are range-checked to 8 bits, e.g., if `decoded` is output by another chip.
+///
+/// Otherwise the character cells are bounded only implicitly by the characters of the lookup tables, which in the universal mode are witnesses bound by their digest, so a decoded value of 256 or more must not rely on them.
+///
+/// # Arguments
+/// * `range` - a configuration for [`RangeConfig`], whose gate must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig) and whose lookup table must be loaded and finalized by the caller.
+/// * `ctx` - a region context.
+/// * `decoded` - the assigned bytes of the decoded string padded with zeros.
+/// * `decoded_len` - the assigned length of the decoded string.
+/// * `regex_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+pub fn assert_regex_input_in_range<'v: 'a, 'a, F: PrimeField>(
+    range: &RangeConfig<F>,
+    ctx: &mut Context<'v, F>,
+    decoded: &[AssignedValue<'a, F>],
+    decoded_len: &AssignedValue<'a, F>,
+    regex_result: &AssignedRegexResult<'a, F>,
+) {
+    range_check_bytes(range, ctx, &regex_result.all_characters);
+    assert_regex_input(range.gate(), ctx, decoded, decoded_len, regex_result);
+}
+
+/// Constrain that each of `bytes` is less than 256.
+///
+/// # Arguments
+/// * `range` - a configuration for [`RangeConfig`].
+/// * `ctx` - a region context.
+/// * `bytes` - the assigned values to be range-checked, e.g., the character cells of [`AssignedRegexResult`].
+pub fn range_check_bytes<'v: 'a, 'a, F: PrimeField>(
+    range: &RangeConfig<F>,
+    ctx: &mut Context<'v, F>,
+    bytes: &[AssignedValue<'a, F>],
+) {
+    for byte in bytes.iter() {
+        range.range_check(ctx, byte, 8);
+    }
+}
+
+/// Assign the characters of an input string padded with zeros up to `max_len`.
+///
+/// # Arguments
+/// * `gate` - a configuration for [`FlexGateConfig`].
+/// * `ctx` - a region context.
+/// * `chars` - characters of the input string.
+/// * `max_len` - the maximum length of the input string.
+///
+/// # Return values
+/// Return the assigned characters, whose length is equal to `max_len`, or [`Error::Synthesis`] if `chars` is longer than `max_len`.
+pub fn assign_chars<'v: 'a, 'a, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    chars: &[u8],
+    max_len: usize,
+) -> Result<Vec<AssignedValue<'a, F>>, Error> {
+    if chars.len() > max_len {
+        return Err(Error::Synthesis);
+    }
+    Ok((0..max_len)
+        .map(|idx| {
+            let char = chars.get(idx).copied().unwrap_or(0);
+            gate.load_witness(ctx, Value::known(F::from(char as u64)))
+        })
+        .collect())
+}
+
+/// Remove the characters flagged by `is_removeds` and move the remaining ones to the front.
+///
+/// Zero characters are constrained to appear only after the end of the string.
+///
+/// # Arguments
+/// * `decimal` - a configuration for [`DecimalConfig`] used to bound the number of the removed characters.
+/// * `ctx` - a region context.
+/// * `chars` - the assigned characters of the string padded with zeros.
+/// * `values` - the assigned values output in place of each character of `chars`.
+/// * `is_removeds` - the assigned flags indicating each character of `chars` is removed.
+/// * `max_removed_size` - the maximum number of the removed characters.
+///
+/// # Return values
+/// Return the assigned characters left after the removal padded with zeros, whose length is equal to that of `chars`, and their assigned length.
+pub(crate) fn remove_chars<'v: 'a, 'a, F: PrimeField>(
+    decimal: &DecimalConfig<F>,
+    ctx: &mut Context<'v, F>,
+    chars: &[AssignedValue<'a, F>],
+    values: &[AssignedValue<'a, F>],
+    is_removeds: &[AssignedValue<'a, F>],
+    max_removed_size: usize,
+) -> (Vec<AssignedValue<'a, F>>, AssignedValue<'a, F>) {
+    let gate = decimal.gate();
+    let n = chars.len();
+    let zero = gate.load_zero(ctx);
+    let mut num_removed = zero.clone();
+    let mut pre_is_zero = zero.clone();
+    let mut is_emitteds = vec![];
+    let mut out_len = zero.clone();
+    // `shifts[idx]` is the number of the removed characters before the `idx`-th character.
+    let mut shifts = vec![zero.clone()];
+    for idx in 0..n {
+        // Zero (padded) characters appear only after the end of the string.
+        let is_zero = gate.is_zero(ctx, &chars[idx]);
+        let not_zero = gate.not(ctx, QuantumCell::Existing(&is_zero));
+        let is_invalid_zero = gate.and(
+            ctx,
+            QuantumCell::Existing(&pre_is_zero),
+            QuantumCell::Existing(&not_zero),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_invalid_zero),
+            QuantumCell::Constant(F::from(0)),
+        );
+        num_removed = gate.add(
+            ctx,
+            QuantumCell::Existing(&num_removed),
+            QuantumCell::Existing(&is_removeds[idx]),
+        );
+        shifts.push(num_removed.clone());
+        let is_removed_or_zero = gate.or(
+            ctx,
+            QuantumCell::Existing(&is_removeds[idx]),
+            QuantumCell::Existing(&is_zero),
+        );
+        let is_emitted = gate.not(ctx, QuantumCell::Existing(&is_removed_or_zero));
+        out_len = gate.add(
+            ctx,
+            QuantumCell::Existing(&out_len),
+            QuantumCell::Existing(&is_emitted),
+        );
+        is_emitteds.push(is_emitted);
+        pre_is_zero = is_zero;
+    }
+    let max_removed = gate.load_constant(ctx, F::from(max_removed_size as u64 + 1));
+    let num_bits = (usize::BITS - (n + max_removed_size + 1).leading_zeros()) as usize;
+    let is_within = decimal.is_less_than(ctx, &num_removed, &max_removed, num_bits);
+    gate.assert_equal(
+        ctx,
+        QuantumCell::Existing(&is_within),
+        QuantumCell::Constant(F::from(1)),
+    );
+
+    // The `idx`-th emitted character moves to `idx - shifts[idx]`.
+    let mut out = vec![];
+    for out_idx in 0..n {
+        let mut byte = zero.clone();
+        for shift in 0..=max_removed_size {
+            let idx = out_idx + shift;
+            if idx >= n {
+                break;
+            }
+            let is_shift = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(&shifts[idx]),
+                QuantumCell::Constant(F::from(shift as u64)),
+            );
+            let is_moved = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_shift),
+                QuantumCell::Existing(&is_emitteds[idx]),
+            );
+            byte = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_moved),
+                QuantumCell::Existing(&values[idx]),
+                QuantumCell::Existing(&byte),
+            );
+        }
+        out.push(byte);
+    }
+    (out, out_len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::gates::range::RangeStrategy;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 64;
+    const K: usize = 13;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestRangeCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        /// The position and the value of a character cell replacing that of both the regex result and the decoded string, if any.
+        injected: Option<(usize, F)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestRangeCircuit<F> {
+        const NUM_ADVICE: usize = 4;
+        const NUM_LOOKUP_ADVICE: usize = 1;
+        const NUM_FIXED: usize = 1;
+        const LOOKUP_BITS: usize = 8;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRangeCircuit<F> {
+        type Config = (RegexVerifyConfig<F>, RangeConfig<F>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                injected: None,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def =
+                AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt");
+            let substr_def =
+                SubstrRegexDef::read_from_text("./test_regexes/substr4_test_lookup.txt");
+            let range = RangeConfig::<F>::configure(
+                meta,
+                RangeStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                &[Self::NUM_LOOKUP_ADVICE],
+                Self::NUM_FIXED,
+                Self::LOOKUP_BITS,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: all_regex_def,
+                substrs: vec![substr_def],
+            }];
+            let regex = RegexVerifyConfig::configure(
+                meta,
+                MAX_STRING_LEN,
+                range.gate().clone(),
+                regex_defs,
+            );
+            (regex, range)
+        }
+
+        fn synthesize(
+            &self,
+            (config, range): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            range.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let mut decoded = assign_chars(&gate, ctx, &self.characters, MAX_STRING_LEN)?;
+                    let mut result = config.match_substrs(ctx, &self.characters)?;
+                    if let Some((idx, value)) = self.injected {
+                        // The same cell replaces both so that only the range check can reject it.
+                        let char = gate.load_witness(ctx, Value::known(value));
+                        result.all_characters[idx] = char.clone();
+                        decoded[idx] = char;
+                    }
+                    let decoded_len =
+                        gate.load_witness(ctx, Value::known(F::from(self.characters.len() as u64)));
+                    assert_regex_input_in_range(&range, ctx, &decoded, &decoded_len, &result);
+                    range.finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_regex_input_in_range() {
+        let circuit = TestRangeCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            injected: None,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_regex_input_out_of_range() {
+        let run = |value: Fr| {
+            let circuit = TestRangeCircuit::<Fr> {
+                characters: b"amount=1000;".to_vec(),
+                injected: Some((8, value)),
+                _marker: PhantomData,
+            };
+            MockProver::run(K as u32, &circuit, vec![])
+                .unwrap()
+                .verify()
+        };
+        assert_eq!(run(Fr::from(b'5' as u64)), Ok(()));
+        assert_eq!(run(Fr::from(255)), Ok(()));
+        assert!(run(Fr::from(256)).is_err());
+        assert!(run(-Fr::from(1)).is_err());
+    }
+
+    #[test]
+    fn test_assign_too_many_chars() {
+        let circuit = TestRangeCircuit::<Fr> {
+            characters: vec![b'a'; MAX_STRING_LEN + 1],
+            injected: None,
+            _marker: PhantomData,
+        };
+        assert!(MockProver::run(K as u32, &circuit, vec![]).is_err());
+    }
+}
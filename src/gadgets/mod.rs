@@ -0,0 +1,22 @@
+/// Base64 decoding of the input string before regex matching.
+pub mod base64;
+/// Conversion of extracted date substrings into Unix timestamps.
+pub mod date;
+/// Normalization of folded lines of the input string before regex matching.
+pub mod folding;
+/// Parsing of extracted hex addresses into field elements.
+pub mod hex;
+/// Connection between decoding stages and the input string of regex matching.
+pub mod input;
+/// Decimal parsing and comparisons on extracted digit substrings.
+pub mod numeric;
+/// Poseidon hash in the circuit.
+pub mod poseidon;
+/// Quoted-printable decoding of the input string before regex matching.
+pub mod qp;
+/// Selector-controlled reveal of extracted substrings or their commitments.
+pub mod reveal;
+/// SHA-256 hash of the input string in the circuit.
+pub mod sha256;
+/// Compaction of extracted substrings and equality with expected values.
+pub mod substr;
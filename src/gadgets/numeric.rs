@@ -0,0 +1,403 @@
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Error definitions of [`DecimalConfig::new`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecimalConfigError {
+    #[error("At least one digit is required")]
+    NoDigit,
+    #[error("{max_digits} digits need {num_bits} bits, which do not fit in the field of {field_bits} bits")]
+    TooManyDigits {
+        max_digits: usize,
+        num_bits: usize,
+        field_bits: usize,
+    },
+}
+
+/// Configuration to parse a decimal substring extracted by [`RegexVerifyConfig`](crate::RegexVerifyConfig) into a field element.
+#[derive(Debug, Clone)]
+pub struct DecimalConfig<F: PrimeField> {
+    gate: FlexGateConfig<F>,
+    /// The maximum number of digits in the parsed substring.
+    pub max_digits: usize,
+}
+
+impl<F: PrimeField> DecimalConfig<F> {
+    /// Construct a new [`DecimalConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    /// * `max_digits` - the maximum number of digits in the parsed substring.
+    ///
+    /// # Return values
+    /// Return a new [`DecimalConfig`], or [`DecimalConfigError`] if `max_digits` is zero or 10^`max_digits` does not fit in the field.
+    pub fn new(gate: FlexGateConfig<F>, max_digits: usize) -> Result<Self, DecimalConfigError> {
+        if max_digits == 0 {
+            return Err(DecimalConfigError::NoDigit);
+        }
+        // 10^`max_digits` and its bit decomposition must fit in the field without wrapping around.
+        let num_bits = Self::bits_of_digits(max_digits);
+        if num_bits >= F::NUM_BITS as usize {
+            return Err(DecimalConfigError::TooManyDigits {
+                max_digits,
+                num_bits,
+                field_bits: F::NUM_BITS as usize,
+            });
+        }
+        Ok(Self { gate, max_digits })
+    }
+
+    /// Same as [`DecimalConfig::new`] except that `max_digits` is not checked, which the other chips use only for [`DecimalConfig::is_less_than`] with a few digits fitting in any field.
+    pub(crate) fn new_unchecked(gate: FlexGateConfig<F>, max_digits: usize) -> Self {
+        Self { gate, max_digits }
+    }
+
+    /// Parse the substring whose substring id is `substr_id` as a decimal number.
+    ///
+    /// Every character of the substring is constrained to be one of `0`-`9`, and the number of the characters is constrained to be at most `max_digits`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    /// * `substr_id` - the substring id of the decimal substring.
+    ///
+    /// # Return values
+    /// Return the assigned value of the parsed number.
+    pub fn parse_substr<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &AssignedRegexResult<'a, F>,
+        substr_id: usize,
+    ) -> Result<AssignedValue<'a, F>, Error> {
+        let gate = self.gate();
+        let mut value = gate.load_zero(ctx);
+        let mut num_digits = gate.load_zero(ctx);
+        let ten = gate.load_constant(ctx, F::from(10));
+        for (char, id) in result
+            .masked_characters
+            .iter()
+            .zip(result.all_substr_ids.iter())
+        {
+            let is_digit = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(id),
+                QuantumCell::Constant(F::from(substr_id as u64)),
+            );
+            let shifted = gate.sub(
+                ctx,
+                QuantumCell::Existing(char),
+                QuantumCell::Constant(F::from(b'0' as u64)),
+            );
+            let digit = gate.mul(
+                ctx,
+                QuantumCell::Existing(&is_digit),
+                QuantumCell::Existing(&shifted),
+            );
+            // The characters below `0` wrap around to the top of the field, which `is_less_than` over 4 bits accepts unless `digit` is first constrained to 4 bits.
+            gate.num_to_bits(ctx, &digit, 4);
+            let is_valid = self.is_less_than(ctx, &digit, &ten, 4);
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(&is_valid),
+                QuantumCell::Constant(F::from(1)),
+            );
+            let appended = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&value),
+                QuantumCell::Constant(F::from(10)),
+                QuantumCell::Existing(&digit),
+            );
+            value = gate.select(
+                ctx,
+                QuantumCell::Existing(&appended),
+                QuantumCell::Existing(&value),
+                QuantumCell::Existing(&is_digit),
+            );
+            num_digits = gate.add(
+                ctx,
+                QuantumCell::Existing(&num_digits),
+                QuantumCell::Existing(&is_digit),
+            );
+        }
+        let count_bits = Self::bit_length(result.masked_characters.len() as u64);
+        let max_count = gate.load_constant(ctx, F::from(self.max_digits as u64 + 1));
+        let is_short = self.is_less_than(ctx, &num_digits, &max_count, count_bits);
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_short),
+            QuantumCell::Constant(F::from(1)),
+        );
+        Ok(value)
+    }
+
+    /// Constrain that `value <= bound`.
+    ///
+    /// Both `value` and `bound` must be less than `10^max_digits`, e.g., `value` is an output of [`DecimalConfig::parse_substr`].
+    /// `bound` can be constrained to be equal to an instance cell to compare the parsed number with a public bound.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `value` - the assigned value to be compared.
+    /// * `bound` - the assigned upper bound.
+    pub fn assert_less_or_equal<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        value: &AssignedValue<'a, F>,
+        bound: &AssignedValue<'a, F>,
+    ) {
+        let num_bits = Self::bits_of_digits(self.max_digits);
+        let is_greater = self.is_less_than(ctx, bound, value, num_bits);
+        self.gate().assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_greater),
+            QuantumCell::Constant(F::from(0)),
+        );
+    }
+
+    /// Return an assigned bit that is one iff `a < b`.
+    ///
+    /// Both `a` and `b` must be less than `2^num_bits`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - the left-hand side of the comparison.
+    /// * `b` - the right-hand side of the comparison.
+    /// * `num_bits` - the maximum bit length of `a` and `b`.
+    pub fn is_less_than<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'a, F>,
+        b: &AssignedValue<'a, F>,
+        num_bits: usize,
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        // `a + 2^num_bits - b` has the `num_bits`-th bit iff `a >= b`.
+        let shifted = gate.add(
+            ctx,
+            QuantumCell::Existing(a),
+            QuantumCell::Constant(F::from(2).pow_vartime(&[num_bits as u64])),
+        );
+        let diff = gate.sub(
+            ctx,
+            QuantumCell::Existing(&shifted),
+            QuantumCell::Existing(b),
+        );
+        let bits = gate.num_to_bits(ctx, &diff, num_bits + 1);
+        gate.not(ctx, QuantumCell::Existing(&bits[num_bits]))
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        &self.gate
+    }
+
+    fn bits_of_digits(num_digits: usize) -> usize {
+        (num_digits as f64 * 10f64.log2()).ceil() as usize
+    }
+
+    fn bit_length(value: u64) -> usize {
+        (u64::BITS - value.leading_zeros()) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 64;
+    const K: usize = 13;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestDecimalCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        bound: u64,
+        /// Whether `bound` is a witness constrained to the instance instead of a constant.
+        public_bound: bool,
+        /// The index and the byte of a masked character replaced after the regex matching, as a malicious prover would assign it.
+        tampered: Option<(usize, u8)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestDecimalCircuit<F> {
+        const NUM_ADVICE: usize = 4;
+        const NUM_FIXED: usize = 1;
+        const MAX_DIGITS: usize = 8;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestDecimalCircuit<F> {
+        type Config = (RegexVerifyConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                bound: 0,
+                public_bound: self.public_bound,
+                tampered: None,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def =
+                AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt");
+            let substr_def =
+                SubstrRegexDef::read_from_text("./test_regexes/substr4_test_lookup.txt");
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: all_regex_def,
+                substrs: vec![substr_def],
+            }];
+            let regex = RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (regex, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            let mut bound_cell = None;
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let mut result = config.match_substrs(ctx, &self.characters)?;
+                    if let Some((idx, char)) = self.tampered {
+                        result.masked_characters[idx] =
+                            gate.load_witness(ctx, Value::known(F::from(char as u64)));
+                    }
+                    let decimal = DecimalConfig::new(gate.clone(), Self::MAX_DIGITS).unwrap();
+                    let value = decimal.parse_substr(ctx, &result, 1)?;
+                    let bound = if self.public_bound {
+                        let bound = gate.load_witness(ctx, Value::known(F::from(self.bound)));
+                        bound_cell = Some(bound.cell());
+                        bound
+                    } else {
+                        gate.load_constant(ctx, F::from(self.bound))
+                    };
+                    decimal.assert_less_or_equal(ctx, &value, &bound);
+                    Ok(())
+                },
+            )?;
+            if let Some(cell) = bound_cell {
+                layouter.constrain_instance(cell, instance, 0)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_decimal_within_bound() {
+        let circuit = TestDecimalCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            bound: 1000,
+            public_bound: false,
+            tampered: None,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_decimal_exceeds_bound() {
+        let circuit = TestDecimalCircuit::<Fr> {
+            characters: b"amount=1001;".to_vec(),
+            bound: 1000,
+            public_bound: false,
+            tampered: None,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_decimal_rejects_char_below_zero() {
+        // '/' is one less than '0', so that its digit wraps around to -1.
+        let circuit = TestDecimalCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            bound: 10000,
+            public_bound: false,
+            tampered: Some((8, b'/')),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_decimal_public_bound() {
+        let circuit = |characters: &[u8], bound| TestDecimalCircuit::<Fr> {
+            characters: characters.to_vec(),
+            bound,
+            public_bound: true,
+            tampered: None,
+            _marker: PhantomData,
+        };
+        let verify = |circuit, public_bound: u64| {
+            MockProver::run(K as u32, &circuit, vec![vec![Fr::from(public_bound)]])
+                .unwrap()
+                .verify()
+        };
+        assert_eq!(verify(circuit(b"amount=1000;", 1000), 1000), Ok(()));
+        assert!(verify(circuit(b"amount=1001;", 1000), 1000).is_err());
+        // The bound assigned by the prover must be the public one.
+        assert!(verify(circuit(b"amount=1001;", 2000), 1000).is_err());
+    }
+
+    #[test]
+    fn test_decimal_config_digits() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let gate = FlexGateConfig::<Fr>::configure(
+            &mut meta,
+            halo2_base::gates::flex_gate::GateStrategy::Vertical,
+            &[1],
+            1,
+            0,
+            K,
+        );
+        assert!(DecimalConfig::new(gate.clone(), 76).is_ok());
+        assert_eq!(
+            DecimalConfig::new(gate.clone(), 0).unwrap_err(),
+            DecimalConfigError::NoDigit
+        );
+        assert!(matches!(
+            DecimalConfig::new(gate, 77),
+            Err(DecimalConfigError::TooManyDigits { max_digits: 77, .. })
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+use ::poseidon::{Poseidon, SparseMDSMatrix, Spec, State};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// The width of the Poseidon permutation.
+pub const POSEIDON_T: usize = 3;
+/// The rate of the Poseidon sponge.
+pub const POSEIDON_RATE: usize = 2;
+/// The number of the full rounds.
+pub const POSEIDON_R_F: usize = 8;
+/// The number of the partial rounds.
+pub const POSEIDON_R_P: usize = 57;
+
+/// Configuration to compute the Poseidon hash in the circuit, which is equal to that computed by [`poseidon_hash`].
+#[derive(Debug, Clone)]
+pub struct PoseidonConfig<F: PrimeField> {
+    gate: FlexGateConfig<F>,
+    spec: Spec<F, POSEIDON_T, POSEIDON_RATE>,
+}
+
+impl<F: PrimeField> PoseidonConfig<F> {
+    /// Construct a new [`PoseidonConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`].
+    ///
+    /// # Return values
+    /// Return a new [`PoseidonConfig`].
+    pub fn new(gate: FlexGateConfig<F>) -> Self {
+        Self {
+            gate,
+            spec: Spec::new(POSEIDON_R_F, POSEIDON_R_P),
+        }
+    }
+
+    /// Hash `inputs` in the circuit.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `inputs` - the assigned field elements to be hashed.
+    ///
+    /// # Return values
+    /// Return the assigned hash value.
+    pub fn hash<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        inputs: &[AssignedValue<'a, F>],
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let mut state = State::<F, POSEIDON_T>::default()
+            .words()
+            .into_iter()
+            .map(|word| gate.load_constant(ctx, word))
+            .collect::<Vec<AssignedValue<F>>>();
+        for chunk in inputs.chunks(POSEIDON_RATE) {
+            state = self.permutation(ctx, state, chunk);
+        }
+        if inputs.len() % POSEIDON_RATE == 0 {
+            state = self.permutation(ctx, state, &[]);
+        }
+        state[1].clone()
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        &self.gate
+    }
+
+    fn permutation<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: Vec<AssignedValue<'a, F>>,
+        inputs: &[AssignedValue<'a, F>],
+    ) -> Vec<AssignedValue<'a, F>> {
+        let r_f = self.spec.r_f() / 2;
+        let mds = self.spec.mds_matrices().mds().rows();
+        let constants = self.spec.constants().start();
+        let mut state = self.absorb_with_pre_constants(ctx, state, inputs, &constants[0]);
+        for constants in constants.iter().skip(1).take(r_f - 1) {
+            state = self.sbox_full(ctx, state, constants);
+            state = self.apply_mds(ctx, state, &mds);
+        }
+        let pre_sparse_mds = self.spec.mds_matrices().pre_sparse_mds().rows();
+        state = self.sbox_full(ctx, state, constants.last().unwrap());
+        state = self.apply_mds(ctx, state, &pre_sparse_mds);
+
+        let sparse_matrices = self.spec.mds_matrices().sparse_matrices();
+        let constants = self.spec.constants().partial();
+        for (constant, sparse_mds) in constants.iter().zip(sparse_matrices.iter()) {
+            state[0] = self.x_power5_with_constant(ctx, &state[0], constant);
+            state = self.apply_sparse_mds(ctx, state, sparse_mds);
+        }
+
+        let constants = self.spec.constants().end();
+        for constants in constants.iter() {
+            state = self.sbox_full(ctx, state, constants);
+            state = self.apply_mds(ctx, state, &mds);
+        }
+        state = self.sbox_full(ctx, state, &[F::zero(); POSEIDON_T]);
+        self.apply_mds(ctx, state, &mds)
+    }
+
+    /// Add `inputs` and the first round constants to the state.
+    fn absorb_with_pre_constants<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: Vec<AssignedValue<'a, F>>,
+        inputs: &[AssignedValue<'a, F>],
+        pre_constants: &[F; POSEIDON_T],
+    ) -> Vec<AssignedValue<'a, F>> {
+        assert!(inputs.len() < POSEIDON_T);
+        let gate = self.gate();
+        state
+            .iter()
+            .enumerate()
+            .map(|(idx, word)| {
+                let mut constant = pre_constants[idx];
+                // The padding of the sponge is the one following the last input.
+                if idx == inputs.len() + 1 {
+                    constant += F::one();
+                }
+                let word = gate.add(
+                    ctx,
+                    QuantumCell::Existing(word),
+                    QuantumCell::Constant(constant),
+                );
+                match idx.checked_sub(1).and_then(|idx| inputs.get(idx)) {
+                    Some(input) => gate.add(
+                        ctx,
+                        QuantumCell::Existing(&word),
+                        QuantumCell::Existing(input),
+                    ),
+                    None => word,
+                }
+            })
+            .collect()
+    }
+
+    fn sbox_full<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: Vec<AssignedValue<'a, F>>,
+        constants: &[F; POSEIDON_T],
+    ) -> Vec<AssignedValue<'a, F>> {
+        state
+            .iter()
+            .zip(constants.iter())
+            .map(|(word, constant)| self.x_power5_with_constant(ctx, word, constant))
+            .collect()
+    }
+
+    /// Return `x^5 + constant`.
+    fn x_power5_with_constant<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        x: &AssignedValue<'a, F>,
+        constant: &F,
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let x2 = gate.mul(ctx, QuantumCell::Existing(x), QuantumCell::Existing(x));
+        let x4 = gate.mul(ctx, QuantumCell::Existing(&x2), QuantumCell::Existing(&x2));
+        gate.mul_add(
+            ctx,
+            QuantumCell::Existing(x),
+            QuantumCell::Existing(&x4),
+            QuantumCell::Constant(*constant),
+        )
+    }
+
+    fn apply_mds<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: Vec<AssignedValue<'a, F>>,
+        mds: &[[F; POSEIDON_T]; POSEIDON_T],
+    ) -> Vec<AssignedValue<'a, F>> {
+        mds.iter()
+            .map(|row| self.inner_product_const(ctx, &state, row))
+            .collect()
+    }
+
+    fn apply_sparse_mds<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: Vec<AssignedValue<'a, F>>,
+        mds: &SparseMDSMatrix<F, POSEIDON_T, POSEIDON_RATE>,
+    ) -> Vec<AssignedValue<'a, F>> {
+        let gate = self.gate();
+        let mut new_state = vec![self.inner_product_const(ctx, &state, mds.row())];
+        for (constant, word) in mds.col_hat().iter().zip(state.iter().skip(1)) {
+            new_state.push(gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&state[0]),
+                QuantumCell::Constant(*constant),
+                QuantumCell::Existing(word),
+            ));
+        }
+        new_state
+    }
+
+    fn inner_product_const<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: &[AssignedValue<'a, F>],
+        constants: &[F; POSEIDON_T],
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let mut sum = gate.mul(
+            ctx,
+            QuantumCell::Existing(&state[0]),
+            QuantumCell::Constant(constants[0]),
+        );
+        for (word, constant) in state.iter().zip(constants.iter()).skip(1) {
+            sum = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(word),
+                QuantumCell::Constant(*constant),
+                QuantumCell::Existing(&sum),
+            );
+        }
+        sum
+    }
+}
+
+/// Compute the Poseidon hash of `inputs` off-circuit.
+///
+/// # Arguments
+/// * `inputs` - the field elements to be hashed.
+///
+/// # Return values
+/// Return the hash value, which is equal to that computed by [`PoseidonConfig::hash`].
+pub fn poseidon_hash<F: PrimeField>(inputs: &[F]) -> F {
+    let mut hasher = Poseidon::<F, POSEIDON_T, POSEIDON_RATE>::new(POSEIDON_R_F, POSEIDON_R_P);
+    hasher.update(inputs);
+    hasher.squeeze()
+}
@@ -0,0 +1,430 @@
+use crate::gadgets::hex::hex_char_to_nibble_unchecked;
+use crate::gadgets::input::{assert_regex_input, assign_chars, remove_chars};
+use crate::gadgets::numeric::DecimalConfig;
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Output type definition of [`QuotedPrintableConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct AssignedQpResult<'a, F: PrimeField> {
+    /// The assigned characters of the quoted-printable encoded string padded with zeros.
+    /// The length is equal to `max_encoded_size`.
+    pub encoded: Vec<AssignedValue<'a, F>>,
+    /// The assigned bytes of the decoded string padded with zeros.
+    /// The length is equal to `max_encoded_size`.
+    pub decoded: Vec<AssignedValue<'a, F>>,
+    /// The assigned length of the decoded string.
+    pub decoded_len: AssignedValue<'a, F>,
+}
+
+/// Configuration to decode a quoted-printable encoded string, e.g., an email body with `Content-Transfer-Encoding: quoted-printable`, before it is matched by [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+///
+/// `=XX` escapes are replaced with the byte of the hex value `XX`, and soft line breaks `=\r\n` are removed.
+#[derive(Debug, Clone)]
+pub struct QuotedPrintableConfig<F: PrimeField> {
+    decimal: DecimalConfig<F>,
+    /// The maximum length of the encoded string.
+    pub max_encoded_size: usize,
+    /// The maximum number of the characters removed by decoding, i.e., two per escape and three per soft line break.
+    pub max_removed_size: usize,
+}
+
+impl<F: PrimeField> QuotedPrintableConfig<F> {
+    /// Construct a new [`QuotedPrintableConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    /// * `max_encoded_size` - the maximum length of the encoded string.
+    /// * `max_removed_size` - the maximum number of the characters removed by decoding.
+    ///
+    /// # Return values
+    /// Return a new [`QuotedPrintableConfig`].
+    pub fn new(gate: FlexGateConfig<F>, max_encoded_size: usize, max_removed_size: usize) -> Self {
+        Self {
+            decimal: DecimalConfig::new_unchecked(gate, 3),
+            max_encoded_size,
+            max_removed_size,
+        }
+    }
+
+    /// Decode the quoted-printable encoded string `encoded` in the circuit.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `encoded` - characters of the quoted-printable encoded string.
+    ///
+    /// # Return values
+    /// Return the assigned values as [`AssignedQpResult`].
+    pub fn decode<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        encoded: &[u8],
+    ) -> Result<AssignedQpResult<'a, F>, Error> {
+        let gate = self.gate();
+        let n = self.max_encoded_size;
+        let assigned_encoded = assign_chars(gate, ctx, encoded, n)?;
+        let zero = gate.load_zero(ctx);
+        let char_at = |idx: usize| assigned_encoded.get(idx).unwrap_or(&zero);
+
+        let is_eqs = assigned_encoded
+            .iter()
+            .map(|char| {
+                gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(char),
+                    QuantumCell::Constant(F::from(b'=' as u64)),
+                )
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        let mut values = vec![];
+        let mut is_removeds = vec![];
+        for idx in 0..n {
+            let char = char_at(idx);
+            let is_soft_break = {
+                let is_cr = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(char_at(idx + 1)),
+                    QuantumCell::Constant(F::from(b'\r' as u64)),
+                );
+                let is_lf = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(char_at(idx + 2)),
+                    QuantumCell::Constant(F::from(b'\n' as u64)),
+                );
+                let is_crlf = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_cr),
+                    QuantumCell::Existing(&is_lf),
+                );
+                gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_eqs[idx]),
+                    QuantumCell::Existing(&is_crlf),
+                )
+            };
+            let is_escape = gate.sub(
+                ctx,
+                QuantumCell::Existing(&is_eqs[idx]),
+                QuantumCell::Existing(&is_soft_break),
+            );
+            // An escape must be followed by two hex characters.
+            let (upper, is_upper_hex) = hex_char_to_nibble_unchecked(gate, ctx, char_at(idx + 1));
+            let (lower, is_lower_hex) = hex_char_to_nibble_unchecked(gate, ctx, char_at(idx + 2));
+            let is_hex = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_upper_hex),
+                QuantumCell::Existing(&is_lower_hex),
+            );
+            let not_hex = gate.not(ctx, QuantumCell::Existing(&is_hex));
+            let is_invalid = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_escape),
+                QuantumCell::Existing(&not_hex),
+            );
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(&is_invalid),
+                QuantumCell::Constant(F::from(0)),
+            );
+            let escaped = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&upper),
+                QuantumCell::Constant(F::from(16)),
+                QuantumCell::Existing(&lower),
+            );
+            values.push(gate.select(
+                ctx,
+                QuantumCell::Existing(&escaped),
+                QuantumCell::Existing(char),
+                QuantumCell::Existing(&is_escape),
+            ));
+
+            // The two characters following `=` are consumed by the escape or the soft line break.
+            let mut is_consumed = zero.clone();
+            for offset in 1..=2 {
+                if idx >= offset {
+                    is_consumed = gate.add(
+                        ctx,
+                        QuantumCell::Existing(&is_consumed),
+                        QuantumCell::Existing(&is_eqs[idx - offset]),
+                    );
+                }
+            }
+            let is_decoding_removed = gate.or(
+                ctx,
+                QuantumCell::Existing(&is_consumed),
+                QuantumCell::Existing(&is_soft_break),
+            );
+            is_removeds.push(is_decoding_removed);
+        }
+        let (decoded, decoded_len) = remove_chars(
+            &self.decimal,
+            ctx,
+            &assigned_encoded,
+            &values,
+            &is_removeds,
+            self.max_removed_size,
+        );
+
+        Ok(AssignedQpResult {
+            encoded: assigned_encoded,
+            decoded,
+            decoded_len,
+        })
+    }
+
+    /// Constrain that the input string of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) is equal to the decoded string.
+    ///
+    /// `match_substrs` must be called with the bytes returned by [`decode_quoted_printable`].
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `decoded` - the output of [`QuotedPrintableConfig::decode`].
+    /// * `regex_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    pub fn assert_regex_input<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        decoded: &AssignedQpResult<'a, F>,
+        regex_result: &AssignedRegexResult<'a, F>,
+    ) {
+        assert_regex_input(
+            self.gate(),
+            ctx,
+            &decoded.decoded,
+            &decoded.decoded_len,
+            regex_result,
+        );
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.decimal.gate()
+    }
+}
+
+/// Decode a quoted-printable encoded string off-circuit, i.e., compute the bytes passed to [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) along with [`QuotedPrintableConfig`].
+///
+/// # Arguments
+/// * `encoded` - characters of the quoted-printable encoded string.
+///
+/// # Return values
+/// Return the decoded bytes, or `None` if `encoded` has an invalid escape.
+pub fn decode_quoted_printable(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = vec![];
+    let mut idx = 0;
+    while idx < encoded.len() {
+        if encoded[idx] != b'=' {
+            decoded.push(encoded[idx]);
+            idx += 1;
+            continue;
+        }
+        let next = &encoded[(idx + 1).min(encoded.len())..(idx + 3).min(encoded.len())];
+        if next == b"\r\n" {
+            idx += 3;
+            continue;
+        }
+        if next.len() != 2 || !next.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+        let hex = std::str::from_utf8(next).ok()?;
+        decoded.push(u8::from_str_radix(hex, 16).ok()?);
+        idx += 3;
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 32;
+    const MAX_REMOVED_SIZE: usize = 6;
+    const K: usize = 13;
+    /// The DFA of `[^;]+;` over `\n`, `\r` and the bytes from 32 to 255, which accepts both the encoded strings and the decoded ones.
+    const ALLSTR_TEXT: &str = "# halo2-regex v2
+0
+2
+2
+0 1 10
+0 1 13
+0 1 32-58
+0 1 60-255
+1 1 10
+1 1 13
+1 1 32-58
+1 1 60-255
+1 2 59
+";
+    /// The substring `[^;]+` of [`ALLSTR_TEXT`].
+    const SUBSTR_TEXT: &str = "# halo2-regex v2
+32
+0
+31
+0
+1
+0 1
+1 1
+";
+
+    #[derive(Clone, Debug)]
+    struct TestQpCircuit<F: PrimeField> {
+        encoded: Vec<u8>,
+        decoded: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestQpCircuit<F> {
+        // Each character is compared with every hex character twice, which takes about 700 cells.
+        const NUM_ADVICE: usize = 8;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestQpCircuit<F> {
+        type Config = RegexVerifyConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                encoded: vec![],
+                decoded: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: AllstrRegexDef::from_text(ALLSTR_TEXT),
+                substrs: vec![SubstrRegexDef::from_text(SUBSTR_TEXT)],
+            }];
+            RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let qp =
+                        QuotedPrintableConfig::new(gate.clone(), MAX_STRING_LEN, MAX_REMOVED_SIZE);
+                    let decoded = qp.decode(ctx, &self.encoded)?;
+                    let result = config.match_substrs(ctx, &self.decoded)?;
+                    qp.assert_regex_input(ctx, &decoded, &result);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Run the circuit decoding `encoded` whose regexes match `decoded`.
+    fn run(encoded: &[u8], decoded: &[u8]) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = TestQpCircuit::<Fr> {
+            encoded: encoded.to_vec(),
+            decoded: decoded.to_vec(),
+            _marker: PhantomData,
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .verify()
+    }
+
+    /// Run the circuit decoding `encoded` whose regexes match the output of [`decode_quoted_printable`], or `encoded` itself if it has an invalid escape.
+    fn run_decoded(encoded: &[u8]) -> Result<(), Vec<VerifyFailure>> {
+        let decoded = decode_quoted_printable(encoded).unwrap_or_else(|| encoded.to_vec());
+        run(encoded, &decoded)
+    }
+
+    #[test]
+    fn test_decode_escapes() {
+        assert_eq!(
+            decode_quoted_printable(b"caf=C3=A9 a=3Db;"),
+            Some(b"caf\xC3\xA9 a=b;".to_vec())
+        );
+        assert_eq!(run_decoded(b"caf=C3=A9 a=3Db;"), Ok(()));
+    }
+
+    #[test]
+    fn test_decode_soft_line_breaks() {
+        assert_eq!(run_decoded(b"long=\r\nline;"), Ok(()));
+        assert_eq!(run_decoded(b"a=\r\nb=3Dc=\r\n;"), Ok(()));
+        // A line break without `=` is kept.
+        assert_eq!(run_decoded(b"two\r\nlines;"), Ok(()));
+    }
+
+    #[test]
+    fn test_decode_lowercase_hex() {
+        assert_eq!(decode_quoted_printable(b"a=3db;"), Some(b"a=b;".to_vec()));
+        assert_eq!(run_decoded(b"a=3db;"), Ok(()));
+    }
+
+    #[test]
+    fn test_decode_invalid_hex() {
+        assert!(run_decoded(b"bad=G1;").is_err());
+        assert!(run_decoded(b"bad=1G;").is_err());
+        assert!(run_decoded(b"end=;").is_err());
+        assert!(run_decoded(b"half=\r;").is_err());
+    }
+
+    #[test]
+    fn test_decode_mismatched_witness() {
+        // The undecoded string.
+        assert!(run(b"a=3Db;", b"a=3Db;").is_err());
+        // A wrong decoded byte.
+        assert!(run(b"a=3Db;", b"a=Cb;").is_err());
+        // The soft line break kept.
+        assert!(run(b"long=\r\nline;", b"long\r\nline;").is_err());
+    }
+
+    #[test]
+    fn test_decode_too_many_removed() {
+        // Four escapes remove eight characters, more than `MAX_REMOVED_SIZE`.
+        assert_eq!(run_decoded(b"=41=42=43;"), Ok(()));
+        assert!(run_decoded(b"=41=42=43=44;").is_err());
+    }
+
+    #[test]
+    fn test_decode_quoted_printable() {
+        assert_eq!(decode_quoted_printable(b"a=3Db"), Some(b"a=b".to_vec()));
+        assert_eq!(
+            decode_quoted_printable(b"long=\r\nline"),
+            Some(b"longline".to_vec())
+        );
+        assert_eq!(decode_quoted_printable(b"bad=G1"), None);
+        assert_eq!(decode_quoted_printable(b"end="), None);
+    }
+}
@@ -0,0 +1,351 @@
+use crate::gadgets::poseidon::{poseidon_hash, PoseidonConfig};
+use crate::gadgets::substr::SubstrCompactConfig;
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// The number of bytes packed into one field element.
+pub const BYTES_PER_FIELD: usize = 31;
+
+/// Configuration to output either the characters of a substring extracted by [`RegexVerifyConfig`](crate::RegexVerifyConfig) or their Poseidon commitment, chosen by a public selector bit.
+#[derive(Debug, Clone)]
+pub struct RevealConfig<F: PrimeField> {
+    compact: SubstrCompactConfig<F>,
+    poseidon: PoseidonConfig<F>,
+}
+
+impl<F: PrimeField> RevealConfig<F> {
+    /// Construct a new [`RevealConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    ///
+    /// # Return values
+    /// Return a new [`RevealConfig`].
+    pub fn new(gate: FlexGateConfig<F>) -> Self {
+        Self {
+            compact: SubstrCompactConfig::new(gate.clone()),
+            poseidon: PoseidonConfig::new(gate),
+        }
+    }
+
+    /// Output the substring whose substring id is `substr_id` if `is_revealed` is true, and its commitment otherwise.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    /// * `substr_id` - the substring id of the substring.
+    /// * `max_length` - the maximum length of the substring.
+    /// * `is_revealed` - whether the characters of the substring are output.
+    ///
+    /// # Return values
+    /// Return the assigned values to be exposed as instances, whose layout is described in [`reveal_instances`].
+    pub fn reveal<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &AssignedRegexResult<'a, F>,
+        substr_id: usize,
+        max_length: usize,
+        is_revealed: bool,
+    ) -> Result<Vec<AssignedValue<'a, F>>, Error> {
+        let gate = self.gate();
+        let chars = self.compact.compact(ctx, result, substr_id, max_length)?;
+        let packed = self.pack_bytes(ctx, &chars);
+        let commitment = self.poseidon.hash(ctx, &packed);
+        let selector = gate.load_witness(ctx, Value::known(F::from(is_revealed as u64)));
+        let not_selector = gate.not(ctx, QuantumCell::Existing(&selector));
+        let is_not_bit = gate.mul(
+            ctx,
+            QuantumCell::Existing(&selector),
+            QuantumCell::Existing(&not_selector),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_not_bit),
+            QuantumCell::Constant(F::from(0)),
+        );
+
+        let mut outputs = vec![selector.clone()];
+        for value in packed.iter() {
+            outputs.push(gate.mul(
+                ctx,
+                QuantumCell::Existing(&selector),
+                QuantumCell::Existing(value),
+            ));
+        }
+        outputs.push(gate.mul(
+            ctx,
+            QuantumCell::Existing(&not_selector),
+            QuantumCell::Existing(&commitment),
+        ));
+        Ok(outputs)
+    }
+
+    /// Output the Poseidon commitment of the substring whose substring id is `substr_id` salted with `salt`, i.e., `Poseidon(salt || packed characters)`.
+    ///
+    /// The salt is a private witness, so that the substring can be opened later off-chain without being revealed in the proof.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    /// * `substr_id` - the substring id of the substring.
+    /// * `max_length` - the maximum length of the substring.
+    /// * `salt` - the salt of the commitment, which is unknown only in the key generation.
+    ///
+    /// # Return values
+    /// Return the assigned commitment, which is equal to that computed by [`salted_commitment`].
+    pub fn commit_salted<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &AssignedRegexResult<'a, F>,
+        substr_id: usize,
+        max_length: usize,
+        salt: Value<F>,
+    ) -> Result<AssignedValue<'a, F>, Error> {
+        let chars = self.compact.compact(ctx, result, substr_id, max_length)?;
+        let assigned_salt = self.gate().load_witness(ctx, salt);
+        let mut inputs = vec![assigned_salt];
+        inputs.append(&mut self.pack_bytes(ctx, &chars));
+        Ok(self.poseidon.hash(ctx, &inputs))
+    }
+
+    /// Pack bytes into field elements, [`BYTES_PER_FIELD`] big-endian bytes per element.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `bytes` - the assigned bytes.
+    ///
+    /// # Return values
+    /// Return the assigned field elements.
+    pub fn pack_bytes<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bytes: &[AssignedValue<'a, F>],
+    ) -> Vec<AssignedValue<'a, F>> {
+        let gate = self.gate();
+        bytes
+            .chunks(BYTES_PER_FIELD)
+            .map(|chunk| {
+                let mut packed = gate.load_zero(ctx);
+                for byte in chunk.iter() {
+                    packed = gate.mul_add(
+                        ctx,
+                        QuantumCell::Existing(&packed),
+                        QuantumCell::Constant(F::from(256)),
+                        QuantumCell::Existing(byte),
+                    );
+                }
+                packed
+            })
+            .collect()
+    }
+
+    /// Return a reference of internally used [`PoseidonConfig`].
+    pub fn poseidon(&self) -> &PoseidonConfig<F> {
+        &self.poseidon
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.compact.gate()
+    }
+}
+
+/// Pack bytes into field elements off-circuit in the same way as [`RevealConfig::pack_bytes`].
+pub fn pack_bytes<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .chunks(BYTES_PER_FIELD)
+        .map(|chunk| {
+            chunk.iter().fold(F::zero(), |packed, byte| {
+                packed * F::from(256) + F::from(*byte as u64)
+            })
+        })
+        .collect()
+}
+
+/// Compute the instances output by [`RevealConfig::reveal`] off-circuit.
+///
+/// The instances are the selector bit, the packed characters of the substring padded with zeros to `max_length`, and the Poseidon hash of those packed characters.
+/// The packed characters are replaced with zeros if the substring is not revealed, and the hash is replaced with zero otherwise.
+///
+/// # Arguments
+/// * `substr` - the characters of the substring.
+/// * `max_length` - the maximum length of the substring.
+/// * `is_revealed` - whether the characters of the substring are output.
+///
+/// # Return values
+/// Return the instances.
+pub fn reveal_instances<F: PrimeField>(
+    substr: &[u8],
+    max_length: usize,
+    is_revealed: bool,
+) -> Vec<F> {
+    assert!(substr.len() <= max_length);
+    let mut padded = substr.to_vec();
+    padded.resize(max_length, 0);
+    let packed = pack_bytes::<F>(&padded);
+    let commitment = poseidon_hash(&packed);
+    let mut instances = vec![F::from(is_revealed as u64)];
+    if is_revealed {
+        instances.extend_from_slice(&packed);
+        instances.push(F::zero());
+    } else {
+        instances.extend(packed.iter().map(|_| F::zero()));
+        instances.push(commitment);
+    }
+    instances
+}
+
+/// Compute the commitment output by [`RevealConfig::commit_salted`] off-circuit.
+///
+/// # Arguments
+/// * `substr` - the characters of the substring.
+/// * `max_length` - the maximum length of the substring.
+/// * `salt` - the salt of the commitment.
+///
+/// # Return values
+/// Return `Poseidon(salt || packed characters)`, where the characters are padded with zeros to `max_length`.
+pub fn salted_commitment<F: PrimeField>(substr: &[u8], max_length: usize, salt: F) -> F {
+    assert!(substr.len() <= max_length);
+    let mut padded = substr.to_vec();
+    padded.resize(max_length, 0);
+    let mut inputs = vec![salt];
+    inputs.append(&mut pack_bytes::<F>(&padded));
+    poseidon_hash(&inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Cell, Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 64;
+    const MAX_SUBSTR_LEN: usize = 8;
+    const K: usize = 13;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestRevealCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        is_revealed: bool,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestRevealCircuit<F> {
+        const NUM_ADVICE: usize = 4;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRevealCircuit<F> {
+        type Config = (RegexVerifyConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                is_revealed: false,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def =
+                AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt");
+            let substr_def =
+                SubstrRegexDef::read_from_text("./test_regexes/substr4_test_lookup.txt");
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: all_regex_def,
+                substrs: vec![substr_def],
+            }];
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            let mut public_cells = vec![];
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    let reveal = RevealConfig::new(gate.clone());
+                    let outputs =
+                        reveal.reveal(ctx, &result, 1, MAX_SUBSTR_LEN, self.is_revealed)?;
+                    public_cells = outputs
+                        .iter()
+                        .map(|output| output.cell())
+                        .collect::<Vec<Cell>>();
+                    Ok(())
+                },
+            )?;
+            for (row, cell) in public_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_reveal_plaintext() {
+        let circuit = TestRevealCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            is_revealed: true,
+            _marker: PhantomData,
+        };
+        let instances = reveal_instances::<Fr>(b"1000", MAX_SUBSTR_LEN, true);
+        let prover = MockProver::run(K as u32, &circuit, vec![instances]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_reveal_commitment() {
+        let circuit = TestRevealCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            is_revealed: false,
+            _marker: PhantomData,
+        };
+        let instances = reveal_instances::<Fr>(b"1000", MAX_SUBSTR_LEN, false);
+        let prover = MockProver::run(K as u32, &circuit, vec![instances]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let wrong_instances = reveal_instances::<Fr>(b"1001", MAX_SUBSTR_LEN, false);
+        let prover = MockProver::run(K as u32, &circuit, vec![wrong_instances]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,617 @@
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// The number of the bytes of a SHA-256 block.
+pub const SHA256_BLOCK_SIZE: usize = 64;
+/// The number of the bytes of a SHA-256 digest.
+pub const SHA256_DIGEST_SIZE: usize = 32;
+
+/// The initial hash values of SHA-256.
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants of SHA-256.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The number of the bits of a sum of at most eight 32-bit words.
+const SUM_BITS: usize = 35;
+
+/// A 32-bit word as its assigned little-endian bits.
+type Word<'a, F> = Vec<AssignedValue<'a, F>>;
+
+/// Configuration to compute the SHA-256 digest of an input string of a variable length in the circuit, which is equal to that computed by [`sha256`].
+///
+/// The characters are the same cells as those matched by [`RegexVerifyConfig`](crate::RegexVerifyConfig), so that the regex verification and the digest are bound to the same bytes.
+#[derive(Debug, Clone)]
+pub struct Sha256Config<F: PrimeField> {
+    gate: FlexGateConfig<F>,
+}
+
+impl<F: PrimeField> Sha256Config<F> {
+    /// Construct a new [`Sha256Config`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    ///
+    /// # Return values
+    /// Return a new [`Sha256Config`].
+    pub fn new(gate: FlexGateConfig<F>) -> Self {
+        Self { gate }
+    }
+
+    /// Compute the SHA-256 digest of the input string matched by [`RegexVerifyConfig`](crate::RegexVerifyConfig), i.e., its characters whose enable flags are one.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `regex_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    ///
+    /// # Return values
+    /// Return the assigned bytes of the digest.
+    pub fn digest_regex_input<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        regex_result: &AssignedRegexResult<'a, F>,
+    ) -> Result<Vec<AssignedValue<'a, F>>, Error> {
+        self.digest(
+            ctx,
+            &regex_result.all_characters,
+            &regex_result.all_enable_flags,
+        )
+    }
+
+    /// Compute the SHA-256 digest of the characters whose enable flags are one.
+    ///
+    /// The enable flags must be ones followed by zeros, as constrained by [`RegexVerifyConfig`](crate::RegexVerifyConfig), and the number of ones is the length of the input string.
+    /// The blocks of the longest input string are compressed, and the digest is selected at the last block of the input string.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `characters` - the assigned characters, each of which is constrained to be a byte.
+    /// * `enable_flags` - the assigned enable flags of `characters`.
+    ///
+    /// # Return values
+    /// Return the assigned bytes of the digest, or [`Error::Synthesis`] if the numbers of `characters` and `enable_flags` differ.
+    pub fn digest<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        characters: &[AssignedValue<'a, F>],
+        enable_flags: &[AssignedValue<'a, F>],
+    ) -> Result<Vec<AssignedValue<'a, F>>, Error> {
+        if characters.len() != enable_flags.len() {
+            return Err(Error::Synthesis);
+        }
+        let gate = self.gate();
+        let max_chars_size = characters.len();
+        // One byte of `0x80` and eight bytes of the length follow the input string.
+        let num_blocks = (max_chars_size + 9 + SHA256_BLOCK_SIZE - 1) / SHA256_BLOCK_SIZE;
+        let padded_size = num_blocks * SHA256_BLOCK_SIZE;
+        let zero = gate.load_zero(ctx);
+        let one = gate.load_constant(ctx, F::from(1));
+        let enable_at = |idx: isize| -> AssignedValue<'a, F> {
+            if idx < 0 {
+                one.clone()
+            } else {
+                enable_flags
+                    .get(idx as usize)
+                    .cloned()
+                    .unwrap_or_else(|| zero.clone())
+            }
+        };
+
+        let mut len = gate.load_zero(ctx);
+        for flag in enable_flags.iter() {
+            len = gate.add(
+                ctx,
+                QuantumCell::Existing(&len),
+                QuantumCell::Existing(flag),
+            );
+        }
+        let bit_len = gate.mul(
+            ctx,
+            QuantumCell::Existing(&len),
+            QuantumCell::Constant(F::from(8)),
+        );
+        let bit_len_bits = gate.num_to_bits(ctx, &bit_len, 64);
+        // The length is big-endian in the last eight bytes of the last block.
+        let len_bytes = (0..8)
+            .map(|byte_idx| self.bits_to_byte(ctx, &bit_len_bits[8 * (7 - byte_idx)..]))
+            .collect::<Vec<AssignedValue<F>>>();
+        // The block `b` is the last one iff `64 * b - 8 <= len <= 64 * b + 55`.
+        let is_lasts = (0..num_blocks)
+            .map(|block_idx| {
+                let start = (block_idx * SHA256_BLOCK_SIZE) as isize;
+                let is_not_before = enable_at(start - 9);
+                let is_after = enable_at(start + 55);
+                let is_not_after = gate.not(ctx, QuantumCell::Existing(&is_after));
+                gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_not_before),
+                    QuantumCell::Existing(&is_not_after),
+                )
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+
+        let mut padded = vec![];
+        for idx in 0..padded_size {
+            let mut byte = match characters.get(idx) {
+                Some(char) => gate.mul(
+                    ctx,
+                    QuantumCell::Existing(char),
+                    QuantumCell::Existing(&enable_flags[idx]),
+                ),
+                None => zero.clone(),
+            };
+            // `0x80` follows the last character.
+            let is_first_pad = gate.sub(
+                ctx,
+                QuantumCell::Existing(&enable_at(idx as isize - 1)),
+                QuantumCell::Existing(&enable_at(idx as isize)),
+            );
+            byte = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&is_first_pad),
+                QuantumCell::Constant(F::from(0x80)),
+                QuantumCell::Existing(&byte),
+            );
+            let offset = idx % SHA256_BLOCK_SIZE;
+            if offset >= SHA256_BLOCK_SIZE - 8 {
+                byte = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&is_lasts[idx / SHA256_BLOCK_SIZE]),
+                    QuantumCell::Existing(&len_bytes[offset + 8 - SHA256_BLOCK_SIZE]),
+                    QuantumCell::Existing(&byte),
+                );
+            }
+            padded.push(byte);
+        }
+
+        let mut state = H0
+            .iter()
+            .map(|word| {
+                (0..32)
+                    .map(|bit_idx| gate.load_constant(ctx, F::from(((word >> bit_idx) & 1) as u64)))
+                    .collect::<Word<F>>()
+            })
+            .collect::<Vec<Word<F>>>();
+        let mut digest = (0..SHA256_DIGEST_SIZE)
+            .map(|_| zero.clone())
+            .collect::<Vec<AssignedValue<F>>>();
+        for (block, is_last) in padded.chunks(SHA256_BLOCK_SIZE).zip(is_lasts.iter()) {
+            // The words are big-endian in the block, while their bits are little-endian.
+            let words = block
+                .chunks(4)
+                .map(|bytes| {
+                    bytes
+                        .iter()
+                        .rev()
+                        .flat_map(|byte| gate.num_to_bits(ctx, byte, 8))
+                        .collect::<Word<F>>()
+                })
+                .collect::<Vec<Word<F>>>();
+            state = self.compress(ctx, &state, words, &zero);
+            let bytes = state
+                .iter()
+                .flat_map(|word| {
+                    (0..4)
+                        .map(|byte_idx| self.bits_to_byte(ctx, &word[8 * (3 - byte_idx)..]))
+                        .collect::<Vec<AssignedValue<F>>>()
+                })
+                .collect::<Vec<AssignedValue<F>>>();
+            for (digest_byte, byte) in digest.iter_mut().zip(bytes.iter()) {
+                *digest_byte = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(is_last),
+                    QuantumCell::Existing(byte),
+                    QuantumCell::Existing(digest_byte),
+                );
+            }
+        }
+        Ok(digest)
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        &self.gate
+    }
+
+    /// Apply the compression function to `state` with the sixteen words of a block.
+    fn compress<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: &[Word<'a, F>],
+        mut words: Vec<Word<'a, F>>,
+        zero: &AssignedValue<'a, F>,
+    ) -> Vec<Word<'a, F>> {
+        let gate = self.gate();
+        for idx in 16..64 {
+            let s0 = self.small_sigma(ctx, &words[idx - 15], [7, 18], 3, zero);
+            let s1 = self.small_sigma(ctx, &words[idx - 2], [17, 19], 10, zero);
+            let sum = self.sum_words(ctx, &[&s1, &words[idx - 7], &s0, &words[idx - 16]]);
+            words.push(self.reduce(ctx, &sum));
+        }
+
+        let mut vars = state.to_vec();
+        for (idx, word) in words.iter().enumerate() {
+            let (a, b, c, d) = (&vars[0], &vars[1], &vars[2], &vars[3]);
+            let (e, f, g, h) = (&vars[4], &vars[5], &vars[6], &vars[7]);
+            let big_s1 = self.big_sigma(ctx, e, [6, 11, 25]);
+            let ch = (0..32)
+                .map(|bit_idx| {
+                    // `ch = g + e * (f - g)` for each bit.
+                    let diff = gate.sub(
+                        ctx,
+                        QuantumCell::Existing(&f[bit_idx]),
+                        QuantumCell::Existing(&g[bit_idx]),
+                    );
+                    gate.mul_add(
+                        ctx,
+                        QuantumCell::Existing(&e[bit_idx]),
+                        QuantumCell::Existing(&diff),
+                        QuantumCell::Existing(&g[bit_idx]),
+                    )
+                })
+                .collect::<Word<F>>();
+            let t1 = self.sum_words(ctx, &[h, &big_s1, &ch, word]);
+            let t1 = gate.add(
+                ctx,
+                QuantumCell::Existing(&t1),
+                QuantumCell::Constant(F::from(K[idx] as u64)),
+            );
+            let big_s0 = self.big_sigma(ctx, a, [2, 13, 22]);
+            let maj = (0..32)
+                .map(|bit_idx| {
+                    // `maj = a * b + c * (a ^ b)` for each bit.
+                    let ab = gate.mul(
+                        ctx,
+                        QuantumCell::Existing(&a[bit_idx]),
+                        QuantumCell::Existing(&b[bit_idx]),
+                    );
+                    let a_xor_b = self.xor(ctx, &a[bit_idx], &b[bit_idx]);
+                    gate.mul_add(
+                        ctx,
+                        QuantumCell::Existing(&c[bit_idx]),
+                        QuantumCell::Existing(&a_xor_b),
+                        QuantumCell::Existing(&ab),
+                    )
+                })
+                .collect::<Word<F>>();
+            let t2 = self.sum_words(ctx, &[&big_s0, &maj]);
+            let d_value = self.word_value(ctx, d);
+            let new_e = gate.add(
+                ctx,
+                QuantumCell::Existing(&d_value),
+                QuantumCell::Existing(&t1),
+            );
+            let new_a = gate.add(ctx, QuantumCell::Existing(&t1), QuantumCell::Existing(&t2));
+            let new_e = self.reduce(ctx, &new_e);
+            let new_a = self.reduce(ctx, &new_a);
+            vars = vec![
+                new_a,
+                a.clone(),
+                b.clone(),
+                c.clone(),
+                new_e,
+                e.clone(),
+                f.clone(),
+                g.clone(),
+            ];
+        }
+        state
+            .iter()
+            .zip(vars.iter())
+            .map(|(word, var)| {
+                let sum = self.sum_words(ctx, &[word, var]);
+                self.reduce(ctx, &sum)
+            })
+            .collect()
+    }
+
+    /// Return `rotr(word, r0) ^ rotr(word, r1) ^ rotr(word, r2)`.
+    fn big_sigma<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        word: &Word<'a, F>,
+        rotations: [usize; 3],
+    ) -> Word<'a, F> {
+        (0..32)
+            .map(|bit_idx| {
+                let [r0, r1, r2] = rotations.map(|rotation| &word[(bit_idx + rotation) % 32]);
+                let xor = self.xor(ctx, r0, r1);
+                self.xor(ctx, &xor, r2)
+            })
+            .collect()
+    }
+
+    /// Return `rotr(word, r0) ^ rotr(word, r1) ^ (word >> shift)`.
+    fn small_sigma<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        word: &Word<'a, F>,
+        rotations: [usize; 2],
+        shift: usize,
+        zero: &AssignedValue<'a, F>,
+    ) -> Word<'a, F> {
+        (0..32)
+            .map(|bit_idx| {
+                let [r0, r1] = rotations.map(|rotation| &word[(bit_idx + rotation) % 32]);
+                let shifted = word.get(bit_idx + shift).unwrap_or(zero);
+                let xor = self.xor(ctx, r0, r1);
+                self.xor(ctx, &xor, shifted)
+            })
+            .collect()
+    }
+
+    /// Return the XOR of the bits `a` and `b`, i.e., `a + b - 2 * a * b`.
+    fn xor<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'a, F>,
+        b: &AssignedValue<'a, F>,
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let ab = gate.mul(ctx, QuantumCell::Existing(a), QuantumCell::Existing(b));
+        let sum = gate.add(ctx, QuantumCell::Existing(a), QuantumCell::Existing(b));
+        gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&ab),
+            QuantumCell::Constant(-F::from(2)),
+            QuantumCell::Existing(&sum),
+        )
+    }
+
+    /// Return the sum of the values of `words` without the reduction modulo `2^32`.
+    fn sum_words<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        words: &[&Word<'a, F>],
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let mut sum = gate.load_zero(ctx);
+        for word in words.iter() {
+            let value = self.word_value(ctx, word);
+            sum = gate.add(
+                ctx,
+                QuantumCell::Existing(&sum),
+                QuantumCell::Existing(&value),
+            );
+        }
+        sum
+    }
+
+    /// Return the word of `sum` modulo `2^32`, where `sum` is a sum of at most eight words.
+    fn reduce<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        sum: &AssignedValue<'a, F>,
+    ) -> Word<'a, F> {
+        let mut bits = self.gate().num_to_bits(ctx, sum, SUM_BITS);
+        bits.truncate(32);
+        bits
+    }
+
+    /// Return the value of the little-endian bits of `word`.
+    fn word_value<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        word: &[AssignedValue<'a, F>],
+    ) -> AssignedValue<'a, F> {
+        let gate = self.gate();
+        let mut value = gate.load_zero(ctx);
+        for bit in word.iter().rev() {
+            value = gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&value),
+                QuantumCell::Constant(F::from(2)),
+                QuantumCell::Existing(bit),
+            );
+        }
+        value
+    }
+
+    /// Return the byte of the first eight little-endian bits of `bits`.
+    fn bits_to_byte<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bits: &[AssignedValue<'a, F>],
+    ) -> AssignedValue<'a, F> {
+        self.word_value(ctx, &bits[0..8])
+    }
+}
+
+/// Compute the SHA-256 digest off-circuit, i.e., the digest computed by [`Sha256Config::digest`] in the circuit.
+///
+/// # Arguments
+/// * `message` - the bytes of the message.
+///
+/// # Return values
+/// Return the bytes of the digest.
+pub fn sha256(message: &[u8]) -> [u8; SHA256_DIGEST_SIZE] {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % SHA256_BLOCK_SIZE != SHA256_BLOCK_SIZE - 8 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+    let mut state = H0;
+    for block in padded.chunks(SHA256_BLOCK_SIZE) {
+        let mut words = block
+            .chunks(4)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect::<Vec<u32>>();
+        for idx in 16..64 {
+            let (w15, w2) = (words[idx - 15], words[idx - 2]);
+            let s0 = w15.rotate_right(7) ^ w15.rotate_right(18) ^ (w15 >> 3);
+            let s1 = w2.rotate_right(17) ^ w2.rotate_right(19) ^ (w2 >> 10);
+            words.push(
+                words[idx - 16]
+                    .wrapping_add(s0)
+                    .wrapping_add(words[idx - 7])
+                    .wrapping_add(s1),
+            );
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for (word, k) in words.iter().zip(K.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(*word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, var) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(var);
+        }
+    }
+    let mut digest = [0; SHA256_DIGEST_SIZE];
+    for (bytes, word) in digest.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const K: usize = 17;
+    const MAX_CHARS_SIZE: usize = 60;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            to_hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            to_hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // The padding of 56 bytes needs the second block.
+        assert_eq!(
+            to_hex(&sha256(&[b'a'; 56])),
+            "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"
+        );
+    }
+
+    #[derive(Default, Clone, Debug)]
+    struct TestCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCircuit<F> {
+        type Config = Sha256Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[8],
+                1,
+                0,
+                K,
+            );
+            Sha256Config::new(gate)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            layouter.assign_region(
+                || "sha256",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let characters = (0..MAX_CHARS_SIZE)
+                        .map(|idx| {
+                            let char = self.characters.get(idx).copied().unwrap_or(0);
+                            gate.load_witness(ctx, Value::known(F::from(char as u64)))
+                        })
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let enable_flags = (0..MAX_CHARS_SIZE)
+                        .map(|idx| {
+                            let flag = (idx < self.characters.len()) as u64;
+                            gate.load_witness(ctx, Value::known(F::from(flag)))
+                        })
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let digest = config.digest(ctx, &characters, &enable_flags)?;
+                    for (assigned, expected) in digest.iter().zip(sha256(&self.characters)) {
+                        assigned
+                            .value()
+                            .map(|v| assert_eq!(*v, F::from(expected as u64)));
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_sha256_config() {
+        // The input strings of one and two blocks are hashed by the same circuit.
+        for characters in [b"amount=1000;".to_vec(), vec![b'a'; 56]] {
+            let circuit = TestCircuit::<Fr> {
+                characters,
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use crate::AssignedRegexResult;
+use halo2_base::halo2_proofs::{circuit::Value, plonk::Error};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Configuration to move the characters of each substring extracted by [`RegexVerifyConfig`](crate::RegexVerifyConfig) to the head of a fixed-length array.
+///
+/// Unlike `masked_characters` in [`AssignedRegexResult`], the compacted array does not depend on the position of the substring in the input string,
+/// so that it can be compared with a value chosen by the verifier.
+#[derive(Debug, Clone)]
+pub struct SubstrCompactConfig<F: PrimeField> {
+    gate: FlexGateConfig<F>,
+}
+
+impl<F: PrimeField> SubstrCompactConfig<F> {
+    /// Construct a new [`SubstrCompactConfig`].
+    ///
+    /// # Arguments
+    /// * `gate` - a configuration for [`FlexGateConfig`], which must be the same one used in [`RegexVerifyConfig`](crate::RegexVerifyConfig).
+    ///
+    /// # Return values
+    /// Return a new [`SubstrCompactConfig`].
+    pub fn new(gate: FlexGateConfig<F>) -> Self {
+        Self { gate }
+    }
+
+    /// Compact the characters of the substring whose substring id is `substr_id`.
+    ///
+    /// The number of the characters is constrained to be at most `max_length`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    /// * `substr_id` - the substring id of the substring.
+    /// * `max_length` - the maximum length of the substring, e.g., `max_length` of its [`SubstrRegexDef`](crate::SubstrRegexDef).
+    ///
+    /// # Return values
+    /// Return the assigned characters of the substring padded with zeros to `max_length`.
+    pub fn compact<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &AssignedRegexResult<'a, F>,
+        substr_id: usize,
+        max_length: usize,
+    ) -> Result<Vec<AssignedValue<'a, F>>, Error> {
+        let gate = self.gate();
+        let mut compacted = (0..max_length)
+            .map(|_| gate.load_zero(ctx))
+            .collect::<Vec<AssignedValue<F>>>();
+        // The number of the substring characters before the current character.
+        let mut substr_idx = gate.load_zero(ctx);
+        for (char, id) in result
+            .masked_characters
+            .iter()
+            .zip(result.all_substr_ids.iter())
+        {
+            let is_target = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(id),
+                QuantumCell::Constant(F::from(substr_id as u64)),
+            );
+            for (idx, compacted_char) in compacted.iter_mut().enumerate() {
+                let is_pos = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&substr_idx),
+                    QuantumCell::Constant(F::from(idx as u64)),
+                );
+                let is_written = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_pos),
+                    QuantumCell::Existing(&is_target),
+                );
+                *compacted_char = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&is_written),
+                    QuantumCell::Existing(char),
+                    QuantumCell::Existing(compacted_char),
+                );
+            }
+            substr_idx = gate.add(
+                ctx,
+                QuantumCell::Existing(&substr_idx),
+                QuantumCell::Existing(&is_target),
+            );
+        }
+        // `substr_idx` must be one of 0..=`max_length`; otherwise some characters are dropped.
+        let mut is_in_range = gate.load_zero(ctx);
+        for len in 0..=max_length {
+            let is_len = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(&substr_idx),
+                QuantumCell::Constant(F::from(len as u64)),
+            );
+            is_in_range = gate.add(
+                ctx,
+                QuantumCell::Existing(&is_in_range),
+                QuantumCell::Existing(&is_len),
+            );
+        }
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&is_in_range),
+            QuantumCell::Constant(F::from(1)),
+        );
+        Ok(compacted)
+    }
+
+    /// Assign the bytes of an expected substring as witnesses padded with zeros to `max_length`.
+    ///
+    /// The returned cells should be constrained to be equal to instance cells so that the verifier chooses the expected value.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `expected` - bytes of the expected substring.
+    /// * `max_length` - the maximum length of the substring.
+    ///
+    /// # Return values
+    /// Return the assigned bytes of the expected substring, or [`Error::Synthesis`] if `expected` is longer than `max_length`.
+    pub fn assign_expected<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        expected: &[u8],
+        max_length: usize,
+    ) -> Result<Vec<AssignedValue<'a, F>>, Error> {
+        if expected.len() > max_length {
+            return Err(Error::Synthesis);
+        }
+        let gate = self.gate();
+        Ok((0..max_length)
+            .map(|idx| {
+                let byte = expected.get(idx).copied().unwrap_or(0);
+                gate.load_witness(ctx, Value::known(F::from(byte as u64)))
+            })
+            .collect())
+    }
+
+    /// Constrain that the substring whose substring id is `substr_id` is equal to `expected`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs).
+    /// * `substr_id` - the substring id of the substring.
+    /// * `expected` - the assigned bytes of the expected substring padded with zeros, e.g., an output of [`SubstrCompactConfig::assign_expected`].
+    pub fn assert_substr_equal<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &AssignedRegexResult<'a, F>,
+        substr_id: usize,
+        expected: &[AssignedValue<'a, F>],
+    ) -> Result<(), Error> {
+        let compacted = self.compact(ctx, result, substr_id, expected.len())?;
+        let gate = self.gate();
+        for (char, expected_char) in compacted.iter().zip(expected.iter()) {
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(char),
+                QuantumCell::Existing(expected_char),
+            );
+        }
+        Ok(())
+    }
+
+    /// Constrain that the substring whose substring id is `lhs_substr_id` in `lhs_result` is equal to that whose substring id is `rhs_substr_id` in `rhs_result`.
+    ///
+    /// The two results can be those of different input strings, e.g., an email header and its body.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `lhs_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) for the first substring.
+    /// * `lhs_substr_id` - the substring id of the first substring.
+    /// * `rhs_result` - the output of [`RegexVerifyConfig::match_substrs`](crate::RegexVerifyConfig::match_substrs) for the second substring.
+    /// * `rhs_substr_id` - the substring id of the second substring.
+    /// * `max_length` - the maximum length of both substrings.
+    pub fn assert_substrs_equal<'v: 'a, 'a>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        lhs_result: &AssignedRegexResult<'a, F>,
+        lhs_substr_id: usize,
+        rhs_result: &AssignedRegexResult<'a, F>,
+        rhs_substr_id: usize,
+        max_length: usize,
+    ) -> Result<(), Error> {
+        let lhs = self.compact(ctx, lhs_result, lhs_substr_id, max_length)?;
+        self.assert_substr_equal(ctx, rhs_result, rhs_substr_id, &lhs)
+    }
+
+    /// Return a reference of internally used [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        &self.gate
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::new_region_context;
+    use crate::{AllstrRegexDef, RegexDefs, RegexVerifyConfig, SubstrRegexDef};
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Column, ConstraintSystem, Instance},
+    };
+    use halo2_base::SKIP_FIRST_PASS;
+    use std::marker::PhantomData;
+
+    const MAX_STRING_LEN: usize = 64;
+    const MAX_SUBSTR_LEN: usize = 8;
+    const K: usize = 13;
+
+    #[derive(Default, Clone, Debug)]
+    struct TestExpectedCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        /// The expected substring assigned by the prover, which is constrained to the instances.
+        expected: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestExpectedCircuit<F> {
+        const NUM_ADVICE: usize = 4;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestExpectedCircuit<F> {
+        type Config = (RegexVerifyConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                expected: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let all_regex_def =
+                AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt");
+            let substr_def =
+                SubstrRegexDef::read_from_text("./test_regexes/substr4_test_lookup.txt");
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let regex_defs = vec![RegexDefs {
+                allstr: all_regex_def,
+                substrs: vec![substr_def],
+            }];
+            let regex = RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (regex, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            let mut expected_cells = vec![];
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let result = config.match_substrs(ctx, &self.characters)?;
+                    let compact = SubstrCompactConfig::new(gate.clone());
+                    let expected = compact.assign_expected(ctx, &self.expected, MAX_SUBSTR_LEN)?;
+                    compact.assert_substr_equal(ctx, &result, 1, &expected)?;
+                    expected_cells = expected.iter().map(|byte| byte.cell()).collect();
+                    Ok(())
+                },
+            )?;
+            for (row, cell) in expected_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run(characters: &[u8], expected: &[u8], public: &[u8]) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = TestExpectedCircuit::<Fr> {
+            characters: characters.to_vec(),
+            expected: expected.to_vec(),
+            _marker: PhantomData,
+        };
+        let mut instances = public
+            .iter()
+            .map(|byte| Fr::from(*byte as u64))
+            .collect::<Vec<Fr>>();
+        instances.resize(MAX_SUBSTR_LEN, Fr::from(0));
+        MockProver::run(K as u32, &circuit, vec![instances])
+            .unwrap()
+            .verify()
+    }
+
+    #[test]
+    fn test_expected_substr() {
+        assert_eq!(run(b"amount=1000;", b"1000", b"1000"), Ok(()));
+    }
+
+    #[test]
+    fn test_unexpected_substr() {
+        // The prover assigns the expected bytes of the substring, but the verifier expects other ones.
+        assert!(run(b"amount=1000;", b"1000", b"2000").is_err());
+        assert!(run(b"amount=1000;", b"1000", b"100").is_err());
+        // The prover assigns the bytes expected by the verifier, which are not the substring.
+        assert!(run(b"amount=1000;", b"2000", b"2000").is_err());
+    }
+
+    #[test]
+    fn test_too_long_expected_substr() {
+        let circuit = TestExpectedCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            expected: vec![b'1'; MAX_SUBSTR_LEN + 1],
+            _marker: PhantomData,
+        };
+        assert!(
+            MockProver::run(K as u32, &circuit, vec![vec![Fr::from(0); MAX_SUBSTR_LEN]]).is_err()
+        );
+    }
+}
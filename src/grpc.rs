@@ -0,0 +1,184 @@
+use crate::helpers::{targets_to_correct_substrs, ProveError, TargetSubstr, VerificationError};
+use crate::server::{ProvingService, ServeError};
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use std::sync::{Arc, Mutex};
+use tonic::{Request, Response, Status};
+
+/// The messages and the service generated from `proto/halo2_regex.proto`.
+pub mod proto {
+    tonic::include_proto!("halo2_regex");
+}
+
+use proto::prover_server::{Prover, ProverServer};
+
+/// The gRPC service of [`ProvingService`], which takes the raw bytes instead of the json of `halo2-regex serve`.
+///
+/// The provings run on the blocking threads of tokio one by one, while `Health` is answered during them.
+pub struct GrpcProvingService {
+    service: Arc<Mutex<ProvingService>>,
+}
+
+impl GrpcProvingService {
+    /// Construct a new [`GrpcProvingService`] of `service`.
+    pub fn new(service: ProvingService) -> Self {
+        Self {
+            service: Arc::new(Mutex::new(service)),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Prover for GrpcProvingService {
+    async fn prove(
+        &self,
+        request: Request<proto::ProveRequest>,
+    ) -> Result<Response<proto::ProveResponse>, Status> {
+        let request = request.into_inner();
+        let targets = request
+            .correct_substrs
+            .iter()
+            .map(substr_from_proto)
+            .collect::<Vec<TargetSubstr>>();
+        let correct_substrs = targets_to_correct_substrs(&targets)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let service = self.service.clone();
+        let output = tokio::task::spawn_blocking(move || {
+            let mut service = service.lock().unwrap();
+            if let Some(error) = service.reload_error() {
+                return Err(Status::unavailable(error));
+            }
+            service
+                .prove_characters(request.characters, correct_substrs, request.is_success)
+                .map_err(prove_status)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))??;
+        Ok(Response::new(proto::ProveResponse {
+            proof: output.proof,
+            instances: output
+                .instances
+                .iter()
+                .map(|instance| instance.to_bytes().to_vec())
+                .collect(),
+            substrs: output.substrs.iter().map(substr_to_proto).collect(),
+        }))
+    }
+
+    async fn verify(
+        &self,
+        request: Request<proto::VerifyRequest>,
+    ) -> Result<Response<proto::VerifyResponse>, Status> {
+        let request = request.into_inner();
+        let instances = request
+            .instances
+            .iter()
+            .map(|bytes| instance_from_bytes(bytes))
+            .collect::<Option<Vec<Fr>>>()
+            .ok_or_else(|| {
+                Status::invalid_argument(
+                    "an instance is not the 32-byte representation of a field element",
+                )
+            })?;
+        let service = self.service.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            service
+                .lock()
+                .unwrap()
+                .verify_proof(&request.proof, &instances)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        match result {
+            Ok(()) => Ok(Response::new(proto::VerifyResponse {
+                valid: true,
+                error: String::new(),
+            })),
+            Err(VerificationError::Io(e)) => Err(Status::internal(e.to_string())),
+            Err(e) => Ok(Response::new(proto::VerifyResponse {
+                valid: false,
+                error: e.to_string(),
+            })),
+        }
+    }
+
+    async fn health(
+        &self,
+        _request: Request<proto::HealthRequest>,
+    ) -> Result<Response<proto::HealthResponse>, Status> {
+        // A running proving holds the lock, during which the service is still healthy.
+        let status = match self.service.try_lock() {
+            Ok(service) if service.reload_error().is_some() => "stale",
+            _ => "ok",
+        };
+        Ok(Response::new(proto::HealthResponse {
+            status: status.to_string(),
+        }))
+    }
+}
+
+/// Serve [`GrpcProvingService`] of `service` on `addr`, e.g., `0.0.0.0:50051`, until the process is terminated.
+///
+/// If [`ProvingService::watch`] is called, the files are polled on a blocking thread at its interval.
+pub fn serve_grpc(service: ProvingService, addr: &str) -> Result<(), ServeError> {
+    let bind_error = |error: String| ServeError::Bind {
+        addr: addr.to_string(),
+        error,
+    };
+    let socket_addr = addr
+        .parse()
+        .map_err(|e: std::net::AddrParseError| bind_error(e.to_string()))?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let watch_interval = service.watch_interval();
+    let service = GrpcProvingService::new(service);
+    if let Some(interval) = watch_interval {
+        let service = service.service.clone();
+        runtime.spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let service = service.clone();
+                let _ =
+                    tokio::task::spawn_blocking(move || service.lock().unwrap().reload_and_log())
+                        .await;
+            }
+        });
+    }
+    runtime
+        .block_on(
+            tonic::transport::Server::builder()
+                .add_service(ProverServer::new(service))
+                .serve(socket_addr),
+        )
+        .map_err(|e| bind_error(e.to_string()))
+}
+
+fn prove_status(error: ProveError) -> Status {
+    match error {
+        ProveError::InvalidBatchInput { error, .. } => Status::invalid_argument(error),
+        ProveError::InputTooLong(_) | ProveError::InvalidInput(_) => {
+            Status::invalid_argument(error.to_string())
+        }
+        ProveError::SanityCheckFailed(_) => Status::failed_precondition(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+fn substr_from_proto(substr: &proto::Substr) -> TargetSubstr {
+    TargetSubstr {
+        pos: substr.pos as usize,
+        string: substr.string.clone(),
+        substr_id: substr.substr_id as usize,
+    }
+}
+
+fn substr_to_proto(substr: &TargetSubstr) -> proto::Substr {
+    proto::Substr {
+        substr_id: substr.substr_id as u64,
+        pos: substr.pos as u64,
+        string: substr.string.clone(),
+    }
+}
+
+fn instance_from_bytes(bytes: &[u8]) -> Option<Fr> {
+    let bytes = <[u8; 32]>::try_from(bytes).ok()?;
+    Option::from(Fr::from_bytes(&bytes))
+}
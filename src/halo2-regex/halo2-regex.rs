@@ -4,13 +4,17 @@ use std::{
     io::Read
 };
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use halo2_base::halo2_proofs::{
     dev::{CircuitCost, FailureLocation, MockProver, VerifyFailure},
     halo2curves::bn256::{Bn256, Fr, G1Affine, G1},
     plonk::{Any, Circuit},
 };
+use halo2_regex::gadgets::folding::FoldingMode;
 use halo2_regex::helpers::*;
+use halo2_regex::presets::{find_preset, PRESETS};
+#[cfg(feature = "serve")]
+use halo2_regex::server::{ProvingService, ServeError};
 use halo2_regex::vrm::*;
 use itertools::Itertools;
 use std::marker::PhantomData;
@@ -20,6 +24,60 @@ use std::marker::PhantomData;
 struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// number of threads used to generate keys and proofs (all the cores by default)
+    #[arg(long, global = true, env = "HALO2_REGEX_THREADS")]
+    pub threads: Option<usize>,
+    /// toml file of the default values of the options, e.g., `params_path = "./build/params.bin"`, which are overridden by the `HALO2_REGEX_*` environment variables and the options given explicitly
+    #[arg(long, global = true, env = "HALO2_REGEX_CONFIG")]
+    pub config: Option<String>,
+    /// format of the results printed to stdout
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+    /// log more details to stderr, i.e., the debug logs by `-v` and the trace logs by `-vv`, which is overridden by `RUST_LOG`
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    /// log only the errors to stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// format of the logs written to stderr
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text, env = "HALO2_REGEX_LOG_FORMAT")]
+    pub log_format: LogFormat,
+}
+
+/// The exit codes of the CLI, which scripts can rely on; 0 means success, e.g., the proof is valid.
+///
+/// The usage errors are reported by clap with the same code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitCode {
+    /// The proof is invalid or the constraints are not satisfied.
+    Invalid = 1,
+    /// The options or the input data are malformed.
+    Usage = 2,
+    /// A file cannot be read or written.
+    Io = 3,
+}
+
+/// The format of the results printed by each command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    /// one json object, e.g., for CI scripts and services
+    Json,
+}
+
+/// The format of the logs written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LogFormat {
+    Text,
+    /// one json object per line, e.g., for log pipelines
+    Json,
+}
+
+/// The output format of the time taken by each phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ProfileFormat {
+    Text,
+    Json,
 }
 
 #[derive(Debug, Subcommand, Clone)]
@@ -27,80 +85,1102 @@ enum Commands {
     /// Generate a setup parameter (not for production).
     GenParams {
         /// k parameter for the one regex verification circuit.
-        #[arg(long)]
+        #[arg(long, env = "HALO2_REGEX_K")]
         k: u32,
         /// setup parameters path
-        #[arg(short, long, default_value = "./build/params.bin")]
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+    },
+    /// Convert a .ptau file of the perpetual powers of tau ceremony, e.g., generated by snarkjs, into a setup parameter.
+    ImportPtau {
+        /// .ptau file path
+        #[arg(long)]
+        ptau_path: String,
+        /// k parameter of the output setup parameter (the power of the ceremony by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<u32>,
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+    },
+    /// Convert a setup parameter into a minimal .ptau file, which includes only the points of the setup parameter and is not accepted by the setups of snarkjs.
+    ExportPtau {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// output .ptau file path
+        #[arg(long, default_value = "./build/params.ptau")]
+        ptau_path: String,
+    },
+    /// Check the structural correctness of a setup parameter, e.g., that downloaded from a ceremony, and print its digest.
+    CheckParams {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
         params_path: String,
     },
     /// Generate proving keys and verifying keys.
     GenKeys {
         /// setup parameters path
-        #[arg(short, long, default_value = "./build/params.bin")]
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
         params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
         /// regex lookup path
-        #[arg(short, long, default_value = "./test_regexes/regex3_test_lookup.txt")]
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
         allstr_file_path: String,
         /// regex substr lookup file apth
-        #[arg(short, long, default_value = "./test_regexes/substr3_test_lookup.txt")]
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
         substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
         /// proving key path
-        #[arg(long, default_value = "./build/app.pk")]
+        #[arg(long, default_value = "./build/app.pk", env = "HALO2_REGEX_PK_PATH")]
         pk_path: String,
         /// verifying key file
-        #[arg(long, default_value = "./build/app.vk")]
+        #[arg(long, default_value = "./build/app.vk", env = "HALO2_REGEX_VK_PATH")]
         vk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+        /// print the time taken by each phase of the key generation
+        #[arg(long, value_enum)]
+        profile: Option<ProfileFormat>,
+        /// abort before reading the setup parameters if the estimated memory of the key generation is not available
+        #[arg(long)]
+        check_memory: bool,
+        /// print the start and the end of each phase to stderr
+        #[arg(long)]
+        progress: bool,
     },
     Prove {
         /// setup parameters path
-        #[arg(short, long, default_value = "./build/params.bin")]
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
         params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
         /// regex lookup path
-        #[arg(short, long, default_value = "./test_regexes/regex3_test_lookup.txt")]
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
         allstr_file_path: String,
         /// regex substr lookup file apth
-        #[arg(short, long, default_value = "./test_regexes/substr3_test_lookup.txt")]
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
         substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
         /// proving key path
-        #[arg(long, default_value = "./build/app.pk")]
+        #[arg(long, default_value = "./build/app.pk", env = "HALO2_REGEX_PK_PATH")]
         pk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
         /// the string to verify
         #[arg(short, long, default_value = "")]
         string_to_verify: String,
-        /// the match target pos
-        #[arg(long)]
-        target_pos: u32,
-        /// the match target string
-        #[arg(short, long, default_value = "")]
-        target_string: String,
+        /// file of the raw bytes to verify, which are witnessed as they are instead of `string_to_verify`
+        #[arg(long, conflicts_with = "string_to_verify")]
+        input_file: Option<String>,
+        /// encoding of `string_to_verify`, in which `\r` and `\n` are replaced only if it is utf8
+        #[arg(long, value_enum, default_value_t = InputEncoding::Utf8)]
+        input_encoding: InputEncoding,
+        /// the match target pos, which is derived by running the DFA on the input if omitted together with `target_string`
+        #[arg(long, requires = "target_string")]
+        target_pos: Option<u32>,
+        /// the match target string, which is derived by running the DFA on the input if omitted together with `target_pos`
+        #[arg(short, long, requires = "target_pos")]
+        target_string: Option<String>,
+        /// a match target as `SUBSTR_ID:POS:STRING`, which can be repeated for each substring
+        #[arg(
+            long = "target",
+            value_parser = parse_target,
+            conflicts_with_all = ["target_pos", "targets_file"]
+        )]
+        targets: Vec<TargetSubstr>,
+        /// json file of the array of the match targets `{"pos": .., "string": .., "substr_id": ..}`
+        #[arg(long, conflicts_with = "target_pos")]
+        targets_file: Option<String>,
         /// the regex match pass or not
         #[arg(long)]
         is_success: bool,
         /// output proof file
-        #[arg(long, default_value = "./build/app.proof")]
+        #[arg(
+            long,
+            default_value = "./build/app.proof",
+            env = "HALO2_REGEX_PROOF_PATH"
+        )]
         proof_path: String,
+        /// output file of the instances of the proof, which is given to `verify`
+        #[arg(
+            long,
+            default_value = "./build/app.instances.json",
+            env = "HALO2_REGEX_INSTANCES_PATH"
+        )]
+        instances_path: String,
+        /// format of the instances file
+        #[arg(long, value_enum, default_value_t = InstancesFormat::Json)]
+        instances_format: InstancesFormat,
+        /// generate the proof with the Keccak256 transcript verified by the contract of `gen-evm-verifier`, which requires the `evm` feature
+        #[arg(long)]
+        evm: bool,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+        /// check the constraints with MockProver before proving
+        #[arg(long)]
+        sanity_check: bool,
+        /// hex of the 32-byte seed of the RNG blinding the proof, which makes the proof reproducible
+        #[arg(long, value_parser = parse_seed)]
+        seed: Option<[u8; 32]>,
+        /// big-endian hex of the secret salt of a public part, which can be repeated for each public part in its order and is required for the committed ones
+        #[arg(long = "salt", value_parser = parse_salt)]
+        salts: Vec<Fr>,
+        /// only synthesize the circuit and check the constraints with MockProver without the proving key
+        #[arg(long)]
+        dry_run: bool,
+        /// print the time taken by each phase of the proving
+        #[arg(long, value_enum)]
+        profile: Option<ProfileFormat>,
+        /// abort before reading the setup parameters if the estimated memory of the proving is not available
+        #[arg(long)]
+        check_memory: bool,
+        /// print the start and the end of each phase to stderr
+        #[arg(long)]
+        progress: bool,
+    },
+    /// Generate a proof for each input string in a jsonl file with the proving key loaded only once.
+    ProveBatch {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// proving key path
+        #[arg(long, default_value = "./build/app.pk", env = "HALO2_REGEX_PK_PATH")]
+        pk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// jsonl file whose each line has `string` or `input_path`, and optionally `correct_substrs` and `is_success`
+        #[arg(long)]
+        inputs: String,
+        /// output directory of the proofs and their instances
+        #[arg(long, default_value = "./build/batch")]
+        proof_dir: String,
+        /// content transfer encoding of the strings to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the strings to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the strings to verify up to their maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole strings to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+        /// hex of the 32-byte seed of the RNG blinding the proofs, which makes the proofs reproducible
+        #[arg(long, value_parser = parse_seed)]
+        seed: Option<[u8; 32]>,
+    },
+    /// Verify many proofs, e.g., generated by prove-batch, with the verifying key loaded only once, which exits with 1 if any proof is invalid.
+    VerifyBatch {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// verifying key file
+        #[arg(long, default_value = "./build/app.vk", env = "HALO2_REGEX_VK_PATH")]
+        vk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// directory of the proofs `{name}.proof` and their instances `{name}.instances.json`
+        #[arg(long, default_value = "./build/batch")]
+        proof_dir: String,
+        /// json array of `proof_path` and `instances_path` of each proof, which is used instead of `proof_dir`
+        #[arg(long)]
+        manifest: Option<String>,
+        /// content transfer encoding of the strings to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the strings to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the strings to verify up to their maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole strings to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
     },
     Verify {
         /// setup parameters path
-        #[arg(short, long, default_value = "./build/params.bin")]
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
         params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
         /// regex lookup path
-        #[arg(short, long, default_value = "./test_regexes/regex3_test_lookup.txt")]
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
         allstr_file_path: String,
         /// regex substr lookup file apth
-        #[arg(short, long, default_value = "./test_regexes/substr3_test_lookup.txt")]
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
         substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
         /// verifying key file
-        #[arg(long, default_value = "./build/app.vk")]
+        #[arg(long, default_value = "./build/app.vk", env = "HALO2_REGEX_VK_PATH")]
         vk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
         /// output proof file
-        #[arg(long, default_value = "./build/app.proof")]
+        #[arg(
+            long,
+            default_value = "./build/app.proof",
+            env = "HALO2_REGEX_PROOF_PATH"
+        )]
         proof_path: String,
+        /// file of the instances of the proof written by `prove` in either format (those of the configuration without public parts by default)
+        #[arg(long, env = "HALO2_REGEX_INSTANCES_PATH")]
+        instances_path: Option<String>,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
     },
-    GenHalo2Texts {
+    /// Print the digest of the verifying key and those of the regex definitions it is built from.
+    VkHash {
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+        /// verifying key file
+        #[arg(long, default_value = "./build/app.vk", env = "HALO2_REGEX_VK_PATH")]
+        vk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+    },
+    /// Generate the Yul code and the deployment code of the EVM verifier contract of the proofs of `prove --evm`.
+    #[cfg(feature = "evm")]
+    GenEvmVerifier {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+        /// verifying key file
+        #[arg(long, default_value = "./build/app.vk", env = "HALO2_REGEX_VK_PATH")]
+        vk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// output file of the Yul code of the verifier
+        #[arg(long, default_value = "./build/verifier.yul")]
+        yul_path: String,
+        /// output file of the hex of the deployment code of the verifier, which requires `solc`
+        #[arg(long)]
+        bytecode_path: Option<String>,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+    },
+    /// Encode the calldata of the EVM verifier contract for a proof of `prove --evm`, and optionally call the contract in a local EVM.
+    #[cfg(feature = "evm")]
+    VerifyEvm {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+        /// verifying key file
+        #[arg(long, default_value = "./build/app.vk", env = "HALO2_REGEX_VK_PATH")]
+        vk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// proof file generated by `prove --evm`
+        #[arg(
+            long,
+            default_value = "./build/app.proof",
+            env = "HALO2_REGEX_PROOF_PATH"
+        )]
+        proof_path: String,
+        /// file of the instances of the proof written by `prove`
+        #[arg(
+            long,
+            default_value = "./build/app.instances.json",
+            env = "HALO2_REGEX_INSTANCES_PATH"
+        )]
+        instances_path: String,
+        /// output file of the hex of the calldata
+        #[arg(long, default_value = "./build/app.calldata")]
+        calldata_path: String,
+        /// deploy the verifier generated from the verifying key in a local EVM and call it with the calldata, which requires `solc`
+        #[arg(long)]
+        execute: bool,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+    },
+    /// Call the EVM verifier contract with a sample proof of `prove --evm` in a local EVM and print the gas used by the call, broken down by the calldata and the pairing check.
+    #[cfg(feature = "evm")]
+    EstimateEvmGas {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+        /// verifying key file
+        #[arg(long, default_value = "./build/app.vk", env = "HALO2_REGEX_VK_PATH")]
+        vk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// file of the Yul code written by `gen-evm-verifier`, which is generated from the verifying key if not given; the contract and that without the pairing check are compiled with `solc`
+        #[arg(long)]
+        yul_path: Option<String>,
+        /// proof file generated by `prove --evm`
+        #[arg(
+            long,
+            default_value = "./build/app.proof",
+            env = "HALO2_REGEX_PROOF_PATH"
+        )]
+        proof_path: String,
+        /// file of the instances of the proof written by `prove`
+        #[arg(
+            long,
+            default_value = "./build/app.instances.json",
+            env = "HALO2_REGEX_INSTANCES_PATH"
+        )]
+        instances_path: String,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+    },
+    /// Run the key generation, the proving and the verification repeatedly and print the mean and standard deviation of their times, the peak memory and the proof size.
+    Bench {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+        /// the string to verify
+        #[arg(long, default_value = "")]
+        string_to_verify: String,
+        /// file of the raw bytes to verify, which are witnessed as they are instead of `string_to_verify`
+        #[arg(long, conflicts_with = "string_to_verify")]
+        input_file: Option<String>,
+        /// the maximum length of the string to verify
+        #[arg(long, env = "HALO2_REGEX_MAX_LEN")]
+        max_len: Option<usize>,
+        /// the regex match pass or not
+        #[arg(long)]
+        is_success: bool,
+        /// number of the iterations
+        #[arg(short = 'n', long, default_value = "3")]
+        iterations: usize,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+    },
+    /// Serve `POST /prove`, `POST /verify` and `GET /health` with the setup parameters and the keys loaded once at the start, which requires the `serve` feature.
+    #[cfg(feature = "serve")]
+    Serve {
+        /// setup parameters path
+        #[arg(
+            short,
+            long,
+            default_value = "./build/params.bin",
+            env = "HALO2_REGEX_PARAMS_PATH"
+        )]
+        params_path: String,
+        /// k parameter of the circuit, to which larger setup parameters are downsized (that of the setup parameters by default)
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: Option<usize>,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+        /// proving key path
+        #[arg(long, default_value = "./build/app.pk", env = "HALO2_REGEX_PK_PATH")]
+        pk_path: String,
+        /// serialization format of the keys
+        #[arg(long, value_enum, default_value_t = KeySerdeFormat::RawUnchecked, env = "HALO2_REGEX_SERDE_FORMAT")]
+        serde_format: KeySerdeFormat,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+        /// address on which the server listens
+        #[arg(long, default_value = "127.0.0.1", env = "HALO2_REGEX_HOST")]
+        host: String,
+        /// port on which the server listens
+        #[arg(long, default_value = "8080", env = "HALO2_REGEX_PORT")]
+        port: u16,
+        /// check the constraints with MockProver before each proving and reject the unsatisfied inputs
+        #[arg(long, env = "HALO2_REGEX_SANITY_CHECK")]
+        sanity_check: bool,
+        /// serve the gRPC service of `proto/halo2_regex.proto` taking the raw bytes instead of the json endpoints, which requires the `grpc` feature
+        #[arg(long)]
+        grpc: bool,
+        /// poll the regex definition files and the proving key every this many seconds and swap in the updated keys once they match the regex definitions
+        #[arg(long, env = "HALO2_REGEX_WATCH_INTERVAL")]
+        watch_interval: Option<u64>,
+    },
+    /// Compile a decomposed regex and check an input with MockProver, printing the masked output and the substring ids, without the setup parameters, the keys or the proof.
+    Mock {
         #[arg(short, long)]
         decomposed_regex_path: String,
+        /// the string to verify
+        #[arg(long, default_value = "")]
+        string_to_verify: String,
+        /// file of the raw bytes to verify, which are witnessed as they are instead of `string_to_verify`
+        #[arg(long, conflicts_with = "string_to_verify")]
+        input_file: Option<String>,
+        /// the regex match pass or not, in which case the substrings are derived from the input
+        #[arg(long)]
+        is_success: bool,
+        /// the maximum length of the string to verify (`max_byte_size` of the decomposed regex by default)
+        #[arg(long, env = "HALO2_REGEX_MAX_LEN")]
+        max_len: Option<usize>,
+        /// k parameter for the one regex verification circuit.
+        #[arg(long, default_value = "17", env = "HALO2_REGEX_K")]
+        k: usize,
+        /// directory in which the regex definition files are generated
+        #[arg(long, default_value = "./build")]
+        build_dir: String,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+    },
+    /// Estimate the rows, the lookups, the proof size and the proving time of the circuit for a decomposed regex without generating keys.
+    Estimate {
+        #[arg(short, long)]
+        decomposed_regex_path: String,
+        /// the maximum length of the string to verify
+        #[arg(long, env = "HALO2_REGEX_MAX_LEN")]
+        max_len: usize,
+        /// k parameter for the one regex verification circuit.
+        #[arg(long, default_value = "17", env = "HALO2_REGEX_K")]
+        k: usize,
+        /// directory in which the regex definition files are generated
+        #[arg(long, default_value = "./build")]
+        build_dir: String,
+    },
+    /// Choose the numbers of the advice and fixed columns for k and print them as json.
+    PlanColumns {
+        /// k parameter for the one regex verification circuit.
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: usize,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+    },
+    /// Print the metrics of the circuit as json, including the rows and cells contributed by each regex definition.
+    CircuitMetrics {
+        /// k parameter for the one regex verification circuit.
+        #[arg(long, env = "HALO2_REGEX_K")]
+        k: usize,
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// content transfer encoding of the string to verify
+        #[arg(long, value_enum, default_value_t = TransferEncoding::Identity, env = "HALO2_REGEX_TRANSFER_ENCODING")]
+        transfer_encoding: TransferEncoding,
+        /// normalization of folded lines in the string to verify
+        #[arg(long, value_enum, env = "HALO2_REGEX_FOLDING_MODE")]
+        folding_mode: Option<FoldingMode>,
+        /// byte padding the string to verify up to its maximum length, e.g., 0x80 if the regex matches 0x00
+        #[arg(long, default_value = "0", value_parser = parse_byte, env = "HALO2_REGEX_PADDING_BYTE")]
+        padding_byte: u8,
+        /// expose the whole string to verify as public inputs
+        #[arg(long, env = "HALO2_REGEX_FULL_TRANSPARENCY")]
+        full_transparency: bool,
+        /// number of advice columns
+        #[arg(long, default_value = "25", env = "HALO2_REGEX_NUM_ADVICE")]
+        num_advice: usize,
+        /// number of fixed columns
+        #[arg(long, default_value = "1", env = "HALO2_REGEX_NUM_FIXED")]
+        num_fixed: usize,
+    },
+    /// Summarize the regex definitions, i.e., the states, the alphabet, the substring transitions and the table rows that the key generation bakes into the keys.
+    Inspect {
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+    },
+    /// Audit the lookup tables of the regex definitions, i.e., the states missing some of the 256 characters without a dead state, the substring transitions unknown to the whole regex and the ones shared by two substrings; exit with 1 if any is found.
+    Audit {
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+    },
+    /// Compute the commitment of the regex definitions, which is the first public input of the proofs.
+    GenRegexCommitment {
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// directory of `substr0.txt`, `substr1.txt`, ..., e.g., written by `gen-halo2-texts`, used instead of `substr_file_path`
+        #[arg(
+            long,
+            conflicts_with = "substr_file_path",
+            env = "HALO2_REGEX_SUBSTRS_DIR"
+        )]
+        substrs_dir: Option<String>,
+        /// decomposed regex json, whose lookup files are generated in `build_dir` instead of reading `allstr_file_path` and `substr_file_path`
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["allstr_file_path", "substr_file_path", "substrs_dir"]
+        )]
+        decomposed_regex_path: Option<String>,
+        /// directory in which the regex definition files are generated
+        #[arg(long, default_value = "./build")]
+        build_dir: String,
+    },
+    /// Merkleize the state transitions of the regex definitions for a regex registry.
+    GenRegexMerkleTree {
+        /// regex lookup path
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/regex3_test_lookup.txt",
+            env = "HALO2_REGEX_ALLSTR_FILE_PATH"
+        )]
+        allstr_file_path: String,
+        /// regex substr lookup file apth
+        #[arg(
+            short,
+            long,
+            default_value = "./test_regexes/substr3_test_lookup.txt",
+            env = "HALO2_REGEX_SUBSTR_FILE_PATH"
+        )]
+        substr_file_path: String,
+        /// output json file of the root and the proofs of all transitions
+        #[arg(short, long, default_value = "./build/regex_merkle_tree.json")]
+        output_path: String,
+    },
+    GenHalo2Texts {
         #[arg(short, long)]
+        decomposed_regex_path: String,
+        #[arg(short, long, env = "HALO2_REGEX_ALLSTR_FILE_PATH")]
         allstr_file_path: String,
         #[arg(short, long)]
         substrs_dir_path: String,
@@ -113,74 +1193,1614 @@ enum Commands {
         #[arg(short, long)]
         template_name: String,
     },
+    /// Write the input json of the witness generator of the template of `gen-circom` for a message and the expected outputs of the template.
+    GenInput {
+        #[arg(short, long)]
+        decomposed_regex_path: String,
+        /// the string to verify
+        #[arg(long, default_value = "")]
+        string_to_verify: String,
+        /// file of the raw bytes to verify, which are witnessed as they are instead of `string_to_verify`
+        #[arg(long, conflicts_with = "string_to_verify")]
+        input_file: Option<String>,
+        /// the parameter `msg_bytes` of the template, to which the message is padded (`max_byte_size` of the decomposed regex by default)
+        #[arg(long)]
+        msg_bytes: Option<usize>,
+        /// directory in which the regex definition files are generated
+        #[arg(long, default_value = "./build")]
+        build_dir: String,
+        /// output json of the input signals
+        #[arg(long, default_value = "./build/input.json")]
+        input_json_path: String,
+        /// output json of the expected output signals
+        #[arg(long, default_value = "./build/expected_outputs.json")]
+        outputs_json_path: String,
+    },
+    /// List or emit the preset regexes shipped with the crate.
+    Preset {
+        #[command(subcommand)]
+        command: PresetCommands,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum PresetCommands {
+    /// Print the names and the descriptions of the presets.
+    List,
+    /// Write the decomposed regex json and the regex definition files of a preset, which are given to the other commands by `--allstr-file-path` and `--substrs-dir`.
+    Emit {
+        /// name of the preset printed by `preset list`
+        #[arg(long)]
+        name: String,
+        /// directory of the output files
+        #[arg(long, default_value = "./build/preset")]
+        out_dir: String,
+    },
+}
+
+fn parse_seed(hex: &str) -> Result<[u8; 32], String> {
+    let hex = hex.trim_start_matches("0x");
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err("the seed must be the hex of 32 bytes".to_string());
+    }
+    let mut seed = [0; 32];
+    for (idx, byte) in seed.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * idx..2 * idx + 2], 16).map_err(|e| e.to_string())?;
+    }
+    Ok(seed)
+}
+
+fn parse_salt(hex: &str) -> Result<Fr, String> {
+    let mut bytes =
+        parse_seed(hex).map_err(|_| "the salt must be the hex of 32 bytes".to_string())?;
+    bytes.reverse();
+    Option::from(Fr::from_bytes(&bytes))
+        .ok_or_else(|| "the salt must be less than the modulus of the field".to_string())
+}
+
+fn parse_byte(byte: &str) -> Result<u8, String> {
+    match byte.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).map_err(|e| e.to_string()),
+        None => byte.parse::<u8>().map_err(|e| e.to_string()),
+    }
+}
+
+fn parse_target(target: &str) -> Result<TargetSubstr, String> {
+    let fields = target.splitn(3, ':').collect::<Vec<&str>>();
+    if fields.len() != 3 {
+        return Err("the target must be SUBSTR_ID:POS:STRING".to_string());
+    }
+    let substr_id = fields[0].parse::<usize>().map_err(|e| e.to_string())?;
+    if substr_id == 0 {
+        return Err("the substring id starts from 1".to_string());
+    }
+    Ok(TargetSubstr {
+        pos: fields[1].parse().map_err(|e| e.to_string())?,
+        string: fields[2].to_string(),
+        substr_id,
+    })
+}
+
+fn exit_with(code: ExitCode, message: impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(code as i32)
+}
+
+/// Exit with [`ExitCode::Io`] if any of `paths` does not exist, instead of panicking while reading it.
+fn require_files(paths: &[&str]) {
+    for path in paths.iter() {
+        if !Path::new(path).exists() {
+            exit_with(ExitCode::Io, format!("{} does not exist", path));
+        }
+    }
+}
+
+/// Return the exit code of the failure of the proving.
+fn prove_exit_code(error: &ProveError) -> ExitCode {
+    match error {
+        ProveError::InvalidProvingKey(_) | ProveError::Io(_) => ExitCode::Io,
+        ProveError::InvalidBatchInput { .. }
+        | ProveError::InputTooLong(_)
+        | ProveError::InvalidInput(_)
+        | ProveError::KeyMismatch(_)
+        | ProveError::Config(_) => ExitCode::Usage,
+        _ => ExitCode::Invalid,
+    }
+}
+
+/// Exit with [`ExitCode::Usage`] if an input string of `circuit` is invalid, e.g., longer than its configured maximum length, as checked by [`RegexCircuit::check_inputs`].
+fn check_inputs_or_exit(circuit: &RegexCircuit<Fr>) {
+    if let Err(e) = circuit.check_inputs() {
+        exit_with(ExitCode::Usage, e);
+    }
+}
+
+#[cfg(feature = "evm")]
+fn prove_evm(
+    params_path: &str,
+    pk_path: &str,
+    serde_format: KeySerdeFormat,
+    seed: Option<[u8; 32]>,
+    proof_path: &str,
+    circuit: RegexCircuit<Fr>,
+    instances: &[Fr],
+) -> Result<(), ProveError> {
+    halo2_regex::evm::prove_evm(
+        params_path,
+        pk_path,
+        serde_format,
+        seed,
+        proof_path,
+        circuit,
+        instances,
+    )
+}
+
+#[cfg(not(feature = "evm"))]
+fn prove_evm(
+    _params_path: &str,
+    _pk_path: &str,
+    _serde_format: KeySerdeFormat,
+    _seed: Option<[u8; 32]>,
+    _proof_path: &str,
+    _circuit: RegexCircuit<Fr>,
+    _instances: &[Fr],
+) -> Result<(), ProveError> {
+    exit_with(ExitCode::Usage, "--evm requires the evm feature")
+}
+
+#[cfg(feature = "grpc")]
+fn serve_grpc(service: ProvingService, addr: &str) -> Result<(), ServeError> {
+    halo2_regex::grpc::serve_grpc(service, addr)
+}
+
+#[cfg(all(feature = "serve", not(feature = "grpc")))]
+fn serve_grpc(_service: ProvingService, _addr: &str) -> Result<(), ServeError> {
+    exit_with(ExitCode::Usage, "--grpc requires the grpc feature")
+}
+
+/// Generate the regex definition files of the decomposed regex of `decomposed_regex_path` in `build_dir`, i.e., `{prefix}_allstr.txt` and `{prefix}_substr{i}.txt` of the `i`-th public part.
+///
+/// # Return values
+/// Return the decomposed regex, the file path of the allstr regex definition and those of the substr regex definitions in order.
+fn gen_decomposed_regex_files(
+    decomposed_regex_path: &str,
+    build_dir: &str,
+    prefix: &str,
+) -> (DecomposedRegexConfig, String, Vec<String>) {
+    require_files(&[decomposed_regex_path]);
+    let regex_decomposed: DecomposedRegexConfig =
+        serde_json::from_reader(File::open(decomposed_regex_path).unwrap())
+            .unwrap_or_else(|e| exit_with(ExitCode::Usage, e));
+    let num_public_part = regex_decomposed
+        .parts
+        .iter()
+        .filter(|part| part.is_public)
+        .count();
+    let allstr_file_path = PathBuf::new()
+        .join(build_dir)
+        .join(format!("{}_allstr.txt", prefix));
+    let substr_file_pathes = (0..num_public_part)
+        .map(|idx| {
+            PathBuf::new()
+                .join(build_dir)
+                .join(format!("{}_substr{}.txt", prefix, idx))
+        })
+        .collect_vec();
+    if let Err(e) = regex_decomposed.gen_regex_files(&allstr_file_path, &substr_file_pathes) {
+        exit_with(
+            ExitCode::Usage,
+            format!("failed to compile the decomposed regex: {}", e),
+        );
+    }
+    (
+        regex_decomposed,
+        allstr_file_path.to_str().unwrap().to_string(),
+        substr_file_pathes
+            .iter()
+            .map(|path| path.to_str().unwrap().to_string())
+            .collect_vec(),
+    )
+}
+
+fn set_substrs_dir(substrs_dir: &str) {
+    // The directory of a match-only regex has no substr0.txt.
+    if !Path::new(substrs_dir).is_dir() {
+        exit_with(ExitCode::Io, format!("{} is not a directory", substrs_dir));
+    }
+    set_config_substr_file_paths(substr_file_paths_in_dir(substrs_dir));
+}
+
+#[cfg(feature = "evm")]
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Return the hex of [`vk_hash`] of the verifying key of [`RegexCircuit`] read from `vk_path`.
+fn vk_hash_hex(vk_path: &str, serde_format: KeySerdeFormat) -> String {
+    let vk = read_verifying_key::<Bn256, RegexCircuit<Fr>>(vk_path, serde_format)
+        .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+    format!("{:?}", vk_hash(&vk))
+}
+
+fn print_json(value: serde_json::Value) {
+    println!("{}", serde_json::to_string_pretty(&value).unwrap());
+}
+
+/// Print the synthesized regions, the masked outputs and the failures of the constraints, and exit with [`ExitCode::Invalid`] if the constraints are not satisfied.
+fn print_dry_run_report(report: DryRunReport, json: bool) {
+    if json {
+        let failures = match &report.verify_result {
+            Ok(()) => vec![],
+            Err(failures) => failures.clone(),
+        };
+        let substrs = report
+            .outputs
+            .iter()
+            .map(|output| output.substrs())
+            .collect_vec();
+        print_json(serde_json::json!({
+            "regions": report.regions,
+            "outputs": report.outputs,
+            "substrs": substrs,
+            "satisfied": report.verify_result.is_ok(),
+            "failures": failures,
+        }));
+        if report.verify_result.is_err() {
+            std::process::exit(ExitCode::Invalid as i32);
+        }
+        return;
+    }
+    for region in report.regions.iter() {
+        println!("region {}: {} rows", region.name, region.rows);
+    }
+    for (input_idx, output) in report.outputs.iter().enumerate() {
+        println!(
+            "masked characters of input {}: {:?}",
+            input_idx,
+            String::from_utf8_lossy(&output.masked_characters)
+        );
+        println!("substr ids of input {}: {:?}", input_idx, output.substr_ids);
+    }
+    match report.verify_result {
+        Ok(()) => println!("constraints are satisfied"),
+        Err(failures) => {
+            for failure in failures.iter() {
+                println!("{}", failure);
+            }
+            println!("constraints are not satisfied");
+            std::process::exit(ExitCode::Invalid as i32);
+        }
+    }
+}
+
+fn print_inspection(inspection: &RegexInspection) {
+    println!("states: {}", inspection.num_states);
+    println!("first state: {}", inspection.first_state);
+    println!("accepted state: {}", inspection.accepted_state);
+    println!("transitions: {}", inspection.num_transitions);
+    println!(
+        "alphabet: {} of 256 characters ({:.1}%): {}",
+        inspection.alphabet.len(),
+        inspection.alphabet_coverage * 100.0,
+        format_alphabet(&inspection.alphabet)
+    );
+    for substr in inspection.substrs.iter() {
+        println!(
+            "substr {}: max length {}, {} transitions in {} table rows, start states {:?}, end states {:?}",
+            substr.substr_id,
+            substr.max_length,
+            substr.transitions.len(),
+            substr.tagged_rows,
+            substr.start_states,
+            substr.end_states
+        );
+        for (cur_state, next_state) in substr.transitions.iter() {
+            println!("  {} -> {}", cur_state, next_state);
+        }
+    }
+    println!("transition table rows: {}", inspection.transition_rows);
+    println!("endpoint table rows: {}", inspection.endpoint_rows);
+}
+
+fn print_audit(audit: &RegexAudit) {
+    println!("states: {}", audit.num_states);
+    match audit.dead_state {
+        Some(state) => println!("dead state: {}", state),
+        None => println!("dead state: none"),
+    }
+    for state in audit.incomplete_states.iter() {
+        println!(
+            "state {} has no transition of {} characters: {}",
+            state.state,
+            state.missing_characters.len(),
+            format_alphabet(&state.missing_characters)
+        );
+    }
+    for transition in audit.unknown_substr_transitions.iter() {
+        println!(
+            "substr {}: {} -> {} is not a transition of the whole regex",
+            transition.substr_id, transition.cur_state, transition.next_state
+        );
+    }
+    for transition in audit.shared_substr_transitions.iter() {
+        println!(
+            "{} -> {} is claimed by the substrs {:?}",
+            transition.cur_state, transition.next_state, transition.substr_ids
+        );
+    }
+    if audit.is_passed() {
+        println!("audit passed");
+    } else {
+        println!("audit failed");
+    }
+}
+
+/// Format sorted `characters` as ranges, e.g., `'a'-'z' 0x0d`, where the non-printable characters are in hex.
+fn format_alphabet(characters: &[u8]) -> String {
+    let format_char = |char: u8| {
+        if char.is_ascii_graphic() {
+            format!("{:?}", char as char)
+        } else {
+            format!("0x{:02x}", char)
+        }
+    };
+    let mut ranges: Vec<(u8, u8)> = vec![];
+    for char in characters.iter() {
+        match ranges.last_mut() {
+            Some((_, end)) if *end as u16 + 1 == *char as u16 => *end = *char,
+            _ => ranges.push((*char, *char)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                format_char(start)
+            } else {
+                format!("{}-{}", format_char(start), format_char(end))
+            }
+        })
+        .join(" ")
+}
+
+fn print_profile(profile: &Profile, format: ProfileFormat) {
+    match format {
+        ProfileFormat::Text => {
+            for phase in profile.phases.iter() {
+                println!("{}: {:.3} s", phase.name, phase.secs);
+            }
+            println!("total: {:.3} s", profile.total_secs());
+        }
+        ProfileFormat::Json => println!("{}", serde_json::to_string_pretty(&profile).unwrap()),
+    }
+}
+
+/// Exit with a clear message if the memory estimated by `required_bytes` is not available, instead of being killed by the OOM killer in the middle.
+fn check_memory_or_exit(params_path: &str, required_bytes: impl Fn(&MemoryEstimate) -> u64) {
+    let estimate =
+        estimate_memory_for_params(params_path).expect("failed to read the setup parameters");
+    if let Err(e) = check_memory(required_bytes(&estimate)) {
+        exit_with(ExitCode::Invalid, e);
+    }
+}
+
+/// Return the callback logging the start and the end of each phase.
+fn progress_logger() -> ProgressCallback {
+    ProgressCallback::new(|event| match event {
+        ProgressEvent::PhaseStarted { name } => tracing::info!(phase = %name, "started"),
+        ProgressEvent::PhaseFinished(phase) => {
+            tracing::info!(phase = %phase.name, secs = phase.secs, "finished")
+        }
+    })
+}
+
+/// Write the logs to stderr at the level given by `-v` and `-q`, or by `RUST_LOG` if it is set.
+fn init_logging(verbose: u8, quiet: bool, log_format: LogFormat) {
+    let level = match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => "info",
+        (false, 1) => "debug",
+        (false, _) => "trace",
+    };
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(level));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    match log_format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+}
+
+/// Return the path of the project config file given by `--config` or `HALO2_REGEX_CONFIG`, which must be known before parsing the other options.
+fn project_config_path() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next();
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.to_string());
+        }
+    }
+    std::env::var("HALO2_REGEX_CONFIG").ok()
+}
+
+/// Set each `key = value` of the project config file as the environment variable `HALO2_REGEX_KEY` unless it is already set, so that it becomes the default value of the option `key`.
+fn load_project_config(config_path: &str) {
+    let text = std::fs::read_to_string(config_path).unwrap_or_else(|e| {
+        exit_with(
+            ExitCode::Io,
+            format!("failed to read the config file {}: {}", config_path, e),
+        )
+    });
+    let config = text.parse::<toml::Table>().unwrap_or_else(|e| {
+        exit_with(
+            ExitCode::Usage,
+            format!("failed to parse the config file {}: {}", config_path, e),
+        )
+    });
+    for (key, value) in config.iter() {
+        let name = format!("HALO2_REGEX_{}", key.to_uppercase().replace('-', "_"));
+        if std::env::var_os(&name).is_some() {
+            continue;
+        }
+        let value = match value {
+            toml::Value::String(value) => value.clone(),
+            toml::Value::Integer(_) | toml::Value::Boolean(_) => value.to_string(),
+            _ => exit_with(
+                ExitCode::Usage,
+                format!(
+                    "the value of {} in the config file must be a string, an integer or a boolean",
+                    key
+                ),
+            ),
+        };
+        std::env::set_var(name, value);
+    }
 }
 
 fn main() {
+    if let Some(config_path) = project_config_path() {
+        load_project_config(&config_path);
+    }
     let cli = Cli::parse();
+    init_logging(cli.verbose, cli.quiet, cli.log_format);
+    if let Some(threads) = cli.threads {
+        set_num_threads(threads).expect("failed to configure the thread pool");
+    }
+    let json = cli.output == OutputFormat::Json;
     match cli.command {
-        Commands::GenParams { k, params_path } => gen_params(&params_path, k).unwrap(),
+        Commands::GenParams { k, params_path } => {
+            gen_params::<Bn256>(&params_path, k).unwrap();
+            if json {
+                print_json(serde_json::json!({ "params_path": params_path, "k": k }));
+            }
+        }
+        Commands::ImportPtau {
+            ptau_path,
+            k,
+            params_path,
+        } => {
+            import_ptau(&ptau_path, &params_path, k).expect("invalid .ptau file");
+            if json {
+                print_json(serde_json::json!({ "params_path": params_path }));
+            }
+        }
+        Commands::CheckParams { params_path } => {
+            require_files(&[&params_path]);
+            match check_params(&params_path) {
+                Ok(summary) if json => print_json(serde_json::json!({
+                    "valid": true,
+                    "k": summary.k,
+                    "digest": summary.digest,
+                })),
+                Ok(summary) => {
+                    println!("params are valid");
+                    println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+                }
+                Err(e) => {
+                    if json {
+                        print_json(serde_json::json!({
+                            "valid": false,
+                            "error": e.to_string(),
+                        }));
+                    } else {
+                        println!("params are invalid: {}", e);
+                    }
+                    match e {
+                        ParamsCheckError::Io(_) => std::process::exit(ExitCode::Io as i32),
+                        _ => std::process::exit(ExitCode::Invalid as i32),
+                    }
+                }
+            }
+        }
+        Commands::ExportPtau {
+            params_path,
+            ptau_path,
+        } => {
+            export_ptau(&params_path, &ptau_path).unwrap();
+            if json {
+                print_json(serde_json::json!({ "ptau_path": ptau_path }));
+            }
+        }
         Commands::GenKeys {
             params_path,
+            k,
             allstr_file_path,
             substr_file_path,
+            substrs_dir,
             pk_path,
             vk_path,
+            serde_format,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+            profile,
+            check_memory,
+            progress,
         } => {
             set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            if check_memory {
+                check_memory_or_exit(&params_path, |estimate| estimate.keygen_bytes);
+            }
+            let progress = progress.then(progress_logger);
 
             let circuit = RegexCircuit::<Fr> {
                 characters: vec![],
                 correct_substrs: vec![],
+                extra_inputs: vec![],
+                revealed_parts: vec![],
+                salts: vec![],
                 is_success: false,
+                cancellation: None,
+                progress: progress.clone(),
+                outputs: Default::default(),
+                stats: Default::default(),
                 _marker: PhantomData,
             };
-            gen_keys(&params_path, &pk_path, &vk_path, circuit).expect("key generation failed");
+            require_files(&[&params_path]);
+            let timings = gen_keys::<Bn256, _>(
+                &params_path,
+                &pk_path,
+                &vk_path,
+                serde_format,
+                None,
+                progress.as_ref(),
+                circuit,
+            )
+            .unwrap_or_else(|e| {
+                let code = match e {
+                    KeygenError::Config(_) => ExitCode::Usage,
+                    KeygenError::Io(_) => ExitCode::Io,
+                    _ => ExitCode::Invalid,
+                };
+                exit_with(code, format!("key generation failed: {}", e))
+            });
+            if json {
+                print_json(serde_json::json!({
+                    "pk_path": pk_path,
+                    "vk_path": vk_path,
+                    "vk_hash": vk_hash_hex(&vk_path, serde_format),
+                    "timings": timings,
+                }));
+            } else if let Some(format) = profile {
+                print_profile(&timings, format);
+            }
         }
         Commands::Prove {
             params_path,
+            k,
             allstr_file_path,
             substr_file_path,
+            substrs_dir,
             pk_path,
+            serde_format,
             string_to_verify,
+            input_file,
+            input_encoding,
             target_pos,
             target_string,
+            targets,
+            targets_file,
             is_success,
             proof_path,
+            instances_path,
+            instances_format,
+            evm,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+            sanity_check,
+            seed,
+            salts,
+            dry_run,
+            profile,
+            check_memory,
+            progress,
         } => {
             set_config_params(allstr_file_path, substr_file_path);
-            // println!("Before replace {:?}", string_to_verify);
-            let mut string_to_verify_fix = string_to_verify.replace("\\r", "\r");
-            string_to_verify_fix = string_to_verify_fix.replace("\\n", "\n");
-            // println!("After replace {:?}", string_to_verify_fix);
-            let characters: Vec<u8> = string_to_verify_fix.bytes().collect();
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            if check_memory && !dry_run {
+                check_memory_or_exit(&params_path, |estimate| estimate.proving_bytes);
+            }
+            let progress = progress.then(progress_logger);
+            let characters: Vec<u8> = match input_file {
+                Some(input_file) => {
+                    std::fs::read(input_file).expect("failed to read the input file")
+                }
+                None if input_encoding != InputEncoding::Utf8 => {
+                    decode_input(&string_to_verify, input_encoding)
+                        .expect("the string to verify is not in the input encoding")
+                }
+                None => {
+                    // println!("Before replace {:?}", string_to_verify);
+                    let mut string_to_verify_fix = string_to_verify.replace("\\r", "\r");
+                    string_to_verify_fix = string_to_verify_fix.replace("\\n", "\n");
+                    // println!("After replace {:?}", string_to_verify_fix);
+                    string_to_verify_fix.bytes().collect()
+                }
+            };
+            let targets = match targets_file {
+                Some(targets_file) => serde_json::from_reader(
+                    File::open(targets_file).expect("failed to open the targets file"),
+                )
+                .expect("failed to parse the targets file"),
+                None => targets,
+            };
+            let correct_substrs = match (target_pos, target_string) {
+                (Some(target_pos), Some(target_string)) => {
+                    vec![(target_pos as usize, target_string)]
+                }
+                _ if !targets.is_empty() => targets_to_correct_substrs(&targets)
+                    .unwrap_or_else(|e| exit_with(ExitCode::Usage, e)),
+                // The substrings are checked only if the regex match passes.
+                _ if !is_success => vec![],
+                _ => derive_correct_substrs(0, &characters)
+                    .expect("failed to derive the substrings of the input"),
+            };
             let circuit = RegexCircuit::<Fr> {
                 characters,
-                correct_substrs: vec![(target_pos as usize, target_string)],
+                correct_substrs,
+                extra_inputs: vec![],
+                revealed_parts: vec![],
+                salts,
                 is_success: is_success,
+                cancellation: None,
+                progress: progress.clone(),
+                outputs: Default::default(),
+                stats: Default::default(),
                 _marker: PhantomData,
             };
-            prove(&params_path, &pk_path, is_success, &proof_path, circuit).unwrap();
-            println!("proof generated");
+            check_inputs_or_exit(&circuit);
+            if dry_run {
+                let report = halo2_regex::helpers::dry_run(&params_path, &circuit).unwrap();
+                print_dry_run_report(report, json);
+                return;
+            }
+            let instances = circuit.instances();
+            require_files(&[&params_path, &pk_path]);
+            let result = if evm {
+                prove_evm(
+                    &params_path,
+                    &pk_path,
+                    serde_format,
+                    seed,
+                    &proof_path,
+                    circuit.clone(),
+                    &instances,
+                )
+                // The EVM proving does not profile its phases.
+                .map(|_| Profile::default())
+            } else {
+                prove::<Bn256, _>(
+                    &params_path,
+                    &pk_path,
+                    serde_format,
+                    sanity_check,
+                    seed,
+                    None,
+                    progress.as_ref(),
+                    &proof_path,
+                    &circuit,
+                    &instances,
+                )
+            };
+            let timings = result.unwrap_or_else(|e| {
+                exit_with(prove_exit_code(&e), format!("proving failed: {}", e))
+            });
+            if let Err(e) =
+                write_instances_with_format(&instances_path, &instances, instances_format)
+            {
+                exit_with(
+                    ExitCode::Io,
+                    format!("failed to write the instances: {}", e),
+                );
+            }
+            if json {
+                let substrs = circuit
+                    .masked_outputs()
+                    .iter()
+                    .map(|output| output.substrs())
+                    .collect_vec();
+                print_json(serde_json::json!({
+                    "proof_path": proof_path,
+                    "instances_path": instances_path,
+                    "instances": instances
+                        .iter()
+                        .map(|instance| format!("{:?}", instance))
+                        .collect_vec(),
+                    "substrs": substrs,
+                    "timings": timings,
+                }));
+                return;
+            }
+            tracing::info!(proof_path = %proof_path, "proof generated");
+            if let Some(format) = profile {
+                print_profile(&timings, format);
+            }
+        }
+        Commands::ProveBatch {
+            params_path,
+            k,
+            allstr_file_path,
+            substr_file_path,
+            pk_path,
+            serde_format,
+            inputs,
+            proof_dir,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+            seed,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            require_files(&[&params_path, &pk_path, &inputs]);
+            let outputs = prove_batch(
+                &params_path,
+                &pk_path,
+                serde_format,
+                seed,
+                None,
+                None,
+                &inputs,
+                &proof_dir,
+            )
+            .unwrap_or_else(|e| exit_with(prove_exit_code(&e), format!("proving failed: {}", e)));
+            if json {
+                print_json(serde_json::json!({ "proofs": outputs }));
+                return;
+            }
+            for output in outputs.iter() {
+                tracing::info!(proof_path = %output.proof_path, "proof generated");
+            }
+        }
+        Commands::VerifyBatch {
+            params_path,
+            k,
+            allstr_file_path,
+            substr_file_path,
+            vk_path,
+            serde_format,
+            proof_dir,
+            manifest,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            let proofs: Vec<BatchProofOutput> = match manifest {
+                Some(manifest) => serde_json::from_reader(File::open(manifest).unwrap()).unwrap(),
+                None => batch_proof_outputs(&proof_dir).unwrap(),
+            };
+            require_files(&[&params_path, &vk_path]);
+            check_key_metadata(&vk_path).unwrap_or_else(|e| exit_with(ExitCode::Usage, e));
+            let results = verify_batch(&params_path, &vk_path, serde_format, &proofs)
+                .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let num_invalid = results.iter().filter(|result| !result.valid).count();
+            if json {
+                print_json(serde_json::json!({
+                    "results": results,
+                    "num_valid": results.len() - num_invalid,
+                    "num_proofs": results.len(),
+                }));
+                if num_invalid > 0 {
+                    std::process::exit(ExitCode::Invalid as i32);
+                }
+                return;
+            }
+            for result in results.iter() {
+                match &result.error {
+                    Some(error) => println!("{}\tinvalid\t{}", result.proof_path, error),
+                    None if result.valid => println!("{}\tvalid", result.proof_path),
+                    None => println!("{}\tinvalid", result.proof_path),
+                }
+            }
+            println!(
+                "{} of {} proofs are valid",
+                results.len() - num_invalid,
+                results.len()
+            );
+            if num_invalid > 0 {
+                std::process::exit(ExitCode::Invalid as i32);
+            }
         }
         Commands::Verify {
             params_path,
+            k,
             allstr_file_path,
             substr_file_path,
+            substrs_dir,
             vk_path,
+            serde_format,
             proof_path,
+            instances_path,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
         } => {
             set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
             let circuit = RegexCircuit::<Fr> {
                 characters: vec![],
                 correct_substrs: vec![],
+                extra_inputs: vec![],
+                revealed_parts: vec![],
+                salts: vec![],
                 is_success: false,
+                cancellation: None,
+                progress: None,
+                outputs: Default::default(),
+                stats: Default::default(),
                 _marker: PhantomData,
             };
-            let result = verify(&params_path, &vk_path, &proof_path, circuit);
-            if result {
-                println!("proof is valid");
+            let instances = match instances_path {
+                Some(instances_path) => read_instances(&instances_path).unwrap_or_else(|e| {
+                    exit_with(ExitCode::Io, format!("failed to read the instances: {}", e))
+                }),
+                None => circuit.instances(),
+            };
+            require_files(&[&params_path, &vk_path, &proof_path]);
+            let result = check_num_instances(&instances).and_then(|_| {
+                verify::<Bn256, _>(
+                    &params_path,
+                    &vk_path,
+                    serde_format,
+                    &proof_path,
+                    circuit,
+                    &instances,
+                )
+            });
+            if json {
+                print_json(serde_json::json!({
+                    "proof_path": proof_path,
+                    "vk_hash": vk_hash_hex(&vk_path, serde_format),
+                    "valid": result.is_ok(),
+                    "error": result.as_ref().err().map(|e| e.to_string()),
+                }));
+            } else {
+                match &result {
+                    Ok(()) => println!("proof is valid"),
+                    Err(e) => println!("proof is invalid: {}", e),
+                }
+            }
+            match result {
+                Ok(()) => {}
+                Err(VerificationError::Io(_)) => std::process::exit(ExitCode::Io as i32),
+                Err(
+                    VerificationError::KeyMismatch(_)
+                    | VerificationError::MetadataMismatch(_)
+                    | VerificationError::Config(_),
+                ) => std::process::exit(ExitCode::Usage as i32),
+                Err(_) => std::process::exit(ExitCode::Invalid as i32),
+            }
+        }
+        #[cfg(feature = "evm")]
+        Commands::GenEvmVerifier {
+            params_path,
+            k,
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+            vk_path,
+            serde_format,
+            yul_path,
+            bytecode_path,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            require_files(&[&params_path, &vk_path]);
+            let params =
+                read_params::<Bn256>(&params_path).unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let vk = read_verifying_key::<Bn256, RegexCircuit<Fr>>(&vk_path, serde_format)
+                .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let num_instance = RegexCircuit::<Fr>::default().instances().len();
+            let yul = halo2_regex::evm::gen_evm_verifier_yul(&params, &vk, num_instance);
+            std::fs::write(&yul_path, &yul).unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            if let Some(bytecode_path) = &bytecode_path {
+                let bytecode = halo2_regex::evm::gen_evm_verifier(&params, &vk, num_instance);
+                std::fs::write(bytecode_path, format!("0x{}", hex_string(&bytecode)))
+                    .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            }
+            if json {
+                print_json(serde_json::json!({
+                    "yul_path": yul_path,
+                    "bytecode_path": bytecode_path,
+                    "num_instance": num_instance,
+                }));
+            } else {
+                tracing::info!(yul_path = %yul_path, "evm verifier generated");
+            }
+        }
+        #[cfg(feature = "evm")]
+        Commands::VerifyEvm {
+            params_path,
+            k,
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+            vk_path,
+            serde_format,
+            proof_path,
+            instances_path,
+            calldata_path,
+            execute,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            require_files(&[&params_path, &vk_path]);
+            let params =
+                read_params::<Bn256>(&params_path).unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let vk = read_verifying_key::<Bn256, RegexCircuit<Fr>>(&vk_path, serde_format)
+                .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            require_files(&[&proof_path, &instances_path]);
+            let proof = std::fs::read(&proof_path).unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let instances = read_instances(&instances_path).unwrap_or_else(|e| {
+                exit_with(ExitCode::Io, format!("failed to read the instances: {}", e))
+            });
+            let calldata = halo2_regex::evm::encode_calldata(&instances, &proof);
+            std::fs::write(&calldata_path, format!("0x{}", hex_string(&calldata)))
+                .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let result = execute.then(|| {
+                let bytecode = halo2_regex::evm::gen_evm_verifier(&params, &vk, instances.len());
+                halo2_regex::evm::evm_verify(bytecode, calldata)
+            });
+            if json {
+                print_json(serde_json::json!({
+                    "calldata_path": calldata_path,
+                    "valid": result.as_ref().map(|result| result.is_ok()),
+                    "gas_used": result.as_ref().and_then(|result| result.as_ref().ok()),
+                }));
+            } else {
+                println!("calldata written to {}", calldata_path);
+                match &result {
+                    Some(Ok(gas_used)) => println!("proof is valid, gas used: {}", gas_used),
+                    Some(Err(e)) => println!("proof is invalid: {}", e),
+                    None => {}
+                }
+            }
+            if let Some(Err(_)) = result {
+                std::process::exit(ExitCode::Invalid as i32);
+            }
+        }
+        #[cfg(feature = "evm")]
+        Commands::EstimateEvmGas {
+            params_path,
+            k,
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+            vk_path,
+            serde_format,
+            yul_path,
+            proof_path,
+            instances_path,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            require_files(&[&proof_path, &instances_path]);
+            let proof = std::fs::read(&proof_path).unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let instances = read_instances(&instances_path).unwrap_or_else(|e| {
+                exit_with(ExitCode::Io, format!("failed to read the instances: {}", e))
+            });
+            let yul = match &yul_path {
+                Some(yul_path) => {
+                    require_files(&[yul_path]);
+                    std::fs::read_to_string(yul_path).unwrap_or_else(|e| exit_with(ExitCode::Io, e))
+                }
+                None => {
+                    require_files(&[&params_path, &vk_path]);
+                    let params = read_params::<Bn256>(&params_path)
+                        .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+                    let vk = read_verifying_key::<Bn256, RegexCircuit<Fr>>(&vk_path, serde_format)
+                        .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+                    halo2_regex::evm::gen_evm_verifier_yul(&params, &vk, instances.len())
+                }
+            };
+            let calldata = halo2_regex::evm::encode_calldata(&instances, &proof);
+            let calldata_size = calldata.len();
+            let report = halo2_regex::evm::estimate_evm_gas(&yul, calldata).unwrap_or_else(|e| {
+                if json {
+                    print_json(serde_json::json!({ "valid": false, "error": e }));
+                } else {
+                    println!("proof is invalid: {}", e);
+                }
+                std::process::exit(ExitCode::Invalid as i32)
+            });
+            if json {
+                print_json(serde_json::json!({
+                    "valid": true,
+                    "calldata_size": calldata_size,
+                    "gas": report,
+                }));
+            } else {
+                println!("total gas: {}", report.total);
+                println!("  transaction base: {}", report.base);
+                println!("  calldata ({} bytes): {}", calldata_size, report.calldata);
+                println!("  pairing check: {}", report.pairing);
+                println!("  other execution: {}", report.execution);
+            }
+        }
+        Commands::VkHash {
+            k,
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+            vk_path,
+            serde_format,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+            }
+            require_files(&[&vk_path]);
+            let vk_hash = vk_hash_hex(&vk_path, serde_format);
+            let regex_digests = config_regex_digests()
+                .unwrap_or_else(|e| exit_with(ExitCode::Usage, e))
+                .iter()
+                .map(|digest| format!("{:?}", digest))
+                .collect_vec();
+            if json {
+                print_json(serde_json::json!({
+                    "vk_hash": vk_hash,
+                    "regex_digests": regex_digests,
+                }));
+            } else {
+                println!("vk hash: {}", vk_hash);
+                for (input_idx, digest) in regex_digests.iter().enumerate() {
+                    println!("regex digest of input {}: {}", input_idx, digest);
+                }
+            }
+        }
+        Commands::Bench {
+            params_path,
+            k,
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+            string_to_verify,
+            input_file,
+            max_len,
+            is_success,
+            iterations,
+            num_advice,
+            num_fixed,
+        } => {
+            if iterations == 0 {
+                exit_with(
+                    ExitCode::Usage,
+                    "the number of the iterations must be positive",
+                );
+            }
+            set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(max_len) = max_len {
+                set_config_max_chars_size(max_len);
+            }
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            let characters: Vec<u8> = match input_file {
+                Some(input_file) => {
+                    std::fs::read(input_file).expect("failed to read the input file")
+                }
+                None => string_to_verify
+                    .replace("\\r", "\r")
+                    .replace("\\n", "\n")
+                    .bytes()
+                    .collect(),
+            };
+            let correct_substrs = if is_success {
+                derive_correct_substrs(0, &characters)
+                    .unwrap_or_else(|e| exit_with(ExitCode::Usage, e))
             } else {
-                println!("proof is invalid");
+                vec![]
+            };
+            let circuit = RegexCircuit::<Fr> {
+                characters,
+                correct_substrs,
+                is_success,
+                ..Default::default()
+            };
+            check_inputs_or_exit(&circuit);
+            let instances = circuit.instances();
+            require_files(&[&params_path]);
+            let params =
+                read_params::<Bn256>(&params_path).unwrap_or_else(|e| exit_with(ExitCode::Io, e));
+            let report = match bench(&params, &circuit, &instances, iterations) {
+                Ok(report) => report,
+                Err(e) => exit_with(prove_exit_code(&e), format!("benchmark failed: {}", e)),
+            };
+            if json {
+                print_json(serde_json::to_value(&report).unwrap());
+            } else {
+                println!("k: {}, iterations: {}", report.k, report.iterations);
+                for (name, stats) in [
+                    ("keygen", report.keygen),
+                    ("prove", report.prove),
+                    ("verify", report.verify),
+                ] {
+                    println!(
+                        "{}: {:.3} s (stddev {:.3} s)",
+                        name, stats.mean_secs, stats.stddev_secs
+                    );
+                }
+                println!("proof size: {} bytes", report.proof_size);
+                match report.peak_memory_bytes {
+                    Some(bytes) => println!("peak memory: {} MiB", bytes >> 20),
+                    None => println!("peak memory: unknown"),
+                }
+            }
+            if !report.valid {
+                exit_with(ExitCode::Invalid, "proof is invalid");
+            }
+        }
+        #[cfg(feature = "serve")]
+        Commands::Serve {
+            params_path,
+            k,
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+            pk_path,
+            serde_format,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+            host,
+            port,
+            sanity_check,
+            grpc,
+            watch_interval,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            if let Some(substrs_dir) = substrs_dir {
+                set_substrs_dir(&substrs_dir);
+            }
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            if let Some(k) = k {
+                set_config_k(k);
+                set_config_downsize_params(true);
+            }
+            require_files(&[&params_path, &pk_path]);
+            let mut service =
+                ProvingService::load(&params_path, &pk_path, serde_format, sanity_check)
+                    .unwrap_or_else(|e| {
+                        exit_with(ExitCode::Io, format!("failed to load the keys: {}", e))
+                    });
+            if let Some(watch_interval) = watch_interval {
+                service.watch(std::time::Duration::from_secs(watch_interval));
+            }
+            let addr = format!("{}:{}", host, port);
+            tracing::info!(addr = %addr, "listening");
+            let result = if grpc {
+                serve_grpc(service, &addr)
+            } else {
+                service.serve(&addr)
+            };
+            if let Err(e) = result {
+                exit_with(ExitCode::Io, e);
+            }
+        }
+        Commands::Mock {
+            decomposed_regex_path,
+            string_to_verify,
+            input_file,
+            is_success,
+            max_len,
+            k,
+            build_dir,
+            num_advice,
+            num_fixed,
+        } => {
+            let (regex_decomposed, allstr_file_path, substr_file_pathes) =
+                gen_decomposed_regex_files(&decomposed_regex_path, &build_dir, "mock");
+            set_config_params(allstr_file_path, substr_file_pathes[0].clone());
+            set_config_substr_file_paths(substr_file_pathes);
+            set_config_max_chars_size(max_len.unwrap_or(regex_decomposed.max_byte_size));
+            set_config_num_columns(num_advice, num_fixed);
+            let characters: Vec<u8> = match input_file {
+                Some(input_file) => {
+                    require_files(&[&input_file]);
+                    std::fs::read(input_file).expect("failed to read the input file")
+                }
+                None => string_to_verify
+                    .replace("\\r", "\r")
+                    .replace("\\n", "\n")
+                    .bytes()
+                    .collect(),
+            };
+            let correct_substrs = if is_success {
+                derive_correct_substrs(0, &characters)
+                    .unwrap_or_else(|e| exit_with(ExitCode::Invalid, e))
+            } else {
+                vec![]
+            };
+            let circuit = RegexCircuit::<Fr> {
+                characters,
+                correct_substrs,
+                is_success,
+                ..Default::default()
+            };
+            check_inputs_or_exit(&circuit);
+            let report = mock_run(k as u32, &circuit).expect("synthesis failed");
+            print_dry_run_report(report, json);
+        }
+        Commands::Estimate {
+            decomposed_regex_path,
+            max_len,
+            k,
+            build_dir,
+        } => {
+            let regex_decomposed: DecomposedRegexConfig =
+                serde_json::from_reader(File::open(decomposed_regex_path).unwrap()).unwrap();
+            let num_public_part = regex_decomposed
+                .parts
+                .iter()
+                .filter(|part| part.is_public)
+                .count();
+            let allstr_file_path = PathBuf::new().join(&build_dir).join("estimate_allstr.txt");
+            let substr_file_pathes = (0..num_public_part)
+                .map(|idx| {
+                    PathBuf::new()
+                        .join(&build_dir)
+                        .join(&format!("estimate_substr{}.txt", idx))
+                })
+                .collect_vec();
+            regex_decomposed
+                .gen_regex_files(&allstr_file_path, &substr_file_pathes)
+                .unwrap();
+            // `RegexCircuit` verifies the first public part of the regex.
+            set_config_params(
+                allstr_file_path.to_str().unwrap().to_string(),
+                substr_file_pathes[0].to_str().unwrap().to_string(),
+            );
+            set_config_max_chars_size(max_len);
+            let estimate = estimate_cost(k).expect("cost estimation failed");
+            let memory = estimate_memory(k, estimate.plan.num_advice, estimate.plan.num_fixed)
+                .expect("memory estimation failed");
+            if json {
+                print_json(serde_json::json!({ "cost": estimate, "memory": memory }));
+                return;
+            }
+            println!("k: {}", estimate.plan.k);
+            println!(
+                "columns: {} advice, {} fixed",
+                estimate.plan.num_advice, estimate.plan.num_fixed
+            );
+            println!(
+                "rows used: {} of {}",
+                estimate.advice_rows, estimate.plan.usable_rows
+            );
+            println!("table rows: {}", estimate.plan.lookup_rows);
+            println!("advice cells: {}", estimate.plan.advice_cells);
+            println!("lookups: {}", estimate.num_lookups);
+            println!("proof size: {} bytes", estimate.proof_size);
+            println!(
+                "guessed proving time: {:.1} s (not measured; run `bench` to measure it)",
+                estimate.estimated_proving_secs
+            );
+            println!("estimated keygen memory: {} bytes", memory.keygen_bytes);
+            println!("estimated proving memory: {} bytes", memory.proving_bytes);
+        }
+        Commands::PlanColumns {
+            k,
+            allstr_file_path,
+            substr_file_path,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+        } => {
+            set_config_params(allstr_file_path, substr_file_path);
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            let plan = plan_columns(k).expect("column planning failed");
+            println!("{}", serde_json::to_string_pretty(&plan).unwrap());
+        }
+        Commands::CircuitMetrics {
+            k,
+            allstr_file_path,
+            substr_file_path,
+            transfer_encoding,
+            folding_mode,
+            padding_byte,
+            full_transparency,
+            num_advice,
+            num_fixed,
+        } => {
+            set_config_k(k);
+            set_config_params(allstr_file_path, substr_file_path);
+            set_config_transfer_encoding(transfer_encoding);
+            set_config_folding_mode(folding_mode);
+            set_config_padding_byte(padding_byte);
+            set_config_full_transparency(full_transparency);
+            set_config_num_columns(num_advice, num_fixed);
+            let metrics = circuit_metrics().expect("invalid configuration");
+            println!("{}", serde_json::to_string_pretty(&metrics).unwrap());
+        }
+        Commands::Inspect {
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+        } => {
+            let substr_file_pathes = match substrs_dir {
+                Some(substrs_dir) => {
+                    let substr_file_pathes = substr_file_paths_in_dir(&substrs_dir);
+                    if substr_file_pathes.is_empty() {
+                        exit_with(ExitCode::Io, format!("{} has no substr0.txt", substrs_dir));
+                    }
+                    substr_file_pathes
+                }
+                None => vec![substr_file_path],
+            };
+            require_files(
+                &std::iter::once(&allstr_file_path)
+                    .chain(substr_file_pathes.iter())
+                    .map(|path| path.as_str())
+                    .collect_vec(),
+            );
+            let mut input =
+                RegexInputParams::new(allstr_file_path, substr_file_pathes[0].clone(), 0);
+            input.extra_substr_file_paths = substr_file_pathes[1..].to_vec();
+            let inspection = inspect_regex_defs(&input);
+            if json {
+                print_json(serde_json::to_value(&inspection).unwrap());
+            } else {
+                print_inspection(&inspection);
+            }
+        }
+        Commands::Audit {
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+        } => {
+            let substr_file_pathes = match substrs_dir {
+                Some(substrs_dir) => {
+                    let substr_file_pathes = substr_file_paths_in_dir(&substrs_dir);
+                    if substr_file_pathes.is_empty() {
+                        exit_with(ExitCode::Io, format!("{} has no substr0.txt", substrs_dir));
+                    }
+                    substr_file_pathes
+                }
+                None => vec![substr_file_path],
+            };
+            require_files(
+                &std::iter::once(&allstr_file_path)
+                    .chain(substr_file_pathes.iter())
+                    .map(|path| path.as_str())
+                    .collect_vec(),
+            );
+            let mut input =
+                RegexInputParams::new(allstr_file_path, substr_file_pathes[0].clone(), 0);
+            input.extra_substr_file_paths = substr_file_pathes[1..].to_vec();
+            let audit = audit_regex_defs(&input);
+            if json {
+                print_json(serde_json::to_value(&audit).unwrap());
+            } else {
+                print_audit(&audit);
+            }
+            if !audit.is_passed() {
+                std::process::exit(ExitCode::Invalid as i32);
+            }
+        }
+        Commands::GenRegexCommitment {
+            allstr_file_path,
+            substr_file_path,
+            substrs_dir,
+            decomposed_regex_path,
+            build_dir,
+        } => {
+            let (allstr_file_path, substr_file_pathes) = match decomposed_regex_path {
+                Some(decomposed_regex_path) => {
+                    let (_, allstr_file_path, substr_file_pathes) = gen_decomposed_regex_files(
+                        &decomposed_regex_path,
+                        &build_dir,
+                        "commitment",
+                    );
+                    (allstr_file_path, substr_file_pathes)
+                }
+                None => match substrs_dir {
+                    Some(substrs_dir) => {
+                        let substr_file_pathes = substr_file_paths_in_dir(&substrs_dir);
+                        if substr_file_pathes.is_empty() {
+                            exit_with(ExitCode::Io, format!("{} has no substr0.txt", substrs_dir));
+                        }
+                        (allstr_file_path, substr_file_pathes)
+                    }
+                    None => (allstr_file_path, vec![substr_file_path]),
+                },
+            };
+            // The commitment does not depend on the maximum length of the string.
+            let mut input =
+                RegexInputParams::new(allstr_file_path, substr_file_pathes[0].clone(), 0);
+            input.extra_substr_file_paths = substr_file_pathes[1..].to_vec();
+            let commitment = RegexCommitmentJson::new(&input);
+            if json {
+                print_json(serde_json::json!({
+                    "commitment": commitment.hex,
+                    "field_element": commitment.field_element,
+                }));
+            } else {
+                println!("hex: {}", commitment.hex);
+                println!("field element: {}", commitment.field_element);
+            }
+        }
+        Commands::GenRegexMerkleTree {
+            allstr_file_path,
+            substr_file_path,
+            output_path,
+        } => {
+            gen_regex_merkle_tree(&allstr_file_path, &substr_file_path, &output_path).unwrap();
+            if json {
+                print_json(serde_json::json!({ "output_path": output_path }));
             }
         }
         Commands::GenHalo2Texts {
@@ -203,12 +2823,21 @@ fn main() {
                         .join(&format!("substr{}.txt", idx))
                 })
                 .collect_vec();
+            // The directory is created even for a match-only regex without substr files.
+            std::fs::create_dir_all(&substrs_dir_path)
+                .unwrap_or_else(|e| exit_with(ExitCode::Io, e));
             regex_decomposed
                 .gen_regex_files(
                     &Path::new(&allstr_file_path).to_path_buf(),
                     &substr_file_pathes,
                 )
                 .unwrap();
+            if json {
+                print_json(serde_json::json!({
+                    "allstr_file_path": allstr_file_path,
+                    "substr_file_paths": substr_file_pathes,
+                }));
+            }
         }
         Commands::GenCircom {
             decomposed_regex_path,
@@ -221,6 +2850,101 @@ fn main() {
             regex_decomposed
                 .gen_circom(&circom_path, &template_name)
                 .unwrap();
+            if json {
+                print_json(serde_json::json!({ "circom_file_path": circom_path }));
+            }
         }
+        Commands::GenInput {
+            decomposed_regex_path,
+            string_to_verify,
+            input_file,
+            msg_bytes,
+            build_dir,
+            input_json_path,
+            outputs_json_path,
+        } => {
+            let (regex_decomposed, allstr_file_path, substr_file_pathes) =
+                gen_decomposed_regex_files(&decomposed_regex_path, &build_dir, "circom");
+            set_config_params(allstr_file_path, substr_file_pathes[0].clone());
+            set_config_substr_file_paths(substr_file_pathes);
+            let characters: Vec<u8> = match input_file {
+                Some(input_file) => {
+                    require_files(&[&input_file]);
+                    std::fs::read(input_file).expect("failed to read the input file")
+                }
+                None => string_to_verify
+                    .replace("\\r", "\r")
+                    .replace("\\n", "\n")
+                    .bytes()
+                    .collect(),
+            };
+            let msg_bytes = msg_bytes.unwrap_or(regex_decomposed.max_byte_size);
+            if characters.len() > msg_bytes {
+                exit_with(
+                    ExitCode::Usage,
+                    format!(
+                        "the message of {} bytes is longer than msg_bytes {}",
+                        characters.len(),
+                        msg_bytes
+                    ),
+                );
+            }
+            let signals = circom_signals(&characters, msg_bytes)
+                .unwrap_or_else(|e| exit_with(ExitCode::Invalid, e));
+            for (path, value) in [
+                (&input_json_path, signals.input_json()),
+                (&outputs_json_path, signals.outputs_json()),
+            ] {
+                if let Err(e) = std::fs::write(path, value.to_string()) {
+                    exit_with(ExitCode::Io, format!("failed to write {}: {}", path, e));
+                }
+            }
+            if json {
+                print_json(serde_json::json!({
+                    "input_json_path": input_json_path,
+                    "outputs_json_path": outputs_json_path,
+                }));
+            }
+        }
+        Commands::Preset { command } => match command {
+            PresetCommands::List => {
+                if json {
+                    print_json(serde_json::json!(PRESETS
+                        .iter()
+                        .map(|preset| serde_json::json!({
+                            "name": preset.name,
+                            "description": preset.description,
+                            "example": preset.example,
+                        }))
+                        .collect_vec()));
+                } else {
+                    for preset in PRESETS.iter() {
+                        println!("{}: {}", preset.name, preset.description);
+                    }
+                }
+            }
+            PresetCommands::Emit { name, out_dir } => {
+                let preset = find_preset(&name).unwrap_or_else(|| {
+                    exit_with(
+                        ExitCode::Usage,
+                        format!("no preset is named {}, see `preset list`", name),
+                    )
+                });
+                let files = preset.emit(&out_dir).unwrap_or_else(|e| {
+                    exit_with(ExitCode::Io, format!("failed to write the preset: {}", e))
+                });
+                if json {
+                    print_json(serde_json::json!({
+                        "files": files,
+                        "example": preset.example,
+                    }));
+                } else {
+                    println!("decomposed regex: {}", files.decomposed_regex_path);
+                    println!("allstr regex definition: {}", files.allstr_file_path);
+                    println!("substr regex definitions: {}", out_dir);
+                    println!("example input: {:?}", preset.example);
+                }
+            }
+        },
     }
 }
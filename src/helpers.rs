@@ -1,332 +1,4601 @@
-use crate::vrm::DecomposedRegexConfig;
-use halo2_base::halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_base::halo2_proofs::arithmetic::best_multiexp;
+use halo2_base::halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
 use halo2_base::halo2_proofs::dev::{CircuitCost, FailureLocation, MockProver, VerifyFailure};
-use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine, G2Affine, G1};
+use halo2_base::halo2_proofs::halo2curves::group::{ff::Field, prime::PrimeCurveAffine, Curve};
+use halo2_base::halo2_proofs::halo2curves::pairing::Engine;
 use halo2_base::halo2_proofs::halo2curves::FieldExt;
 use halo2_base::halo2_proofs::plonk::{
-    create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ConstraintSystem, Error, ProvingKey,
-    VerifyingKey,
+    keygen_pk, keygen_vk, Advice, Any, Assigned, Assignment, Challenge, Circuit, Column,
+    ConstraintSystem, Error, Fixed, FloorPlanner, Instance, ProvingKey, Selector, VerifyingKey,
 };
-use halo2_base::halo2_proofs::poly::commitment::{Params, ParamsProver};
-use halo2_base::halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
-use halo2_base::halo2_proofs::poly::kzg::multiopen::{ProverGWC, VerifierGWC, VerifierSHPLONK};
+use halo2_base::halo2_proofs::poly::commitment::{Blind, Params};
+use halo2_base::halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_base::halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
 use halo2_base::halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
-use halo2_base::halo2_proofs::poly::kzg::strategy::SingleStrategy;
-use halo2_base::halo2_proofs::poly::VerificationStrategy;
-use halo2_base::halo2_proofs::transcript::{
-    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
-};
+use halo2_base::halo2_proofs::poly::{EvaluationDomain, VerificationStrategy};
 use halo2_base::halo2_proofs::SerdeFormat;
 use halo2_base::{
     gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions, RangeInstructions},
     utils::{bigint_to_fe, biguint_to_fe, fe_to_biguint, modulus, PrimeField},
-    AssignedValue, Context, ContextParams, QuantumCell, SKIP_FIRST_PASS,
+    AssignedValue, Context, QuantumCell, SKIP_FIRST_PASS,
 };
 
 use itertools::Itertools;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use lazy_static::lazy_static;
-use std::arch::x86_64::_CMP_TRUE_UQ;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
 use std::env::set_var;
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::io::{BufRead, BufWriter, Read, Write};
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
 
 use crate::defs::*;
-use crate::RegexVerifyConfig;
+use crate::engine::ProvingEngine;
+use crate::gadgets::base64::{decode_base64, Base64DecodeConfig};
+use crate::gadgets::date::{date_to_timestamp, DateConfig, DateFormat};
+use crate::gadgets::folding::{normalize_folding, FoldingConfig, FoldingMode};
+use crate::gadgets::hex::{address_limbs, HexAddressConfig};
+use crate::gadgets::input::assert_regex_input;
+use crate::gadgets::poseidon::{poseidon_hash, PoseidonConfig};
+use crate::gadgets::qp::{decode_quoted_printable, QuotedPrintableConfig};
+use crate::gadgets::reveal::{reveal_instances, salted_commitment, RevealConfig};
+use crate::gadgets::substr::SubstrCompactConfig;
+use crate::merkle::RegexMerkleTree;
+#[cfg(feature = "prover")]
+use crate::ptau::{read_ptau, write_ptau, PtauError};
+use crate::table::{
+    check_universal_bounds, endpoint_rows, regex_defs_digest, transition_rows,
+    universal_table_digest, RegexTableCache, RegexTableKey, UniversalBoundsError,
+    UniversalTableBounds,
+};
+use crate::{new_region_context, AssignedRegexResult, RegexVerifyConfig, RegexWitness};
 
 const MAX_STRING_LEN: usize = 1024;
 
+/// Content transfer encoding of the input string of [`RegexCircuit`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum TransferEncoding {
+    /// The input string is matched as it is.
+    Identity,
+    /// The input string is decoded from quoted-printable in the circuit before it is matched.
+    QuotedPrintable,
+    /// The input string is decoded from base64 in the circuit before it is matched.
+    /// Its maximum length must be a multiple of four, and the [`poseidon_hash`] of its encoded characters is an instance of [`RegexCircuit`].
+    Base64,
+}
+
+/// Encoding of an input string passed on the command line or in a batch file, which lets binary or whitespace-heavy inputs be passed losslessly.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum InputEncoding {
+    /// The bytes of the string as it is.
+    #[default]
+    Utf8,
+    /// The hex of the bytes.
+    Hex,
+    /// The base64 of the bytes padded with `=`.
+    Base64,
+}
+
+/// Decode `input` encoded by `encoding` into the bytes to verify.
+///
+/// The whitespaces in the hex and the base64 are ignored.
+///
+/// # Return values
+/// Return the decoded bytes, or `None` if `input` is not valid in `encoding`.
+pub fn decode_input(input: &str, encoding: InputEncoding) -> Option<Vec<u8>> {
+    let compact = || {
+        input
+            .bytes()
+            .filter(|char| !char.is_ascii_whitespace())
+            .collect::<Vec<u8>>()
+    };
+    match encoding {
+        InputEncoding::Utf8 => Some(input.as_bytes().to_vec()),
+        InputEncoding::Hex => {
+            let hex = compact();
+            if hex.len() % 2 != 0 {
+                return None;
+            }
+            hex.chunks(2)
+                .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+                .collect()
+        }
+        InputEncoding::Base64 => decode_base64(&compact()),
+    }
+}
+
+/// Serialization format of the proving and verifying keys.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum KeySerdeFormat {
+    /// Curve points are compressed, and validated when they are read.
+    Processed,
+    /// Curve points are not compressed, and validated when they are read.
+    Raw,
+    /// Curve points are not compressed, and not validated when they are read.
+    RawUnchecked,
+}
+
+impl From<KeySerdeFormat> for SerdeFormat {
+    fn from(format: KeySerdeFormat) -> Self {
+        match format {
+            KeySerdeFormat::Processed => SerdeFormat::Processed,
+            KeySerdeFormat::Raw => SerdeFormat::RawBytes,
+            KeySerdeFormat::RawUnchecked => SerdeFormat::RawBytesUnchecked,
+        }
+    }
+}
+
+/// Parameters of one input string of [`RegexCircuit`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RegexInputParams {
+    pub allstr_file_path: String,
+    /// The file path of the first substr regex definition, or an empty string for a match-only input string from which no substring is extracted.
+    pub substr_file_path: String,
+    /// The file paths of the substr regex definitions following that of `substr_file_path`, whose substring ids are 2, 3, ...
+    #[serde(default)]
+    pub extra_substr_file_paths: Vec<String>,
+    /// The maximum length of the input string.
+    pub max_chars_size: usize,
+    /// The content transfer encoding of the input string.
+    pub transfer_encoding: TransferEncoding,
+    /// The maximum number of the characters removed by the quoted-printable decoding of the input string, which is at most `max_chars_size`.
+    #[serde(default = "default_max_qp_removed_size")]
+    pub max_qp_removed_size: usize,
+    /// How folding sequences of the input string are normalized, or `None` to keep them.
+    pub folding_mode: Option<FoldingMode>,
+    /// The maximum number of the characters removed by the folding normalization of the input string, which is at most `max_chars_size`.
+    #[serde(default = "default_max_folding_removed_size")]
+    pub max_folding_removed_size: usize,
+    /// The byte of the characters beyond the input string up to `max_chars_size`, which is constrained by [`RegexVerifyConfig`].
+    #[serde(default)]
+    pub padding_byte: u8,
+}
+
+impl RegexInputParams {
+    /// Construct a new [`RegexInputParams`] for an input string matched as it is.
+    pub fn new(allstr_file_path: String, substr_file_path: String, max_chars_size: usize) -> Self {
+        Self {
+            allstr_file_path,
+            substr_file_path,
+            extra_substr_file_paths: vec![],
+            max_chars_size,
+            transfer_encoding: TransferEncoding::Identity,
+            max_qp_removed_size: default_max_qp_removed_size(),
+            folding_mode: None,
+            max_folding_removed_size: default_max_folding_removed_size(),
+            padding_byte: 0,
+        }
+    }
+}
+
+/// A constraint that a substring of one input string of [`RegexCircuit`] is equal to a substring of another one.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct SubstrEquality {
+    /// The index of the input string and the substring id of the first substring.
+    pub lhs: (usize, usize),
+    /// The index of the input string and the substring id of the second substring.
+    pub rhs: (usize, usize),
+    /// The maximum length of both substrings.
+    pub max_length: usize,
+}
+
+/// A substring of an input string of [`RegexCircuit`] exposed as instances, either as its characters, as its Poseidon commitment, as the limbs of its address, as the bytes expected by the verifier, or as its Unix timestamp.
+///
+/// At most one of `commit_only`, `as_address`, `expected` and `timestamp_format` is set, which [`RegexVerifyConfigParams::validate`] checks.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct PublicPart {
+    /// The index of the input string.
+    pub input_idx: usize,
+    /// The substring id of the substring.
+    pub substr_id: usize,
+    /// The maximum length of the substring.
+    pub max_length: usize,
+    /// Whether only the salted commitment of the substring is exposed, instead of the output selected by [`RegexCircuit::revealed_parts`].
+    #[serde(default)]
+    pub commit_only: bool,
+    /// Whether the substring is parsed as a hex address by [`HexAddressConfig::parse_address`] and its limbs `[hi, lo]` are exposed instead of its characters.
+    #[serde(default)]
+    pub as_address: bool,
+    /// Whether the substring is constrained to be equal to the bytes supplied by the verifier as the instances, padded with zeros to `max_length`, instead of being revealed by its prover.
+    #[serde(default)]
+    pub expected: bool,
+    /// The format of the date substring, which is converted by [`DateConfig::parse_timestamp`] and whose Unix timestamp is exposed instead of its characters, if any.
+    /// The verifier compares the timestamp with its own bounds, e.g., that an email is sent after a deadline.
+    #[serde(default)]
+    pub timestamp_format: Option<DateFormat>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct RegexVerifyConfigParams {
     /// The degree of the number of rows, i.e., 2^(`degree`) rows are set.
     pub k: usize,
-    pub allstr_file_path: String,
-    pub substr_file_path: String,
+    /// The parameters of each input string, e.g., an email header and its body.
+    /// The first one is set by [`set_config_params`] and the others are added by [`add_config_input`].
+    pub inputs: Vec<RegexInputParams>,
+    /// The equality constraints between substrings of the input strings.
+    pub substr_equalities: Vec<SubstrEquality>,
+    /// The substrings exposed as instances in this order.
+    pub public_parts: Vec<PublicPart>,
+    /// Whether every character matched by the regexes is exposed as an instance following the outputs of the public parts.
+    #[serde(default)]
+    pub full_transparency: bool,
+    /// Whether only the Poseidon hash of all the instances is exposed as the single instance, e.g., to keep few instances in a wrapper proof of [`compress`](crate::compression::compress).
+    #[serde(default)]
+    pub commit_instances: bool,
+    /// The size bounds of the lookup tables in the universal mode, or `None` to fix the tables at the key generation.
+    /// In the universal mode, the digest of the tables of each input string is exposed as an instance instead of its [`regex_commitment`].
+    #[serde(default)]
+    pub universal_bounds: Option<UniversalTableBounds>,
+    /// The number of the advice columns of [`FlexGateConfig`].
+    #[serde(default = "default_num_advice")]
+    pub num_advice: usize,
+    /// The number of the fixed columns of [`FlexGateConfig`].
+    #[serde(default = "default_num_fixed")]
+    pub num_fixed: usize,
+    /// Whether the setup parameters larger than 2^`k` rows are downsized to `k` when they are read by [`read_params`], instead of setting `k` to theirs.
+    #[serde(default)]
+    pub downsize_params: bool,
+    /// The maximum substring id, i.e., the maximum number of the substr regex definitions of each input string.
+    #[serde(default = "default_max_substr_id")]
+    pub max_substr_id: usize,
+}
+
+fn default_num_advice() -> usize {
+    25
+}
+
+fn default_num_fixed() -> usize {
+    1
+}
+
+fn default_max_substr_id() -> usize {
+    DEFAULT_MAX_SUBSTR_ID
+}
+
+fn default_max_qp_removed_size() -> usize {
+    96
+}
+
+fn default_max_folding_removed_size() -> usize {
+    64
+}
+
+/// Error definitions related to the configuration of [`RegexCircuit`].
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("At least one advice column is required")]
+    NoAdviceColumn,
+    #[error("At least one fixed column is required")]
+    NoFixedColumn,
+    #[error(
+        "The input string {input_idx} of at most {max_chars_size} bytes does not fit in 2^{k} rows"
+    )]
+    TooLongInput {
+        input_idx: usize,
+        max_chars_size: usize,
+        k: usize,
+    },
+    #[error("2^{k} rows are fewer than {required_rows} rows required by the input strings and the lookup tables")]
+    TooFewRows { k: usize, required_rows: usize },
+    #[error("The regex needs k >= {required_k} for {required_rows} rows of the input strings and the lookup tables, but k = {k} is configured")]
+    TooSmallK {
+        k: usize,
+        required_k: usize,
+        required_rows: usize,
+    },
+    #[error("The substr regex definitions {first} and {second} of the input string {input_idx} tag the same transitions {transitions:?}, whose substring id is ambiguous")]
+    OverlappingSubstrs {
+        input_idx: usize,
+        first: usize,
+        second: usize,
+        transitions: Vec<(u64, u64)>,
+    },
+    #[error("The input string {input_idx} has {num_substrs} substr regex definitions, which exceed the maximum substring id {max_substr_id}")]
+    TooManySubstrs {
+        input_idx: usize,
+        num_substrs: usize,
+        max_substr_id: usize,
+    },
+    #[error("The regex definition file {path} of the input string {input_idx} does not exist")]
+    MissingRegexFile { input_idx: usize, path: String },
+    #[error("The public part {part_idx} refers to the substring {substr_id} of the input string {input_idx}, which does not exist")]
+    UnknownPublicPart {
+        part_idx: usize,
+        input_idx: usize,
+        substr_id: usize,
+    },
+    #[error("The public part {part_idx} sets more than one of `commit_only`, `as_address`, `expected` and `timestamp_format`")]
+    ConflictingPublicPart { part_idx: usize },
+    #[error("The regex definitions of the input string {input_idx} are malformed: {error}")]
+    InvalidRegexFile {
+        input_idx: usize,
+        error: RegexDefError,
+    },
+    #[error("The regex definitions of the input string {input_idx} do not fit in the universal bounds: {error}")]
+    UniversalBounds {
+        input_idx: usize,
+        error: UniversalBoundsError,
+    },
+    #[error("The maximum length {max_chars_size} of the base64-encoded input string {input_idx} is not a multiple of four")]
+    UnalignedBase64Size {
+        input_idx: usize,
+        max_chars_size: usize,
+    },
+    #[error("The quoted-printable input string {input_idx} of at most {max_chars_size} bytes cannot have {max_qp_removed_size} characters removed")]
+    TooManyQpRemovedChars {
+        input_idx: usize,
+        max_chars_size: usize,
+        max_qp_removed_size: usize,
+    },
+    #[error("The input string {input_idx} of at most {max_chars_size} bytes cannot have {max_folding_removed_size} characters removed by the folding normalization")]
+    TooManyFoldingRemovedChars {
+        input_idx: usize,
+        max_chars_size: usize,
+        max_folding_removed_size: usize,
+    },
+    #[error("The circuit without witnesses cannot be synthesized: {0:?}")]
+    Synthesis(Error),
+}
+
+impl RegexVerifyConfigParams {
+    /// Check that the columns and the rows are enough for the configured input strings, that their regex definition files exist and are well-formed, that the maximum lengths suit the transfer encodings, that each public part refers to an existing substring and sets at most one of its modes, that the substring ids of each input string are at most `max_substr_id`, that no two substr regex definitions of an input string tag the same transition, and that the regex definitions fit in the universal bounds if any.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.num_advice == 0 {
+            return Err(ConfigError::NoAdviceColumn);
+        }
+        if self.num_fixed == 0 {
+            return Err(ConfigError::NoFixedColumn);
+        }
+        let mut num_substrs_of_inputs = vec![];
+        for (input_idx, input) in self.inputs.iter().enumerate() {
+            // The characters and their states occupy `max_chars_size + 1` rows of the columns of `RegexVerifyConfig`.
+            if input.max_chars_size + 1 >= 1 << self.k {
+                return Err(ConfigError::TooLongInput {
+                    input_idx,
+                    max_chars_size: input.max_chars_size,
+                    k: self.k,
+                });
+            }
+            if input.transfer_encoding == TransferEncoding::Base64 && input.max_chars_size % 4 != 0
+            {
+                return Err(ConfigError::UnalignedBase64Size {
+                    input_idx,
+                    max_chars_size: input.max_chars_size,
+                });
+            }
+            if input.transfer_encoding == TransferEncoding::QuotedPrintable
+                && input.max_qp_removed_size > input.max_chars_size
+            {
+                return Err(ConfigError::TooManyQpRemovedChars {
+                    input_idx,
+                    max_chars_size: input.max_chars_size,
+                    max_qp_removed_size: input.max_qp_removed_size,
+                });
+            }
+            if input.folding_mode.is_some() && input.max_folding_removed_size > input.max_chars_size
+            {
+                return Err(ConfigError::TooManyFoldingRemovedChars {
+                    input_idx,
+                    max_chars_size: input.max_chars_size,
+                    max_folding_removed_size: input.max_folding_removed_size,
+                });
+            }
+            // The substr regex definition files are optional for a match-only input string.
+            let substr_file_paths = std::iter::once(&input.substr_file_path)
+                .chain(input.extra_substr_file_paths.iter())
+                .filter(|path| !path.is_empty());
+            if let Some(path) = std::iter::once(&input.allstr_file_path)
+                .chain(substr_file_paths)
+                .find(|path| !Path::new(path).is_file() && !lock(&regexTexts).contains_key(*path))
+            {
+                return Err(ConfigError::MissingRegexFile {
+                    input_idx,
+                    path: path.to_string(),
+                });
+            }
+            let regex_defs = try_read_regex_defs(input)
+                .map_err(|error| ConfigError::InvalidRegexFile { input_idx, error })?;
+            // The substring ids of an input string are assigned from 1 over all its regex definitions.
+            let num_substrs = regex_defs
+                .iter()
+                .map(|defs| defs.substrs.len())
+                .sum::<usize>();
+            if num_substrs > self.max_substr_id {
+                return Err(ConfigError::TooManySubstrs {
+                    input_idx,
+                    num_substrs,
+                    max_substr_id: self.max_substr_id,
+                });
+            }
+            for defs in regex_defs.iter() {
+                if let Some(bounds) = &self.universal_bounds {
+                    check_universal_bounds(defs, bounds)
+                        .map_err(|error| ConfigError::UniversalBounds { input_idx, error })?;
+                }
+                if let Some(overlap) = defs.substr_overlaps().into_iter().next() {
+                    return Err(ConfigError::OverlappingSubstrs {
+                        input_idx,
+                        first: overlap.first,
+                        second: overlap.second,
+                        transitions: overlap.transitions,
+                    });
+                }
+            }
+            num_substrs_of_inputs.push(num_substrs);
+        }
+        for (part_idx, part) in self.public_parts.iter().enumerate() {
+            let num_substrs = num_substrs_of_inputs
+                .get(part.input_idx)
+                .copied()
+                .unwrap_or(0);
+            if part.substr_id == 0 || part.substr_id > num_substrs {
+                return Err(ConfigError::UnknownPublicPart {
+                    part_idx,
+                    input_idx: part.input_idx,
+                    substr_id: part.substr_id,
+                });
+            }
+            if [
+                part.commit_only,
+                part.as_address,
+                part.expected,
+                part.timestamp_format.is_some(),
+            ]
+            .iter()
+            .filter(|mode| **mode)
+            .count()
+                > 1
+            {
+                return Err(ConfigError::ConflictingPublicPart { part_idx });
+            }
+        }
+        Ok(())
+    }
 }
 
 lazy_static! {
     static ref regexConfigParams: Mutex<RegexVerifyConfigParams> =
         Mutex::new(RegexVerifyConfigParams {
             k: 17,
-            allstr_file_path: "".to_string(),
-            substr_file_path: "".to_string(),
+            inputs: vec![RegexInputParams::new(
+                "".to_string(),
+                "".to_string(),
+                MAX_STRING_LEN
+            )],
+            substr_equalities: vec![],
+            public_parts: vec![],
+            full_transparency: false,
+            commit_instances: false,
+            universal_bounds: None,
+            num_advice: default_num_advice(),
+            num_fixed: default_num_fixed(),
+            downsize_params: false,
+            max_substr_id: DEFAULT_MAX_SUBSTR_ID,
         });
+    static ref regexTexts: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
 }
 
-pub fn set_config_params(allstr: String, substr: String) {
-    let mut params = regexConfigParams.lock().unwrap();
-    params.allstr_file_path = allstr;
-    params.substr_file_path = substr;
+/// Lock `mutex` of a global state even if a thread panicked while holding it, since every global state is left consistent between its updates.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
-pub fn set_config_k(_k: usize) {
-    regexConfigParams.lock().unwrap().k = _k;
+/// Statistics of the cells assigned by the last synthesis of [`RegexCircuit`], which are read by [`RegexCircuit::synthesis_stats`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct SynthesisStats {
+    /// The number of the advice cells assigned by [`FlexGateConfig`].
+    pub advice_cells: usize,
+    /// The number of the fixed cells assigned by [`FlexGateConfig`].
+    pub fixed_cells: usize,
+    /// The number of the advice cells assigned by [`FlexGateConfig`] for each regex definition of each input string.
+    pub def_advice_cells: Vec<Vec<usize>>,
+    /// The time taken by the synthesis in seconds, including the witness generation.
+    pub synthesis_secs: f64,
 }
 
-#[derive(Default, Clone, Debug)]
-pub struct RegexCircuit<F: PrimeField> {
-    pub characters: Vec<u8>,
-    pub correct_substrs: Vec<(usize, String)>,
-    pub is_success: bool,
-    pub _marker: PhantomData<F>,
+/// The time taken by a phase of [`gen_keys`] or [`prove`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct PhaseTiming {
+    /// The name of the phase.
+    pub name: String,
+    /// The time taken by the phase in seconds.
+    pub secs: f64,
 }
 
-impl<F: PrimeField> RegexCircuit<F> {
-    const NUM_ADVICE: usize = 25;
-    const NUM_FIXED: usize = 1;
+/// The time taken by each phase of [`gen_keys`], [`prove`] or [`prove_with_keys`], which is returned by them.
+///
+/// The FFTs, the commitments and the opening are not separated since they all run inside `create_proof` of halo2.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    /// The phases in the order of the execution.
+    pub phases: Vec<PhaseTiming>,
 }
 
-impl<F: PrimeField> Circuit<F> for RegexCircuit<F> {
-    type Config = RegexVerifyConfig<F>;
-    type FloorPlanner = SimpleFloorPlanner;
+impl Profile {
+    /// Return the total time of the phases in seconds.
+    pub fn total_secs(&self) -> f64 {
+        self.phases.iter().map(|phase| phase.secs).sum()
+    }
+}
 
-    // Circuit without witnesses, called only during key generation
-    fn without_witnesses(&self) -> Self {
+/// A [`Profile`] being recorded, whose phases are reported to `progress`.
+struct Profiler<'a> {
+    profile: Profile,
+    progress: Option<&'a ProgressCallback>,
+}
+
+impl<'a> Profiler<'a> {
+    fn new(progress: Option<&'a ProgressCallback>) -> Self {
         Self {
-            characters: vec![],
-            correct_substrs: vec![],
-            is_success: false,
-            _marker: PhantomData,
+            profile: Profile::default(),
+            progress,
         }
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let params = regexConfigParams.lock().unwrap();
-        let all_regex_def = AllstrRegexDef::read_from_text(&params.allstr_file_path);
-        let substr_def = SubstrRegexDef::read_from_text(&params.substr_file_path);
-        let gate = FlexGateConfig::<F>::configure(
-            meta,
-            halo2_base::gates::flex_gate::GateStrategy::Vertical,
-            &[Self::NUM_ADVICE],
-            Self::NUM_FIXED,
-            0,
-            params.k,
+    /// Report the start of the phase of `name` and return the current time.
+    fn start(&self, name: &str) -> Instant {
+        report_progress(
+            self.progress,
+            ProgressEvent::PhaseStarted {
+                name: name.to_string(),
+            },
         );
-        let regex_defs = vec![RegexDefs {
-            allstr: all_regex_def,
-            substrs: vec![substr_def],
-        }];
-        let config = RegexVerifyConfig::configure(meta, MAX_STRING_LEN, gate, regex_defs);
-        config
+        Instant::now()
     }
 
-    fn synthesize(
-        &self,
-        config: Self::Config,
-        mut layouter: impl Layouter<F>,
-    ) -> Result<(), Error> {
-        config.load(&mut layouter)?;
-
-        // println!("Synthesize being called...");
-        let mut first_pass = SKIP_FIRST_PASS;
-        let gate = config.gate().clone();
+    /// Record and report the phase of `name` that started at `start`.
+    fn record(&mut self, name: &str, start: Instant) {
+        let phase = PhaseTiming {
+            name: name.to_string(),
+            secs: start.elapsed().as_secs_f64(),
+        };
+        report_progress(self.progress, ProgressEvent::PhaseFinished(phase.clone()));
+        self.profile.phases.push(phase);
+    }
 
-        layouter.assign_region(
-            || "regex",
-            |region| {
-                if first_pass {
-                    first_pass = false;
-                    return Ok(());
-                }
-                let mut aux = Context::new(
-                    region,
-                    ContextParams {
-                        max_rows: gate.max_rows,
-                        num_context_ids: 1,
-                        fixed_columns: gate.constants.clone(),
-                    },
-                );
-                let ctx = &mut aux;
-                let result = config.match_substrs(ctx, &self.characters)?;
-                let mut expected_masked_chars = vec![0; MAX_STRING_LEN];
-                let mut expected_substr_ids = vec![0; MAX_STRING_LEN];
-
-                if self.is_success {
-                    for (substr_idx, (start, chars)) in self.correct_substrs.iter().enumerate() {
-                        for (idx, char) in chars.as_bytes().iter().enumerate() {
-                            expected_masked_chars[start + idx] = *char;
-                            expected_substr_ids[start + idx] = substr_idx + 1;
-                        }
-                    }
-                    for idx in 0..MAX_STRING_LEN {
-                        result.masked_characters[idx]
-                            .value()
-                            .map(|v| assert_eq!(*v, F::from(expected_masked_chars[idx] as u64)));
-                        result.all_substr_ids[idx]
-                            .value()
-                            .map(|v| assert_eq!(*v, F::from(expected_substr_ids[idx] as u64)));
-                    }
-                }
-                Ok(())
-            },
-        )?;
-        Ok(())
+    /// Record and report the phase of `name` that started at `start` and includes one synthesis of each of `circuits`, which are recorded separately as `witness synthesis`.
+    ///
+    /// The synthesis time is read from each [`RegexCircuit`] of `circuits`, so that concurrent phases of other circuits do not affect it.
+    fn record_with_synthesis<F: PrimeField, C: 'static>(
+        &mut self,
+        name: &str,
+        start: Instant,
+        circuits: &[C],
+    ) {
+        let secs = start.elapsed().as_secs_f64();
+        let synthesis_secs = circuits
+            .iter()
+            .filter_map(|circuit| (circuit as &dyn Any).downcast_ref::<RegexCircuit<F>>())
+            .map(|circuit| circuit.synthesis_stats().synthesis_secs)
+            .sum::<f64>();
+        report_progress(
+            self.progress,
+            ProgressEvent::PhaseFinished(PhaseTiming {
+                name: name.to_string(),
+                secs,
+            }),
+        );
+        self.profile.phases.push(PhaseTiming {
+            name: "witness synthesis".to_string(),
+            secs: synthesis_secs,
+        });
+        self.profile.phases.push(PhaseTiming {
+            name: name.to_string(),
+            secs: (secs - synthesis_secs).max(0.0),
+        });
     }
 }
 
-// /// The number of limbs of the accumulator in the aggregation circuit.
-// pub const NUM_ACC_INSTANCES: usize = 4 * LIMBS;
-// /// The name of env variable for the path to the configuration json of the aggregation circuit.
-// pub const VERIFY_CONFIG_KEY: &'static str = "VERIFY_CONFIG";
+/// A progress of [`gen_keys`], [`prove`] or [`prove_with_keys`] reported to the [`ProgressCallback`] given to them.
+///
+/// The phases may be nested, e.g., `witness synthesis` starts and finishes inside `keygen vk`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// The phase of `name` started.
+    PhaseStarted { name: String },
+    /// The phase finished and took the given time.
+    PhaseFinished(PhaseTiming),
+}
 
-/// Generate SRS parameters.
+/// A callback receiving [`ProgressEvent`], which may be called from any thread.
 ///
-/// # Arguments
-/// * `params_path` - a file path of the SRS parameters.
-/// * `k` - the SRS size.
-pub fn gen_params(params_path: &str, k: u32) -> Result<(), Error> {
-    let rng = thread_rng();
-    let params = ParamsKZG::<Bn256>::setup(k, rng);
-    let f = File::create(params_path).unwrap();
-    let mut writer = BufWriter::new(f);
-    params.write(&mut writer).unwrap();
-    writer.flush().unwrap();
-    Ok(())
+/// Like [`CancellationToken`], it is given to [`gen_keys`], [`prove`] or [`prove_with_keys`] for their phases and set to [`RegexCircuit::progress`] for the witness synthesis, so concurrent operations report to their own callbacks.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(&ProgressEvent) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Construct a new [`ProgressCallback`] calling `callback` for each event.
+    pub fn new(callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Construct a new [`ProgressCallback`] that sends every [`ProgressEvent`] to the returned channel, e.g., for showing the progress in a GUI or a server.
+    ///
+    /// The events sent after the receiver is dropped are discarded.
+    pub fn channel() -> (Self, Receiver<ProgressEvent>) {
+        let (sender, receiver) = channel();
+        let sender = Mutex::new(sender);
+        let callback = Self::new(move |event| {
+            let _ = lock(&sender).send(event.clone());
+        });
+        (callback, receiver)
+    }
+}
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
 }
 
-/// Generate proving and verifying keys for the regex verification circuit.
+/// A token to cancel [`gen_keys`], [`prove`] or [`prove_with_keys`] from another thread, e.g., when a proving service abandons a request.
 ///
-/// # Arguments
-/// * `params_path` - a file path of the SRS parameters.
-/// * `pk_path` - a file path of the output proving key.
-/// * `vk_path` - a file path of the output verifying key.
-/// * `circuit` - an regex verification circuit.
-pub fn gen_keys<C: Circuit<Fr>>(
-    params_path: &str,
-    pk_path: &str,
-    vk_path: &str,
-    circuit: C,
-) -> Result<(), Error> {
-    let mut params = {
-        let f = File::open(Path::new(params_path)).unwrap();
-        let mut reader = BufReader::new(f);
-        ParamsKZG::<Bn256>::read(&mut reader).unwrap()
-    };
+/// The functions given the token check it between their phases, and the synthesis of [`RegexCircuit`] checks [`RegexCircuit::cancellation`] between the input strings of the witness generation, so the running phase finishes before the cancellation.
+/// Each circuit holds its own token, so cancelling one of concurrent operations does not affect the others.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Construct a new [`CancellationToken`] that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    set_config_k(params.k() as usize);
+    /// Cancel the operations given this token or its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
 
-    let vk = keygen_vk(&params, &circuit).unwrap();
-    println!("app vk generated");
-    {
-        let f = File::create(vk_path).unwrap();
-        let mut writer = BufWriter::new(f);
-        vk.write(&mut writer, SerdeFormat::RawBytesUnchecked)
-            .unwrap();
-        writer.flush().unwrap();
+    /// Return true iff [`CancellationToken::cancel`] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
     }
+}
 
-    let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+fn is_cancelled(cancellation: Option<&CancellationToken>) -> bool {
+    cancellation.map_or(false, |cancellation| cancellation.is_cancelled())
+}
 
-    println!("app pk generated");
-    {
-        let f = File::create(pk_path).unwrap();
-        let mut writer = BufWriter::new(f);
-        pk.write(&mut writer, SerdeFormat::RawBytesUnchecked)
-            .unwrap();
-        writer.flush().unwrap();
+fn report_progress(progress: Option<&ProgressCallback>, event: ProgressEvent) {
+    if let Some(callback) = progress {
+        (callback.0)(&event);
     }
+}
 
-    Ok(())
+/// The masked characters and substring ids of an input string assigned by the last synthesis of [`RegexCircuit`].
+///
+/// Both are all zeros if the circuit is synthesized without witnesses.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaskedOutput {
+    /// The characters of the input string, which are turned to zero iff they belong to no substring.
+    pub masked_characters: Vec<u8>,
+    /// The substring id of each character.
+    pub substr_ids: Vec<usize>,
+}
+
+impl MaskedOutput {
+    /// Return the substrings extracted from the masked characters in the order of the substring ids, whose invalid UTF-8 sequences are replaced.
+    pub fn substrs(&self) -> Vec<TargetSubstr> {
+        // The start position and the characters of each substring id.
+        let mut substrs = std::collections::BTreeMap::<usize, (usize, Vec<u8>)>::new();
+        for (pos, (char, substr_id)) in self
+            .masked_characters
+            .iter()
+            .zip(self.substr_ids.iter())
+            .enumerate()
+        {
+            if *substr_id != 0 {
+                substrs
+                    .entry(*substr_id)
+                    .or_insert((pos, vec![]))
+                    .1
+                    .push(*char);
+            }
+        }
+        substrs
+            .into_iter()
+            .map(|(substr_id, (pos, chars))| TargetSubstr {
+                pos,
+                string: String::from_utf8_lossy(&chars).to_string(),
+                substr_id,
+            })
+            .collect()
+    }
+}
+
+pub fn set_config_params(allstr: String, substr: String) {
+    let mut params = lock(&regexConfigParams);
+    params.inputs[0].allstr_file_path = allstr;
+    params.inputs[0].substr_file_path = substr;
+    params.inputs[0].extra_substr_file_paths = vec![];
+}
+
+/// Set the substr regex definitions of the first input string, whose substring ids are 1, 2, ... in the order of `substrs`.
+///
+/// The input string is match-only if `substrs` is empty, e.g., compiled from a decomposed regex without public parts.
+pub fn set_config_substr_file_paths(substrs: Vec<String>) {
+    let mut params = lock(&regexConfigParams);
+    let mut substrs = substrs.into_iter();
+    params.inputs[0].substr_file_path = substrs.next().unwrap_or_default();
+    params.inputs[0].extra_substr_file_paths = substrs.collect();
 }
 
-/// Generate a proof for the regex verification circuit.
+/// Register `text` as the content of the regex definition file at `path`, which is read instead of the file, e.g., on wasm32 without a file system.
 ///
 /// # Arguments
-/// * `params_path` - a file path of the SRS parameters.
-/// * `pk_path` - a file path of the proving key.
-/// * `is_success` - is the proof should pass or not.
-/// * `proof_path` - a file path of the output proof.
-/// * `circuit` - a regex verification circuit.
-pub fn prove<C: Circuit<Fr>>(
-    params_path: &str,
-    pk_path: &str,
-    is_success: bool,
-    proof_path: &str,
-    circuit: C,
-) -> Result<(), Error> {
-    let mut params = {
-        let f = File::open(Path::new(params_path)).unwrap();
-        let mut reader = BufReader::new(f);
-        ParamsKZG::<Bn256>::read(&mut reader).unwrap()
-    };
-    set_config_k(params.k() as usize);
+/// * `path` - a file path of the allstr or substr regex definition configured for an input string.
+/// * `text` - the content of the file in the format of [`AllstrRegexDef::read_from_text`] or [`SubstrRegexDef::read_from_text`].
+pub fn set_config_regex_text(path: &str, text: String) {
+    lock(&regexTexts).insert(path.to_string(), text);
+}
 
-    let prover = MockProver::run(params.k(), &circuit, vec![]).unwrap();
-    if is_success {
-        assert_eq!(prover.verify(), Ok(()));
-    }else {
-        assert_ne!(prover.verify(), Ok(()));
-    }
+/// Return the file paths of `substr0.txt`, `substr1.txt`, ... in `substrs_dir_path`, e.g., written by `gen-halo2-texts`, until one of them does not exist.
+pub fn substr_file_paths_in_dir(substrs_dir_path: &str) -> Vec<String> {
+    (0..)
+        .map(|idx| Path::new(substrs_dir_path).join(format!("substr{}.txt", idx)))
+        .take_while(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
 
-    let pk = {
-        let f = File::open(Path::new(pk_path)).unwrap();
-        let mut reader = BufReader::new(f);
-        ProvingKey::<G1Affine>::read::<_, C>(&mut reader, SerdeFormat::RawBytesUnchecked).unwrap()
-    };
-    let rng = thread_rng();
-    let proof = {
-        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
-        create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
-            &params,
-            &pk,
-            &[circuit],
-            &[&[]],
-            rng,
-            &mut transcript,
-        )
-        .unwrap();
-        transcript.finalize()
-    };
-    {
-        let f = File::create(proof_path).unwrap();
-        let mut writer = BufWriter::new(f);
-        writer.write_all(&proof).unwrap();
-        writer.flush().unwrap();
-    };
-    Ok(())
+pub fn set_config_k(_k: usize) {
+    lock(&regexConfigParams).k = _k;
 }
 
-pub fn verify<C: Circuit<Fr>>(
-    params_path: &str,
-    vk_path: &str,
-    proof_path: &str,
-    _circuit: C,
-) -> bool {
-    let params = {
-        let f = File::open(Path::new(params_path)).unwrap();
-        let mut reader = BufReader::new(f);
-        ParamsKZG::<Bn256>::read(&mut reader).unwrap()
-    };
-    let vk = {
-        let f = File::open(Path::new(vk_path)).unwrap();
-        let mut reader = BufReader::new(f);
-        VerifyingKey::<G1Affine>::read::<_, C>(&mut reader, SerdeFormat::RawBytesUnchecked).unwrap()
-    };
-    let proof = {
-        let mut f = File::open(&proof_path).unwrap();
-        let mut buf = Vec::new();
-        f.read_to_end(&mut buf).unwrap();
-        buf
-    };
+/// Set the maximum length of the first input string.
+pub fn set_config_max_chars_size(max_chars_size: usize) {
+    lock(&regexConfigParams).inputs[0].max_chars_size = max_chars_size;
+}
 
-    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
-    let verifier_params = params.verifier_params();
-    let strategy = SingleStrategy::new(&verifier_params);
-    let verify_result = verify_proof::<_, VerifierGWC<_>, _, _, _>(
-        verifier_params,
-        &vk,
-        strategy,
-        &[&[]],
-        &mut transcript,
-    );
+pub fn set_config_transfer_encoding(transfer_encoding: TransferEncoding) {
+    lock(&regexConfigParams).inputs[0].transfer_encoding = transfer_encoding;
+}
 
-    return match verify_result {
-        Ok(_value) => true,
-        Err(_e) => false,
-    };
+/// Set the maximum number of the characters removed by the quoted-printable decoding of the first input string.
+pub fn set_config_max_qp_removed_size(max_qp_removed_size: usize) {
+    lock(&regexConfigParams).inputs[0].max_qp_removed_size = max_qp_removed_size;
+}
+
+pub fn set_config_folding_mode(folding_mode: Option<FoldingMode>) {
+    lock(&regexConfigParams).inputs[0].folding_mode = folding_mode;
+}
+
+/// Set the maximum number of the characters removed by the folding normalization of the first input string.
+pub fn set_config_max_folding_removed_size(max_folding_removed_size: usize) {
+    lock(&regexConfigParams).inputs[0].max_folding_removed_size = max_folding_removed_size;
+}
+
+/// Set the byte padding the first input string up to its maximum length, which is zero by default.
+pub fn set_config_padding_byte(padding_byte: u8) {
+    lock(&regexConfigParams).inputs[0].padding_byte = padding_byte;
+}
+
+/// Add an input string following those already configured, which is then given by [`RegexCircuit::extra_inputs`].
+pub fn add_config_input(input: RegexInputParams) {
+    lock(&regexConfigParams).inputs.push(input);
+}
+
+/// Add a constraint that two substrings of the configured input strings are equal.
+pub fn add_config_substr_equality(equality: SubstrEquality) {
+    lock(&regexConfigParams).substr_equalities.push(equality);
+}
+
+pub fn set_config_full_transparency(full_transparency: bool) {
+    lock(&regexConfigParams).full_transparency = full_transparency;
+}
+
+/// Set whether only the Poseidon hash of all the instances is exposed, which the verifier recomputes by [`RegexCircuit::instances`].
+pub fn set_config_commit_instances(commit_instances: bool) {
+    lock(&regexConfigParams).commit_instances = commit_instances;
+}
+
+/// Set the size bounds of the lookup tables to enable the universal mode, where one proving/verifying key serves any regexes within the bounds.
+pub fn set_config_universal_bounds(bounds: Option<UniversalTableBounds>) {
+    lock(&regexConfigParams).universal_bounds = bounds;
+}
+
+/// Set the numbers of the advice and fixed columns, which are validated against `k` when the circuit is configured.
+pub fn set_config_num_columns(num_advice: usize, num_fixed: usize) {
+    let mut params = lock(&regexConfigParams);
+    params.num_advice = num_advice;
+    params.num_fixed = num_fixed;
+}
+
+/// Set the maximum substring id of each input string, which is validated against its substr regex definitions when the circuit is configured.
+pub fn set_config_max_substr_id(max_substr_id: usize) {
+    lock(&regexConfigParams).max_substr_id = max_substr_id;
+}
+
+/// Set whether the setup parameters larger than 2^`k` rows are downsized to `k`, so that one trusted setup serves the circuits of any smaller size.
+pub fn set_config_downsize_params(downsize_params: bool) {
+    lock(&regexConfigParams).downsize_params = downsize_params;
+}
+
+/// Set the number of the threads of the global rayon pool, which is used by halo2 to generate keys and proofs as well as to derive the witnesses.
+///
+/// It must be called before anything runs on the pool, and only once in a process; otherwise, the pool keeps using all the cores.
+///
+/// # Arguments
+/// * `num_threads` - the number of the threads.
+pub fn set_num_threads(num_threads: usize) -> Result<(), rayon::ThreadPoolBuildError> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build_global()
+}
+
+/// Add a substring exposed as instances following those already added.
+pub fn add_config_public_part(part: PublicPart) {
+    lock(&regexConfigParams).public_parts.push(part);
+}
+
+/// Generate a random salt for each of the configured public parts, which is set to [`RegexCircuit::salts`].
+///
+/// The salts must be kept secret and reused to open the commitments of the `commit_only` parts.
+pub fn random_salts<F: PrimeField>(rng: &mut impl rand::RngCore) -> Vec<F> {
+    let num_public_parts = lock(&regexConfigParams).public_parts.len();
+    (0..num_public_parts)
+        .map(|_| F::random(&mut *rng))
+        .collect()
+}
+
+/// An input string of [`RegexCircuit`] and its expected substrings.
+#[derive(Default, Clone, Debug)]
+pub struct RegexCircuitInput {
+    pub characters: Vec<u8>,
+    pub correct_substrs: Vec<(usize, String)>,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct RegexCircuit<F: PrimeField> {
+    pub characters: Vec<u8>,
+    pub correct_substrs: Vec<(usize, String)>,
+    /// The input strings following `characters`, one for each input added by [`add_config_input`].
+    pub extra_inputs: Vec<RegexCircuitInput>,
+    /// Whether the characters of each public part added by [`add_config_public_part`] are revealed instead of its commitment.
+    /// A public part without the corresponding flag is revealed.
+    pub revealed_parts: Vec<bool>,
+    /// The private salt of each public part, which is used only if the part is `commit_only`.
+    /// Each `commit_only` part requires its salt, which is checked by [`RegexCircuit::check_inputs`], since a commitment without a secret salt does not hide a short substring; [`random_salts`] generates them.
+    pub salts: Vec<F>,
+    pub is_success: bool,
+    /// A token to cancel the synthesis of this circuit, which is usually the one given to [`gen_keys`] or [`prove`] as well, or `None` if it is never cancelled.
+    pub cancellation: Option<CancellationToken>,
+    /// A callback receiving the progress of the synthesis of this circuit, which is usually the one given to [`gen_keys`] or [`prove`] as well, or `None` if it is not reported.
+    pub progress: Option<ProgressCallback>,
+    /// The masked output of each input string assigned by the last synthesis of this circuit, which is read by [`RegexCircuit::masked_outputs`].
+    /// It is shared by the clones of this circuit, but not by its [`Circuit::without_witnesses`].
+    pub outputs: Arc<Mutex<Vec<MaskedOutput>>>,
+    /// The statistics of the last synthesis of this circuit, which are read by [`RegexCircuit::synthesis_stats`].
+    /// Like `outputs`, it is shared by the clones of this circuit, but not by its [`Circuit::without_witnesses`].
+    pub stats: Arc<Mutex<SynthesisStats>>,
+    pub _marker: PhantomData<F>,
+}
+
+/// Configuration of [`RegexCircuit`], which has one [`RegexVerifyConfig`] for each input string.
+#[derive(Debug, Clone)]
+pub struct RegexCircuitConfig<F: PrimeField> {
+    pub regex_configs: Vec<RegexVerifyConfig<F>>,
+    /// The instance column for the outputs of the public parts.
+    pub instance: Column<Instance>,
+}
+
+impl<F: PrimeField> RegexCircuitConfig<F> {
+    /// Return a reference of [`FlexGateConfig`] shared by all input strings.
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.regex_configs[0].gate()
+    }
+}
+
+impl<F: PrimeField> RegexCircuit<F> {
+    /// Return the instances of the circuit, i.e., the outputs of the public parts added by [`add_config_public_part`] in order.
+    ///
+    /// They are preceded by the [`regex_commitment`] of each input string, or the digest of its lookup tables in the universal mode.
+    /// The characters of each public part, the limbs of its address if `as_address`, its bytes padded with zeros to `max_length` if `expected`, or its Unix timestamp if `timestamp_format` is set, are taken from `correct_substrs` of its input string.
+    /// In the full-transparency mode, they are followed by the characters matched by the regexes of each input string padded with its padding byte to its maximum length.
+    /// Then the [`poseidon_hash`] of the encoded characters of each [`TransferEncoding::Base64`] input string padded with zeros to its maximum length follows, which links the decoded string to upstream checks of the encoded one.
+    /// If `commit_instances` is set, they are replaced by their [`poseidon_hash`], which is the only instance.
+    pub fn instances(&self) -> Vec<F> {
+        let params = lock(&regexConfigParams);
+        let mut instances = regex_digests(&params);
+        for (part_idx, part) in params.public_parts.iter().enumerate() {
+            let substr = self.part_substr(part);
+            if part.as_address {
+                // An invalid address fails the constraints of `HexAddressConfig::parse_address`, whose proof is not generated.
+                instances.extend(address_limbs(&substr).unwrap_or([F::zero(); 2]));
+            } else if let Some(format) = part.timestamp_format {
+                // An invalid date fails the constraints of `DateConfig::parse_timestamp`, whose proof is not generated.
+                instances.push(F::from(date_to_timestamp(&substr, format).unwrap_or(0)));
+            } else if part.expected {
+                let mut padded = substr;
+                padded.resize(part.max_length, 0);
+                instances.extend(padded.iter().map(|byte| F::from(*byte as u64)));
+            } else if part.commit_only {
+                // The commitment of a part without its salt is unknown, whose proof is not generated.
+                instances.push(self.salts.get(part_idx).map_or(F::zero(), |salt| {
+                    salted_commitment(&substr, part.max_length, *salt)
+                }));
+            } else {
+                instances.append(&mut reveal_instances(
+                    &substr,
+                    part.max_length,
+                    self.is_revealed(part_idx),
+                ));
+            }
+        }
+        if params.full_transparency {
+            for (input_idx, input_params) in params.inputs.iter().enumerate() {
+                let mut chars = matched_characters(input_params, &self.input(input_idx).characters)
+                    .unwrap_or_default();
+                chars.resize(input_params.max_chars_size, input_params.padding_byte);
+                instances.extend(chars.iter().map(|char| F::from(*char as u64)));
+            }
+        }
+        for (input_idx, input_params) in params.inputs.iter().enumerate() {
+            if input_params.transfer_encoding == TransferEncoding::Base64 {
+                let mut chars = self.input(input_idx).characters;
+                chars.resize(input_params.max_chars_size, 0);
+                let chars = chars
+                    .iter()
+                    .map(|char| F::from(*char as u64))
+                    .collect::<Vec<F>>();
+                instances.push(poseidon_hash(&chars));
+            }
+        }
+        if params.commit_instances {
+            return vec![poseidon_hash(&instances)];
+        }
+        instances
+    }
+
+    /// Check that each input string is not longer than the maximum length configured for it, e.g., by [`set_config_max_chars_size`].
+    ///
+    /// The synthesis of a circuit failing this check fails with [`Error::Synthesis`], which the provers report as [`ProveError::InputTooLong`].
+    pub fn check_input_lengths(&self) -> Result<(), InputTooLongError> {
+        let params = lock(&regexConfigParams);
+        for (input_idx, input_params) in params.inputs.iter().enumerate() {
+            let len = self.input(input_idx).characters.len();
+            if len > input_params.max_chars_size {
+                return Err(InputTooLongError {
+                    input_idx,
+                    len,
+                    max_chars_size: input_params.max_chars_size,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a proof of the circuit can be generated for its inputs, i.e., each input string passes [`RegexCircuit::check_input_lengths`], is decoded by its decoding stages and matches its regexes, its `correct_substrs` are the substrings extracted from it if `is_success`, and every `commit_only` public part has its salt.
+    ///
+    /// The proving of a circuit failing this check fails with [`Error::Synthesis`], which the provers report as [`ProveError::InvalidInput`], or [`ProveError::InputTooLong`] for a too long input string.
+    pub fn check_inputs(&self) -> Result<(), InvalidInputError> {
+        self.check_strings()?;
+        self.check_salts(&lock(&regexConfigParams).public_parts)
+    }
+
+    /// Check the input strings as [`RegexCircuit::check_inputs`], without which the circuit cannot even be synthesized for the key generation.
+    fn check_strings(&self) -> Result<(), InvalidInputError> {
+        self.check_input_lengths()?;
+        let input_params = lock(&regexConfigParams).inputs.clone();
+        for (input_idx, params) in input_params.iter().enumerate() {
+            let input = self.input(input_idx);
+            let unmatched = |error| InvalidInputError::Unmatched { input_idx, error };
+            if !self.is_success {
+                let chars = matched_characters(params, &input.characters)
+                    .ok_or_else(|| unmatched(DeriveSubstrsError::InvalidEncoding))?;
+                for defs in read_regex_defs(params).iter() {
+                    let trace = defs.trace(&chars);
+                    if let Some(position) = trace.failed_position {
+                        return Err(unmatched(DeriveSubstrsError::InvalidTransition {
+                            position,
+                            char: chars[position],
+                            state: trace.states[position],
+                        }));
+                    }
+                }
+                continue;
+            }
+            for (substr_idx, (start, substr)) in input.correct_substrs.iter().enumerate() {
+                let end = start.saturating_add(substr.len());
+                if end > params.max_chars_size {
+                    return Err(InvalidInputError::SubstrOutOfRange {
+                        input_idx,
+                        substr_id: substr_idx + 1,
+                        end,
+                        max_chars_size: params.max_chars_size,
+                    });
+                }
+            }
+            let extracted =
+                derive_correct_substrs(input_idx, &input.characters).map_err(unmatched)?;
+            // An empty substring is the same as a missing one wherever it starts.
+            let non_empty = |substr: Option<&(usize, String)>| {
+                substr.filter(|(_, substr)| !substr.is_empty()).cloned()
+            };
+            for substr_idx in 0..extracted.len().max(input.correct_substrs.len()) {
+                if non_empty(input.correct_substrs.get(substr_idx))
+                    != non_empty(extracted.get(substr_idx))
+                {
+                    return Err(InvalidInputError::WrongSubstr {
+                        input_idx,
+                        substr_id: substr_idx + 1,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every `commit_only` part of `public_parts` has its salt in [`RegexCircuit::salts`].
+    fn check_salts(&self, public_parts: &[PublicPart]) -> Result<(), InvalidInputError> {
+        match public_parts
+            .iter()
+            .enumerate()
+            .find(|(part_idx, part)| part.commit_only && *part_idx >= self.salts.len())
+        {
+            Some((part_idx, _)) => Err(InvalidInputError::MissingSalt { part_idx }),
+            None => Ok(()),
+        }
+    }
+
+    /// Return the masked output of each input string assigned by the last synthesis of this circuit or its clones, e.g., by [`prove`] or [`mock_run`], which is empty before the synthesis.
+    pub fn masked_outputs(&self) -> Vec<MaskedOutput> {
+        lock(&self.outputs).clone()
+    }
+
+    /// Return the statistics of the last synthesis of this circuit or its clones, e.g., by [`gen_keys`] or [`mock_run`], which are zero before the synthesis.
+    pub fn synthesis_stats(&self) -> SynthesisStats {
+        lock(&self.stats).clone()
+    }
+
+    /// Return [`Error::Synthesis`] if [`RegexCircuit::cancellation`] is cancelled.
+    fn check_cancellation(&self) -> Result<(), Error> {
+        if is_cancelled(self.cancellation.as_ref()) {
+            return Err(Error::Synthesis);
+        }
+        Ok(())
+    }
+
+    /// Return the bytes of the substring of `part` taken from `correct_substrs` of its input string.
+    fn part_substr(&self, part: &PublicPart) -> Vec<u8> {
+        let input = self.input(part.input_idx);
+        // A public part of the substring id 0, which `validate` rejects, has no substring.
+        part.substr_id
+            .checked_sub(1)
+            .and_then(|substr_idx| input.correct_substrs.get(substr_idx))
+            .map(|(_, substr)| substr.as_bytes().to_vec())
+            .unwrap_or_default()
+    }
+
+    fn is_revealed(&self, part_idx: usize) -> bool {
+        self.revealed_parts.get(part_idx).copied().unwrap_or(true)
+    }
+
+    /// Return the `idx`-th input string, which is empty if it is not given.
+    fn input(&self, idx: usize) -> RegexCircuitInput {
+        if idx == 0 {
+            RegexCircuitInput {
+                characters: self.characters.clone(),
+                correct_substrs: self.correct_substrs.clone(),
+            }
+        } else {
+            self.extra_inputs.get(idx - 1).cloned().unwrap_or_default()
+        }
+    }
+
+    /// Apply the decoding stages configured by `params` to `characters` and match the result with `config`.
+    ///
+    /// `witness` must be derived by `config` from the output of [`matched_characters`].
+    ///
+    /// # Return values
+    /// Return the result of the matching and the assigned encoded characters of a [`TransferEncoding::Base64`] input string, if so.
+    #[allow(clippy::type_complexity)]
+    fn match_input<'v: 'a, 'a>(
+        ctx: &mut Context<'v, F>,
+        config: &RegexVerifyConfig<F>,
+        params: &RegexInputParams,
+        characters: &[u8],
+        witness: &RegexWitness,
+    ) -> Result<
+        (
+            AssignedRegexResult<'a, F>,
+            Option<Vec<AssignedValue<'a, F>>>,
+        ),
+        Error,
+    > {
+        let gate = config.gate();
+        let max_chars_size = params.max_chars_size;
+        // The assigned input string of `match_substrs` output by the preceding stages, if any.
+        let mut stage_output = None;
+        let mut encoded = None;
+        match params.transfer_encoding {
+            TransferEncoding::Identity => {}
+            TransferEncoding::QuotedPrintable => {
+                let qp = QuotedPrintableConfig::new(
+                    gate.clone(),
+                    max_chars_size,
+                    params.max_qp_removed_size,
+                );
+                let decoded = qp.decode(ctx, characters)?;
+                stage_output = Some((decoded.decoded, decoded.decoded_len));
+            }
+            TransferEncoding::Base64 => {
+                // `validate` rejects an unaligned maximum length before the synthesis.
+                let base64 =
+                    Base64DecodeConfig::new(gate.clone(), max_chars_size).map_err(|e| {
+                        tracing::error!("{}", e);
+                        Error::Synthesis
+                    })?;
+                let mut decoded = base64.decode(ctx, characters)?;
+                // The decoded string is shorter than the encoded one, and is padded with zeros to the length of the other stages.
+                let zero = gate.load_zero(ctx);
+                decoded.decoded.resize(max_chars_size, zero);
+                stage_output = Some((decoded.decoded, decoded.decoded_len));
+                encoded = Some(decoded.encoded);
+            }
+        }
+        if let Some(mode) = params.folding_mode {
+            let folding = FoldingConfig::new(
+                gate.clone(),
+                max_chars_size,
+                params.max_folding_removed_size,
+                mode,
+            );
+            let normalized = match &stage_output {
+                Some((decoded, _)) => folding.normalize_assigned(ctx, decoded)?,
+                None => folding.normalize(ctx, characters)?,
+            };
+            stage_output = Some((normalized.normalized, normalized.normalized_len));
+        }
+        let result = config.match_substrs_with_witness(ctx, witness)?;
+        if let Some((input, input_len)) = stage_output {
+            assert_regex_input(gate, ctx, &input, &input_len, &result);
+        }
+        Ok((result, encoded))
+    }
+
+    /// Assert that the masked characters and substring ids assigned in `result` are equal to those extracted by the software DFA of `config` from the characters of `witness`.
+    ///
+    /// Only the characters accepted by every regex definition are checked, since the substrings of a rejected input string are not defined.
+    #[cfg(feature = "debug-assertions")]
+    fn assert_software_extraction(
+        config: &RegexVerifyConfig<F>,
+        input_idx: usize,
+        witness: &RegexWitness,
+        result: &AssignedRegexResult<F>,
+    ) {
+        let chars = &witness.characters;
+        let mut expected_substr_ids = vec![0; result.all_substr_ids.len()];
+        let mut substr_id_offset = 0;
+        for defs in config.defs_of(witness).iter() {
+            let trace = defs.trace(chars);
+            if !trace.is_accepted {
+                return;
+            }
+            // The substring ids of the regex definitions are summed into the masked ones as in the circuit.
+            for (idx, substr_id) in trace.substr_ids.iter().enumerate() {
+                if *substr_id != 0 {
+                    expected_substr_ids[idx] += substr_id_offset + substr_id;
+                }
+            }
+            substr_id_offset += defs.substrs.len();
+        }
+        let to_u64 = |assigned: &AssignedValue<F>| {
+            let mut val = 0;
+            assigned.value().map(|v| val = v.get_lower_128() as u64);
+            val
+        };
+        for (idx, expected_substr_id) in expected_substr_ids.iter().enumerate() {
+            let expected_char = if *expected_substr_id == 0 {
+                0
+            } else {
+                chars[idx]
+            };
+            assert_eq!(
+                to_u64(&result.masked_characters[idx]),
+                expected_char as u64,
+                "the masked character at {} of the input string {} differs from the software extraction",
+                idx,
+                input_idx
+            );
+            assert_eq!(
+                to_u64(&result.all_substr_ids[idx]),
+                *expected_substr_id as u64,
+                "the substring id at {} of the input string {} differs from the software extraction",
+                idx,
+                input_idx
+            );
+        }
+    }
+}
+
+/// Return false iff the value of `cell` is known and is not `expected`, e.g., for the key generation without witnesses.
+fn is_known_as<F: PrimeField>(cell: &AssignedValue<F>, expected: u64) -> bool {
+    let mut is_expected = true;
+    cell.value().map(|v| is_expected = *v == F::from(expected));
+    is_expected
+}
+
+/// Read the regex definitions of the input string configured by `params`.
+///
+/// It panics if they are malformed, which [`RegexVerifyConfigParams::validate`] checks by [`try_read_regex_defs`] before the public functions of this module read them.
+fn read_regex_defs(params: &RegexInputParams) -> Vec<RegexDefs> {
+    try_read_regex_defs(params).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as [`read_regex_defs`] except that it returns [`RegexDefError`] instead of panicking.
+fn try_read_regex_defs(params: &RegexInputParams) -> Result<Vec<RegexDefs>, RegexDefError> {
+    let texts = lock(&regexTexts);
+    let all_regex_def = match texts.get(&params.allstr_file_path) {
+        Some(text) => AllstrRegexDef::try_from_text(text)?,
+        None => AllstrRegexDef::try_read_from_text(&params.allstr_file_path)?,
+    };
+    // A match-only input string has no substr regex definition.
+    let substr_defs = std::iter::once(&params.substr_file_path)
+        .chain(params.extra_substr_file_paths.iter())
+        .filter(|path| !path.is_empty())
+        .map(|path| match texts.get(path) {
+            Some(text) => SubstrRegexDef::try_from_text(text),
+            None => SubstrRegexDef::try_read_from_text(path),
+        })
+        .collect::<Result<Vec<SubstrRegexDef>, RegexDefError>>()?;
+    Ok(vec![RegexDefs {
+        allstr: all_regex_def,
+        substrs: substr_defs,
+    }])
+}
+
+/// Return the [`regex_commitment`] of each input string configured by `params`, or the digest of its lookup tables in the universal mode.
+fn regex_digests<F: PrimeField>(params: &RegexVerifyConfigParams) -> Vec<F> {
+    try_regex_digests(params).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Same as [`regex_digests`] except that it returns [`ConfigError::InvalidRegexFile`] instead of panicking.
+fn try_regex_digests<F: PrimeField>(
+    params: &RegexVerifyConfigParams,
+) -> Result<Vec<F>, ConfigError> {
+    params
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(input_idx, input)| {
+            let regex_defs = try_read_regex_defs(input)
+                .map_err(|error| ConfigError::InvalidRegexFile { input_idx, error })?;
+            Ok(match &params.universal_bounds {
+                // The regex definitions exceeding the bounds, which `validate` rejects, have no proof, whose digest is zero.
+                Some(bounds) => {
+                    universal_table_digest(&regex_defs, bounds).unwrap_or_else(|_| F::zero())
+                }
+                None => regex_defs_digest(&regex_defs),
+            })
+        })
+        .collect()
+}
+
+/// Return the digest of the regex definitions of each configured input string, i.e., the first instances of [`RegexCircuit`].
+///
+/// # Return values
+/// Return the digests, or [`ConfigError`] if the configuration is invalid, e.g., a regex definition file is missing or malformed.
+pub fn config_regex_digests() -> Result<Vec<Fr>, ConfigError> {
+    let params = lock(&regexConfigParams);
+    params.validate()?;
+    try_regex_digests(&params)
+}
+
+/// Return the file paths of the regex definitions of the configured input strings, i.e., the files read by [`config_regex_digests`].
+pub fn config_regex_file_paths() -> Vec<String> {
+    lock(&regexConfigParams)
+        .inputs
+        .iter()
+        .flat_map(|input| {
+            std::iter::once(&input.allstr_file_path)
+                .chain(std::iter::once(&input.substr_file_path))
+                .chain(input.extra_substr_file_paths.iter())
+                .filter(|path| !path.is_empty())
+                .cloned()
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+/// Compute the canonical digest of the regex definitions of the input string configured by `params`, which is exposed as an instance of [`RegexCircuit`] with the fixed lookup tables.
+///
+/// # Return values
+/// Return the digest computed by [`regex_defs_digest`].
+pub fn regex_commitment<F: PrimeField>(params: &RegexInputParams) -> F {
+    regex_defs_digest(&read_regex_defs(params))
+}
+
+/// The commitment of [`regex_commitment`] in the forms given to the verifiers, e.g., a `bytes32` or `uint256` constant of a contract.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RegexCommitmentJson {
+    /// The hex string of the 32-byte big-endian representation.
+    pub hex: String,
+    /// The decimal string of the field element.
+    pub field_element: String,
+}
+
+impl RegexCommitmentJson {
+    /// Compute the commitment of the regex definitions of the input string configured by `params`.
+    pub fn new(params: &RegexInputParams) -> Self {
+        let commitment: Fr = regex_commitment(params);
+        Self {
+            hex: format!("{:?}", commitment),
+            field_element: fe_to_biguint(&commitment).to_string(),
+        }
+    }
+}
+
+/// A state transition and its Merkle proof written by [`gen_regex_merkle_tree`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct TransitionMerkleProofJson {
+    pub character: u8,
+    pub cur_state: u64,
+    pub next_state: u64,
+    pub substr_id: usize,
+    /// The index of the leaf.
+    pub leaf_idx: usize,
+    /// The hex strings of the sibling nodes from the leaf to the root.
+    pub siblings: Vec<String>,
+}
+
+/// The Merkle root of the state transitions and their proofs written by [`gen_regex_merkle_tree`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RegexMerkleTreeJson {
+    /// The hex string of the root.
+    pub root: String,
+    pub transitions: Vec<TransitionMerkleProofJson>,
+}
+
+/// Merkleize the state transitions of the regex definitions and write the root and the proofs of all transitions as json.
+///
+/// # Arguments
+/// * `allstr_file_path` - a file path of the allstr regex definition.
+/// * `substr_file_path` - a file path of the substr regex definition.
+/// * `output_path` - a file path of the output json.
+pub fn gen_regex_merkle_tree(
+    allstr_file_path: &str,
+    substr_file_path: &str,
+    output_path: &str,
+) -> Result<(), Error> {
+    let regex_defs = vec![RegexDefs {
+        allstr: AllstrRegexDef::read_from_text(allstr_file_path),
+        substrs: vec![SubstrRegexDef::read_from_text(substr_file_path)],
+    }];
+    let tree = RegexMerkleTree::<Fr>::new(&regex_defs);
+    let transitions = (0..tree.rows().len())
+        .filter_map(|leaf_idx| tree.proof(leaf_idx))
+        .map(|proof| {
+            let (character, cur_state, next_state, substr_id) = proof.row;
+            TransitionMerkleProofJson {
+                character,
+                cur_state,
+                next_state,
+                substr_id,
+                leaf_idx: proof.leaf_idx,
+                siblings: proof
+                    .siblings
+                    .iter()
+                    .map(|sibling| format!("{:?}", sibling))
+                    .collect(),
+            }
+        })
+        .collect();
+    let tree_json = RegexMerkleTreeJson {
+        root: format!("{:?}", tree.root()),
+        transitions,
+    };
+    let f = File::create(output_path)?;
+    serde_json::to_writer_pretty(BufWriter::new(f), &tree_json).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+/// Apply the decoding stages configured by `params` to `characters` off-circuit.
+///
+/// # Return values
+/// Return the bytes matched by the regexes, or `None` if `characters` cannot be decoded.
+pub fn matched_characters(params: &RegexInputParams, characters: &[u8]) -> Option<Vec<u8>> {
+    let mut chars = match params.transfer_encoding {
+        TransferEncoding::Identity => characters.to_vec(),
+        TransferEncoding::QuotedPrintable => decode_quoted_printable(characters)?,
+        TransferEncoding::Base64 => decode_base64(characters)?,
+    };
+    if let Some(mode) = params.folding_mode {
+        chars = normalize_folding(&chars, mode);
+    }
+    Some(chars)
+}
+
+/// Error definitions of [`derive_correct_substrs`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DeriveSubstrsError {
+    #[error("There is no input string of the index {0}")]
+    UnknownInput(usize),
+    #[error("The input string cannot be decoded")]
+    InvalidEncoding,
+    #[error("The transition from {state} by {char} at the position {position} is invalid")]
+    InvalidTransition {
+        position: usize,
+        char: u8,
+        state: u64,
+    },
+    #[error("The characters of the substring {substr_id} are not contiguous")]
+    NonContiguousSubstr { substr_id: usize },
+    #[error("The substring {substr_id} is not a valid UTF-8 string")]
+    NonUtf8Substr { substr_id: usize },
+    #[error("The message of {len} bytes is longer than {msg_bytes} bytes")]
+    TooLongMessage { len: usize, msg_bytes: usize },
+}
+
+/// Derive [`RegexCircuit::correct_substrs`] of an input string by running the DFA of its regex definitions off-circuit.
+///
+/// # Arguments
+/// * `input_idx` - the index of the input string in the configured parameters.
+/// * `characters` - the characters of the input string before the decoding stages.
+///
+/// # Return values
+/// Return the start position and the string of each substring in the order of the substring ids, where an unmatched substring is `(0, "")`.
+pub fn derive_correct_substrs(
+    input_idx: usize,
+    characters: &[u8],
+) -> Result<Vec<(usize, String)>, DeriveSubstrsError> {
+    let params = lock(&regexConfigParams)
+        .inputs
+        .get(input_idx)
+        .cloned()
+        .ok_or(DeriveSubstrsError::UnknownInput(input_idx))?;
+    let chars =
+        matched_characters(&params, characters).ok_or(DeriveSubstrsError::InvalidEncoding)?;
+    let mut correct_substrs = vec![];
+    for defs in read_regex_defs(&params).iter() {
+        let trace = defs.trace(&chars);
+        if let Some(position) = trace.failed_position {
+            return Err(DeriveSubstrsError::InvalidTransition {
+                position,
+                char: chars[position],
+                state: trace.states[position],
+            });
+        }
+        // The positions of the characters of each substring of this definition.
+        let mut positions = vec![vec![]; defs.substrs.len()];
+        for (position, substr_id) in trace.substr_ids.iter().enumerate() {
+            if *substr_id > 0 {
+                positions[substr_id - 1].push(position);
+            }
+        }
+        for positions in positions.into_iter() {
+            let substr_id = correct_substrs.len() + 1;
+            let start = positions.first().copied().unwrap_or(0);
+            if positions
+                .iter()
+                .enumerate()
+                .any(|(idx, position)| *position != start + idx)
+            {
+                return Err(DeriveSubstrsError::NonContiguousSubstr { substr_id });
+            }
+            let substr = String::from_utf8(positions.iter().map(|idx| chars[*idx]).collect())
+                .map_err(|_| DeriveSubstrsError::NonUtf8Substr { substr_id })?;
+            correct_substrs.push((start, substr));
+        }
+    }
+    Ok(correct_substrs)
+}
+
+/// The signals of the template generated by [`DecomposedRegexConfig::gen_circom`] for a message, i.e., its input `msg` and its expected outputs `reveal0`, `reveal1`, ...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircomSignals {
+    /// The bytes of the message padded with zeros to `msg_bytes` of the template.
+    pub msg: Vec<u8>,
+    /// The revealed bytes of each public part, which are zero except for those of the substring.
+    pub reveals: Vec<Vec<u8>>,
+}
+
+impl CircomSignals {
+    /// Return the input json of the witness generator of circom, i.e., `{"msg": [..]}`.
+    pub fn input_json(&self) -> serde_json::Value {
+        serde_json::json!({ "msg": decimal_signals(&self.msg) })
+    }
+
+    /// Return the json of the expected outputs, i.e., `{"reveal0": [..], ..}`.
+    pub fn outputs_json(&self) -> serde_json::Value {
+        let outputs = self
+            .reveals
+            .iter()
+            .enumerate()
+            .map(|(idx, reveal)| (format!("reveal{}", idx), decimal_signals(reveal).into()))
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+        serde_json::Value::Object(outputs)
+    }
+}
+
+fn decimal_signals(bytes: &[u8]) -> Vec<String> {
+    bytes.iter().map(|byte| byte.to_string()).collect()
+}
+
+/// Compute the signals of the circom template of the regex definitions of the first input string for `characters`, whose substrings are derived by [`derive_correct_substrs`].
+///
+/// The regex definitions must be compiled from the same decomposed regex as the template, so that the substring ids correspond to the public parts in order.
+///
+/// # Arguments
+/// * `characters` - the message, which must not be longer than `msg_bytes`.
+/// * `msg_bytes` - the parameter `msg_bytes` of the template.
+///
+/// # Return values
+/// Return [`CircomSignals`], or [`DeriveSubstrsError`] if the message is longer than `msg_bytes` or the substrings cannot be derived, e.g., the message does not match the regex.
+pub fn circom_signals(
+    characters: &[u8],
+    msg_bytes: usize,
+) -> Result<CircomSignals, DeriveSubstrsError> {
+    if characters.len() > msg_bytes {
+        return Err(DeriveSubstrsError::TooLongMessage {
+            len: characters.len(),
+            msg_bytes,
+        });
+    }
+    let correct_substrs = derive_correct_substrs(0, characters)?;
+    let mut msg = characters.to_vec();
+    msg.resize(msg_bytes, 0);
+    let reveals = correct_substrs
+        .iter()
+        .map(|(start, substr)| {
+            let mut reveal = vec![0; msg_bytes];
+            for (idx, char) in substr.as_bytes().iter().enumerate() {
+                reveal[start + idx] = *char;
+            }
+            reveal
+        })
+        .collect();
+    Ok(CircomSignals { msg, reveals })
+}
+
+/// An expected substring of an input string, e.g., given by the `--target` flags of the CLI.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TargetSubstr {
+    /// The start position of the substring.
+    pub pos: usize,
+    /// The characters of the substring.
+    pub string: String,
+    /// The substring id of the substring, which starts from 1.
+    pub substr_id: usize,
+}
+
+/// Convert `targets` to [`RegexCircuit::correct_substrs`], which is indexed by the substring ids.
+///
+/// # Return values
+/// Return the start position and the string of each substring in the order of the substring ids, where a substring id missing in `targets` is `(0, "")`, or [`InvalidInputError::InvalidSubstrId`] if a substring id is zero or exceeds the configured maximum substring id.
+pub fn targets_to_correct_substrs(
+    targets: &[TargetSubstr],
+) -> Result<Vec<(usize, String)>, InvalidInputError> {
+    let max_substr_id = lock(&regexConfigParams).max_substr_id;
+    if let Some(target) = targets
+        .iter()
+        .find(|target| target.substr_id == 0 || target.substr_id > max_substr_id)
+    {
+        return Err(InvalidInputError::InvalidSubstrId {
+            substr_id: target.substr_id,
+            max_substr_id,
+        });
+    }
+    let num_substrs = targets
+        .iter()
+        .map(|target| target.substr_id)
+        .max()
+        .unwrap_or(0);
+    let mut correct_substrs = vec![(0, String::new()); num_substrs];
+    for target in targets.iter() {
+        correct_substrs[target.substr_id - 1] = (target.pos, target.string.clone());
+    }
+    Ok(correct_substrs)
+}
+
+impl<F: PrimeField> Circuit<F> for RegexCircuit<F> {
+    type Config = RegexCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // Circuit without witnesses, called only during key generation
+    fn without_witnesses(&self) -> Self {
+        Self {
+            characters: vec![],
+            correct_substrs: vec![],
+            extra_inputs: vec![],
+            revealed_parts: vec![],
+            salts: vec![],
+            is_success: false,
+            cancellation: self.cancellation.clone(),
+            progress: self.progress.clone(),
+            outputs: Arc::default(),
+            stats: Arc::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let params = lock(&regexConfigParams);
+        // The public functions of this module validate the configuration before it is configured, which cannot return an error.
+        params
+            .validate()
+            .expect("invalid configuration of RegexCircuit");
+        let gate = FlexGateConfig::<F>::configure(
+            meta,
+            halo2_base::gates::flex_gate::GateStrategy::Vertical,
+            &[params.num_advice],
+            params.num_fixed,
+            0,
+            params.k,
+        );
+        // The identical fixed tables of the input strings are shared.
+        let mut table_cache = RegexTableCache::new();
+        let regex_configs = params
+            .inputs
+            .iter()
+            .map(|input| {
+                // In the universal mode, the regex definitions are read at the synthesis, so that the keys do not depend on them.
+                let regex_defs = match params.universal_bounds {
+                    Some(_) => vec![RegexDefs::default()],
+                    None => read_regex_defs(input),
+                };
+                RegexVerifyConfig::configure_with_padding(
+                    meta,
+                    input.max_chars_size,
+                    gate.clone(),
+                    regex_defs,
+                    params.universal_bounds,
+                    input.padding_byte,
+                    &mut table_cache,
+                )
+            })
+            .collect();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        RegexCircuitConfig {
+            regex_configs,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        report_progress(
+            self.progress.as_ref(),
+            ProgressEvent::PhaseStarted {
+                name: "witness synthesis".to_string(),
+            },
+        );
+        let synthesis_start = Instant::now();
+        self.check_cancellation()?;
+        // The expected substrings are assigned at their positions only after they are checked.
+        // The key generation needs no salt, but the proving without a salt fails by its unknown witness; the provers find both errors again by `check_inputs`.
+        if let Err(e) = self.check_strings() {
+            tracing::error!("{}", e);
+            return Err(Error::Synthesis);
+        }
+        let mut loaded_tables = HashSet::new();
+        for regex_config in config.regex_configs.iter() {
+            regex_config.load_shared(&mut layouter, &mut loaded_tables)?;
+        }
+
+        // println!("Synthesize being called...");
+        let mut first_pass = SKIP_FIRST_PASS;
+        let gate = config.gate().clone();
+        let input_params = lock(&regexConfigParams).inputs.clone();
+        let substr_equalities = lock(&regexConfigParams).substr_equalities.clone();
+        let public_parts = lock(&regexConfigParams).public_parts.clone();
+        let full_transparency = lock(&regexConfigParams).full_transparency;
+        let commit_instances = lock(&regexConfigParams).commit_instances;
+        let universal = lock(&regexConfigParams).universal_bounds.is_some();
+        let mut public_cells = vec![];
+        // The DFA of each input string is run in parallel before the witnesses are assigned.
+        let witnesses = config
+            .regex_configs
+            .par_iter()
+            .enumerate()
+            .map(|(input_idx, regex_config)| {
+                self.check_cancellation()?;
+                let chars =
+                    matched_characters(&input_params[input_idx], &self.input(input_idx).characters)
+                        .ok_or(Error::Synthesis)?;
+                if !universal {
+                    return Ok(regex_config.derive_witness(&chars)?);
+                }
+                let regex_defs = try_read_regex_defs(&input_params[input_idx]).map_err(|e| {
+                    tracing::error!("{}", e);
+                    Error::Synthesis
+                })?;
+                Ok(regex_config.derive_universal_witness(&regex_defs, &chars)?)
+            })
+            .collect::<Result<Vec<RegexWitness>, Error>>()?;
+
+        layouter.assign_region(
+            || "regex",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = new_region_context(region, &gate);
+                let ctx = &mut aux;
+                let mut results = vec![];
+                let mut encoded_inputs = vec![];
+                for (input_idx, regex_config) in config.regex_configs.iter().enumerate() {
+                    self.check_cancellation()?;
+                    let params = &input_params[input_idx];
+                    let input = self.input(input_idx);
+                    let (result, encoded) = Self::match_input(
+                        ctx,
+                        regex_config,
+                        params,
+                        &input.characters,
+                        &witnesses[input_idx],
+                    )?;
+                    let mut expected_masked_chars = vec![0; params.max_chars_size];
+                    let mut expected_substr_ids = vec![0; params.max_chars_size];
+
+                    if self.is_success {
+                        for (substr_idx, (start, chars)) in input.correct_substrs.iter().enumerate()
+                        {
+                            for (idx, char) in chars.as_bytes().iter().enumerate() {
+                                expected_masked_chars[start + idx] = *char;
+                                expected_substr_ids[start + idx] = substr_idx + 1;
+                            }
+                        }
+                        let is_expected = (0..params.max_chars_size).all(|idx| {
+                            is_known_as(
+                                &result.masked_characters[idx],
+                                expected_masked_chars[idx] as u64,
+                            ) && is_known_as(
+                                &result.all_substr_ids[idx],
+                                expected_substr_ids[idx] as u64,
+                            )
+                        });
+                        if !is_expected {
+                            tracing::error!(
+                                "the substrings extracted from the input string {} are not the expected ones",
+                                input_idx
+                            );
+                            return Err(Error::Synthesis);
+                        }
+                    }
+                    #[cfg(feature = "debug-assertions")]
+                    Self::assert_software_extraction(
+                        regex_config,
+                        input_idx,
+                        &witnesses[input_idx],
+                        &result,
+                    );
+                    results.push(result);
+                    encoded_inputs.extend(encoded);
+                }
+                let compact = SubstrCompactConfig::new(gate.clone());
+                for equality in substr_equalities.iter() {
+                    let (lhs_idx, lhs_substr_id) = equality.lhs;
+                    let (rhs_idx, rhs_substr_id) = equality.rhs;
+                    compact.assert_substrs_equal(
+                        ctx,
+                        &results[lhs_idx],
+                        lhs_substr_id,
+                        &results[rhs_idx],
+                        rhs_substr_id,
+                        equality.max_length,
+                    )?;
+                }
+                let reveal = RevealConfig::new(gate.clone());
+                let mut public_outputs = vec![];
+                let poseidon = PoseidonConfig::new(gate.clone());
+                let hex = HexAddressConfig::new(gate.clone());
+                for (regex_config, result) in config.regex_configs.iter().zip(results.iter()) {
+                    // The fixed commitment binds the verifying key to the regex definitions used at the key generation.
+                    let digest = if universal {
+                        poseidon.hash(ctx, &result.table_values)
+                    } else {
+                        gate.load_constant(ctx, regex_config.regex_commitment())
+                    };
+                    public_outputs.push(digest);
+                }
+                for (part_idx, part) in public_parts.iter().enumerate() {
+                    let result = &results[part.input_idx];
+                    if part.as_address {
+                        public_outputs.extend(hex.parse_address(ctx, result, part.substr_id)?);
+                    } else if let Some(format) = part.timestamp_format {
+                        let date = DateConfig::new(gate.clone(), format);
+                        public_outputs.push(date.parse_timestamp(ctx, result, part.substr_id)?);
+                    } else if part.expected {
+                        // The expected bytes are equal to the instances supplied by the verifier, and the substring to them.
+                        let expected =
+                            compact.assign_expected(ctx, &self.part_substr(part), part.max_length)?;
+                        compact.assert_substr_equal(ctx, result, part.substr_id, &expected)?;
+                        public_outputs.extend(expected);
+                    } else if part.commit_only {
+                        public_outputs.push(reveal.commit_salted(
+                            ctx,
+                            result,
+                            part.substr_id,
+                            part.max_length,
+                            // The proving without the salt fails with `Error::Synthesis` by the unknown witness.
+                            self.salts
+                                .get(part_idx)
+                                .map_or(Value::unknown(), |salt| Value::known(*salt)),
+                        )?);
+                    } else {
+                        public_outputs.append(&mut reveal.reveal(
+                            ctx,
+                            result,
+                            part.substr_id,
+                            part.max_length,
+                            self.is_revealed(part_idx),
+                        )?);
+                    }
+                }
+                if full_transparency {
+                    for result in results.iter() {
+                        public_outputs.extend(result.all_characters.iter().cloned());
+                    }
+                }
+                for encoded in encoded_inputs.iter() {
+                    public_outputs.push(poseidon.hash(ctx, encoded));
+                }
+                if commit_instances {
+                    public_outputs = vec![poseidon.hash(ctx, &public_outputs)];
+                }
+                public_cells = public_outputs
+                    .iter()
+                    .map(|output| output.cell())
+                    .collect::<Vec<Cell>>();
+                *lock(&self.stats) = SynthesisStats {
+                    advice_cells: ctx.total_advice,
+                    fixed_cells: ctx.total_fixed,
+                    def_advice_cells: results
+                        .iter()
+                        .map(|result| result.def_advice_cells.clone())
+                        .collect(),
+                    synthesis_secs: 0.0,
+                };
+                // The substring ids are read with 64 bits, since their maximum is configurable.
+                let to_u64 = |assigned: &AssignedValue<F>| {
+                    let mut val = 0;
+                    assigned.value().map(|v| val = v.get_lower_128() as u64);
+                    val
+                };
+                *lock(&self.outputs) = results
+                    .iter()
+                    .map(|result| MaskedOutput {
+                        masked_characters: result
+                            .masked_characters
+                            .iter()
+                            .map(|char| to_u64(char) as u8)
+                            .collect(),
+                        substr_ids: result
+                            .all_substr_ids
+                            .iter()
+                            .map(|id| to_u64(id) as usize)
+                            .collect(),
+                    })
+                    .collect();
+                Ok(())
+            },
+        )?;
+        for (row, cell) in public_cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.instance, row)?;
+        }
+        let synthesis_secs = synthesis_start.elapsed().as_secs_f64();
+        lock(&self.stats).synthesis_secs = synthesis_secs;
+        report_progress(
+            self.progress.as_ref(),
+            ProgressEvent::PhaseFinished(PhaseTiming {
+                name: "witness synthesis".to_string(),
+                secs: synthesis_secs,
+            }),
+        );
+        Ok(())
+    }
+}
+
+// /// The number of limbs of the accumulator in the aggregation circuit.
+// pub const NUM_ACC_INSTANCES: usize = 4 * LIMBS;
+// /// The name of env variable for the path to the configuration json of the aggregation circuit.
+// pub const VERIFY_CONFIG_KEY: &'static str = "VERIFY_CONFIG";
+
+/// Generate SRS parameters over the curve `E`, e.g., [`Bn256`].
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `k` - the SRS size.
+#[cfg(feature = "prover")]
+pub fn gen_params<E: ProvingEngine>(params_path: &str, k: u32) -> Result<(), Error> {
+    let rng = thread_rng();
+    let params = E::setup(k, rng);
+    let f = File::create(params_path)?;
+    let mut writer = BufWriter::new(f);
+    E::write_params(&params, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Convert a `.ptau` file of the perpetual powers of tau ceremony into SRS parameters.
+///
+/// # Arguments
+/// * `ptau_path` - a file path of the `.ptau` file.
+/// * `params_path` - a file path of the output SRS parameters.
+/// * `k` - the SRS size, or `None` to use the power of the ceremony.
+#[cfg(feature = "prover")]
+pub fn import_ptau(ptau_path: &str, params_path: &str, k: Option<u32>) -> Result<(), PtauError> {
+    let params = read_ptau(ptau_path, k)?;
+    let f = File::create(params_path)?;
+    let mut writer = BufWriter::new(f);
+    params.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Convert SRS parameters into a `.ptau` file, which includes only the points of the parameters as described in [`write_ptau`].
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `ptau_path` - a file path of the output `.ptau` file.
+#[cfg(feature = "prover")]
+pub fn export_ptau(params_path: &str, ptau_path: &str) -> Result<(), PtauError> {
+    let params = {
+        let mut reader = open_key_file(params_path)?;
+        ParamsKZG::<Bn256>::read(&mut reader)?
+    };
+    write_ptau(&params, ptau_path)
+}
+
+/// Error definitions of [`check_params`].
+#[derive(thiserror::Error, Debug)]
+pub enum ParamsCheckError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The first point of G1 or G2 is not the generator")]
+    InvalidGenerator,
+    #[error("The points of G1 are not the powers of the secret in G2")]
+    InconsistentPowers,
+    #[error("The Lagrange basis is not consistent with the powers of the secret")]
+    InconsistentLagrangeBasis,
+}
+
+/// Return the hex of the BLAKE2b digest of the file at `path`, e.g., to identify the setup parameters or the keys.
+pub fn file_digest(path: &str) -> Result<String, std::io::Error> {
+    let mut reader = open_key_file(path)?;
+    let mut state = blake2b_simd::State::new();
+    std::io::copy(&mut reader, &mut state)?;
+    Ok(state.finalize().to_hex().to_string())
+}
+
+/// The summary of the SRS parameters checked by [`check_params`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ParamsSummary {
+    /// The degree of the number of rows.
+    pub k: u32,
+    /// The hex of the BLAKE2b digest of the file.
+    pub digest: String,
+}
+
+/// Check the structural correctness of SRS parameters, e.g., those downloaded from a ceremony, before the key generation.
+///
+/// It checks that 1) the first points of G1 and G2 are the generators, 2) every point of G1 is the secret times the previous one, whose secret is that of G2, by a randomized pairing check, and 3) the Lagrange basis commits to the same values as the powers.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+///
+/// # Return values
+/// Return [`ParamsSummary`] if the parameters are valid.
+pub fn check_params(params_path: &str) -> Result<ParamsSummary, ParamsCheckError> {
+    let digest = file_digest(params_path)?;
+    let params = {
+        let mut reader = open_key_file(params_path)?;
+        ParamsKZG::<Bn256>::read(&mut reader)?
+    };
+    let g = params.get_g();
+    if g[0] != G1Affine::generator() || params.g2() != G2Affine::generator() {
+        return Err(ParamsCheckError::InvalidGenerator);
+    }
+
+    // e(sum r_i * g_{i+1}, g2) = e(sum r_i * g_i, s_g2) holds for random r_i iff g_{i+1} = s * g_i for every i.
+    let mut rng = thread_rng();
+    let coeffs = (0..g.len() - 1)
+        .map(|_| Fr::random(&mut rng))
+        .collect::<Vec<Fr>>();
+    let lhs = best_multiexp(&coeffs, &g[1..]).to_affine();
+    let rhs = best_multiexp(&coeffs, &g[..g.len() - 1]).to_affine();
+    if bool::from(params.s_g2().is_identity())
+        || Bn256::pairing(&lhs, &params.g2()) != Bn256::pairing(&rhs, &params.s_g2())
+    {
+        return Err(ParamsCheckError::InconsistentPowers);
+    }
+
+    let domain = EvaluationDomain::<Fr>::new(1, params.k());
+    let mut evals = domain.empty_lagrange();
+    for eval in evals.iter_mut() {
+        *eval = Fr::random(&mut rng);
+    }
+    let lagrange_commitment = params.commit_lagrange(&evals, Blind::default());
+    let commitment = params.commit(&domain.lagrange_to_coeff(evals), Blind::default());
+    if lagrange_commitment != commitment {
+        return Err(ParamsCheckError::InconsistentLagrangeBasis);
+    }
+    Ok(ParamsSummary {
+        k: params.k(),
+        digest,
+    })
+}
+
+/// Error definitions related to the key generation of the regex verification circuit.
+#[cfg(feature = "prover")]
+#[derive(thiserror::Error, Debug)]
+pub enum KeygenError {
+    #[error("The key generation was cancelled")]
+    Cancelled,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Halo2Error(#[from] Error),
+}
+
+/// Generate proving and verifying keys for the regex verification circuit over the curve `E`, e.g., [`Bn256`] for the EVM.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `pk_path` - a file path of the output proving key.
+/// * `vk_path` - a file path of the output verifying key.
+/// * `serde_format` - the serialization format of the keys.
+/// * `cancellation` - a token to cancel the key generation, or `None` if it is never cancelled.
+/// * `progress` - a callback receiving the progress of the key generation, or `None` if it is not reported.
+/// * `circuit` - an regex verification circuit.
+///
+/// # Return values
+/// Return the time taken by each phase as [`Profile`].
+/// Return [`KeygenError::Config`] if the input strings or the lookup tables do not fit in the rows of the parameters, which is checked by [`check_table_rows`] before reading the parameters.
+/// Return [`KeygenError::Cancelled`] if `cancellation` is cancelled, in which case the keys not yet generated are not written.
+/// Each key is accompanied by [`KeyMetadata`] at [`key_metadata_path`], which is checked by [`prove`] and [`verify`].
+#[cfg(feature = "prover")]
+#[tracing::instrument(skip_all, fields(pk_path = %pk_path, vk_path = %vk_path))]
+pub fn gen_keys<E: ProvingEngine, C: Circuit<E::Fr> + 'static>(
+    params_path: &str,
+    pk_path: &str,
+    vk_path: &str,
+    serde_format: KeySerdeFormat,
+    cancellation: Option<&CancellationToken>,
+    progress: Option<&ProgressCallback>,
+    circuit: C,
+) -> Result<Profile, KeygenError> {
+    let check_cancellation = || {
+        if is_cancelled(cancellation) {
+            Err(KeygenError::Cancelled)
+        } else {
+            Ok(())
+        }
+    };
+    let cancelled_or = |e: Error| {
+        if is_cancelled(cancellation) {
+            KeygenError::Cancelled
+        } else {
+            KeygenError::Halo2Error(e)
+        }
+    };
+    check_table_rows(params_k(params_path)? as usize)?;
+    let mut profile = Profiler::new(progress);
+    let start = profile.start("load params");
+    let params = read_params::<E>(params_path)?;
+    profile.record("load params", start);
+    check_cancellation()?;
+
+    let start = profile.start("keygen vk");
+    let vk = E::keygen_vk(&params, &circuit).map_err(cancelled_or)?;
+    profile.record_with_synthesis::<E::Fr, _>("keygen vk", start, std::slice::from_ref(&circuit));
+    tracing::info!("app vk generated");
+    let start = profile.start("write vk");
+    {
+        let f = File::create(vk_path)?;
+        let mut writer = BufWriter::new(f);
+        E::write_vk(&vk, &mut writer, serde_format.into())?;
+        writer.flush()?;
+    }
+    write_key_metadata(vk_path, &KeyMetadata::new())?;
+    profile.record("write vk", start);
+    check_cancellation()?;
+
+    let start = profile.start("keygen pk");
+    let pk = E::keygen_pk(&params, vk.clone(), &circuit).map_err(cancelled_or)?;
+    profile.record_with_synthesis::<E::Fr, _>("keygen pk", start, std::slice::from_ref(&circuit));
+
+    tracing::info!("app pk generated");
+    let start = profile.start("write pk");
+    {
+        let f = File::create(pk_path)?;
+        let mut writer = BufWriter::new(f);
+        E::write_pk(&pk, &mut writer, serde_format.into())?;
+        writer.flush()?;
+    }
+    write_key_metadata(pk_path, &KeyMetadata::new())?;
+    profile.record("write pk", start);
+
+    Ok(profile.profile)
+}
+
+/// The number of the rows used to count the cells of [`RegexCircuit`] in [`plan_columns`], which is large enough to put all the cells in one column.
+const PLANNING_K: usize = 30;
+
+/// The shape of [`RegexCircuit`] set to the current parameters until it is dropped, which then restores the original shape even if the code using it panics.
+struct ShapeOverride {
+    k: usize,
+    num_advice: usize,
+    num_fixed: usize,
+}
+
+impl ShapeOverride {
+    /// Set `k`, `num_advice` and `num_fixed` to the current parameters, and keep their original values.
+    fn new(k: usize, num_advice: usize, num_fixed: usize) -> Self {
+        let mut params = lock(&regexConfigParams);
+        Self {
+            k: std::mem::replace(&mut params.k, k),
+            num_advice: std::mem::replace(&mut params.num_advice, num_advice),
+            num_fixed: std::mem::replace(&mut params.num_fixed, num_fixed),
+        }
+    }
+}
+
+impl Drop for ShapeOverride {
+    fn drop(&mut self) {
+        let mut params = lock(&regexConfigParams);
+        params.k = self.k;
+        params.num_advice = self.num_advice;
+        params.num_fixed = self.num_fixed;
+    }
+}
+
+/// An [`Assignment`] discarding everything assigned to it, which synthesizes a circuit without allocating its rows, unlike [`CircuitCost`] and [`MockProver`].
+struct DryAssignment;
+
+impl<F: Field> Assignment<F> for DryAssignment {
+    fn enter_region<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        _annotation: A,
+        _selector: &Selector,
+        _row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn query_instance(&self, _column: Column<Instance>, _row: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _annotation: A,
+        _column: Column<Advice>,
+        _row: usize,
+        _to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _annotation: A,
+        _column: Column<Fixed>,
+        _row: usize,
+        _to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _left_column: Column<Any>,
+        _left_row: usize,
+        _right_column: Column<Any>,
+        _right_row: usize,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _column: Column<Fixed>,
+        _row: usize,
+        _to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _challenge: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _gadget_name: Option<String>) {}
+}
+
+/// The columns and rows of [`RegexCircuit`] chosen by [`plan_columns`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+pub struct ColumnPlan {
+    /// The degree of the number of rows.
+    pub k: usize,
+    /// The number of the advice columns.
+    pub num_advice: usize,
+    /// The number of the fixed columns.
+    pub num_fixed: usize,
+    /// The number of the advice cells assigned by [`FlexGateConfig`].
+    pub advice_cells: usize,
+    /// The number of the fixed cells assigned by [`FlexGateConfig`].
+    pub fixed_cells: usize,
+    /// The largest number of the rows of the lookup tables.
+    pub lookup_rows: usize,
+    /// The number of the rows usable in each column, i.e., 2^`k` minus the blinding rows.
+    pub usable_rows: usize,
+}
+
+/// Choose the minimum numbers of the advice and fixed columns of [`RegexCircuit`] configured by the current parameters for 2^`k` rows.
+///
+/// The cells are counted by synthesizing the circuit without witnesses in one column of 2^[`PLANNING_K`] rows, whose shape does not depend on the input strings.
+/// The synthesis discards the assigned cells in [`DryAssignment`], so that it takes the memory of the cells of one region rather than that of 2^[`PLANNING_K`] rows.
+/// The chosen numbers are not set to the parameters; call [`set_config_num_columns`] to use them.
+///
+/// # Arguments
+/// * `k` - the degree of the number of rows.
+///
+/// # Return values
+/// Return the chosen [`ColumnPlan`], or [`ConfigError::TooFewRows`] if the input strings or the lookup tables do not fit in 2^`k` rows.
+pub fn plan_columns(k: usize) -> Result<ColumnPlan, ConfigError> {
+    lock(&regexConfigParams).validate()?;
+    let (stats, minimum_rows) = {
+        let _shape = ShapeOverride::new(PLANNING_K, 1, 1);
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = RegexCircuit::<Fr>::configure(&mut meta);
+        let circuit = RegexCircuit::<Fr>::default();
+        <RegexCircuit<Fr> as Circuit<Fr>>::FloorPlanner::synthesize(
+            &mut DryAssignment,
+            &circuit,
+            config,
+            meta.constants().clone(),
+        )
+        .map_err(ConfigError::Synthesis)?;
+        (circuit.synthesis_stats(), meta.minimum_rows())
+    };
+    let params = lock(&regexConfigParams);
+
+    let usable_rows = (1usize << k).saturating_sub(minimum_rows);
+    let (lookup_rows, required_rows) = required_rows(&params);
+    if !fits_in_rows(required_rows, usable_rows) {
+        return Err(ConfigError::TooFewRows { k, required_rows });
+    }
+    let num_advice = ((stats.advice_cells + usable_rows - 4) / (usable_rows - 3)).max(1);
+    let num_fixed = ((stats.fixed_cells + usable_rows - 1) / usable_rows).max(1);
+    Ok(ColumnPlan {
+        k,
+        num_advice,
+        num_fixed,
+        advice_cells: stats.advice_cells,
+        fixed_cells: stats.fixed_cells,
+        lookup_rows,
+        usable_rows,
+    })
+}
+
+/// Return the number of the rows of the largest lookup table and the number of the rows required by the input strings and the lookup tables configured by `params`.
+fn required_rows(params: &RegexVerifyConfigParams) -> (usize, usize) {
+    let lookup_rows = params
+        .inputs
+        .iter()
+        .flat_map(|input| read_regex_defs(input))
+        .map(|defs| match &params.universal_bounds {
+            Some(bounds) => bounds.max_transitions.max(bounds.max_endpoints),
+            None => {
+                let dummy_state = defs.allstr.largest_state_val + 1;
+                let num_transitions = transition_rows(&defs, 1).len();
+                let num_endpoints = endpoint_rows(&defs, 1, dummy_state).len();
+                // The fixed tables have one dummy row.
+                num_transitions.max(num_endpoints) + 1
+            }
+        })
+        .max()
+        .unwrap_or(0);
+    let required_rows = params
+        .inputs
+        .iter()
+        .map(|input| input.max_chars_size + 1)
+        .max()
+        .unwrap_or(0)
+        .max(lookup_rows);
+    (lookup_rows, required_rows)
+}
+
+/// Return true iff `required_rows` fit in `usable_rows` of each column.
+fn fits_in_rows(required_rows: usize, usable_rows: usize) -> bool {
+    // A gate of `FlexGateConfig` does not span two columns, so at most 3 rows are unused at the end of each column.
+    required_rows <= usable_rows && usable_rows > 3
+}
+
+/// Check that the input strings and the lookup tables of [`RegexCircuit`] configured by the current parameters fit in 2^`k` rows, without reading the SRS parameters.
+///
+/// The key generation with too few rows fails inside halo2 with an error that does not tell how many rows are needed.
+///
+/// # Arguments
+/// * `k` - the degree of the number of rows, e.g., [`params_k`] of the parameters.
+///
+/// # Return values
+/// Return [`ConfigError::TooSmallK`] with the minimum `k` if they do not fit, or the error of [`RegexVerifyConfigParams::validate`].
+pub fn check_table_rows(k: usize) -> Result<(), ConfigError> {
+    // `RegexCircuit::configure` panics with an invalid configuration.
+    lock(&regexConfigParams).validate()?;
+    // The blinding rows do not depend on `k`, which may be too small to configure the circuit.
+    let minimum_rows = {
+        let (num_advice, num_fixed) = {
+            let params = lock(&regexConfigParams);
+            (params.num_advice, params.num_fixed)
+        };
+        let _shape = ShapeOverride::new(PLANNING_K, num_advice, num_fixed);
+        let mut meta = ConstraintSystem::<Fr>::default();
+        RegexCircuit::<Fr>::configure(&mut meta);
+        meta.minimum_rows()
+    };
+    let (_, required_rows) = required_rows(&lock(&regexConfigParams));
+    let fits = |k: usize| fits_in_rows(required_rows, (1usize << k).saturating_sub(minimum_rows));
+    if fits(k) {
+        return Ok(());
+    }
+    let required_k = (k + 1..usize::BITS as usize - 1)
+        .find(|k| fits(*k))
+        .unwrap_or(usize::BITS as usize - 1);
+    Err(ConfigError::TooSmallK {
+        k,
+        required_k,
+        required_rows,
+    })
+}
+
+/// A guessed proving time per cell of the advice, fixed and lookup columns, which is used only by [`estimate_cost`].
+///
+/// It is a rough guess for a desktop CPU, which is neither measured nor calibrated, so that the actual time varies by an order of magnitude with the hardware, the number of the threads and `k`; `bench` of the `prover` feature measures it instead.
+const ESTIMATED_PROVING_SECS_PER_CELL: f64 = 1e-5;
+
+/// The cost of [`RegexCircuit`] estimated by [`estimate_cost`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+pub struct CostEstimate {
+    /// The columns and rows chosen by [`plan_columns`].
+    pub plan: ColumnPlan,
+    /// The number of the rows used in each advice column.
+    pub advice_rows: usize,
+    /// The number of the lookup arguments.
+    pub num_lookups: usize,
+    /// The number of the instances.
+    pub num_instances: usize,
+    /// The size of a proof in bytes.
+    pub proof_size: usize,
+    /// A rough guess of the proving time in seconds by a fixed time per cell, which is not measured on this machine.
+    pub estimated_proving_secs: f64,
+}
+
+/// Estimate the cost of [`RegexCircuit`] configured by the current parameters for 2^`k` rows without generating keys.
+///
+/// The columns are chosen by [`plan_columns`].
+///
+/// # Arguments
+/// * `k` - the degree of the number of rows.
+///
+/// # Return values
+/// Return the estimated [`CostEstimate`].
+pub fn estimate_cost(k: usize) -> Result<CostEstimate, ConfigError> {
+    let plan = plan_columns(k)?;
+    let (num_instances, cost, meta) = {
+        let _shape = ShapeOverride::new(k, plan.num_advice, plan.num_fixed);
+        let circuit = RegexCircuit::<Fr>::default();
+        let num_instances = circuit.instances().len();
+        let cost = CircuitCost::<G1, RegexCircuit<Fr>>::measure(k, &circuit);
+        let mut meta = ConstraintSystem::<Fr>::default();
+        RegexCircuit::<Fr>::configure(&mut meta);
+        (num_instances, cost, meta)
+    };
+
+    let num_lookups = meta.lookups().len();
+    let num_columns = meta.num_advice_columns() + meta.num_fixed_columns() + num_lookups;
+    Ok(CostEstimate {
+        plan,
+        advice_rows: (plan.advice_cells + plan.num_advice - 1) / plan.num_advice,
+        num_lookups,
+        num_instances,
+        proof_size: cost.proof_size(num_instances).into(),
+        estimated_proving_secs: (num_columns << k) as f64 * ESTIMATED_PROVING_SECS_PER_CELL,
+    })
+}
+
+/// The number of the bytes of a field element or a coordinate.
+const FIELD_BYTES: u64 = 32;
+
+/// The peak memory of [`gen_keys`] and [`prove`] estimated by [`estimate_memory`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// The degree of the number of rows.
+    pub k: usize,
+    /// The peak memory of the key generation in bytes.
+    pub keygen_bytes: u64,
+    /// The peak memory of the proving in bytes.
+    pub proving_bytes: u64,
+}
+
+/// Error definitions of the pre-flight check of the memory.
+#[derive(thiserror::Error, Debug)]
+pub enum MemoryError {
+    #[error("{required} bytes are required but only {available} bytes are available; reduce k or the number of the columns, or use a machine with more memory")]
+    InsufficientMemory { required: u64, available: u64 },
+    #[error("The available memory cannot be read on this platform")]
+    UnknownAvailableMemory,
+}
+
+/// Estimate the peak memory of [`gen_keys`] and [`prove`] for [`RegexCircuit`] configured by the current parameters for 2^`k` rows and the given columns.
+///
+/// Every column is counted as its values and its coefficients of 2^`k` field elements and its coset evaluations on the extended domain, which is an upper bound of what halo2 keeps at once.
+///
+/// # Arguments
+/// * `k` - the degree of the number of rows.
+/// * `num_advice` - the number of the advice columns.
+/// * `num_fixed` - the number of the fixed columns.
+///
+/// # Return values
+/// Return the estimated [`MemoryEstimate`], or the error of [`RegexVerifyConfigParams::validate`].
+pub fn estimate_memory(
+    k: usize,
+    num_advice: usize,
+    num_fixed: usize,
+) -> Result<MemoryEstimate, ConfigError> {
+    lock(&regexConfigParams).validate()?;
+    let meta = {
+        let _shape = ShapeOverride::new(k, num_advice, num_fixed);
+        let mut meta = ConstraintSystem::<Fr>::default();
+        RegexCircuit::<Fr>::configure(&mut meta);
+        meta
+    };
+
+    let n = 1u64 << k;
+    let extended_n = n * (meta.degree() as u64 - 1).next_power_of_two();
+    let column_bytes = (2 * n + extended_n) * FIELD_BYTES;
+    // `g` and `g_lagrange` of the setup parameters.
+    let params_bytes = 2 * n * 2 * FIELD_BYTES;
+    let num_permutation_columns = meta.permutation().get_columns().len() as u64;
+    // The fixed columns include the selectors, and `l_0`, `l_last` and `l_active_row` are evaluated on the extended domain.
+    let pk_bytes = (meta.num_fixed_columns() + meta.num_selectors()) as u64 * column_bytes
+        + num_permutation_columns * column_bytes
+        + 3 * extended_n * FIELD_BYTES;
+    let num_lookups = meta.lookups().len() as u64;
+    let chunk_len = (meta.degree() as u64 - 2).max(1);
+    let num_permutation_chunks = (num_permutation_columns + chunk_len - 1) / chunk_len;
+    // The permuted input, the permuted table and the product of each lookup, the products of the permutation and the quotient.
+    let witness_bytes = (meta.num_advice_columns() + meta.num_instance_columns()) as u64
+        * column_bytes
+        + (3 * num_lookups + num_permutation_chunks) * column_bytes
+        + 2 * extended_n * FIELD_BYTES;
+    Ok(MemoryEstimate {
+        k,
+        keygen_bytes: params_bytes + pk_bytes,
+        proving_bytes: params_bytes + pk_bytes + witness_bytes,
+    })
+}
+
+/// Estimate the peak memory of [`gen_keys`] and [`prove`] with the setup parameters at `params_path` by [`estimate_memory`], without reading the whole parameters.
+///
+/// The degree of the number of rows is that of the parameters, or that of the current parameters if the parameters are downsized to it as in [`read_params`].
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+///
+/// # Return values
+/// Return the estimated [`MemoryEstimate`], or the error if the header of the parameters cannot be read or the configuration is invalid.
+pub fn estimate_memory_for_params(params_path: &str) -> Result<MemoryEstimate, std::io::Error> {
+    let k = params_k(params_path)? as usize;
+    let (num_advice, num_fixed) = {
+        let config = lock(&regexConfigParams);
+        (config.num_advice, config.num_fixed)
+    };
+    estimate_memory(k, num_advice, num_fixed)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+}
+
+/// Return the degree of the number of rows of the SRS parameters read from only the header of the file, which is downsized as in [`read_params`].
+pub fn params_k(params_path: &str) -> Result<u32, std::io::Error> {
+    // The serialization of `ParamsKZG` starts with `k` in little endian.
+    let mut k = [0; 4];
+    File::open(Path::new(params_path))?.read_exact(&mut k)?;
+    let k = u32::from_le_bytes(k);
+    let config = lock(&regexConfigParams);
+    if config.downsize_params {
+        Ok(k.min(config.k as u32))
+    } else {
+        Ok(k)
+    }
+}
+
+/// Return the memory available to a new process in bytes, or `None` if it cannot be read, i.e., except on Linux.
+pub fn available_memory() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kib = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kib * 1024)
+}
+
+/// Return the peak resident memory of the current process in bytes, or `None` if it cannot be read, i.e., except on Linux.
+pub fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kib * 1024)
+}
+
+/// Check that the machine has `required` bytes of memory available, e.g., [`MemoryEstimate::keygen_bytes`], so that a large keygen or proving aborts before the OOM killer kills it in the middle.
+///
+/// # Arguments
+/// * `required` - the number of the required bytes.
+///
+/// # Return values
+/// Return [`MemoryError`] if the memory is insufficient or unknown.
+pub fn check_memory(required: u64) -> Result<(), MemoryError> {
+    let available = available_memory().ok_or(MemoryError::UnknownAvailableMemory)?;
+    if required > available {
+        return Err(MemoryError::InsufficientMemory {
+            required,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// The rows used by a region of [`RegexCircuit`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RegionMetrics {
+    /// The name of the region.
+    pub name: String,
+    /// The number of the rows used by the region.
+    pub rows: usize,
+}
+
+/// Metrics of [`RegexCircuit`] returned by [`circuit_metrics`], which can be exported as json to track the growth of the circuit across regex changes.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CircuitMetrics {
+    /// The degree of the number of rows.
+    pub k: usize,
+    /// The number of the advice columns.
+    pub num_advice: usize,
+    /// The number of the fixed columns.
+    pub num_fixed: usize,
+    /// The number of the instances.
+    pub num_instances: usize,
+    /// The number of the lookup arguments.
+    pub num_lookups: usize,
+    /// The number of the advice cells assigned by [`FlexGateConfig`].
+    pub advice_cells: usize,
+    /// The number of the fixed cells assigned by [`FlexGateConfig`].
+    pub fixed_cells: usize,
+    /// The rows used by each region in the order of the assignment.
+    pub regions: Vec<RegionMetrics>,
+    /// The rows and cells contributed by each regex definition.
+    pub regex_defs: Vec<RegexDefMetrics>,
+}
+
+/// The rows and cells of [`RegexCircuit`] contributed by one regex definition, which help to find the pattern that makes the circuit large.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RegexDefMetrics {
+    /// The index of the input string.
+    pub input_idx: usize,
+    /// The index of the regex definition in the input string.
+    pub def_idx: usize,
+    /// The number of the rows of the transition table including the dummy row.
+    pub transition_rows: usize,
+    /// The number of the rows of the endpoint table including the dummy row.
+    pub endpoint_rows: usize,
+    /// The number of the rows of each advice column dedicated to the regex definition, i.e., the states, substring ids and start and end flags.
+    pub column_rows: usize,
+    /// The number of the advice cells of [`FlexGateConfig`] assigned for the regex definition.
+    pub advice_cells: usize,
+    /// The number of the rows of the advice columns of [`FlexGateConfig`] taken by `advice_cells`.
+    pub advice_rows: usize,
+}
+
+/// Measure the metrics of [`RegexCircuit`] configured by the current parameters.
+///
+/// # Return values
+/// Return the measured [`CircuitMetrics`], or [`ConfigError`] if the current parameters are invalid.
+pub fn circuit_metrics() -> Result<CircuitMetrics, ConfigError> {
+    let (k, num_advice, num_fixed, inputs, universal) = {
+        let params = lock(&regexConfigParams);
+        params.validate()?;
+        (
+            params.k,
+            params.num_advice,
+            params.num_fixed,
+            params.inputs.clone(),
+            params.universal_bounds.is_some(),
+        )
+    };
+    let circuit = RegexCircuit::<Fr>::default();
+    let num_instances = circuit.instances().len();
+    CircuitCost::<G1, RegexCircuit<Fr>>::measure(k, &circuit);
+    let stats = circuit.synthesis_stats();
+    let mut meta = ConstraintSystem::<Fr>::default();
+    RegexCircuit::<Fr>::configure(&mut meta);
+    let usable_rows = (1usize << k).saturating_sub(meta.minimum_rows());
+
+    let regions = region_metrics(&inputs, universal, &stats, usable_rows);
+    let mut regex_defs = vec![];
+    for (input_idx, input) in inputs.iter().enumerate() {
+        for (def_idx, defs) in read_regex_defs(input).iter().enumerate() {
+            let dummy_state = defs.allstr.largest_state_val + 1;
+            let advice_cells = stats
+                .def_advice_cells
+                .get(input_idx)
+                .and_then(|cells| cells.get(def_idx))
+                .copied()
+                .unwrap_or(0);
+            regex_defs.push(RegexDefMetrics {
+                input_idx,
+                def_idx,
+                transition_rows: transition_rows(defs, 1).len() + 1,
+                endpoint_rows: endpoint_rows(defs, 1, dummy_state).len() + 1,
+                column_rows: input.max_chars_size + 1,
+                advice_cells,
+                advice_rows: (advice_cells + num_advice - 1) / num_advice,
+            });
+        }
+    }
+    Ok(CircuitMetrics {
+        k,
+        num_advice,
+        num_fixed,
+        num_instances,
+        num_lookups: meta.lookups().len(),
+        advice_cells: stats.advice_cells,
+        fixed_cells: stats.fixed_cells,
+        regions,
+        regex_defs,
+    })
+}
+
+/// A summary of the regex definitions of one input string, i.e., what the key generation bakes into the lookup tables.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct RegexInspection {
+    /// The number of the distinct states appearing in the definition of the whole string.
+    pub num_states: usize,
+    /// The first state id.
+    pub first_state: u64,
+    /// The id of the accepted state.
+    pub accepted_state: u64,
+    /// The number of the state transitions of the definition of the whole string.
+    pub num_transitions: usize,
+    /// The characters having at least one state transition in ascending order.
+    pub alphabet: Vec<u8>,
+    /// The ratio of the characters in `alphabet` to all 256 byte values.
+    pub alphabet_coverage: f64,
+    /// The summaries of the substr regex definitions, whose substring ids are 1, 2, ...
+    pub substrs: Vec<SubstrInspection>,
+    /// The number of the rows of the transition table including the dummy row.
+    pub transition_rows: usize,
+    /// The number of the rows of the endpoint table including the dummy row.
+    pub endpoint_rows: usize,
+}
+
+/// A summary of one substr regex definition in [`RegexInspection`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SubstrInspection {
+    /// The substring id assigned in the transition table.
+    pub substr_id: usize,
+    /// The maximum length of the substring.
+    pub max_length: usize,
+    /// The state transitions `(cur_state, next_state)` of the substring in ascending order.
+    pub transitions: Vec<(u64, u64)>,
+    /// The number of the rows of the transition table tagged with `substr_id`, i.e., one per character of each transition.
+    pub tagged_rows: usize,
+    /// The states at which the substring can start in ascending order.
+    pub start_states: Vec<u64>,
+    /// The states at which the substring can end in ascending order.
+    pub end_states: Vec<u64>,
+}
+
+/// Summarize the regex definitions of the input string configured by `params` without generating any key.
+///
+/// # Arguments
+/// * `params` - the parameters of the input string, whose `max_chars_size` is ignored.
+///
+/// # Return values
+/// Return the [`RegexInspection`] of the regex definitions.
+pub fn inspect_regex_defs(params: &RegexInputParams) -> RegexInspection {
+    let defs = read_regex_defs(params).remove(0);
+    let allstr = &defs.allstr;
+    let rows = transition_rows(&defs, 1);
+    let mut states = HashSet::new();
+    states.insert(allstr.first_state_val);
+    states.insert(allstr.accepted_state_val);
+    let mut alphabet = std::collections::BTreeSet::new();
+    for (char, cur_state, next_state, _) in rows.iter() {
+        states.insert(*cur_state);
+        states.insert(*next_state);
+        alphabet.insert(*char);
+    }
+    let substrs = defs
+        .substrs
+        .iter()
+        .enumerate()
+        .map(|(idx, substr_def)| {
+            let substr_id = idx + 1;
+            let mut transitions = substr_def
+                .valid_state_transitions
+                .iter()
+                .copied()
+                .collect::<Vec<(u64, u64)>>();
+            transitions.sort();
+            let mut start_states = substr_def.start_states.clone();
+            start_states.sort();
+            let mut end_states = substr_def.end_states.clone();
+            end_states.sort();
+            SubstrInspection {
+                substr_id,
+                max_length: substr_def.max_length,
+                transitions,
+                tagged_rows: rows.iter().filter(|row| row.3 == substr_id).count(),
+                start_states,
+                end_states,
+            }
+        })
+        .collect();
+    let dummy_state = allstr.largest_state_val + 1;
+    RegexInspection {
+        num_states: states.len(),
+        first_state: allstr.first_state_val,
+        accepted_state: allstr.accepted_state_val,
+        num_transitions: rows.len(),
+        alphabet_coverage: alphabet.len() as f64 / 256.0,
+        alphabet: alphabet.into_iter().collect(),
+        substrs,
+        transition_rows: rows.len() + 1,
+        endpoint_rows: endpoint_rows(&defs, 1, dummy_state).len() + 1,
+    }
+}
+
+/// The report of [`audit_regex_defs`] on the completeness and the consistency of the lookup tables of one input string.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RegexAudit {
+    /// The number of the distinct states appearing in the definition of the whole string.
+    pub num_states: usize,
+    /// The non-accepted state whose 256 characters all move to itself, if any, in which the missing transitions are regarded to end.
+    pub dead_state: Option<u64>,
+    /// The states not defining the transitions of all 256 characters in ascending order.
+    pub incomplete_states: Vec<IncompleteState>,
+    /// The transitions of the substr regex definitions that are not any transition of the definition of the whole string.
+    pub unknown_substr_transitions: Vec<SubstrTransition>,
+    /// The transitions claimed by two or more substr regex definitions, of which the transition table tags only the first one.
+    pub shared_substr_transitions: Vec<SharedTransition>,
+}
+
+/// A state in [`RegexAudit`] without the transitions of some characters.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IncompleteState {
+    /// The state id.
+    pub state: u64,
+    /// The characters without any transition from `state` in ascending order.
+    pub missing_characters: Vec<u8>,
+}
+
+/// A state transition `(cur_state, next_state)` of the substr regex definition of `substr_id`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SubstrTransition {
+    /// The substring id, i.e., 1, 2, ... in the order of the substr regex definitions.
+    pub substr_id: usize,
+    /// The state before the transition.
+    pub cur_state: u64,
+    /// The state after the transition.
+    pub next_state: u64,
+}
+
+/// A state transition `(cur_state, next_state)` claimed by the substr regex definitions of `substr_ids`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SharedTransition {
+    /// The state before the transition.
+    pub cur_state: u64,
+    /// The state after the transition.
+    pub next_state: u64,
+    /// The substring ids claiming the transition in ascending order.
+    pub substr_ids: Vec<usize>,
+}
+
+impl RegexAudit {
+    /// Return true iff every state defines all 256 characters or an explicit dead state exists, and the substr regex definitions reference only the distinct transitions of the definition of the whole string.
+    pub fn is_passed(&self) -> bool {
+        (self.incomplete_states.is_empty() || self.dead_state.is_some())
+            && self.unknown_substr_transitions.is_empty()
+            && self.shared_substr_transitions.is_empty()
+    }
+}
+
+/// Audit the lookup tables of the regex definitions of the input string configured by `params` without generating any key.
+///
+/// # Arguments
+/// * `params` - the parameters of the input string, whose `max_chars_size` is ignored.
+///
+/// # Return values
+/// Return the [`RegexAudit`] of the regex definitions.
+pub fn audit_regex_defs(params: &RegexInputParams) -> RegexAudit {
+    let defs = read_regex_defs(params).remove(0);
+    let allstr = &defs.allstr;
+    let mut states = std::collections::BTreeSet::new();
+    states.insert(allstr.first_state_val);
+    states.insert(allstr.accepted_state_val);
+    let mut allstr_transitions = HashSet::new();
+    for ((_, cur_state), (_, next_state)) in allstr.state_lookup.iter() {
+        states.insert(*cur_state);
+        states.insert(*next_state);
+        allstr_transitions.insert((*cur_state, *next_state));
+    }
+    let dead_state = states.iter().copied().find(|state| {
+        *state != allstr.accepted_state_val
+            && (0..=255u8).all(|char| {
+                allstr
+                    .state_lookup
+                    .get(&(char, *state))
+                    .map(|(_, next)| *next)
+                    == Some(*state)
+            })
+    });
+    let incomplete_states = states
+        .iter()
+        .filter_map(|state| {
+            let missing_characters = (0..=255u8)
+                .filter(|char| !allstr.state_lookup.contains_key(&(*char, *state)))
+                .collect::<Vec<u8>>();
+            if missing_characters.is_empty() {
+                None
+            } else {
+                Some(IncompleteState {
+                    state: *state,
+                    missing_characters,
+                })
+            }
+        })
+        .collect();
+    let mut unknown_substr_transitions = vec![];
+    let mut claimed_transitions = std::collections::BTreeMap::<(u64, u64), Vec<usize>>::new();
+    for (idx, substr_def) in defs.substrs.iter().enumerate() {
+        let mut transitions = substr_def
+            .valid_state_transitions
+            .iter()
+            .copied()
+            .collect::<Vec<(u64, u64)>>();
+        transitions.sort();
+        for (cur_state, next_state) in transitions.into_iter() {
+            if !allstr_transitions.contains(&(cur_state, next_state)) {
+                unknown_substr_transitions.push(SubstrTransition {
+                    substr_id: idx + 1,
+                    cur_state,
+                    next_state,
+                });
+            }
+            claimed_transitions
+                .entry((cur_state, next_state))
+                .or_default()
+                .push(idx + 1);
+        }
+    }
+    let shared_substr_transitions = claimed_transitions
+        .into_iter()
+        .filter(|(_, substr_ids)| substr_ids.len() > 1)
+        .map(|((cur_state, next_state), substr_ids)| SharedTransition {
+            cur_state,
+            next_state,
+            substr_ids,
+        })
+        .collect();
+    RegexAudit {
+        num_states: states.len(),
+        dead_state,
+        incomplete_states,
+        unknown_substr_transitions,
+        shared_substr_transitions,
+    }
+}
+
+/// Return the rows used by each region of [`RegexCircuit`] given the statistics of its synthesis.
+fn region_metrics(
+    inputs: &[RegexInputParams],
+    universal: bool,
+    stats: &SynthesisStats,
+    usable_rows: usize,
+) -> Vec<RegionMetrics> {
+    let mut regions = vec![];
+    // The fixed tables are loaded before the region of the gates, and the universal tables are assigned in it.
+    if !universal {
+        // The identical tables are loaded only once.
+        let mut loaded_tables = HashSet::new();
+        for input in inputs.iter() {
+            let mut substr_id_offset = 1;
+            for defs in read_regex_defs(input).iter() {
+                let dummy_state = defs.allstr.largest_state_val + 1;
+                let is_loaded = !loaded_tables.insert(RegexTableKey::new(defs, substr_id_offset));
+                substr_id_offset += defs.substrs.len();
+                if is_loaded {
+                    continue;
+                }
+                // Each table has one dummy row.
+                regions.push(RegionMetrics {
+                    name: "load transition table".to_string(),
+                    rows: transition_rows(defs, 1).len() + 1,
+                });
+                regions.push(RegionMetrics {
+                    name: "endpoint states".to_string(),
+                    rows: endpoint_rows(defs, 1, dummy_state).len() + 1,
+                });
+            }
+        }
+    }
+    // `FlexGateConfig` fills each advice column before moving to the next one.
+    regions.push(RegionMetrics {
+        name: "regex".to_string(),
+        rows: stats.advice_cells.min(usable_rows),
+    });
+    regions
+}
+
+/// The result of [`dry_run`].
+#[derive(Debug)]
+pub struct DryRunReport {
+    /// The rows used by each region in the order of the assignment.
+    pub regions: Vec<RegionMetrics>,
+    /// The masked output of each input string.
+    pub outputs: Vec<MaskedOutput>,
+    /// The result of verifying the constraints with [`MockProver`], whose failures are described by [`explain_failures`].
+    pub verify_result: Result<(), Vec<String>>,
+}
+
+/// Synthesize `circuit` with its witnesses and check the constraints with [`MockProver`] without proving.
+///
+/// It is useful to iterate on new regex definitions, since neither the proving key nor the proof is generated.
+///
+/// # Arguments
+/// * `params_path` - a file path of the setup parameters, which is read only to know `k`.
+/// * `circuit` - the circuit to synthesize.
+///
+/// # Return values
+/// Return [`DryRunReport`] even if the constraints are not satisfied.
+pub fn dry_run(params_path: &str, circuit: &RegexCircuit<Fr>) -> Result<DryRunReport, Error> {
+    let params = read_params::<Bn256>(params_path)?;
+    mock_run(params.k(), circuit)
+}
+
+/// Synthesize `circuit` with 2^`k` rows and check the constraints with [`MockProver`], which needs neither the setup parameters nor the keys.
+///
+/// # Arguments
+/// * `k` - the degree of the number of rows, which is set by [`set_config_k`].
+/// * `circuit` - the circuit to synthesize.
+///
+/// # Return values
+/// Return [`DryRunReport`] even if the constraints are not satisfied, or [`Error::Synthesis`] if the configuration is invalid or an input of `circuit` fails [`RegexCircuit::check_inputs`].
+pub fn mock_run(k: u32, circuit: &RegexCircuit<Fr>) -> Result<DryRunReport, Error> {
+    set_config_k(k as usize);
+    lock(&regexConfigParams).validate().map_err(|e| {
+        tracing::error!("{}", e);
+        Error::Synthesis
+    })?;
+    let (inputs, universal) = {
+        let params = lock(&regexConfigParams);
+        (params.inputs.clone(), params.universal_bounds.is_some())
+    };
+    let prover = MockProver::run(k, circuit, vec![circuit.instances()])?;
+    let stats = circuit.synthesis_stats();
+    let outputs = circuit.masked_outputs();
+    let minimum_rows = {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        RegexCircuit::<Fr>::configure(&mut meta);
+        meta.minimum_rows()
+    };
+    let usable_rows = (1usize << k).saturating_sub(minimum_rows);
+    Ok(DryRunReport {
+        regions: region_metrics(&inputs, universal, &stats, usable_rows),
+        outputs,
+        verify_result: prover
+            .verify()
+            .map_err(|failures| explain_failures(circuit, &failures)),
+    })
+}
+
+/// Describe `failures` of [`MockProver`] in terms of the characters of the input strings of `circuit` and the states of their regex definitions, instead of the rows and the columns.
+///
+/// A failure of the lookups of a regex definition is located at the character checked by the failed row, e.g., "character at index 417 (byte 0x40) has no transition from state 12 in regex #0".
+/// The other failures are described by their [`Display`](std::fmt::Display).
+///
+/// # Arguments
+/// * `circuit` - the circuit checked by [`MockProver`].
+/// * `failures` - the failures returned by [`MockProver::verify`].
+///
+/// # Return values
+/// Return the description of each failure in the same order.
+pub fn explain_failures<F: PrimeField>(
+    circuit: &RegexCircuit<F>,
+    failures: &[VerifyFailure],
+) -> Vec<String> {
+    let inputs = lock(&regexConfigParams).inputs.clone();
+    // The lookups of each regex definition are configured in the order of the input strings, i.e., those of the transitions, the start states and the end states.
+    let mut lookup_defs = vec![];
+    for (input_idx, params) in inputs.iter().enumerate() {
+        let chars =
+            matched_characters(params, &circuit.input(input_idx).characters).unwrap_or_default();
+        for defs in read_regex_defs(params).into_iter() {
+            let mut name = format!("regex #{}", lookup_defs.len());
+            if inputs.len() > 1 {
+                name.push_str(&format!(" of input #{}", input_idx));
+            }
+            lookup_defs.push((name, chars.clone(), defs));
+        }
+    }
+    failures
+        .iter()
+        .map(|failure| {
+            let explanation = match failure {
+                VerifyFailure::Lookup {
+                    lookup_index,
+                    location,
+                    ..
+                } => lookup_defs
+                    .get(lookup_index / 3)
+                    .map(|(name, chars, defs)| {
+                        let position = match location {
+                            FailureLocation::InRegion { offset, .. } => *offset,
+                            FailureLocation::OutsideRegion { row } => *row,
+                        };
+                        explain_lookup_failure(lookup_index % 3, position, chars, defs, name)
+                    }),
+                _ => None,
+            };
+            explanation.unwrap_or_else(|| failure.to_string())
+        })
+        .collect()
+}
+
+/// Describe `failures` of `circuit` by [`explain_failures`] if it is a [`RegexCircuit`], or by their [`Display`](std::fmt::Display) otherwise, e.g., for a larger circuit proven by [`prove`].
+fn explain_circuit_failures<F: PrimeField, C: 'static>(
+    circuit: &C,
+    failures: &[VerifyFailure],
+) -> Vec<String> {
+    match (circuit as &dyn Any).downcast_ref::<RegexCircuit<F>>() {
+        Some(circuit) => explain_failures(circuit, failures),
+        None => failures.iter().map(|failure| failure.to_string()).collect(),
+    }
+}
+
+/// Describe the failure of the `kind`-th lookup of `defs`, i.e., that of the transitions, the start states or the end states, at the character of `position`.
+fn explain_lookup_failure(
+    kind: usize,
+    position: usize,
+    chars: &[u8],
+    defs: &RegexDefs,
+    name: &str,
+) -> String {
+    let no_transition = |idx: usize, char: u8, state: u64| {
+        format!(
+            "character at index {} (byte 0x{:02x}) has no transition from state {} in {}",
+            idx, char, state, name
+        )
+    };
+    // The first invalid transition is reported even if it precedes the failed row.
+    let trace = defs.trace(chars);
+    if let Some(idx) = trace.failed_position.filter(|idx| *idx <= position) {
+        return no_transition(idx, chars[idx], trace.states[idx]);
+    }
+    if position >= chars.len() {
+        return format!(
+            "row {} after the {} characters is not padded with the dummy state in {}",
+            position,
+            chars.len(),
+            name
+        );
+    }
+    let (char, state, next_state) = (
+        chars[position],
+        trace.states[position],
+        trace.states[position + 1],
+    );
+    let role = match kind {
+        0 => "is assigned a wrong substring id",
+        1 => "does not start any substring",
+        _ => "does not end any substring",
+    };
+    format!(
+        "character at index {} (byte 0x{:02x}) moves from state {} to state {}, which {} in {}",
+        position, char, state, next_state, role, name
+    )
+}
+
+/// Read the setup parameters from `params_path`.
+///
+/// If [`set_config_downsize_params`] is enabled and the parameters have more than 2^`k` rows, they are downsized to `k`.
+/// Otherwise, `k` is set to that of the parameters.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+///
+/// # Return values
+/// Return the read parameters, or the error of opening or reading the file.
+pub fn read_params<E: ProvingEngine>(params_path: &str) -> Result<ParamsKZG<E>, std::io::Error> {
+    read_params_from::<E, _>(&mut open_key_file(params_path)?)
+}
+
+/// Read the SRS parameters from `reader` as [`read_params`] does, e.g., from the bytes of the file.
+pub fn read_params_from<E: ProvingEngine, R: Read>(
+    reader: &mut R,
+) -> Result<ParamsKZG<E>, std::io::Error> {
+    let mut params = E::read_params(reader)?;
+    let k = E::params_k(&params);
+    let mut config = lock(&regexConfigParams);
+    if config.downsize_params && k as usize > config.k {
+        tracing::debug!(from = k, to = config.k, "downsizing the params");
+        E::downsize_params(&mut params, config.k as u32);
+    } else {
+        config.k = k as usize;
+    }
+    Ok(params)
+}
+
+/// Read the verifying key of the circuit `C` over the curve `E` written by [`gen_keys`].
+pub fn read_verifying_key<E: ProvingEngine, C: Circuit<E::Fr>>(
+    vk_path: &str,
+    serde_format: KeySerdeFormat,
+) -> Result<VerifyingKey<E::Affine>, std::io::Error> {
+    read_verifying_key_from::<E, C, _>(&mut open_key_file(vk_path)?, serde_format)
+}
+
+/// Read the verifying key of the circuit `C` over the curve `E` from `reader` as [`read_verifying_key`] does, e.g., from the bytes of the file.
+pub fn read_verifying_key_from<E: ProvingEngine, C: Circuit<E::Fr>, R: Read>(
+    reader: &mut R,
+    serde_format: KeySerdeFormat,
+) -> Result<VerifyingKey<E::Affine>, std::io::Error> {
+    E::read_vk::<C, _>(reader, serde_format.into())
+}
+
+/// Return the digest of `vk`, i.e., its representation absorbed into the transcript, which does not depend on the serialization format of the key.
+pub fn vk_hash(vk: &VerifyingKey<G1Affine>) -> Fr {
+    vk.transcript_repr()
+}
+
+/// Regenerate the verifying key of [`RegexCircuit`] for the input strings `inputs` in memory and check that it is equal to `vk`, e.g., to confirm that a distributed key corresponds to the claimed regexes and parameters.
+///
+/// The other configuration, e.g., the numbers of the columns and the public parts, is the current one, and the configured input strings are restored after the check.
+///
+/// # Arguments
+/// * `params` - the SRS parameters of `vk`.
+/// * `inputs` - the parameters of each input string claimed for `vk`, including the file paths of its regex definitions.
+/// * `vk` - the verifying key to check, e.g., read by [`read_verifying_key`].
+///
+/// # Return values
+/// Return true iff the regenerated key has the same [`vk_hash`] as `vk`, which binds the constraint system and the commitments of the fixed columns including the lookup tables.
+/// Return false if the configuration with `inputs` is invalid or does not fit in the rows of `params`.
+pub fn verify_vk_matches(
+    params: &ParamsKZG<Bn256>,
+    inputs: &[RegexInputParams],
+    vk: &VerifyingKey<G1Affine>,
+) -> bool {
+    let (original_k, original_inputs) = {
+        let mut config = lock(&regexConfigParams);
+        (
+            std::mem::replace(&mut config.k, params.k() as usize),
+            std::mem::replace(&mut config.inputs, inputs.to_vec()),
+        )
+    };
+    let is_valid = check_table_rows(params.k() as usize).is_ok();
+    let regenerated = if is_valid {
+        keygen_vk(params, &RegexCircuit::<Fr>::default()).ok()
+    } else {
+        None
+    };
+    {
+        let mut config = lock(&regexConfigParams);
+        config.k = original_k;
+        config.inputs = original_inputs;
+    }
+    regenerated.map_or(false, |regenerated| vk_hash(&regenerated) == vk_hash(vk))
+}
+
+/// Open `path` to read the setup parameters or a key.
+///
+/// With the `mmap` feature, the file is memory-mapped instead of being copied into the buffer of [`BufReader`](std::io::BufReader), so that its pages are loaded on demand and shared with other processes reading the same file.
+pub(crate) fn open_key_file(path: &str) -> Result<Box<dyn Read>, std::io::Error> {
+    let f = File::open(Path::new(path))?;
+    #[cfg(feature = "mmap")]
+    {
+        // The file must not be modified while it is mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&f) }?;
+        Ok(Box::new(std::io::Cursor::new(mmap)))
+    }
+    #[cfg(not(feature = "mmap"))]
+    Ok(Box::new(std::io::BufReader::new(f)))
+}
+
+/// Error definitions related to proving the regex verification circuit.
+#[cfg(feature = "prover")]
+#[derive(thiserror::Error, Debug)]
+pub enum ProveError {
+    #[error("The sanity check by MockProver failed: {}", .0.join("; "))]
+    SanityCheckFailed(Vec<String>),
+    #[error("The proving key could not be read: {0}")]
+    InvalidProvingKey(std::io::Error),
+    #[error("The proving was cancelled")]
+    Cancelled,
+    #[error("The line {line} of the batch inputs is invalid: {error}")]
+    InvalidBatchInput { line: usize, error: String },
+    #[error(transparent)]
+    InputTooLong(#[from] InputTooLongError),
+    #[error(transparent)]
+    InvalidInput(InvalidInputError),
+    #[error(transparent)]
+    KeyMismatch(#[from] KeyMismatchError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Halo2Error(#[from] Error),
+}
+
+/// An input string of [`RegexCircuit`] longer than its configured maximum length, which is found by [`RegexCircuit::check_input_lengths`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("The input string {input_idx} has {len} bytes, but the circuit is configured for at most {max_chars_size} bytes")]
+pub struct InputTooLongError {
+    pub input_idx: usize,
+    pub len: usize,
+    pub max_chars_size: usize,
+}
+
+/// An input of [`RegexCircuit`] for which the circuit cannot be synthesized, which is found by [`RegexCircuit::check_inputs`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum InvalidInputError {
+    #[error(transparent)]
+    TooLong(#[from] InputTooLongError),
+    #[error("The input string {input_idx} does not match its regexes: {error}")]
+    Unmatched {
+        input_idx: usize,
+        error: DeriveSubstrsError,
+    },
+    #[error("The substring {substr_id} of the input string {input_idx} ends at {end}, but the circuit is configured for at most {max_chars_size} bytes")]
+    SubstrOutOfRange {
+        input_idx: usize,
+        substr_id: usize,
+        end: usize,
+        max_chars_size: usize,
+    },
+    #[error("The substring {substr_id} of the input string {input_idx} is not the one extracted by its regexes")]
+    WrongSubstr { input_idx: usize, substr_id: usize },
+    #[error("The substring id {substr_id} is not in 1..={max_substr_id}")]
+    InvalidSubstrId {
+        substr_id: usize,
+        max_substr_id: usize,
+    },
+    #[error("The public part {part_idx} is committed without its salt")]
+    MissingSalt { part_idx: usize },
+}
+
+#[cfg(feature = "prover")]
+impl From<InvalidInputError> for ProveError {
+    fn from(error: InvalidInputError) -> Self {
+        match error {
+            InvalidInputError::TooLong(error) => ProveError::InputTooLong(error),
+            error => ProveError::InvalidInput(error),
+        }
+    }
+}
+
+/// Return [`ProveError::Cancelled`] instead of `error` if `cancellation` is cancelled, or [`ProveError::InputTooLong`] or [`ProveError::InvalidInput`] if the synthesis failed by an invalid input of `circuits` found by [`RegexCircuit::check_inputs`], since all of them fail with [`Error::Synthesis`].
+#[cfg(feature = "prover")]
+fn cancelled_or<F: PrimeField, C: 'static>(
+    cancellation: Option<&CancellationToken>,
+    circuits: &[C],
+    error: Error,
+) -> ProveError {
+    if is_cancelled(cancellation) {
+        return ProveError::Cancelled;
+    }
+    let input_error = match error {
+        Error::Synthesis => circuits
+            .iter()
+            .filter_map(|circuit| (circuit as &dyn Any).downcast_ref::<RegexCircuit<F>>())
+            .find_map(|circuit| circuit.check_inputs().err()),
+        _ => None,
+    };
+    match input_error {
+        Some(input_error) => ProveError::from(input_error),
+        None => ProveError::Halo2Error(error),
+    }
+}
+
+/// Generate a proof for the regex verification circuit over the curve `E`.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `pk_path` - a file path of the proving key.
+/// * `serde_format` - the serialization format of the proving key.
+/// * `sanity_check` - whether the constraints are checked by [`MockProver`] before proving, which doubles the witness generation time.
+/// * `seed` - the seed of the RNG used to blind the proof, which makes the proof reproducible, or `None` to use a random seed.
+/// * `cancellation` - a token to cancel the proving, or `None` if it is never cancelled.
+/// * `progress` - a callback receiving the progress of the proving, or `None` if it is not reported.
+/// * `proof_path` - a file path of the output proof.
+/// * `circuit` - a regex verification circuit, whose [`RegexCircuit::masked_outputs`] are those of the proving.
+/// * `instances` - the instances of the circuit, e.g., the output of [`RegexCircuit::instances`].
+///
+/// # Return values
+/// Return [`ProveError::KeyMismatch`] if the proving key was generated from other regex definitions, [`ProveError::SanityCheckFailed`] if `sanity_check` is true and the constraints are not satisfied, [`ProveError::InvalidProvingKey`] if the proving key is not in `serde_format`, [`ProveError::InputTooLong`] if an input string is longer than its maximum length, [`ProveError::InvalidInput`] if an input string does not match its regexes or its expected substrings, [`ProveError::Config`] if the configuration is invalid, or [`ProveError::Cancelled`] if `cancellation` is cancelled.
+/// Otherwise, the proof is written even if it is invalid, and the time taken by each phase is returned as [`Profile`].
+#[cfg(feature = "prover")]
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all, fields(proof_path = %proof_path))]
+pub fn prove<E: ProvingEngine, C: Circuit<E::Fr> + 'static>(
+    params_path: &str,
+    pk_path: &str,
+    serde_format: KeySerdeFormat,
+    sanity_check: bool,
+    seed: Option<[u8; 32]>,
+    cancellation: Option<&CancellationToken>,
+    progress: Option<&ProgressCallback>,
+    proof_path: &str,
+    circuit: &C,
+    instances: &[E::Fr],
+) -> Result<Profile, ProveError> {
+    let check_cancellation = || {
+        if is_cancelled(cancellation) {
+            Err(ProveError::Cancelled)
+        } else {
+            Ok(())
+        }
+    };
+    lock(&regexConfigParams).validate()?;
+    check_key_metadata(pk_path)?;
+    let mut profile = Profiler::new(progress);
+    let start = profile.start("load params");
+    let params = read_params::<E>(params_path)?;
+    let k = E::params_k(&params);
+    profile.record("load params", start);
+    check_cancellation()?;
+
+    if sanity_check {
+        let start = profile.start("sanity check");
+        let prover = MockProver::run(k, circuit, vec![instances.to_vec()]).map_err(|e| {
+            cancelled_or::<E::Fr, _>(cancellation, std::slice::from_ref(circuit), e)
+        })?;
+        prover.verify().map_err(|failures| {
+            ProveError::SanityCheckFailed(explain_circuit_failures::<E::Fr, _>(circuit, &failures))
+        })?;
+        profile.record("sanity check", start);
+        check_cancellation()?;
+    }
+
+    let start = profile.start("load pk");
+    let pk = {
+        let mut reader = open_key_file(pk_path)?;
+        E::read_pk::<C, _>(&mut reader, serde_format.into())
+            .map_err(ProveError::InvalidProvingKey)?
+    };
+    profile.record("load pk", start);
+    check_cancellation()?;
+    let (proof, mut proving) = prove_with_keys(
+        &params,
+        &pk,
+        false,
+        seed,
+        cancellation,
+        progress,
+        circuit,
+        instances,
+    )?;
+    let start = profile.start("write proof");
+    {
+        let f = File::create(proof_path)?;
+        let mut writer = BufWriter::new(f);
+        writer.write_all(&proof)?;
+        writer.flush()?;
+    };
+    write_proof_metadata(proof_path, &ProofMetadata::new(k, instances))?;
+    profile.profile.phases.append(&mut proving.phases);
+    profile.record("write proof", start);
+    Ok(profile.profile)
+}
+
+/// Generate a proof for the regex verification circuit with the setup parameters and the proving key already loaded, e.g., by [`KeyStore`](crate::keystore::KeyStore).
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `pk` - the proving key.
+/// * `sanity_check` - whether the constraints are checked by [`MockProver`] before proving.
+/// * `seed` - the seed of the RNG used to blind the proof, which makes the proof reproducible, or `None` to use a random seed.
+/// * `cancellation` - a token to cancel the proving, or `None` if it is never cancelled.
+/// * `progress` - a callback receiving the progress of the proving, or `None` if it is not reported.
+/// * `circuit` - a regex verification circuit, whose [`RegexCircuit::masked_outputs`] are those of the proving.
+/// * `instances` - the instances of the circuit, e.g., the output of [`RegexCircuit::instances`].
+///
+/// # Return values
+/// Return the bytes of the proof and the time taken by each phase as [`Profile`], [`ProveError::SanityCheckFailed`] if `sanity_check` is true and the constraints are not satisfied, [`ProveError::InputTooLong`] if an input string is longer than its maximum length, [`ProveError::InvalidInput`] if an input string does not match its regexes or its expected substrings, or [`ProveError::Cancelled`] if `cancellation` is cancelled.
+#[cfg(feature = "prover")]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_with_keys<E: ProvingEngine, C: Circuit<E::Fr> + 'static>(
+    params: &ParamsKZG<E>,
+    pk: &ProvingKey<E::Affine>,
+    sanity_check: bool,
+    seed: Option<[u8; 32]>,
+    cancellation: Option<&CancellationToken>,
+    progress: Option<&ProgressCallback>,
+    circuit: &C,
+    instances: &[E::Fr],
+) -> Result<(Vec<u8>, Profile), ProveError> {
+    prove_batch_with_keys(
+        params,
+        pk,
+        sanity_check,
+        seed,
+        cancellation,
+        progress,
+        std::slice::from_ref(circuit),
+        &[instances.to_vec()],
+    )
+}
+
+/// Generate one proof for a batch of the regex verification circuits with the same proving key and different inputs, which amortizes the opening over the circuits.
+///
+/// The proof is verified by [`verify_batch_with_keys`] with the instances in the same order.
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `pk` - the proving key shared by the circuits.
+/// * `sanity_check` - whether the constraints of each circuit are checked by [`MockProver`] before proving.
+/// * `seed` - the seed of the RNG used to blind the proof, which makes the proof reproducible, or `None` to use a random seed.
+/// * `cancellation` - a token to cancel the proving, or `None` if it is never cancelled.
+/// * `progress` - a callback receiving the progress of the proving, or `None` if it is not reported.
+/// * `circuits` - the regex verification circuits.
+/// * `instances` - the instances of each circuit, e.g., the outputs of [`RegexCircuit::instances`].
+///
+/// # Return values
+/// Return the bytes of the proof and the time taken by each phase as [`Profile`], [`ProveError::SanityCheckFailed`] if `sanity_check` is true and the constraints of any circuit are not satisfied, [`ProveError::InputTooLong`] if an input string is longer than its maximum length, [`ProveError::InvalidInput`] if an input string does not match its regexes or its expected substrings, or [`ProveError::Cancelled`] if `cancellation` is cancelled.
+#[cfg(feature = "prover")]
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all, fields(num_circuits = circuits.len()))]
+pub fn prove_batch_with_keys<E: ProvingEngine, C: Circuit<E::Fr> + 'static>(
+    params: &ParamsKZG<E>,
+    pk: &ProvingKey<E::Affine>,
+    sanity_check: bool,
+    seed: Option<[u8; 32]>,
+    cancellation: Option<&CancellationToken>,
+    progress: Option<&ProgressCallback>,
+    circuits: &[C],
+    instances: &[Vec<E::Fr>],
+) -> Result<(Vec<u8>, Profile), ProveError> {
+    assert_eq!(circuits.len(), instances.len());
+    let mut profile = Profiler::new(progress);
+    let k = E::params_k(params);
+    set_config_k(k as usize);
+    if sanity_check {
+        let start = profile.start("sanity check");
+        for (circuit, instances) in circuits.iter().zip(instances.iter()) {
+            let prover = MockProver::run(k, circuit, vec![instances.clone()]).map_err(|e| {
+                cancelled_or::<E::Fr, _>(cancellation, std::slice::from_ref(circuit), e)
+            })?;
+            prover.verify().map_err(|failures| {
+                ProveError::SanityCheckFailed(explain_circuit_failures::<E::Fr, _>(
+                    circuit, &failures,
+                ))
+            })?;
+        }
+        profile.record("sanity check", start);
+    }
+    if is_cancelled(cancellation) {
+        return Err(ProveError::Cancelled);
+    }
+    let start = profile.start("fft, commitments and opening");
+    let rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    let instances = instances
+        .iter()
+        .map(|instances| vec![&instances[..]])
+        .collect::<Vec<Vec<&[E::Fr]>>>();
+    let instances = instances
+        .iter()
+        .map(|instances| &instances[..])
+        .collect::<Vec<&[&[E::Fr]]>>();
+    let proof = E::create_proof(params, pk, circuits, &instances, rng)
+        .map_err(|e| cancelled_or::<E::Fr, _>(cancellation, circuits, e))?;
+    profile.record_with_synthesis::<E::Fr, _>("fft, commitments and opening", start, circuits);
+    Ok((proof, profile.profile))
+}
+
+/// An input string read from a line of the jsonl file of [`prove_batch`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct BatchProveInput {
+    /// The string to verify.
+    #[serde(default)]
+    pub string: Option<String>,
+    /// A file path of the raw bytes to verify, which is used if `string` is not given.
+    #[serde(default)]
+    pub input_path: Option<String>,
+    /// The encoding of `string`.
+    #[serde(default)]
+    pub encoding: InputEncoding,
+    /// The expected substrings and their positions, which are derived by [`derive_correct_substrs`] if empty.
+    #[serde(default)]
+    pub correct_substrs: Vec<(usize, String)>,
+    /// Whether the regex match is expected to pass.
+    #[serde(default)]
+    pub is_success: bool,
+}
+
+impl BatchProveInput {
+    /// Return the circuit proving this input.
+    fn circuit(&self) -> Result<RegexCircuit<Fr>, String> {
+        let characters = match (&self.string, &self.input_path) {
+            (Some(string), _) => decode_input(string, self.encoding)
+                .ok_or_else(|| format!("the string is not valid {:?}", self.encoding))?,
+            (None, Some(input_path)) => fs::read(input_path).map_err(|e| e.to_string())?,
+            (None, None) => return Err("either string or input_path is required".to_string()),
+        };
+        let correct_substrs = if self.correct_substrs.is_empty() && self.is_success {
+            derive_correct_substrs(0, &characters).map_err(|e| e.to_string())?
+        } else {
+            self.correct_substrs.clone()
+        };
+        let circuit = RegexCircuit {
+            characters,
+            correct_substrs,
+            is_success: self.is_success,
+            ..Default::default()
+        };
+        circuit.check_inputs().map_err(|e| e.to_string())?;
+        Ok(circuit)
+    }
+}
+
+/// The files written by [`prove_batch`] for one input string.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchProofOutput {
+    /// A file path of the proof.
+    pub proof_path: String,
+    /// A file path of the instances written by [`write_instances`].
+    pub instances_path: String,
+}
+
+/// Generate a proof for each input string in a jsonl file with the setup parameters and the proving key loaded only once.
+///
+/// Each line of the file is a json of [`BatchProveInput`], and the proof and the instances of the `i`-th line are written to `{proof_dir}/{i}.proof` and `{proof_dir}/{i}.instances.json`.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `pk_path` - a file path of the proving key.
+/// * `serde_format` - the serialization format of the proving key.
+/// * `seed` - the seed of the RNG used to blind each proof, or `None` to use a random seed.
+/// * `cancellation` - a token to cancel the proving, or `None` if it is never cancelled.
+/// * `progress` - a callback receiving the progress of each proving, or `None` if it is not reported.
+/// * `inputs_path` - a file path of the jsonl inputs.
+/// * `proof_dir` - a directory of the output proofs and instances.
+///
+/// # Return values
+/// Return the files written for each line, [`ProveError::InvalidBatchInput`] if a line is invalid, or [`ProveError::KeyMismatch`] if the proving key was generated from other regex definitions, in which case no proof is generated.
+#[cfg(feature = "prover")]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_batch(
+    params_path: &str,
+    pk_path: &str,
+    serde_format: KeySerdeFormat,
+    seed: Option<[u8; 32]>,
+    cancellation: Option<&CancellationToken>,
+    progress: Option<&ProgressCallback>,
+    inputs_path: &str,
+    proof_dir: &str,
+) -> Result<Vec<BatchProofOutput>, ProveError> {
+    let circuits = fs::read_to_string(inputs_path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str::<BatchProveInput>(line)
+                .map_err(|e| e.to_string())
+                .and_then(|input| input.circuit())
+                .map_err(|error| ProveError::InvalidBatchInput {
+                    line: idx + 1,
+                    error,
+                })
+        })
+        .collect::<Result<Vec<RegexCircuit<Fr>>, ProveError>>()?;
+    lock(&regexConfigParams).validate()?;
+    check_key_metadata(pk_path)?;
+    let params = read_params::<Bn256>(params_path)?;
+    let pk = {
+        let mut reader = open_key_file(pk_path)?;
+        ProvingKey::<G1Affine>::read::<_, RegexCircuit<Fr>>(&mut reader, serde_format.into())
+            .map_err(ProveError::InvalidProvingKey)?
+    };
+    fs::create_dir_all(proof_dir)?;
+    let mut outputs = vec![];
+    for (idx, mut circuit) in circuits.into_iter().enumerate() {
+        circuit.cancellation = cancellation.cloned();
+        circuit.progress = progress.cloned();
+        let instances = circuit.instances();
+        let (proof, _) = prove_with_keys(
+            &params,
+            &pk,
+            false,
+            seed,
+            cancellation,
+            progress,
+            &circuit,
+            &instances,
+        )?;
+        let output = BatchProofOutput {
+            proof_path: Path::new(proof_dir)
+                .join(format!("{}.proof", idx))
+                .to_string_lossy()
+                .to_string(),
+            instances_path: Path::new(proof_dir)
+                .join(format!("{}.instances.json", idx))
+                .to_string_lossy()
+                .to_string(),
+        };
+        fs::write(&output.proof_path, &proof)?;
+        write_instances(&output.instances_path, &instances)?;
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
+
+/// Return the proofs in `proof_dir` written by [`prove_batch`], i.e., each `{name}.proof` with its `{name}.instances.json`, sorted by the file names.
+pub fn batch_proof_outputs(proof_dir: &str) -> Result<Vec<BatchProofOutput>, std::io::Error> {
+    let mut outputs = vec![];
+    for entry in fs::read_dir(proof_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("proof") {
+            continue;
+        }
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        outputs.push(BatchProofOutput {
+            proof_path: path.to_string_lossy().to_string(),
+            instances_path: path
+                .with_file_name(format!("{}.instances.json", stem))
+                .to_string_lossy()
+                .to_string(),
+        });
+    }
+    outputs.sort_by(|a, b| a.proof_path.cmp(&b.proof_path));
+    Ok(outputs)
+}
+
+/// The result of [`verify_batch`] for one proof.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchVerifyResult {
+    /// A file path of the proof.
+    pub proof_path: String,
+    /// Whether the proof is valid.
+    pub valid: bool,
+    /// The reason why the proof is invalid or it and its instances could not be read, if any.
+    pub error: Option<String>,
+}
+
+/// Verify each proof and its instances, e.g., written by [`prove_batch`], with the setup parameters and the verifying key loaded only once.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `vk_path` - a file path of the verifying key.
+/// * `serde_format` - the serialization format of the verifying key.
+/// * `proofs` - the file paths of the proofs and their instances.
+///
+/// # Return values
+/// Return the result of each proof in the same order, or the error if the verifying key cannot be read.
+pub fn verify_batch(
+    params_path: &str,
+    vk_path: &str,
+    serde_format: KeySerdeFormat,
+    proofs: &[BatchProofOutput],
+) -> Result<Vec<BatchVerifyResult>, std::io::Error> {
+    lock(&regexConfigParams)
+        .validate()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let params = read_params::<Bn256>(params_path)?;
+    let vk = {
+        let mut reader = open_key_file(vk_path)?;
+        VerifyingKey::<G1Affine>::read::<_, RegexCircuit<Fr>>(&mut reader, serde_format.into())?
+    };
+    let results = proofs
+        .iter()
+        .map(|output| {
+            let read = fs::read(&output.proof_path)
+                .and_then(|proof| Ok((proof, read_instances(&output.instances_path)?)));
+            match read {
+                Ok((proof, instances)) => {
+                    let result = check_num_instances(&instances)
+                        .and_then(|_| {
+                            check_proof_metadata(&output.proof_path, params.k(), &instances)
+                        })
+                        .and_then(|_| verify_with_keys(&params, &vk, &proof, &instances));
+                    BatchVerifyResult {
+                        proof_path: output.proof_path.clone(),
+                        valid: result.is_ok(),
+                        error: result.err().map(|e| e.to_string()),
+                    }
+                }
+                Err(e) => BatchVerifyResult {
+                    proof_path: output.proof_path.clone(),
+                    valid: false,
+                    error: Some(e.to_string()),
+                },
+            }
+        })
+        .collect();
+    Ok(results)
+}
+
+/// The format of the file of the instances of a proof.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum InstancesFormat {
+    /// A json array of the big-endian hex strings of the field elements.
+    #[default]
+    Json,
+    /// The concatenation of the 32-byte little-endian representations of the field elements.
+    Binary,
+}
+
+/// Write `instances` as a json array of their hex strings.
+pub fn write_instances(instances_path: &str, instances: &[Fr]) -> Result<(), std::io::Error> {
+    write_instances_with_format(instances_path, instances, InstancesFormat::Json)
+}
+
+/// Write `instances` in `format`, which is read by [`read_instances`] whichever the format is.
+pub fn write_instances_with_format(
+    instances_path: &str,
+    instances: &[Fr],
+    format: InstancesFormat,
+) -> Result<(), std::io::Error> {
+    let f = File::create(instances_path)?;
+    let mut writer = BufWriter::new(f);
+    match format {
+        InstancesFormat::Json => {
+            let hexes = instances
+                .iter()
+                .map(|instance| format!("{:?}", instance))
+                .collect::<Vec<String>>();
+            serde_json::to_writer_pretty(&mut writer, &hexes)?;
+        }
+        InstancesFormat::Binary => {
+            for instance in instances.iter() {
+                writer.write_all(&instance.to_bytes())?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Read the instances written by [`write_instances`] or [`write_instances_with_format`], whose format is detected from the content.
+pub fn read_instances(instances_path: &str) -> Result<Vec<Fr>, std::io::Error> {
+    decode_instances(&fs::read(instances_path)?)
+}
+
+/// Decode the bytes of the instances in either format of [`InstancesFormat`], which is detected from the content as [`read_instances`] does.
+pub fn decode_instances(bytes: &[u8]) -> Result<Vec<Fr>, std::io::Error> {
+    // The binary instances are not a valid json in practice, since the first one is the digest of the regex definitions.
+    if let Ok(hexes) = serde_json::from_slice::<Vec<String>>(bytes) {
+        return hexes
+            .iter()
+            .map(|hex| {
+                parse_fr_hex(hex).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{} is not a hex string of a field element", hex),
+                    )
+                })
+            })
+            .collect();
+    }
+    if bytes.len() % 32 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "the binary instances are not a multiple of 32 bytes",
+        ));
+    }
+    bytes
+        .chunks(32)
+        .map(|chunk| {
+            let mut repr = [0u8; 32];
+            repr.copy_from_slice(chunk);
+            Option::from(Fr::from_bytes(&repr)).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "the binary instances have a non-canonical field element",
+                )
+            })
+        })
+        .collect()
+}
+
+/// The metadata of a proof written by [`prove`] to [`proof_metadata_path`], which makes the proof self-describing.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProofMetadata {
+    /// The version of this crate that generated the proof.
+    pub crate_version: String,
+    /// The hex of the digest of the regex definitions of each input string, i.e., its first instance.
+    pub regex_digests: Vec<String>,
+    /// The degree of the number of rows.
+    pub k: u32,
+    /// The maximum length of each input string.
+    pub max_chars_sizes: Vec<usize>,
+    /// The number of the advice columns, or `None` for the proofs generated by the older versions.
+    #[serde(default)]
+    pub num_advice: Option<usize>,
+    /// The number of the fixed columns, or `None` for the proofs generated by the older versions.
+    #[serde(default)]
+    pub num_fixed: Option<usize>,
+    /// The multiopen scheme of the proof.
+    pub multiopen: String,
+    /// The transcript of the proof.
+    pub transcript: String,
+    /// The hex strings of the instances.
+    pub instances: Vec<String>,
+}
+
+impl ProofMetadata {
+    /// Construct the metadata of a proof of the configured [`RegexCircuit`].
+    ///
+    /// # Arguments
+    /// * `k` - the degree of the number of rows of the setup parameters.
+    /// * `instances` - the instances of the proof.
+    pub fn new<F: PrimeField>(k: u32, instances: &[F]) -> Self {
+        let config = lock(&regexConfigParams);
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            regex_digests: regex_digests::<Fr>(&config)
+                .iter()
+                .map(|digest| format!("{:?}", digest))
+                .collect(),
+            k,
+            max_chars_sizes: config
+                .inputs
+                .iter()
+                .map(|input| input.max_chars_size)
+                .collect(),
+            num_advice: Some(config.num_advice),
+            num_fixed: Some(config.num_fixed),
+            multiopen: "gwc".to_string(),
+            transcript: "blake2b".to_string(),
+            instances: instances
+                .iter()
+                .map(|instance| format!("{:?}", instance))
+                .collect(),
+        }
+    }
+
+    /// Return the names of the fields of `other` different from those of `self`, except for the crate version and the fields missing in `other`.
+    pub fn mismatches(&self, other: &Self) -> Vec<&'static str> {
+        let mut mismatches = vec![];
+        if self.regex_digests != other.regex_digests {
+            mismatches.push("regex_digests");
+        }
+        if self.k != other.k {
+            mismatches.push("k");
+        }
+        if self.max_chars_sizes != other.max_chars_sizes {
+            mismatches.push("max_chars_sizes");
+        }
+        if other.num_advice.is_some() && self.num_advice != other.num_advice {
+            mismatches.push("num_advice");
+        }
+        if other.num_fixed.is_some() && self.num_fixed != other.num_fixed {
+            mismatches.push("num_fixed");
+        }
+        if self.multiopen != other.multiopen {
+            mismatches.push("multiopen");
+        }
+        if self.transcript != other.transcript {
+            mismatches.push("transcript");
+        }
+        if self.instances != other.instances {
+            mismatches.push("instances");
+        }
+        mismatches
+    }
+}
+
+/// Return the file path of the metadata of the proof at `proof_path`, e.g., `app.proof.json` of `app.proof`.
+pub fn proof_metadata_path(proof_path: &str) -> String {
+    format!("{}.json", proof_path)
+}
+
+/// Write `metadata` to [`proof_metadata_path`] of `proof_path`.
+pub fn write_proof_metadata(
+    proof_path: &str,
+    metadata: &ProofMetadata,
+) -> Result<(), std::io::Error> {
+    let f = File::create(proof_metadata_path(proof_path))?;
+    let mut writer = BufWriter::new(f);
+    serde_json::to_writer_pretty(&mut writer, metadata)?;
+    writer.flush()
+}
+
+/// Read the metadata written by [`write_proof_metadata`] for the proof at `proof_path`.
+pub fn read_proof_metadata(proof_path: &str) -> Result<ProofMetadata, std::io::Error> {
+    let f = File::open(proof_metadata_path(proof_path))?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(f))?)
+}
+
+/// Check that the configuration matches the metadata of the proof at `proof_path`, before reading the proof.
+///
+/// The metadata is optional, e.g., for the proofs generated by the older versions, in which case the check passes.
+///
+/// # Arguments
+/// * `proof_path` - a file path of the proof.
+/// * `k` - the degree of the number of rows of the setup parameters.
+/// * `instances` - the instances to verify the proof with.
+///
+/// # Return values
+/// Return [`VerificationError::MetadataMismatch`] with the names of the mismatched fields.
+pub fn check_proof_metadata<F: PrimeField>(
+    proof_path: &str,
+    k: u32,
+    instances: &[F],
+) -> Result<(), VerificationError> {
+    let metadata = match read_proof_metadata(proof_path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(()),
+    };
+    let mismatches = ProofMetadata::new(k, instances).mismatches(&metadata);
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(VerificationError::MetadataMismatch(mismatches))
+    }
+}
+
+/// The metadata of a proving or verifying key written by [`gen_keys`] to [`key_metadata_path`], which binds the key to the regex definitions it was generated from.
+///
+/// The lookup tables of the regex definitions are fixed in the keys, so that a key paired with other regex definition files produces or accepts only invalid proofs.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeyMetadata {
+    /// The version of this crate that generated the key.
+    pub crate_version: String,
+    /// The hex of the digest of the regex definitions of each input string.
+    pub regex_digests: Vec<String>,
+    /// The maximum length of each input string.
+    pub max_chars_sizes: Vec<usize>,
+}
+
+impl KeyMetadata {
+    /// Construct the metadata of a key of the configured [`RegexCircuit`].
+    pub fn new() -> Self {
+        let config = lock(&regexConfigParams);
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            regex_digests: regex_digests::<Fr>(&config)
+                .iter()
+                .map(|digest| format!("{:?}", digest))
+                .collect(),
+            max_chars_sizes: config
+                .inputs
+                .iter()
+                .map(|input| input.max_chars_size)
+                .collect(),
+        }
+    }
+
+    /// Return the names of the fields of `other` different from those of `self`, except for the crate version.
+    pub fn mismatches(&self, other: &Self) -> Vec<&'static str> {
+        let mut mismatches = vec![];
+        if self.regex_digests != other.regex_digests {
+            mismatches.push("regex_digests");
+        }
+        if self.max_chars_sizes != other.max_chars_sizes {
+            mismatches.push("max_chars_sizes");
+        }
+        mismatches
+    }
+}
+
+impl Default for KeyMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The error of [`check_key_metadata`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("The key {key_path} was generated from other regex definitions than the configured ones: {} mismatch", .mismatches.join(", "))]
+pub struct KeyMismatchError {
+    /// The file path of the key.
+    pub key_path: String,
+    /// The names of the mismatched fields of [`KeyMetadata`].
+    pub mismatches: Vec<&'static str>,
+}
+
+/// Return the file path of the metadata of the key at `key_path`, e.g., `app.vk.json` of `app.vk`.
+pub fn key_metadata_path(key_path: &str) -> String {
+    format!("{}.json", key_path)
+}
+
+/// Write `metadata` to [`key_metadata_path`] of `key_path`.
+pub fn write_key_metadata(key_path: &str, metadata: &KeyMetadata) -> Result<(), std::io::Error> {
+    let f = File::create(key_metadata_path(key_path))?;
+    let mut writer = BufWriter::new(f);
+    serde_json::to_writer_pretty(&mut writer, metadata)?;
+    writer.flush()
+}
+
+/// Read the metadata written by [`write_key_metadata`] for the key at `key_path`.
+pub fn read_key_metadata(key_path: &str) -> Result<KeyMetadata, std::io::Error> {
+    let f = File::open(key_metadata_path(key_path))?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(f))?)
+}
+
+/// Check that the key at `key_path` was generated from the configured regex definitions, before reading the key.
+///
+/// The metadata is optional, e.g., for the keys generated by the older versions, in which case the check passes.
+///
+/// # Return values
+/// Return [`KeyMismatchError`] if the metadata of the key differs from [`KeyMetadata::new`].
+pub fn check_key_metadata(key_path: &str) -> Result<(), KeyMismatchError> {
+    let metadata = match read_key_metadata(key_path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(()),
+    };
+    let mismatches = KeyMetadata::new().mismatches(&metadata);
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(KeyMismatchError {
+            key_path: key_path.to_string(),
+            mismatches,
+        })
+    }
+}
+
+/// Parse the big-endian hex string of a field element, i.e., the output of its debug format.
+pub(crate) fn parse_fr_hex(hex: &str) -> Option<Fr> {
+    let hex = hex.trim_start_matches("0x");
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    for (idx, byte) in bytes.iter_mut().rev().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * idx..2 * idx + 2], 16).ok()?;
+    }
+    Option::from(Fr::from_bytes(&bytes))
+}
+
+/// Error definitions related to verifying a proof of the regex verification circuit.
+#[derive(thiserror::Error, Debug)]
+pub enum VerificationError {
+    #[error("The proof could not be decoded, e.g., it is truncated or generated with another transcript: {0}")]
+    InvalidTranscript(std::io::Error),
+    #[error("The pairing check failed, i.e., the proof does not match the instances or the verifying key")]
+    PairingFailed,
+    #[error("{found} instances are given, but the configured circuit has {expected} instances")]
+    InstanceCount { expected: usize, found: usize },
+    #[error("The instances do not fit in the instance column of the verifying key: {0:?}")]
+    InstanceShape(Error),
+    #[error(transparent)]
+    KeyMismatch(#[from] KeyMismatchError),
+    #[error("The configuration mismatches the metadata of the proof: {}", .0.join(", "))]
+    MetadataMismatch(Vec<&'static str>),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Halo2Error(Error),
+}
+
+impl From<Error> for VerificationError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Transcript(e) => Self::InvalidTranscript(e),
+            Error::ConstraintSystemFailure | Error::Opening => Self::PairingFailed,
+            Error::InvalidInstances | Error::InstanceTooLarge => Self::InstanceShape(error),
+            _ => Self::Halo2Error(error),
+        }
+    }
+}
+
+/// Check that the number of `instances` is equal to that of [`RegexCircuit::instances`] configured by the current parameters, since the missing instances are regarded as zeros by the verifier.
+///
+/// # Return values
+/// Return [`VerificationError::InstanceCount`] if the numbers are different.
+pub fn check_num_instances(instances: &[Fr]) -> Result<(), VerificationError> {
+    let expected = RegexCircuit::<Fr>::default().instances().len();
+    if instances.len() != expected {
+        return Err(VerificationError::InstanceCount {
+            expected,
+            found: instances.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Verify a proof of the regex verification circuit written by [`prove`].
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `vk_path` - a file path of the verifying key.
+/// * `serde_format` - the serialization format of the verifying key.
+/// * `proof_path` - a file path of the proof.
+/// * `circuit` - a regex verification circuit, whose witnesses are not used.
+/// * `instances` - the instances of the circuit.
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not, e.g., [`VerificationError::MetadataMismatch`] if the configuration does not match the metadata of the proof or [`VerificationError::KeyMismatch`] if the verifying key was generated from other regex definitions, which are checked before reading the proof.
+#[tracing::instrument(skip_all, fields(proof_path = %proof_path))]
+pub fn verify<E: ProvingEngine, C: Circuit<E::Fr>>(
+    params_path: &str,
+    vk_path: &str,
+    serde_format: KeySerdeFormat,
+    proof_path: &str,
+    _circuit: C,
+    instances: &[E::Fr],
+) -> Result<(), VerificationError> {
+    lock(&regexConfigParams).validate()?;
+    check_proof_metadata(proof_path, params_k(params_path)?, instances)?;
+    check_key_metadata(vk_path)?;
+    let params = read_params::<E>(params_path)?;
+    let vk = read_verifying_key::<E, C>(vk_path, serde_format)?;
+    let proof = fs::read(proof_path)?;
+    verify_with_keys(&params, &vk, &proof, instances)
+}
+
+/// Verify a proof of the regex verification circuit with the setup parameters and the verifying key already loaded, e.g., by [`KeyStore`](crate::keystore::KeyStore).
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `vk` - the verifying key.
+/// * `proof` - the bytes of the proof.
+/// * `instances` - the instances of the circuit.
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not.
+pub fn verify_with_keys<E: ProvingEngine>(
+    params: &ParamsKZG<E>,
+    vk: &VerifyingKey<E::Affine>,
+    proof: &[u8],
+    instances: &[E::Fr],
+) -> Result<(), VerificationError> {
+    verify_batch_with_keys(params, vk, proof, &[instances.to_vec()])
+}
+
+/// Verify a proof of a batch of the regex verification circuits generated by [`prove_batch_with_keys`].
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `vk` - the verifying key shared by the circuits.
+/// * `proof` - the bytes of the proof.
+/// * `instances` - the instances of each circuit in the order of the proving.
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not.
+pub fn verify_batch_with_keys<E: ProvingEngine>(
+    params: &ParamsKZG<E>,
+    vk: &VerifyingKey<E::Affine>,
+    proof: &[u8],
+    instances: &[Vec<E::Fr>],
+) -> Result<(), VerificationError> {
+    let instances = instances
+        .iter()
+        .map(|instances| vec![&instances[..]])
+        .collect::<Vec<Vec<&[E::Fr]>>>();
+    let instances = instances
+        .iter()
+        .map(|instances| &instances[..])
+        .collect::<Vec<&[&[E::Fr]]>>();
+    E::verify_proof(params, vk, proof, &instances).map_err(|e| {
+        tracing::debug!("the proof is rejected: {:?}", e);
+        VerificationError::from(e)
+    })
+}
+
+/// The mean and the standard deviation of the times taken by the iterations of a phase of [`bench`].
+#[cfg(feature = "prover")]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimingStats {
+    /// The mean time in seconds.
+    pub mean_secs: f64,
+    /// The sample standard deviation of the times in seconds, which is zero for a single iteration.
+    pub stddev_secs: f64,
+}
+
+#[cfg(feature = "prover")]
+impl TimingStats {
+    /// Return the statistics of `secs`, the time of each iteration in seconds.
+    pub fn new(secs: &[f64]) -> Self {
+        if secs.is_empty() {
+            return Self::default();
+        }
+        let n = secs.len() as f64;
+        let mean_secs = secs.iter().sum::<f64>() / n;
+        let stddev_secs = if secs.len() > 1 {
+            (secs.iter().map(|s| (s - mean_secs).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        Self {
+            mean_secs,
+            stddev_secs,
+        }
+    }
+}
+
+/// The measurements of [`bench`].
+#[cfg(feature = "prover")]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct BenchReport {
+    /// The degree of the number of rows.
+    pub k: u32,
+    /// The number of the iterations.
+    pub iterations: usize,
+    /// The times of generating the proving and verifying keys.
+    pub keygen: TimingStats,
+    /// The times of generating the proof.
+    pub prove: TimingStats,
+    /// The times of verifying the proof.
+    pub verify: TimingStats,
+    /// The size of the proof in bytes.
+    pub proof_size: usize,
+    /// The peak resident memory of the process in bytes, or `None` if it cannot be read.
+    pub peak_memory_bytes: Option<u64>,
+    /// Whether the proofs of all the iterations are valid.
+    pub valid: bool,
+}
+
+/// Measure the key generation, the proving and the verification of the regex verification circuit repeatedly, without writing the keys and the proofs.
+///
+/// Each iteration regenerates the keys from scratch and proves with a fixed seed, so that the iterations are comparable with each other.
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `circuit` - a regex verification circuit.
+/// * `instances` - the instances of the circuit, e.g., the output of [`RegexCircuit::instances`].
+/// * `iterations` - the number of the iterations, which must be positive.
+///
+/// # Return values
+/// Return [`BenchReport`], or [`ProveError`] if the key generation or the proving fails.
+#[cfg(feature = "prover")]
+pub fn bench<C: Circuit<Fr> + 'static>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+    instances: &[Fr],
+    iterations: usize,
+) -> Result<BenchReport, ProveError> {
+    assert!(iterations > 0);
+    let mut keygen_secs = vec![];
+    let mut prove_secs = vec![];
+    let mut verify_secs = vec![];
+    let mut proof_size = 0;
+    let mut valid = true;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let vk = keygen_vk(params, circuit)?;
+        let pk = keygen_pk(params, vk, circuit)?;
+        keygen_secs.push(start.elapsed().as_secs_f64());
+
+        let start = Instant::now();
+        let (proof, _) = prove_with_keys(
+            params,
+            &pk,
+            false,
+            Some([0; 32]),
+            None,
+            None,
+            circuit,
+            instances,
+        )?;
+        prove_secs.push(start.elapsed().as_secs_f64());
+        proof_size = proof.len();
+
+        let start = Instant::now();
+        valid &= verify_with_keys(params, pk.get_vk(), &proof, instances).is_ok();
+        verify_secs.push(start.elapsed().as_secs_f64());
+    }
+    Ok(BenchReport {
+        k: params.k(),
+        iterations,
+        keygen: TimingStats::new(&keygen_secs),
+        prove: TimingStats::new(&prove_secs),
+        verify: TimingStats::new(&verify_secs),
+        proof_size,
+        peak_memory_bytes: peak_memory(),
+        valid,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cancel_one_of_concurrent_syntheses() {
+        set_config_params(
+            "./test_regexes/regex4_test_lookup.txt".to_string(),
+            "./test_regexes/substr4_test_lookup.txt".to_string(),
+        );
+        set_config_max_chars_size(64);
+        set_config_k(13);
+        let circuit = |cancellation| RegexCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            correct_substrs: vec![(7, "1000".to_string())],
+            is_success: true,
+            cancellation,
+            ..Default::default()
+        };
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let cancelled = circuit(Some(cancellation));
+        let running = circuit(None);
+        let (cancelled, running) = std::thread::scope(|scope| {
+            let cancelled = scope
+                .spawn(|| MockProver::run(13, &cancelled, vec![cancelled.instances()]).map(|_| ()));
+            let running = scope.spawn(|| {
+                MockProver::run(13, &running, vec![running.instances()])
+                    .map(|prover| prover.verify().is_ok())
+            });
+            (cancelled.join().unwrap(), running.join().unwrap())
+        });
+        assert!(matches!(cancelled, Err(Error::Synthesis)));
+        assert!(matches!(running, Ok(true)));
+    }
+
+    #[test]
+    fn test_report_progress_to_each_circuit() {
+        set_config_params(
+            "./test_regexes/regex4_test_lookup.txt".to_string(),
+            "./test_regexes/substr4_test_lookup.txt".to_string(),
+        );
+        set_config_max_chars_size(64);
+        set_config_k(13);
+        let (progress, receiver) = ProgressCallback::channel();
+        let reported = RegexCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            correct_substrs: vec![(7, "1000".to_string())],
+            is_success: true,
+            progress: Some(progress),
+            ..Default::default()
+        };
+        let silent = RegexCircuit::<Fr> {
+            progress: None,
+            ..reported.clone()
+        };
+        MockProver::run(13, &silent, vec![silent.instances()]).unwrap();
+        assert!(receiver.try_recv().is_err());
+        MockProver::run(13, &reported, vec![reported.instances()]).unwrap();
+        let events = receiver.try_iter().collect_vec();
+        assert_eq!(
+            events.first(),
+            Some(&ProgressEvent::PhaseStarted {
+                name: "witness synthesis".to_string()
+            })
+        );
+        assert!(matches!(
+            events.last(),
+            Some(ProgressEvent::PhaseFinished(phase)) if phase.name == "witness synthesis"
+        ));
+    }
+
+    #[test]
+    fn test_masked_outputs_of_each_circuit() {
+        set_config_params(
+            "./test_regexes/regex4_test_lookup.txt".to_string(),
+            "./test_regexes/substr4_test_lookup.txt".to_string(),
+        );
+        set_config_max_chars_size(64);
+        set_config_k(13);
+        let circuit = |string: &str, substr: &str| RegexCircuit::<Fr> {
+            characters: string.as_bytes().to_vec(),
+            correct_substrs: vec![(7, substr.to_string())],
+            is_success: true,
+            ..Default::default()
+        };
+        let first = circuit("amount=1000;", "1000");
+        let second = circuit("amount=25;", "25");
+        assert!(first.masked_outputs().is_empty());
+        MockProver::run(13, &first, vec![first.instances()]).unwrap();
+        MockProver::run(13, &second, vec![second.instances()]).unwrap();
+        let substrs = |circuit: &RegexCircuit<Fr>| {
+            circuit
+                .masked_outputs()
+                .iter()
+                .map(|output| output.substrs())
+                .collect_vec()
+        };
+        let target = |string: &str| TargetSubstr {
+            pos: 7,
+            string: string.to_string(),
+            substr_id: 1,
+        };
+        assert_eq!(substrs(&first), vec![vec![target("1000")]]);
+        assert_eq!(substrs(&second), vec![vec![target("25")]]);
+    }
+
+    #[test]
+    fn test_synthesis_stats_of_each_circuit() {
+        set_config_params(
+            "./test_regexes/regex4_test_lookup.txt".to_string(),
+            "./test_regexes/substr4_test_lookup.txt".to_string(),
+        );
+        set_config_max_chars_size(64);
+        set_config_k(13);
+        let circuit = || RegexCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            correct_substrs: vec![(7, "1000".to_string())],
+            is_success: true,
+            ..Default::default()
+        };
+        let first = circuit();
+        let second = circuit();
+        MockProver::run(13, &first.clone(), vec![first.instances()]).unwrap();
+        // The clone synthesized above shares the statistics with `first`, but not with `second`.
+        assert!(first.synthesis_stats().advice_cells > 0);
+        assert_eq!(first.synthesis_stats().def_advice_cells.len(), 1);
+        assert_eq!(second.synthesis_stats().advice_cells, 0);
+        assert_eq!(second.synthesis_stats().synthesis_secs, 0.0);
+    }
+
+    #[test]
+    fn test_targets_of_substr_id_zero() {
+        let target = |substr_id| TargetSubstr {
+            pos: 7,
+            string: "1000".to_string(),
+            substr_id,
+        };
+        assert_eq!(
+            targets_to_correct_substrs(&[target(1)]),
+            Ok(vec![(7, "1000".to_string())])
+        );
+        assert!(matches!(
+            targets_to_correct_substrs(&[target(0)]),
+            Err(InvalidInputError::InvalidSubstrId { substr_id: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_commit_without_salt() {
+        let part = |commit_only| PublicPart {
+            input_idx: 0,
+            substr_id: 1,
+            max_length: 4,
+            commit_only,
+            as_address: false,
+            expected: false,
+            timestamp_format: None,
+        };
+        let public_parts = vec![part(false), part(true)];
+        let mut circuit = RegexCircuit::<Fr> {
+            salts: vec![Fr::one()],
+            ..Default::default()
+        };
+        assert_eq!(
+            circuit.check_salts(&public_parts),
+            Err(InvalidInputError::MissingSalt { part_idx: 1 })
+        );
+        circuit.salts = vec![Fr::one(), Fr::from(2)];
+        assert_eq!(circuit.check_salts(&public_parts), Ok(()));
+    }
+
+    #[test]
+    fn test_too_many_removed_chars() {
+        let params = |max_qp_removed_size, max_folding_removed_size| {
+            let mut input = RegexInputParams::new(
+                "./test_regexes/regex4_test_lookup.txt".to_string(),
+                "./test_regexes/substr4_test_lookup.txt".to_string(),
+                64,
+            );
+            input.transfer_encoding = TransferEncoding::QuotedPrintable;
+            input.max_qp_removed_size = max_qp_removed_size;
+            input.folding_mode = Some(FoldingMode::Unfold);
+            input.max_folding_removed_size = max_folding_removed_size;
+            RegexVerifyConfigParams {
+                k: 13,
+                inputs: vec![input],
+                substr_equalities: vec![],
+                public_parts: vec![],
+                full_transparency: false,
+                commit_instances: false,
+                universal_bounds: None,
+                num_advice: default_num_advice(),
+                num_fixed: default_num_fixed(),
+                downsize_params: false,
+                max_substr_id: DEFAULT_MAX_SUBSTR_ID,
+            }
+        };
+        assert!(params(64, 64).validate().is_ok());
+        assert!(matches!(
+            params(65, 64).validate(),
+            Err(ConfigError::TooManyQpRemovedChars {
+                input_idx: 0,
+                max_chars_size: 64,
+                max_qp_removed_size: 65,
+            })
+        ));
+        assert!(matches!(
+            params(64, 65).validate(),
+            Err(ConfigError::TooManyFoldingRemovedChars {
+                input_idx: 0,
+                max_chars_size: 64,
+                max_folding_removed_size: 65,
+            })
+        ));
+    }
+
+    #[cfg(feature = "prover")]
+    #[test]
+    fn test_invalid_input_of_failed_synthesis() {
+        set_config_params(
+            "./test_regexes/regex4_test_lookup.txt".to_string(),
+            "./test_regexes/substr4_test_lookup.txt".to_string(),
+        );
+        set_config_max_chars_size(64);
+        let circuit = |string: &str| RegexCircuit::<Fr> {
+            characters: string.as_bytes().to_vec(),
+            correct_substrs: vec![(7, "1000".to_string())],
+            is_success: true,
+            ..Default::default()
+        };
+        let valid = circuit("amount=1000;");
+        let invalid = circuit("amount=x;");
+        assert!(matches!(
+            cancelled_or::<Fr, _>(None, &[valid.clone()], Error::Synthesis),
+            ProveError::Halo2Error(Error::Synthesis)
+        ));
+        assert!(matches!(
+            cancelled_or::<Fr, _>(None, &[valid, invalid.clone()], Error::Synthesis),
+            ProveError::InvalidInput(InvalidInputError::Unmatched { input_idx: 0, .. })
+        ));
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        assert!(matches!(
+            cancelled_or::<Fr, _>(Some(&cancellation), &[invalid], Error::Synthesis),
+            ProveError::Cancelled
+        ));
+    }
 }
@@ -0,0 +1,125 @@
+use crate::helpers::{
+    check_table_rows, open_key_file, set_config_k, KeygenError, ProveError, VerificationError,
+};
+use halo2_base::halo2_proofs::halo2curves::pasta::{EqAffine, Fp};
+use halo2_base::halo2_proofs::plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+};
+use halo2_base::halo2_proofs::poly::commitment::{Params, ParamsProver};
+use halo2_base::halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+use halo2_base::halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+use halo2_base::halo2_proofs::poly::ipa::strategy::SingleStrategy;
+use halo2_base::halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Generate the IPA parameters over the Pasta curves, which are derived from a hash to the curve without any trusted setup.
+///
+/// # Arguments
+/// * `params_path` - a file path of the IPA parameters.
+/// * `k` - the SRS size.
+pub fn gen_ipa_params(params_path: &str, k: u32) -> Result<(), Error> {
+    let params = ParamsIPA::<EqAffine>::new(k);
+    let f = File::create(params_path)?;
+    let mut writer = BufWriter::new(f);
+    params.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read the IPA parameters written by [`gen_ipa_params`] and set `k` to that of the parameters.
+///
+/// # Arguments
+/// * `params_path` - a file path of the IPA parameters.
+///
+/// # Return values
+/// Return the read parameters, or the error of opening or reading the file.
+pub fn read_ipa_params(params_path: &str) -> Result<ParamsIPA<EqAffine>, std::io::Error> {
+    let params = ParamsIPA::<EqAffine>::read(&mut open_key_file(params_path)?)?;
+    set_config_k(params.k() as usize);
+    Ok(params)
+}
+
+/// Generate the proving key of the circuit `C` for the IPA parameters.
+///
+/// # Arguments
+/// * `params` - the IPA parameters.
+/// * `circuit` - a circuit over the scalar field of the Pallas curve, e.g., [`RegexCircuit<Fp>`](crate::helpers::RegexCircuit).
+///
+/// # Return values
+/// Return the proving key, or [`KeygenError::Config`] if the input strings or the lookup tables do not fit in the rows of the parameters.
+pub fn gen_ipa_keys<C: Circuit<Fp>>(
+    params: &ParamsIPA<EqAffine>,
+    circuit: &C,
+) -> Result<ProvingKey<EqAffine>, KeygenError> {
+    check_table_rows(params.k() as usize)?;
+    let vk = keygen_vk(params, circuit)?;
+    Ok(keygen_pk(params, vk, circuit)?)
+}
+
+/// Generate a proof of the circuit `C` with the IPA commitment scheme, which is not verifiable on the EVM.
+///
+/// # Arguments
+/// * `params` - the IPA parameters.
+/// * `pk` - the proving key generated by [`gen_ipa_keys`].
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+/// * `circuit` - a circuit over the scalar field of the Pallas curve.
+/// * `instances` - the instances of the circuit, e.g., the output of [`RegexCircuit::instances`](crate::helpers::RegexCircuit::instances).
+///
+/// # Return values
+/// Return the bytes of the proof.
+pub fn prove_ipa<C: Circuit<Fp>>(
+    params: &ParamsIPA<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    seed: Option<[u8; 32]>,
+    circuit: C,
+    instances: &[Fp],
+) -> Result<Vec<u8>, ProveError> {
+    let rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[instances]],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Verify a proof generated by [`prove_ipa`].
+///
+/// # Arguments
+/// * `params` - the IPA parameters.
+/// * `vk` - the verifying key, e.g., that of the proving key generated by [`gen_ipa_keys`].
+/// * `proof` - the bytes of the proof.
+/// * `instances` - the instances of the circuit.
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not.
+pub fn verify_ipa(
+    params: &ParamsIPA<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[Fp],
+) -> Result<(), VerificationError> {
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    let verifier_params = params.verifier_params();
+    let strategy = SingleStrategy::new(verifier_params);
+    verify_proof::<_, VerifierIPA<_>, _, _, _>(
+        verifier_params,
+        vk,
+        strategy,
+        &[&[instances]],
+        &mut transcript,
+    )
+    .map_err(|e| {
+        tracing::debug!("the proof is rejected: {:?}", e);
+        VerificationError::from(e)
+    })
+}
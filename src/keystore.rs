@@ -0,0 +1,135 @@
+use crate::helpers::{open_key_file, KeySerdeFormat};
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_base::halo2_proofs::plonk::{Circuit, ProvingKey, VerifyingKey};
+use halo2_base::halo2_proofs::poly::commitment::Params;
+use halo2_base::halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// An in-memory cache of the setup parameters and the keys, so that a server proving many requests reads and deserializes each of them only once.
+///
+/// The parameters are keyed by their file paths, and the keys are keyed by the digest of the regex definitions, e.g., [`regex_commitment`](crate::helpers::regex_commitment).
+/// Since the keys also depend on the other parameters of [`RegexCircuit`](crate::helpers::RegexCircuit), e.g., the numbers of the columns, the digest must be distinct for each such configuration.
+#[derive(Debug, Default)]
+pub struct KeyStore {
+    params: HashMap<String, Arc<ParamsKZG<Bn256>>>,
+    proving_keys: HashMap<[u8; 32], Arc<ProvingKey<G1Affine>>>,
+    verifying_keys: HashMap<[u8; 32], Arc<VerifyingKey<G1Affine>>>,
+}
+
+impl KeyStore {
+    /// Construct a new empty [`KeyStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the setup parameters read from `params_path`, which are read only at the first call.
+    ///
+    /// # Arguments
+    /// * `params_path` - a file path of the SRS parameters.
+    ///
+    /// # Return values
+    /// Return the cached parameters, or the error if they cannot be read.
+    pub fn load_params(
+        &mut self,
+        params_path: &str,
+    ) -> Result<Arc<ParamsKZG<Bn256>>, std::io::Error> {
+        if let Some(params) = self.params.get(params_path) {
+            return Ok(params.clone());
+        }
+        let mut reader = open_key_file(params_path)?;
+        let params = Arc::new(ParamsKZG::<Bn256>::read(&mut reader)?);
+        self.params.insert(params_path.to_string(), params.clone());
+        Ok(params)
+    }
+
+    /// Return the proving key of the regex definitions whose digest is `digest`, which is read from `pk_path` only if it is not cached.
+    ///
+    /// The verifying key included in the proving key is also cached.
+    ///
+    /// # Arguments
+    /// * `digest` - the digest of the regex definitions.
+    /// * `pk_path` - a file path of the proving key.
+    /// * `serde_format` - the serialization format of the proving key.
+    ///
+    /// # Return values
+    /// Return the cached proving key, or the error if it cannot be read.
+    pub fn load_proving_key<C: Circuit<Fr>>(
+        &mut self,
+        digest: Fr,
+        pk_path: &str,
+        serde_format: KeySerdeFormat,
+    ) -> Result<Arc<ProvingKey<G1Affine>>, std::io::Error> {
+        if let Some(pk) = self.proving_key(digest) {
+            return Ok(pk);
+        }
+        let mut reader = open_key_file(pk_path)?;
+        let pk = ProvingKey::<G1Affine>::read::<_, C>(&mut reader, serde_format.into())?;
+        Ok(self.insert_proving_key(digest, pk))
+    }
+
+    /// Return the verifying key of the regex definitions whose digest is `digest`, which is read from `vk_path` only if it is not cached.
+    ///
+    /// # Arguments
+    /// * `digest` - the digest of the regex definitions.
+    /// * `vk_path` - a file path of the verifying key.
+    /// * `serde_format` - the serialization format of the verifying key.
+    ///
+    /// # Return values
+    /// Return the cached verifying key, or the error if it cannot be read.
+    pub fn load_verifying_key<C: Circuit<Fr>>(
+        &mut self,
+        digest: Fr,
+        vk_path: &str,
+        serde_format: KeySerdeFormat,
+    ) -> Result<Arc<VerifyingKey<G1Affine>>, std::io::Error> {
+        if let Some(vk) = self.verifying_key(digest) {
+            return Ok(vk);
+        }
+        let mut reader = open_key_file(vk_path)?;
+        let vk = VerifyingKey::<G1Affine>::read::<_, C>(&mut reader, serde_format.into())?;
+        Ok(self.insert_verifying_key(digest, vk))
+    }
+
+    /// Cache `pk`, e.g., generated by `keygen_pk`, and its verifying key for the regex definitions whose digest is `digest`.
+    pub fn insert_proving_key(
+        &mut self,
+        digest: Fr,
+        pk: ProvingKey<G1Affine>,
+    ) -> Arc<ProvingKey<G1Affine>> {
+        let key = digest.to_bytes();
+        self.verifying_keys
+            .insert(key, Arc::new(pk.get_vk().clone()));
+        let pk = Arc::new(pk);
+        self.proving_keys.insert(key, pk.clone());
+        pk
+    }
+
+    /// Cache `vk` for the regex definitions whose digest is `digest`.
+    pub fn insert_verifying_key(
+        &mut self,
+        digest: Fr,
+        vk: VerifyingKey<G1Affine>,
+    ) -> Arc<VerifyingKey<G1Affine>> {
+        let vk = Arc::new(vk);
+        self.verifying_keys.insert(digest.to_bytes(), vk.clone());
+        vk
+    }
+
+    /// Return the cached proving key of the regex definitions whose digest is `digest`, if any.
+    pub fn proving_key(&self, digest: Fr) -> Option<Arc<ProvingKey<G1Affine>>> {
+        self.proving_keys.get(&digest.to_bytes()).cloned()
+    }
+
+    /// Return the cached verifying key of the regex definitions whose digest is `digest`, if any.
+    pub fn verifying_key(&self, digest: Fr) -> Option<Arc<VerifyingKey<G1Affine>>> {
+        self.verifying_keys.get(&digest.to_bytes()).cloned()
+    }
+
+    /// Remove the cached keys of the regex definitions whose digest is `digest`.
+    pub fn remove_keys(&mut self, digest: Fr) {
+        let key = digest.to_bytes();
+        self.proving_keys.remove(&key);
+        self.verifying_keys.remove(&key);
+    }
+}
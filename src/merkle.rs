@@ -0,0 +1,136 @@
+use crate::defs::RegexDefs;
+use crate::gadgets::poseidon::poseidon_hash;
+use crate::table::canonical_transition_rows;
+use halo2_base::utils::PrimeField;
+
+/// A Merkle tree whose leaves are the state transitions of regex definitions, which lets a registry publish the root and anyone audit each transition of the regex.
+///
+/// Each leaf is the Poseidon hash of `(character, cur_state, next_state, substr_id)` taken from the transition table in the canonical order, and each node is the Poseidon hash of its two children.
+/// The leaves are padded with zeros to a power of two.
+#[derive(Debug, Clone)]
+pub struct RegexMerkleTree<F: PrimeField> {
+    rows: Vec<(u8, u64, u64, usize)>,
+    layers: Vec<Vec<F>>,
+}
+
+/// A Merkle proof that one state transition is included in [`RegexMerkleTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionMerkleProof<F: PrimeField> {
+    /// The index of the leaf.
+    pub leaf_idx: usize,
+    /// The included transition `(character, cur_state, next_state, substr_id)`.
+    pub row: (u8, u64, u64, usize),
+    /// The sibling nodes from the leaf to the root.
+    pub siblings: Vec<F>,
+}
+
+impl<F: PrimeField> RegexMerkleTree<F> {
+    /// Construct a new [`RegexMerkleTree`].
+    ///
+    /// # Arguments
+    /// * `regex_defs` - a vector of regex definitions applied to the input string.
+    ///
+    /// # Return values
+    /// Return a new [`RegexMerkleTree`].
+    pub fn new(regex_defs: &[RegexDefs]) -> Self {
+        let mut rows = vec![];
+        let mut substr_id_offset = 1;
+        for defs in regex_defs.iter() {
+            rows.append(&mut canonical_transition_rows(defs, substr_id_offset));
+            substr_id_offset += defs.substrs.len();
+        }
+        let mut leaves = rows.iter().map(transition_leaf).collect::<Vec<F>>();
+        leaves.resize(rows.len().next_power_of_two(), F::zero());
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let nodes = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| poseidon_hash(pair))
+                .collect::<Vec<F>>();
+            layers.push(nodes);
+        }
+        Self { rows, layers }
+    }
+
+    /// Return the root of the tree.
+    pub fn root(&self) -> F {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// Return the state transitions of the leaves in order.
+    pub fn rows(&self) -> &[(u8, u64, u64, usize)] {
+        &self.rows
+    }
+
+    /// Return the Merkle proof of the `leaf_idx`-th state transition, or `None` if it does not exist.
+    pub fn proof(&self, leaf_idx: usize) -> Option<TransitionMerkleProof<F>> {
+        let row = *self.rows.get(leaf_idx)?;
+        let mut idx = leaf_idx;
+        let mut siblings = vec![];
+        for layer in self.layers[..self.layers.len() - 1].iter() {
+            siblings.push(layer[idx ^ 1]);
+            idx /= 2;
+        }
+        Some(TransitionMerkleProof {
+            leaf_idx,
+            row,
+            siblings,
+        })
+    }
+}
+
+impl<F: PrimeField> TransitionMerkleProof<F> {
+    /// Return true iff the proof derives `root`.
+    pub fn verify(&self, root: F) -> bool {
+        let mut node = transition_leaf(&self.row);
+        let mut idx = self.leaf_idx;
+        for sibling in self.siblings.iter() {
+            node = if idx % 2 == 0 {
+                poseidon_hash(&[node, *sibling])
+            } else {
+                poseidon_hash(&[*sibling, node])
+            };
+            idx /= 2;
+        }
+        node == root
+    }
+}
+
+fn transition_leaf<F: PrimeField>(row: &(u8, u64, u64, usize)) -> F {
+    let (char, cur_state, next_state, substr_id) = *row;
+    poseidon_hash(&[
+        F::from(char as u64),
+        F::from(cur_state),
+        F::from(next_state),
+        F::from(substr_id as u64),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AllstrRegexDef, SubstrRegexDef};
+    use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_transition_merkle_proofs() {
+        let regex_defs = vec![RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                "./test_regexes/substr4_test_lookup.txt",
+            )],
+        }];
+        let tree = RegexMerkleTree::<Fr>::new(&regex_defs);
+        let root = tree.root();
+        for leaf_idx in 0..tree.rows().len() {
+            assert!(tree.proof(leaf_idx).unwrap().verify(root));
+        }
+        assert!(tree.proof(tree.rows().len()).is_none());
+
+        let mut proof = tree.proof(0).unwrap();
+        proof.row.0 += 1;
+        assert!(!proof.verify(root));
+    }
+}
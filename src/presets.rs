@@ -0,0 +1,125 @@
+use std::fs;
+use std::path::Path;
+
+/// A regex shipped with the crate, i.e., its decomposed regex and the regex definition files compiled from it.
+#[derive(Debug, Clone, Copy)]
+pub struct Preset {
+    /// The name given to `halo2-regex preset emit --name`.
+    pub name: &'static str,
+    /// A short description of the strings matched by the regex.
+    pub description: &'static str,
+    /// The json of [`DecomposedRegexConfig`](crate::vrm::DecomposedRegexConfig).
+    pub decomposed_regex: &'static str,
+    /// The text of [`AllstrRegexDef`](crate::AllstrRegexDef).
+    pub allstr: &'static str,
+    /// The texts of [`SubstrRegexDef`](crate::SubstrRegexDef) of the public parts in order.
+    pub substrs: &'static [&'static str],
+    /// A string matched by the regex, e.g., for the first proof.
+    pub example: &'static str,
+}
+
+/// The presets in the order of `halo2-regex preset list`.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "email_from",
+        description: "the email address in the from header of an email",
+        decomposed_regex: include_str!("../test_regexes/regex3_test.json"),
+        allstr: include_str!("../test_regexes/regex3_test_lookup.txt"),
+        substrs: &[include_str!("../test_regexes/substr3_test_lookup.txt")],
+        example: "dummy\r\nfrom:alice<alice@gmail.com>\r\n",
+    },
+    Preset {
+        name: "email_recipient",
+        description: "the lowercase name following \"email was meant for @\"",
+        decomposed_regex: include_str!("../test_regexes/regex1_test.json"),
+        allstr: include_str!("../test_regexes/regex1_test_lookup.txt"),
+        substrs: &[include_str!("../test_regexes/substr1_test_lookup.txt")],
+        example: "email was meant for @y.",
+    },
+    Preset {
+        name: "amount",
+        description: "the digits of \"amount=<digits>;\"",
+        decomposed_regex: include_str!("../test_regexes/regex4_test.json"),
+        allstr: include_str!("../test_regexes/regex4_test_lookup.txt"),
+        substrs: &[include_str!("../test_regexes/substr4_test_lookup.txt")],
+        example: "amount=1000;",
+    },
+];
+
+/// The file paths written by [`Preset::emit`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PresetFiles {
+    /// The file path of the decomposed regex json.
+    pub decomposed_regex_path: String,
+    /// The file path of the allstr regex definition.
+    pub allstr_file_path: String,
+    /// The file paths of the substr regex definitions, i.e., `substr0.txt`, `substr1.txt`, ... read by `--substrs-dir`.
+    pub substr_file_paths: Vec<String>,
+}
+
+/// Return the preset of `name`, or `None` if no preset has the name.
+pub fn find_preset(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+impl Preset {
+    /// Write the decomposed regex and the regex definition files of the preset to `out_dir`, which is created if it does not exist.
+    ///
+    /// # Arguments
+    /// * `out_dir` - a directory of the output files.
+    ///
+    /// # Return values
+    /// Return the paths of the written files.
+    pub fn emit(&self, out_dir: &str) -> Result<PresetFiles, std::io::Error> {
+        fs::create_dir_all(out_dir)?;
+        let write = |file_name: &str, contents: &str| {
+            let path = Path::new(out_dir).join(file_name);
+            fs::write(&path, contents)?;
+            Ok::<String, std::io::Error>(path.to_string_lossy().to_string())
+        };
+        let decomposed_regex_path = write(&format!("{}.json", self.name), self.decomposed_regex)?;
+        let allstr_file_path = write("allstr.txt", self.allstr)?;
+        let substr_file_paths = self
+            .substrs
+            .iter()
+            .enumerate()
+            .map(|(idx, substr)| write(&format!("substr{}.txt", idx), substr))
+            .collect::<Result<Vec<String>, std::io::Error>>()?;
+        Ok(PresetFiles {
+            decomposed_regex_path,
+            allstr_file_path,
+            substr_file_paths,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "compiler"))]
+mod test {
+    use super::*;
+    use crate::vrm::DecomposedRegexConfig;
+    use crate::{AllstrRegexDef, SubstrRegexDef};
+
+    #[test]
+    fn test_emit_presets() {
+        for preset in PRESETS.iter() {
+            let out_dir =
+                std::env::temp_dir().join(format!("halo2_regex_test_preset_{}", preset.name));
+            let files = preset.emit(out_dir.to_str().unwrap()).unwrap();
+            let decomposed: DecomposedRegexConfig =
+                serde_json::from_str(&fs::read_to_string(&files.decomposed_regex_path).unwrap())
+                    .unwrap();
+            let num_public_part = decomposed
+                .parts
+                .iter()
+                .filter(|part| part.is_public)
+                .count();
+            assert_eq!(files.substr_file_paths.len(), num_public_part);
+            AllstrRegexDef::read_from_text(&files.allstr_file_path);
+            for substr_file_path in files.substr_file_paths.iter() {
+                SubstrRegexDef::read_from_text(substr_file_path);
+            }
+        }
+        assert!(find_preset("email_from").is_some());
+        assert!(find_preset("unknown").is_none());
+    }
+}
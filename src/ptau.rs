@@ -0,0 +1,262 @@
+use halo2_base::halo2_proofs::arithmetic::g_to_lagrange;
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fq, G1Affine, G2Affine, G1};
+use halo2_base::halo2_proofs::halo2curves::serde::SerdeObject;
+use halo2_base::halo2_proofs::poly::commitment::Params;
+use halo2_base::halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_base::halo2_proofs::SerdeFormat;
+use halo2_base::utils::modulus;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+/// The magic bytes at the head of a `.ptau` file.
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+/// The id of the section of the header, i.e., the field size, the modulus and the power.
+const HEADER_SECTION: u32 = 1;
+/// The id of the section of `tau^i * G1`.
+const TAU_G1_SECTION: u32 = 2;
+/// The id of the section of `tau^i * G2`.
+const TAU_G2_SECTION: u32 = 3;
+/// The id of the section of the contributions to the ceremony.
+const CONTRIBUTIONS_SECTION: u32 = 7;
+/// The version of the `.ptau` format.
+const PTAU_VERSION: u32 = 1;
+/// The number of the bytes of an element of the base field.
+const FQ_BYTES: usize = 32;
+
+/// Error definitions of the conversion of `.ptau` files.
+#[derive(thiserror::Error, Debug)]
+pub enum PtauError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The file does not start with the magic bytes of .ptau")]
+    InvalidMagic,
+    #[error("The section {0} is missing")]
+    MissingSection(u32),
+    #[error("The base field is not that of BN254")]
+    UnsupportedField,
+    #[error("The requested k {k} is larger than the power {power} of the ceremony")]
+    TooLargeK { k: u32, power: u32 },
+    #[error("The {idx}-th point of the section {section} is not on the curve")]
+    InvalidPoint { section: u32, idx: usize },
+}
+
+/// Read the setup parameters from a `.ptau` file of the perpetual powers of tau ceremony, e.g., generated by snarkjs.
+///
+/// # Arguments
+/// * `ptau_path` - a file path of the `.ptau` file.
+/// * `k` - the degree of the number of rows of the output parameters, or `None` to use the power of the ceremony.
+///
+/// # Return values
+/// Return the setup parameters of 2^`k` rows, or [`PtauError`] if the file is malformed.
+pub fn read_ptau(ptau_path: &str, k: Option<u32>) -> Result<ParamsKZG<Bn256>, PtauError> {
+    let mut reader = BufReader::new(File::open(ptau_path)?);
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != PTAU_MAGIC {
+        return Err(PtauError::InvalidMagic);
+    }
+    let _version = read_u32(&mut reader)?;
+    let num_sections = read_u32(&mut reader)?;
+    // The offset of the data of each section.
+    let mut sections = HashMap::new();
+    for _ in 0..num_sections {
+        let section = read_u32(&mut reader)?;
+        let size = read_u64(&mut reader)?;
+        sections.insert(section, reader.stream_position()?);
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    let seek_section = |reader: &mut BufReader<File>, section: u32| {
+        let offset = sections
+            .get(&section)
+            .ok_or(PtauError::MissingSection(section))?;
+        reader.seek(SeekFrom::Start(*offset))?;
+        Ok::<(), PtauError>(())
+    };
+
+    seek_section(&mut reader, HEADER_SECTION)?;
+    let n8 = read_u32(&mut reader)? as usize;
+    let mut q = vec![0; n8];
+    reader.read_exact(&mut q)?;
+    if n8 != FQ_BYTES || q != modulus::<Fq>().to_bytes_le() {
+        return Err(PtauError::UnsupportedField);
+    }
+    let power = read_u32(&mut reader)?;
+    let k = k.unwrap_or(power);
+    if k > power {
+        return Err(PtauError::TooLargeK { k, power });
+    }
+
+    // The points are stored as the little-endian Montgomery forms of their coordinates, which are the raw bytes of halo2curves.
+    seek_section(&mut reader, TAU_G1_SECTION)?;
+    let mut g = vec![];
+    let mut buf = [0; 2 * FQ_BYTES];
+    for idx in 0..(1usize << k) {
+        reader.read_exact(&mut buf)?;
+        g.push(
+            G1Affine::from_raw_bytes(&buf).ok_or(PtauError::InvalidPoint {
+                section: TAU_G1_SECTION,
+                idx,
+            })?,
+        );
+    }
+    seek_section(&mut reader, TAU_G2_SECTION)?;
+    let mut g2s = vec![];
+    let mut buf = [0; 4 * FQ_BYTES];
+    for idx in 0..2 {
+        reader.read_exact(&mut buf)?;
+        g2s.push(
+            G2Affine::from_raw_bytes(&buf).ok_or(PtauError::InvalidPoint {
+                section: TAU_G2_SECTION,
+                idx,
+            })?,
+        );
+    }
+    let g_lagrange = g_to_lagrange::<G1Affine>(g.iter().map(|point| G1::from(*point)).collect(), k);
+
+    // `ParamsKZG` is constructed from its own serialization, i.e., `k`, `g`, `g_lagrange`, `g2` and `s_g2`.
+    let mut bytes = k.to_le_bytes().to_vec();
+    for point in g.iter().chain(g_lagrange.iter()) {
+        point.write_raw(&mut bytes)?;
+    }
+    for point in g2s.iter() {
+        point.write_raw(&mut bytes)?;
+    }
+    Ok(ParamsKZG::<Bn256>::read_custom(
+        &mut &bytes[..],
+        SerdeFormat::RawBytesUnchecked,
+    )?)
+}
+
+/// Write the setup parameters to a minimal `.ptau` file, which can be imported again by [`read_ptau`].
+///
+/// The setup parameters include only `tau^i * G1` for `i < 2^k` and `G2` and `tau * G2`, while the `.ptau` format of the power `k` also has the powers up to `2^(k+1) - 2`, the points of `alpha` and `beta` and, once prepared for the phase 2, their Lagrange forms in the sections 12 to 15.
+/// Hence, the output file contains only the sections 1, 2, 3 and 7 with no contribution, which neither the Groth16 nor the PLONK setup of snarkjs accepts, and which snarkjs cannot prepare without `alpha` and `beta`.
+///
+/// # Arguments
+/// * `params` - the setup parameters.
+/// * `ptau_path` - a file path of the output `.ptau` file.
+pub fn write_ptau(params: &ParamsKZG<Bn256>, ptau_path: &str) -> Result<(), PtauError> {
+    let mut header = vec![];
+    header.extend_from_slice(&(FQ_BYTES as u32).to_le_bytes());
+    header.extend_from_slice(&modulus::<Fq>().to_bytes_le());
+    // The power of the ceremony and that of the contributions.
+    header.extend_from_slice(&params.k().to_le_bytes());
+    header.extend_from_slice(&params.k().to_le_bytes());
+    let mut tau_g1 = vec![];
+    for point in params.get_g().iter() {
+        point.write_raw(&mut tau_g1)?;
+    }
+    let mut tau_g2 = vec![];
+    params.g2().write_raw(&mut tau_g2)?;
+    params.s_g2().write_raw(&mut tau_g2)?;
+    let contributions = 0u32.to_le_bytes().to_vec();
+    let sections = [
+        (HEADER_SECTION, header),
+        (TAU_G1_SECTION, tau_g1),
+        (TAU_G2_SECTION, tau_g2),
+        (CONTRIBUTIONS_SECTION, contributions),
+    ];
+
+    let mut writer = BufWriter::new(File::create(ptau_path)?);
+    writer.write_all(PTAU_MAGIC)?;
+    writer.write_all(&PTAU_VERSION.to_le_bytes())?;
+    writer.write_all(&(sections.len() as u32).to_le_bytes())?;
+    for (section, data) in sections.iter() {
+        writer.write_all(&section.to_le_bytes())?;
+        writer.write_all(&(data.len() as u64).to_le_bytes())?;
+        writer.write_all(data)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, std::io::Error> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, std::io::Error> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_base::halo2_proofs::halo2curves::group::prime::PrimeCurveAffine;
+    use halo2_base::halo2_proofs::poly::commitment::ParamsProver;
+    use rand::thread_rng;
+
+    /// Write a `.ptau` file of `power` in the layout of a prepared file of snarkjs, e.g., `powersOfTau28_hez_final_*.ptau`, whose points of `tau` are taken from `params` of the degree `power + 1`.
+    ///
+    /// The sections not read by [`read_ptau`] are filled with the generators, and those of snarkjs whose sizes are those of the points of `tau` are written with the same sizes.
+    fn write_snarkjs_layout(params: &ParamsKZG<Bn256>, power: u32, ptau_path: &str) {
+        fn raw<P: SerdeObject>(points: &[P]) -> Vec<u8> {
+            let mut bytes = vec![];
+            for point in points {
+                point.write_raw(&mut bytes).unwrap();
+            }
+            bytes
+        }
+        let num_g1 = (1usize << (power + 1)) - 1;
+        let num_g2 = 1usize << power;
+        let mut header = vec![];
+        header.extend_from_slice(&(FQ_BYTES as u32).to_le_bytes());
+        header.extend_from_slice(&modulus::<Fq>().to_bytes_le());
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&28u32.to_le_bytes());
+        let tau_g1 = raw(&params.get_g()[..num_g1]);
+        let mut tau_g2 = vec![params.g2(), params.s_g2()];
+        tau_g2.resize(num_g2, G2Affine::generator());
+        let sections: Vec<(u32, Vec<u8>)> = vec![
+            (HEADER_SECTION, header),
+            (TAU_G1_SECTION, tau_g1),
+            (TAU_G2_SECTION, raw(&tau_g2)),
+            (4, raw(&vec![G1Affine::generator(); num_g2])),
+            (5, raw(&vec![G1Affine::generator(); num_g2])),
+            (6, raw(&[G2Affine::generator()])),
+            (CONTRIBUTIONS_SECTION, 0u32.to_le_bytes().to_vec()),
+            (12, raw(&vec![G1Affine::generator(); num_g1])),
+            (13, raw(&vec![G2Affine::generator(); num_g1])),
+            (14, raw(&vec![G1Affine::generator(); num_g1])),
+            (15, raw(&vec![G1Affine::generator(); num_g1])),
+        ];
+        let mut writer = BufWriter::new(File::create(ptau_path).unwrap());
+        writer.write_all(PTAU_MAGIC).unwrap();
+        writer.write_all(&PTAU_VERSION.to_le_bytes()).unwrap();
+        writer
+            .write_all(&(sections.len() as u32).to_le_bytes())
+            .unwrap();
+        for (section, data) in sections.iter() {
+            writer.write_all(&section.to_le_bytes()).unwrap();
+            writer
+                .write_all(&(data.len() as u64).to_le_bytes())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+
+    #[test]
+    fn test_read_snarkjs_ptau() {
+        let params = ParamsKZG::<Bn256>::setup(4, thread_rng());
+        let ptau_path = std::env::temp_dir().join("halo2_regex_test_read_snarkjs.ptau");
+        let ptau_path = ptau_path.to_str().unwrap();
+        write_snarkjs_layout(&params, 3, ptau_path);
+
+        let imported = read_ptau(ptau_path, None).unwrap();
+        assert_eq!(imported.k(), 3);
+        assert_eq!(imported.get_g(), &params.get_g()[0..8]);
+        assert_eq!(imported.g2(), params.g2());
+        assert_eq!(imported.s_g2(), params.s_g2());
+        let downsized = read_ptau(ptau_path, Some(2)).unwrap();
+        assert_eq!(downsized.get_g(), &params.get_g()[0..4]);
+        assert!(matches!(
+            read_ptau(ptau_path, Some(4)),
+            Err(PtauError::TooLargeK { k: 4, power: 3 })
+        ));
+    }
+}
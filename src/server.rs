@@ -0,0 +1,455 @@
+use crate::helpers::{
+    check_key_metadata, config_regex_digests, config_regex_file_paths, decode_input,
+    derive_correct_substrs, parse_fr_hex, prove_with_keys, read_params, verify_with_keys, vk_hash,
+    ConfigError, InputEncoding, InvalidInputError, KeySerdeFormat, ProveError, RegexCircuit,
+    TargetSubstr, VerificationError,
+};
+use crate::keystore::KeyStore;
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fr};
+use halo2_base::halo2_proofs::plonk::keygen_vk;
+use halo2_base::halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use std::io::Read;
+use std::time::{Duration, SystemTime};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Error definitions of the HTTP proving service.
+#[derive(thiserror::Error, Debug)]
+pub enum ServeError {
+    #[error("The server could not listen on {addr}: {error}")]
+    Bind { addr: String, error: String },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Error definitions of reloading the regex definitions and the proving key of [`ProvingService`].
+#[derive(thiserror::Error, Debug)]
+pub enum ReloadError {
+    #[error("The regex definitions could not be read: {0}")]
+    InvalidRegexDefs(#[from] ConfigError),
+    #[error("The proving key could not be read: {0}")]
+    InvalidProvingKey(#[from] std::io::Error),
+    #[error("The proving key does not match the regex definitions: the verifying key hash is {found} but {expected} is generated")]
+    DigestMismatch { expected: String, found: String },
+    #[error("The verifying key could not be generated: {0:?}")]
+    Keygen(halo2_base::halo2_proofs::plonk::Error),
+}
+
+/// The body of `POST /prove`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct ProveRequest {
+    /// The string to verify.
+    pub string: String,
+    /// The encoding of `string`.
+    #[serde(default)]
+    pub encoding: InputEncoding,
+    /// The expected substrings and their positions, which are derived from `string` if empty and `is_success` is true.
+    #[serde(default)]
+    pub correct_substrs: Vec<(usize, String)>,
+    /// Whether the regex match is expected to pass.
+    #[serde(default)]
+    pub is_success: bool,
+}
+
+/// The response of `POST /prove`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProveResponse {
+    /// The hex string of the proof.
+    pub proof: String,
+    /// The hex strings of the instances.
+    pub instances: Vec<String>,
+    /// The substrings extracted from the string to verify.
+    pub substrs: Vec<TargetSubstr>,
+}
+
+/// A proof generated by [`ProvingService::prove_characters`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofOutput {
+    /// The bytes of the proof.
+    pub proof: Vec<u8>,
+    /// The instances of the proof.
+    pub instances: Vec<Fr>,
+    /// The substrings extracted from the bytes to verify.
+    pub substrs: Vec<TargetSubstr>,
+}
+
+/// The body of `POST /verify`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyRequest {
+    /// The hex string of the proof.
+    pub proof: String,
+    /// The hex strings of the instances, e.g., those of [`ProveResponse`].
+    pub instances: Vec<String>,
+}
+
+/// The response of `POST /verify`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyResponse {
+    /// Whether the proof is valid.
+    pub valid: bool,
+    /// The reason why the proof is invalid, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A proving service of the regex verification circuit configured by the setters of [`helpers`](crate::helpers), whose setup parameters and keys are loaded once at the start.
+///
+/// The requests are handled one by one, since the circuit is configured globally and each proving already uses all the threads.
+///
+/// If [`ProvingService::watch`] is called, the regex definition files and the proving key are polled and swapped in once they match each other.
+/// While the files on disk do not match, the provings are rejected, since the new regex definitions would be proven with the old key, but the proofs are still verified by the old key.
+pub struct ProvingService {
+    params: ParamsKZG<Bn256>,
+    store: KeyStore,
+    digest: Fr,
+    pk_path: String,
+    serde_format: KeySerdeFormat,
+    sanity_check: bool,
+    watch_interval: Option<Duration>,
+    modified: Vec<Option<SystemTime>>,
+    reload_error: Option<String>,
+}
+
+impl ProvingService {
+    /// Load the setup parameters and the proving key, whose verifying key is used by `POST /verify`.
+    ///
+    /// # Arguments
+    /// * `params_path` - a file path of the SRS parameters.
+    /// * `pk_path` - a file path of the proving key.
+    /// * `serde_format` - the serialization format of the proving key.
+    /// * `sanity_check` - whether the constraints are checked by `MockProver` before each proving, so that an unsatisfied input is rejected instead of returning an invalid proof.
+    ///
+    /// # Return values
+    /// Return the service, or the error if the proving key cannot be read or was generated from other regex definitions.
+    pub fn load(
+        params_path: &str,
+        pk_path: &str,
+        serde_format: KeySerdeFormat,
+        sanity_check: bool,
+    ) -> Result<Self, std::io::Error> {
+        check_key_metadata(pk_path)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let params = read_params::<Bn256>(params_path)?;
+        let digest = config_regex_digests()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?[0];
+        let mut store = KeyStore::new();
+        store.load_proving_key::<RegexCircuit<Fr>>(digest, pk_path, serde_format)?;
+        let mut service = Self {
+            params,
+            store,
+            digest,
+            pk_path: pk_path.to_string(),
+            serde_format,
+            sanity_check,
+            watch_interval: None,
+            modified: vec![],
+            reload_error: None,
+        };
+        service.modified = service.modified_times();
+        Ok(service)
+    }
+
+    /// Poll the regex definition files and the proving key every `interval` while serving, and reload them by [`ProvingService::reload_if_changed`].
+    pub fn watch(&mut self, interval: Duration) {
+        self.watch_interval = Some(interval);
+    }
+
+    /// Return the interval given to [`ProvingService::watch`], if any.
+    pub fn watch_interval(&self) -> Option<Duration> {
+        self.watch_interval
+    }
+
+    /// Return the error of the last reload if the files on disk do not match the loaded proving key, during which the provings are rejected.
+    pub fn reload_error(&self) -> Option<&str> {
+        self.reload_error.as_deref()
+    }
+
+    /// Reload the regex definitions and the proving key if any of their files is modified.
+    ///
+    /// The new proving key is swapped in only if its verifying key is identical to that generated from the new regex definitions.
+    /// Otherwise, e.g., when only some of the files are replaced yet, the old keys are kept and the provings are rejected until the next successful reload.
+    ///
+    /// # Return values
+    /// Return true iff the keys are swapped, or [`ReloadError`] if the modified files do not match.
+    pub fn reload_if_changed(&mut self) -> Result<bool, ReloadError> {
+        let modified = self.modified_times();
+        if modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+        match self.reload() {
+            Ok(()) => {
+                self.reload_error = None;
+                Ok(true)
+            }
+            Err(e) => {
+                self.reload_error = Some(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    fn reload(&mut self) -> Result<(), ReloadError> {
+        // The files can be written partially, which is rejected by the validation of the regex definitions before the keys read them.
+        let digest = config_regex_digests()?[0];
+        let mut store = KeyStore::new();
+        let pk =
+            store.load_proving_key::<RegexCircuit<Fr>>(digest, &self.pk_path, self.serde_format)?;
+        let vk =
+            keygen_vk(&self.params, &RegexCircuit::<Fr>::default()).map_err(ReloadError::Keygen)?;
+        let expected = vk_hash(&vk);
+        let found = vk_hash(pk.get_vk());
+        if expected != found {
+            return Err(ReloadError::DigestMismatch {
+                expected: format!("{:?}", expected),
+                found: format!("{:?}", found),
+            });
+        }
+        self.store = store;
+        self.digest = digest;
+        Ok(())
+    }
+
+    /// Return the modification times of the regex definition files and the proving key, or `None` for those that are missing.
+    fn modified_times(&self) -> Vec<Option<SystemTime>> {
+        config_regex_file_paths()
+            .iter()
+            .chain(std::iter::once(&self.pk_path))
+            .map(|path| {
+                std::fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Serve `POST /prove`, `POST /verify` and `GET /health` on `addr`, e.g., `0.0.0.0:8080`, until the process is terminated.
+    pub fn serve(&mut self, addr: &str) -> Result<(), ServeError> {
+        let server = Server::http(addr).map_err(|e| ServeError::Bind {
+            addr: addr.to_string(),
+            error: e.to_string(),
+        })?;
+        loop {
+            let mut request = match self.watch_interval {
+                Some(interval) => {
+                    let request = server.recv_timeout(interval)?;
+                    self.reload_and_log();
+                    match request {
+                        Some(request) => request,
+                        None => continue,
+                    }
+                }
+                None => server.recv()?,
+            };
+            let (status, body) = self.handle(&mut request);
+            tracing::info!(status, url = %request.url(), "responded");
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(
+                    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+                );
+            // A client that disconnected does not stop the service.
+            if let Err(e) = request.respond(response) {
+                tracing::warn!("failed to respond: {}", e);
+            }
+        }
+    }
+
+    /// Call [`ProvingService::reload_if_changed`] and log its result, since the service keeps running in either case.
+    pub fn reload_and_log(&mut self) {
+        match self.reload_if_changed() {
+            Ok(true) => tracing::info!("reloaded the regex definitions and the proving key"),
+            Ok(false) => {}
+            Err(e) => tracing::warn!("kept the previous keys: {}", e),
+        }
+    }
+
+    /// Return the status code and the json body of the response to `request`.
+    #[tracing::instrument(skip_all, fields(method = %request.method(), url = %request.url()))]
+    fn handle(&mut self, request: &mut Request) -> (u16, serde_json::Value) {
+        let path = request.url().split('?').next().unwrap_or("").to_string();
+        let method = request.method().clone();
+        let mut body = String::new();
+        if let Err(e) = request.as_reader().read_to_string(&mut body) {
+            return error_response(400, e);
+        }
+        match (method, path.as_str()) {
+            (Method::Get, "/health") => match self.reload_error() {
+                None => (200, serde_json::json!({ "status": "ok" })),
+                Some(error) => (
+                    200,
+                    serde_json::json!({ "status": "stale", "error": error }),
+                ),
+            },
+            (Method::Post, "/prove") if self.reload_error.is_some() => {
+                error_response(503, self.reload_error().unwrap())
+            }
+            (Method::Post, "/prove") => match serde_json::from_str(&body) {
+                Ok(body) => match self.prove(&body) {
+                    Ok(response) => (200, serde_json::to_value(response).unwrap()),
+                    Err(ProveError::InvalidBatchInput { error, .. }) => error_response(400, error),
+                    Err(e @ ProveError::InputTooLong(_)) => error_response(400, e),
+                    Err(e @ ProveError::InvalidInput(_)) => error_response(400, e),
+                    Err(e @ ProveError::SanityCheckFailed(_)) => error_response(422, e),
+                    Err(e) => error_response(500, e),
+                },
+                Err(e) => error_response(400, e),
+            },
+            (Method::Post, "/verify") => match serde_json::from_str(&body) {
+                Ok(body) => match self.verify(&body) {
+                    Ok(response) => (200, serde_json::to_value(response).unwrap()),
+                    Err(error) => error_response(400, error),
+                },
+                Err(e) => error_response(400, e),
+            },
+            (_, "/health") | (_, "/prove") | (_, "/verify") => {
+                error_response(405, "method not allowed")
+            }
+            _ => error_response(404, "not found"),
+        }
+    }
+
+    /// Generate a proof for `request`.
+    pub fn prove(&mut self, request: &ProveRequest) -> Result<ProveResponse, ProveError> {
+        let characters = decode_input(&request.string, request.encoding).ok_or_else(|| {
+            ProveError::InvalidBatchInput {
+                line: 0,
+                error: format!("the string is not valid {:?}", request.encoding),
+            }
+        })?;
+        let output = self.prove_characters(
+            characters,
+            request.correct_substrs.clone(),
+            request.is_success,
+        )?;
+        Ok(ProveResponse {
+            proof: to_hex(&output.proof),
+            instances: output
+                .instances
+                .iter()
+                .map(|instance| format!("{:?}", instance))
+                .collect(),
+            substrs: output.substrs,
+        })
+    }
+
+    /// Generate a proof for the raw bytes `characters`.
+    ///
+    /// # Arguments
+    /// * `characters` - the bytes to verify.
+    /// * `correct_substrs` - the expected substrings and their positions, which are derived from `characters` if empty and `is_success` is true.
+    /// * `is_success` - whether the regex match is expected to pass.
+    ///
+    /// # Return values
+    /// Return the proof, its instances and the extracted substrings, or [`ProveError::InvalidInput`] if `characters` does not match the regexes or `correct_substrs` are not its substrings, which is checked by [`RegexCircuit::check_inputs`] before proving.
+    pub fn prove_characters(
+        &mut self,
+        characters: Vec<u8>,
+        correct_substrs: Vec<(usize, String)>,
+        is_success: bool,
+    ) -> Result<ProofOutput, ProveError> {
+        let correct_substrs = if correct_substrs.is_empty() && is_success {
+            derive_correct_substrs(0, &characters).map_err(|error| {
+                InvalidInputError::Unmatched {
+                    input_idx: 0,
+                    error,
+                }
+            })?
+        } else {
+            correct_substrs
+        };
+        let circuit = RegexCircuit::<Fr> {
+            characters,
+            correct_substrs,
+            is_success,
+            ..Default::default()
+        };
+        // The synthesis would fail after the proving key is loaded, or panic in the worker threads of the prover.
+        circuit.check_inputs()?;
+        let instances = circuit.instances();
+        let pk = self.store.load_proving_key::<RegexCircuit<Fr>>(
+            self.digest,
+            &self.pk_path,
+            self.serde_format,
+        )?;
+        let (proof, _) = prove_with_keys(
+            &self.params,
+            &pk,
+            self.sanity_check,
+            None,
+            None,
+            None,
+            &circuit,
+            &instances,
+        )?;
+        Ok(ProofOutput {
+            proof,
+            instances,
+            substrs: circuit
+                .masked_outputs()
+                .first()
+                .map(|output| output.substrs())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Verify the proof of `request`.
+    ///
+    /// # Return values
+    /// Return whether the proof is valid and why it is not, or the error if the request is malformed or the proving key cannot be read again.
+    pub fn verify(&mut self, request: &VerifyRequest) -> Result<VerifyResponse, String> {
+        let proof = from_hex(&request.proof).ok_or("the proof is not a hex string")?;
+        let instances = request
+            .instances
+            .iter()
+            .map(|instance| parse_fr_hex(instance))
+            .collect::<Option<Vec<Fr>>>()
+            .ok_or("an instance is not the hex string of a field element")?;
+        match self.verify_proof(&proof, &instances) {
+            Ok(()) => Ok(VerifyResponse {
+                valid: true,
+                error: None,
+            }),
+            Err(VerificationError::Io(e)) => Err(e.to_string()),
+            Err(e) => Ok(VerifyResponse {
+                valid: false,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+
+    /// Verify `proof` with `instances` by the verifying key included in the proving key.
+    ///
+    /// # Return values
+    /// Return `Ok(())` iff the proof is valid, [`VerificationError::Io`] if the proving key cannot be read again, or the other [`VerificationError`] telling why the proof is invalid.
+    pub fn verify_proof(
+        &mut self,
+        proof: &[u8],
+        instances: &[Fr],
+    ) -> Result<(), VerificationError> {
+        let pk = self.store.load_proving_key::<RegexCircuit<Fr>>(
+            self.digest,
+            &self.pk_path,
+            self.serde_format,
+        )?;
+        verify_with_keys(&self.params, pk.get_vk(), proof, instances)
+    }
+}
+
+fn error_response(status: u16, error: impl std::fmt::Display) -> (u16, serde_json::Value) {
+    (status, serde_json::json!({ "error": error.to_string() }))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.trim_start_matches("0x");
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16).ok())
+        .collect()
+}
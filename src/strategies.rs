@@ -0,0 +1,239 @@
+use crate::vrm::{DecomposedRegexConfig, RegexPartConfig};
+use crate::{RegexDefs, DEFAULT_MAX_SUBSTR_ID};
+use proptest::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// The characters of the regexes generated by [`regex_str`] and [`decomposed_regex_config`].
+pub const ALPHABET: &[u8] = b"abc";
+
+/// A strategy of the regex strings over [`ALPHABET`] with concatenations, alternations, `+` and `*`, which have at most `max_chars` characters.
+///
+/// The position automaton of such a regex has at most `max_chars + 1` states, which bounds the DFA compiled from it for the regexes of this size in practice.
+///
+/// # Arguments
+/// * `max_chars` - the maximum number of the characters in the regex, which must be at least one.
+pub fn regex_str(max_chars: usize) -> BoxedStrategy<String> {
+    assert!(max_chars > 0, "a regex has at least one character.");
+    let leaf = prop::sample::select(ALPHABET).prop_map(|char| (char as char).to_string());
+    leaf.prop_recursive(4, max_chars as u32, 2, |inner| {
+        prop_oneof![
+            (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| lhs + &rhs),
+            (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| format!("({}|{})", lhs, rhs)),
+            inner.clone().prop_map(|inner| format!("({})+", inner)),
+            inner.prop_map(|inner| format!("({})*", inner)),
+        ]
+    })
+    .prop_filter("too many characters", move |regex| {
+        num_chars(regex) <= max_chars
+    })
+    .boxed()
+}
+
+/// A strategy of [`DecomposedRegexConfig`] of one public part between an optional private prefix and an optional private suffix, whose regexes are of [`regex_str`].
+///
+/// # Arguments
+/// * `max_states` - the budget of the states, i.e., the characters of all the parts are at most `max_states - 1`, which must be at least two.
+/// * `max_byte_size` - the maximum length of the input string and of each part.
+pub fn decomposed_regex_config(
+    max_states: usize,
+    max_byte_size: usize,
+) -> BoxedStrategy<DecomposedRegexConfig> {
+    assert!(max_states > 1, "a regex needs at least two states.");
+    let max_chars = max_states - 1;
+    (
+        prop::option::of(regex_str(max_chars)),
+        regex_str(max_chars),
+        prop::option::of(regex_str(max_chars)),
+    )
+        .prop_filter("too many states", move |(prefix, public, suffix)| {
+            [prefix.as_ref(), Some(public), suffix.as_ref()]
+                .iter()
+                .flatten()
+                .map(|regex| num_chars(regex))
+                .sum::<usize>()
+                <= max_chars
+        })
+        .prop_map(move |(prefix, public, suffix)| {
+            let part = |is_public: bool, regex_def: String| RegexPartConfig {
+                is_public,
+                regex_def,
+                max_size: max_byte_size,
+                solidity: None,
+            };
+            let mut parts = vec![];
+            if let Some(prefix) = prefix {
+                parts.push(part(false, prefix));
+            }
+            parts.push(part(true, public));
+            if let Some(suffix) = suffix {
+                parts.push(part(false, suffix));
+            }
+            DecomposedRegexConfig {
+                max_byte_size,
+                parts,
+                max_substr_id: DEFAULT_MAX_SUBSTR_ID,
+            }
+        })
+        .boxed()
+}
+
+/// A strategy of the strings of at most `max_len` bytes accepted by the DFA of `regex_defs`, which are built by a random walk over its transitions and shrink to shorter strings.
+///
+/// It panics if `regex_defs` accepts no string of at most `max_len` bytes.
+///
+/// # Arguments
+/// * `regex_defs` - the regex definitions, e.g., read from the files compiled from the regex of an integration.
+/// * `max_len` - the maximum length of the strings, e.g., the maximum length of the input string of the circuit.
+pub fn accepted_strings(regex_defs: &RegexDefs, max_len: usize) -> BoxedStrategy<Vec<u8>> {
+    let allstr = &regex_defs.allstr;
+    // The transitions from each state in a fixed order, since the iteration order of `state_lookup` varies.
+    let mut transitions = HashMap::<u64, Vec<(u8, u64)>>::new();
+    for ((char, cur_state), (_, next_state)) in allstr.state_lookup.iter() {
+        transitions
+            .entry(*cur_state)
+            .or_default()
+            .push((*char, *next_state));
+    }
+    for nexts in transitions.values_mut() {
+        nexts.sort();
+    }
+    // The minimum number of the characters from each state to the accepted state.
+    let mut distances = HashMap::<u64, usize>::new();
+    distances.insert(allstr.accepted_state_val, 0);
+    let mut queue = VecDeque::from([allstr.accepted_state_val]);
+    while let Some(state) = queue.pop_front() {
+        let distance = distances[&state];
+        for ((_, cur_state), (_, next_state)) in allstr.state_lookup.iter() {
+            if *next_state == state && !distances.contains_key(cur_state) {
+                distances.insert(*cur_state, distance + 1);
+                queue.push_back(*cur_state);
+            }
+        }
+    }
+    match distances.get(&allstr.first_state_val) {
+        Some(distance) if *distance <= max_len => {}
+        _ => panic!(
+            "the regex accepts no string of at most {} characters.",
+            max_len
+        ),
+    }
+    let first_state = allstr.first_state_val;
+    let accepted_state = allstr.accepted_state_val;
+    // Each choice picks stopping at the accepted state or one of the transitions that can still reach it, where the smaller choices, to which they shrink, stop earlier.
+    prop::collection::vec(any::<u32>(), max_len)
+        .prop_map(move |choices| {
+            let mut state = first_state;
+            let mut chars = vec![];
+            for (idx, choice) in choices.iter().enumerate() {
+                let remaining = max_len - idx - 1;
+                let nexts = transitions
+                    .get(&state)
+                    .map(|nexts| {
+                        nexts
+                            .iter()
+                            .filter(|(_, next)| {
+                                distances
+                                    .get(next)
+                                    .map_or(false, |distance| *distance <= remaining)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let can_stop = state == accepted_state;
+                let num_options = nexts.len() + can_stop as usize;
+                let option = *choice as usize % num_options;
+                if can_stop && option == 0 {
+                    break;
+                }
+                let (char, next) = nexts[option - can_stop as usize];
+                chars.push(*char);
+                state = *next;
+            }
+            chars
+        })
+        .boxed()
+}
+
+/// A strategy of the strings of at most `max_len` bytes rejected by the DFA of `regex_defs`, which are random bytes or the strings of [`accepted_strings`] with one byte replaced, inserted or removed.
+///
+/// Too many strings are filtered out if `regex_defs` accepts almost every string.
+///
+/// # Arguments
+/// * `regex_defs` - the regex definitions, e.g., read from the files compiled from the regex of an integration.
+/// * `max_len` - the maximum length of the strings, e.g., the maximum length of the input string of the circuit.
+pub fn rejected_strings(regex_defs: &RegexDefs, max_len: usize) -> BoxedStrategy<Vec<u8>> {
+    let mutated = (
+        accepted_strings(regex_defs, max_len),
+        any::<prop::sample::Index>(),
+        any::<u8>(),
+        0..3u8,
+    )
+        .prop_map(move |(mut chars, index, byte, mutation)| {
+            match (mutation, chars.is_empty()) {
+                (0, false) => {
+                    let idx = index.index(chars.len());
+                    chars[idx] = byte;
+                }
+                (1, false) => {
+                    chars.remove(index.index(chars.len()));
+                }
+                _ if chars.len() < max_len => {
+                    chars.insert(index.index(chars.len() + 1), byte);
+                }
+                _ => {
+                    chars.pop();
+                }
+            }
+            chars
+        });
+    let regex_defs = regex_defs.clone();
+    prop_oneof![mutated, prop::collection::vec(any::<u8>(), 0..=max_len)]
+        .prop_filter("accepted by the regex", move |chars| {
+            !regex_defs.trace(chars).is_accepted
+        })
+        .boxed()
+}
+
+fn num_chars(regex: &str) -> usize {
+    regex.bytes().filter(|char| ALPHABET.contains(char)).count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AllstrRegexDef, SubstrRegexDef};
+
+    fn regex_defs() -> RegexDefs {
+        RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                "./test_regexes/substr4_test_lookup.txt",
+            )],
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_accepted_strings(chars in accepted_strings(&regex_defs(), 16)) {
+            prop_assert!(chars.len() <= 16);
+            prop_assert!(regex_defs().trace(&chars).is_accepted);
+        }
+
+        #[test]
+        fn test_rejected_strings(chars in rejected_strings(&regex_defs(), 16)) {
+            prop_assert!(chars.len() <= 16);
+            prop_assert!(!regex_defs().trace(&chars).is_accepted);
+        }
+
+        #[test]
+        fn test_decomposed_regex_config(config in decomposed_regex_config(6, 32)) {
+            let num_chars = config
+                .parts
+                .iter()
+                .map(|part| num_chars(&part.regex_def))
+                .sum::<usize>();
+            prop_assert!(num_chars <= 5);
+            prop_assert_eq!(config.parts.iter().filter(|part| part.is_public).count(), 1);
+        }
+    }
+}
@@ -2,13 +2,15 @@ use std::{collections::HashMap, fmt::format, marker::PhantomData};
 
 use halo2_base::halo2_proofs::{
     circuit::{Layouter, Value},
-    plonk::{ConstraintSystem, Error, TableColumn},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
 };
-use halo2_base::utils::PrimeField;
+use halo2_base::{utils::PrimeField, Context};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+use crate::backend::{assign_advice, AssignedAdvice};
 use crate::defs::{AllstrRegexDef, RegexDefs, SubstrRegexDef};
+use crate::gadgets::poseidon::poseidon_hash;
 use crate::RegexVerifyConfig;
 
 /// Lookup tables used in [`RegexVerifyConfig`].
@@ -99,26 +101,10 @@ impl<F: PrimeField> RegexTableConfig<F> {
                         Ok::<(), Error>(())
                     };
                 assign_row(0, dummy_state, dummy_state, 0)?;
-                // [IMPORTANT] We must sort the keys of `state_lookup`. Otherwise, its order is variable, which derives different verifying key for each setup.
-                let mut lookups = regex_defs
-                    .allstr
-                    .state_lookup
-                    .iter()
-                    .collect::<Vec<(&(u8, u64), &(usize, u64))>>();
-                lookups.sort_by(|a, b| a.1 .0.cmp(&b.1 .0));
-                for ((char, cur_state), (idx, next_state)) in lookups.into_iter() {
-                    let mut substr_id = 0;
-                    for (j, substr_def) in regex_defs.substrs.iter().enumerate() {
-                        if substr_def
-                            .valid_state_transitions
-                            .get(&(*cur_state, *next_state))
-                            .is_some()
-                        {
-                            substr_id = substr_id_offset + j;
-                            break;
-                        }
-                    }
-                    assign_row(*char, *cur_state, *next_state, substr_id)?;
+                for (char, cur_state, next_state, substr_id) in
+                    transition_rows(regex_defs, substr_id_offset)
+                {
+                    assign_row(char, cur_state, next_state, substr_id)?;
                 }
                 Ok(())
             },
@@ -197,3 +183,724 @@ impl<F: PrimeField> RegexTableConfig<F> {
         Ok(substr_id_offset + regex_defs.substrs.len())
     }
 }
+
+/// The content of the lookup tables loaded by [`RegexTableConfig::load`] for a regex definition, by which the regex definitions with the identical tables share one [`RegexTableConfig`].
+///
+/// The rows are kept in the canonical order since the lookup arguments do not depend on the order of the rows.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegexTableKey {
+    dummy_state: u64,
+    transitions: Vec<(u8, u64, u64, usize)>,
+    endpoints: Vec<(usize, u64, u64)>,
+}
+
+impl RegexTableKey {
+    /// Construct the [`RegexTableKey`] of the tables loaded for `regex_defs` whose first substring id is `substr_id_offset`.
+    pub fn new(regex_defs: &RegexDefs, substr_id_offset: usize) -> Self {
+        let dummy_state = regex_defs.allstr.largest_state_val + 1;
+        let mut endpoints = endpoint_rows(regex_defs, substr_id_offset, dummy_state);
+        endpoints.sort();
+        Self {
+            dummy_state,
+            transitions: canonical_transition_rows(regex_defs, substr_id_offset),
+            endpoints,
+        }
+    }
+}
+
+/// A cache of [`RegexTableConfig`] configured for each distinct [`RegexTableKey`], which lets several [`RegexVerifyConfig`] share the fixed tables, e.g., of a common email-header pattern.
+///
+/// The shared tables must be loaded once by [`RegexVerifyConfig::load_shared`] with the same set of the loaded keys.
+#[derive(Debug, Clone)]
+pub struct RegexTableCache<F: PrimeField> {
+    tables: HashMap<RegexTableKey, RegexTableConfig<F>>,
+}
+
+impl<F: PrimeField> Default for RegexTableCache<F> {
+    fn default() -> Self {
+        Self {
+            tables: HashMap::new(),
+        }
+    }
+}
+
+impl<F: PrimeField> RegexTableCache<F> {
+    /// Construct a new empty [`RegexTableCache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the tables of `key`, which are configured only at the first call.
+    ///
+    /// # Arguments
+    /// * `meta` - a constrain system in which the table columns are defined.
+    /// * `key` - the content of the tables.
+    pub fn get_or_configure(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+        key: &RegexTableKey,
+    ) -> RegexTableConfig<F> {
+        self.tables
+            .entry(key.clone())
+            .or_insert_with(|| RegexTableConfig::configure(meta))
+            .clone()
+    }
+
+    /// Return the number of the distinct tables.
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Return true iff no table is configured.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+}
+
+/// Return the rows `(character, cur_state, next_state, substr_id)` of the transition table of `regex_defs` in the order they are loaded.
+pub(crate) fn transition_rows(
+    regex_defs: &RegexDefs,
+    substr_id_offset: usize,
+) -> Vec<(u8, u64, u64, usize)> {
+    // [IMPORTANT] We must sort the keys of `state_lookup`. Otherwise, its order is variable, which derives different verifying key for each setup.
+    let mut lookups = regex_defs
+        .allstr
+        .state_lookup
+        .iter()
+        .collect::<Vec<(&(u8, u64), &(usize, u64))>>();
+    lookups.sort_by(|a, b| a.1 .0.cmp(&b.1 .0));
+    lookups
+        .into_iter()
+        .map(|((char, cur_state), (_, next_state))| {
+            let mut substr_id = 0;
+            for (j, substr_def) in regex_defs.substrs.iter().enumerate() {
+                if substr_def
+                    .valid_state_transitions
+                    .get(&(*cur_state, *next_state))
+                    .is_some()
+                {
+                    substr_id = substr_id_offset + j;
+                    break;
+                }
+            }
+            (*char, *cur_state, *next_state, substr_id)
+        })
+        .collect()
+}
+
+/// Return the rows of [`transition_rows`] sorted by `(cur_state, character, next_state, substr_id)`, which do not depend on the order of the lines in the definition files.
+pub(crate) fn canonical_transition_rows(
+    regex_defs: &RegexDefs,
+    substr_id_offset: usize,
+) -> Vec<(u8, u64, u64, usize)> {
+    let mut rows = transition_rows(regex_defs, substr_id_offset);
+    rows.sort_by_key(|(char, cur_state, next_state, substr_id)| {
+        (*cur_state, *char, *next_state, *substr_id)
+    });
+    rows
+}
+
+/// Return the rows `(substr_id, start_state, end_state)` of the endpoint table of `regex_defs`, where either state is `dummy_state`.
+pub(crate) fn endpoint_rows(
+    regex_defs: &RegexDefs,
+    substr_id_offset: usize,
+    dummy_state: u64,
+) -> Vec<(usize, u64, u64)> {
+    let mut rows = vec![];
+    for (idx, substr_def) in regex_defs.substrs.iter().enumerate() {
+        let substr_id = substr_id_offset + idx;
+        for start in substr_def.start_states.iter() {
+            rows.push((substr_id, *start, dummy_state));
+        }
+        for end in substr_def.end_states.iter() {
+            rows.push((substr_id, dummy_state, *end));
+        }
+    }
+    rows
+}
+
+/// Size bounds of the lookup tables in the universal mode of [`RegexVerifyConfig`], where the tables are assigned as witnesses and only these bounds are fixed at the key generation.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniversalTableBounds {
+    /// The maximum number of the state transitions.
+    pub max_transitions: usize,
+    /// The maximum number of the start and end states of all substrings.
+    pub max_endpoints: usize,
+    /// The maximum number of the substr regex definitions of each regex definition, whose substring ids and maximum lengths are committed with the tables.
+    pub max_substrs: usize,
+    /// The dummy state id, which must be larger than every state id of the regexes.
+    pub dummy_state: u64,
+}
+
+/// A regex definition that does not fit in [`UniversalTableBounds`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum UniversalBoundsError {
+    #[error(
+        "The largest state id {largest_state} is not smaller than the dummy state {dummy_state}"
+    )]
+    TooLargeState {
+        largest_state: u64,
+        dummy_state: u64,
+    },
+    #[error("{num_transitions} state transitions exceed the maximum {max_transitions}")]
+    TooManyTransitions {
+        num_transitions: usize,
+        max_transitions: usize,
+    },
+    #[error("{num_endpoints} start and end states exceed the maximum {max_endpoints}")]
+    TooManyEndpoints {
+        num_endpoints: usize,
+        max_endpoints: usize,
+    },
+    #[error("{num_substrs} substr regex definitions exceed the maximum {max_substrs}")]
+    TooManySubstrs {
+        num_substrs: usize,
+        max_substrs: usize,
+    },
+}
+
+impl From<UniversalBoundsError> for Error {
+    fn from(error: UniversalBoundsError) -> Self {
+        tracing::error!("{}", error);
+        Error::Synthesis
+    }
+}
+
+/// Check that the lookup tables of `regex_defs` fit in `bounds`.
+///
+/// # Arguments
+/// * `regex_defs` - a regex definition that the input string must satisfy.
+/// * `bounds` - size bounds of the lookup tables.
+///
+/// # Return values
+/// Return [`UniversalBoundsError`] of the first bound that `regex_defs` exceeds.
+pub fn check_universal_bounds(
+    regex_defs: &RegexDefs,
+    bounds: &UniversalTableBounds,
+) -> Result<(), UniversalBoundsError> {
+    let largest_state = regex_defs.allstr.largest_state_val;
+    if largest_state >= bounds.dummy_state {
+        return Err(UniversalBoundsError::TooLargeState {
+            largest_state,
+            dummy_state: bounds.dummy_state,
+        });
+    }
+    let num_transitions = regex_defs.allstr.state_lookup.len();
+    if num_transitions > bounds.max_transitions {
+        return Err(UniversalBoundsError::TooManyTransitions {
+            num_transitions,
+            max_transitions: bounds.max_transitions,
+        });
+    }
+    let num_endpoints = endpoint_rows(regex_defs, 1, bounds.dummy_state).len();
+    if num_endpoints > bounds.max_endpoints {
+        return Err(UniversalBoundsError::TooManyEndpoints {
+            num_endpoints,
+            max_endpoints: bounds.max_endpoints,
+        });
+    }
+    let num_substrs = regex_defs.substrs.len();
+    if num_substrs > bounds.max_substrs {
+        return Err(UniversalBoundsError::TooManySubstrs {
+            num_substrs,
+            max_substrs: bounds.max_substrs,
+        });
+    }
+    Ok(())
+}
+
+/// Lookup tables assigned as witnesses, used in the universal mode of [`RegexVerifyConfig`].
+#[derive(Debug, Clone)]
+pub struct UniversalRegexTableConfig<F: PrimeField> {
+    pub(crate) characters: Column<Advice>,
+    pub(crate) cur_states: Column<Advice>,
+    pub(crate) next_states: Column<Advice>,
+    pub(crate) substr_ids: Column<Advice>,
+    pub(crate) endpoints_substr_ids: Column<Advice>,
+    pub(crate) start_states: Column<Advice>,
+    pub(crate) end_states: Column<Advice>,
+    pub(crate) q_transition: Selector,
+    pub(crate) q_endpoint: Selector,
+    pub(crate) bounds: UniversalTableBounds,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> UniversalRegexTableConfig<F> {
+    /// Configure a new [`UniversalRegexTableConfig`].
+    /// # Arguments
+    /// * `meta` - a constrain system in which contraints are defined.
+    /// * `bounds` - size bounds of the lookup tables.
+    pub fn configure(meta: &mut ConstraintSystem<F>, bounds: UniversalTableBounds) -> Self {
+        let mut advice_column = || {
+            let column = meta.advice_column();
+            meta.enable_equality(column);
+            column
+        };
+        let characters = advice_column();
+        let cur_states = advice_column();
+        let next_states = advice_column();
+        let substr_ids = advice_column();
+        let endpoints_substr_ids = advice_column();
+        let start_states = advice_column();
+        let end_states = advice_column();
+        let q_transition = meta.complex_selector();
+        let q_endpoint = meta.complex_selector();
+        Self {
+            characters,
+            cur_states,
+            next_states,
+            substr_ids,
+            endpoints_substr_ids,
+            start_states,
+            end_states,
+            q_transition,
+            q_endpoint,
+            bounds,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assign the lookup tables of `regex_defs` as witnesses.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `regex_defs` - a regex definition that the input string must satisfy.
+    /// * `substr_id_offset` - a `substr_id` of the first substring of `regex_defs`.
+    ///
+    /// # Return values
+    /// Return the assigned cells of the tables in the order of [`universal_table_values`] without its first two values and the substring limits, or [`Error::Synthesis`] if `regex_defs` exceeds the bounds.
+    pub fn assign<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        regex_defs: &RegexDefs,
+        substr_id_offset: usize,
+    ) -> Result<Vec<AssignedAdvice<'v, F>>, Error> {
+        let values = universal_table_values(regex_defs, substr_id_offset, &self.bounds)?;
+        let (transitions, rest) = values[2..].split_at(4 * self.bounds.max_transitions);
+        // The substring limits following the endpoints are not looked up, which the caller assigns.
+        let endpoints = &rest[..3 * self.bounds.max_endpoints];
+        let mut cells = vec![];
+        let transition_columns = [
+            self.characters,
+            self.cur_states,
+            self.next_states,
+            self.substr_ids,
+        ];
+        for (offset, row) in transitions.chunks(4).enumerate() {
+            self.q_transition.enable(&mut ctx.region, offset)?;
+            for (column, value) in transition_columns.iter().zip(row.iter()) {
+                cells.push(assign_advice(
+                    &mut ctx.region,
+                    || format!("transition table at {}", offset),
+                    *column,
+                    offset,
+                    Value::known(F::from(*value)),
+                )?);
+            }
+        }
+        let endpoint_columns = [
+            self.endpoints_substr_ids,
+            self.start_states,
+            self.end_states,
+        ];
+        for (offset, row) in endpoints.chunks(3).enumerate() {
+            self.q_endpoint.enable(&mut ctx.region, offset)?;
+            for (column, value) in endpoint_columns.iter().zip(row.iter()) {
+                cells.push(assign_advice(
+                    &mut ctx.region,
+                    || format!("endpoint table at {}", offset),
+                    *column,
+                    offset,
+                    Value::known(F::from(*value)),
+                )?);
+            }
+        }
+        Ok(cells)
+    }
+}
+
+/// Return the values committed in the universal mode of [`RegexVerifyConfig`] for `regex_defs`.
+///
+/// The values are the first state id, the accepted state id, the rows of the transition table padded with `(0, dummy_state, dummy_state, 0)` to `max_transitions` rows, the rows of the endpoint table padded with `(0, dummy_state, dummy_state)` to `max_endpoints` rows, and the rows of [`substr_limit_rows`].
+///
+/// # Arguments
+/// * `regex_defs` - a regex definition that the input string must satisfy.
+/// * `substr_id_offset` - a `substr_id` of the first substring of `regex_defs`.
+/// * `bounds` - size bounds of the lookup tables.
+///
+/// # Return values
+/// Return the values, or [`UniversalBoundsError`] if `regex_defs` does not fit in `bounds`.
+pub fn universal_table_values(
+    regex_defs: &RegexDefs,
+    substr_id_offset: usize,
+    bounds: &UniversalTableBounds,
+) -> Result<Vec<u64>, UniversalBoundsError> {
+    check_universal_bounds(regex_defs, bounds)?;
+    let dummy_state = bounds.dummy_state;
+    let mut transitions = transition_rows(regex_defs, substr_id_offset);
+    let mut endpoints = endpoint_rows(regex_defs, substr_id_offset, dummy_state);
+    transitions.resize(bounds.max_transitions, (0, dummy_state, dummy_state, 0));
+    endpoints.resize(bounds.max_endpoints, (0, dummy_state, dummy_state));
+
+    let mut values = vec![
+        regex_defs.allstr.first_state_val,
+        regex_defs.allstr.accepted_state_val,
+    ];
+    for (char, cur_state, next_state, substr_id) in transitions.into_iter() {
+        values.append(&mut vec![
+            char as u64,
+            cur_state,
+            next_state,
+            substr_id as u64,
+        ]);
+    }
+    for (substr_id, start_state, end_state) in endpoints.into_iter() {
+        values.append(&mut vec![substr_id as u64, start_state, end_state]);
+    }
+    for (substr_id, max_length) in substr_limit_rows(regex_defs, substr_id_offset, bounds) {
+        values.append(&mut vec![substr_id, max_length]);
+    }
+    Ok(values)
+}
+
+/// Return the rows `(substr_id, max_length)` of each substr regex definition of `regex_defs` padded with `(0, 0)` to `max_substrs` rows, which bound the runs of the substrings in the universal mode of [`RegexVerifyConfig`].
+///
+/// `regex_defs` must pass [`check_universal_bounds`].
+pub(crate) fn substr_limit_rows(
+    regex_defs: &RegexDefs,
+    substr_id_offset: usize,
+    bounds: &UniversalTableBounds,
+) -> Vec<(u64, u64)> {
+    let mut rows = regex_defs
+        .substrs
+        .iter()
+        .enumerate()
+        .map(|(idx, substr_def)| {
+            (
+                (substr_id_offset + idx) as u64,
+                substr_def.max_length as u64,
+            )
+        })
+        .collect::<Vec<(u64, u64)>>();
+    rows.resize(bounds.max_substrs, (0, 0));
+    rows
+}
+
+/// Compute the digest of the lookup tables output by the universal mode of [`RegexVerifyConfig`] off-circuit.
+///
+/// # Arguments
+/// * `regex_defs` - a vector of regex definitions applied to the input string.
+/// * `bounds` - size bounds of the lookup tables.
+///
+/// # Return values
+/// Return the Poseidon hash of [`universal_table_values`] of all regex definitions, or [`UniversalBoundsError`] if one of them does not fit in `bounds`.
+pub fn universal_table_digest<F: PrimeField>(
+    regex_defs: &[RegexDefs],
+    bounds: &UniversalTableBounds,
+) -> Result<F, UniversalBoundsError> {
+    let mut values = vec![];
+    let mut substr_id_offset = 1;
+    for defs in regex_defs.iter() {
+        values.append(&mut universal_table_values(defs, substr_id_offset, bounds)?);
+        substr_id_offset += defs.substrs.len();
+    }
+    let values = values
+        .into_iter()
+        .map(|value| F::from(value))
+        .collect::<Vec<F>>();
+    Ok(poseidon_hash(&values))
+}
+
+/// Compute the canonical digest of the regex definitions, which commits to the lookup tables loaded by [`RegexVerifyConfig::load`] independently of the order of the lines in the definition files.
+///
+/// # Arguments
+/// * `regex_defs` - a vector of regex definitions applied to the input string.
+///
+/// # Return values
+/// Return the Poseidon hash of the first, accepted and largest state ids and the sorted rows of the lookup tables of all regex definitions, each table preceded by its number of rows.
+pub fn regex_defs_digest<F: PrimeField>(regex_defs: &[RegexDefs]) -> F {
+    let mut values = vec![];
+    let mut substr_id_offset = 1;
+    for defs in regex_defs.iter() {
+        let dummy_state = defs.allstr.largest_state_val + 1;
+        let transitions = canonical_transition_rows(defs, substr_id_offset);
+        let mut endpoints = endpoint_rows(defs, substr_id_offset, dummy_state);
+        endpoints.sort();
+        values.append(&mut vec![
+            defs.allstr.first_state_val,
+            defs.allstr.accepted_state_val,
+            defs.allstr.largest_state_val,
+            transitions.len() as u64,
+        ]);
+        for (char, cur_state, next_state, substr_id) in transitions.into_iter() {
+            values.append(&mut vec![
+                char as u64,
+                cur_state,
+                next_state,
+                substr_id as u64,
+            ]);
+        }
+        values.push(endpoints.len() as u64);
+        for (substr_id, start_state, end_state) in endpoints.into_iter() {
+            values.append(&mut vec![substr_id as u64, start_state, end_state]);
+        }
+        substr_id_offset += defs.substrs.len();
+    }
+    let values = values
+        .into_iter()
+        .map(|value| F::from(value))
+        .collect::<Vec<F>>();
+    poseidon_hash(&values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::poseidon::PoseidonConfig;
+    use crate::new_region_context;
+    use halo2_base::halo2_proofs::{
+        circuit::{Cell, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::{Bn256, Fr, G1Affine},
+        plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Instance},
+        poly::commitment::ParamsProver,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        poly::kzg::multiopen::{ProverGWC, VerifierGWC},
+        poly::kzg::strategy::SingleStrategy,
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use halo2_base::{gates::flex_gate::FlexGateConfig, SKIP_FIRST_PASS};
+    use rand::rngs::OsRng;
+
+    const MAX_STRING_LEN: usize = 64;
+    const K: usize = 13;
+    const BOUNDS: UniversalTableBounds = UniversalTableBounds {
+        max_transitions: 128,
+        max_endpoints: 16,
+        max_substrs: 4,
+        dummy_state: 128,
+    };
+
+    fn regex4_defs() -> Vec<RegexDefs> {
+        vec![RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                "./test_regexes/substr4_test_lookup.txt",
+            )],
+        }]
+    }
+
+    fn regex5_defs() -> Vec<RegexDefs> {
+        vec![RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex5_test_lookup.txt"),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                "./test_regexes/substr5_test_lookup.txt",
+            )],
+        }]
+    }
+
+    #[derive(Default, Clone, Debug)]
+    struct TestUniversalCircuit<F: PrimeField> {
+        characters: Vec<u8>,
+        // The regex definitions are witnesses, which are not known at the configuration.
+        regex_defs: Vec<RegexDefs>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestUniversalCircuit<F> {
+        const NUM_ADVICE: usize = 4;
+        const NUM_FIXED: usize = 1;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestUniversalCircuit<F> {
+        type Config = (RegexVerifyConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                characters: vec![],
+                regex_defs: vec![RegexDefs::default()],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let gate = FlexGateConfig::<F>::configure(
+                meta,
+                halo2_base::gates::flex_gate::GateStrategy::Vertical,
+                &[Self::NUM_ADVICE],
+                Self::NUM_FIXED,
+                0,
+                K,
+            );
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                RegexVerifyConfig::configure_universal(meta, MAX_STRING_LEN, gate, 1, BOUNDS),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let gate = config.gate().clone();
+            let mut digest_cell = None;
+            layouter.assign_region(
+                || "regex",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = new_region_context(region, &gate);
+                    let ctx = &mut aux;
+                    let witness =
+                        config.derive_universal_witness(&self.regex_defs, &self.characters)?;
+                    let result = config.match_substrs_with_witness(ctx, &witness)?;
+                    let poseidon = PoseidonConfig::new(gate.clone());
+                    let digest = poseidon.hash(ctx, &result.table_values);
+                    digest_cell = Some(digest.cell());
+                    Ok(())
+                },
+            )?;
+            let digest_cell: Cell = digest_cell.ok_or(Error::Synthesis)?;
+            layouter.constrain_instance(digest_cell, instance, 0)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_regex_defs_digest() {
+        let digest = regex_defs_digest::<Fr>(&regex4_defs());
+        assert_eq!(digest, regex_defs_digest::<Fr>(&regex4_defs()));
+        let mut other_defs = regex4_defs();
+        other_defs[0]
+            .allstr
+            .state_lookup
+            .retain(|(char, _), _| *char != b'9');
+        assert_ne!(digest, regex_defs_digest::<Fr>(&other_defs));
+    }
+
+    #[test]
+    fn test_universal_table_values_len() {
+        let values = universal_table_values(&regex4_defs()[0], 1, &BOUNDS).unwrap();
+        assert_eq!(
+            values.len(),
+            2 + 4 * BOUNDS.max_transitions + 3 * BOUNDS.max_endpoints + 2 * BOUNDS.max_substrs
+        );
+    }
+
+    #[test]
+    fn test_universal_bounds_exceeded() {
+        let bounds = UniversalTableBounds {
+            max_transitions: 8,
+            ..BOUNDS
+        };
+        assert!(matches!(
+            universal_table_values(&regex4_defs()[0], 1, &bounds),
+            Err(UniversalBoundsError::TooManyTransitions {
+                max_transitions: 8,
+                ..
+            })
+        ));
+        let bounds = UniversalTableBounds {
+            dummy_state: 9,
+            ..BOUNDS
+        };
+        assert_eq!(
+            universal_table_digest::<Fr>(&regex4_defs(), &bounds),
+            Err(UniversalBoundsError::TooLargeState {
+                largest_state: 9,
+                dummy_state: 9,
+            })
+        );
+    }
+
+    #[test]
+    fn test_universal_table_digest() {
+        let circuit = TestUniversalCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            regex_defs: regex4_defs(),
+            _marker: PhantomData,
+        };
+        let digest = universal_table_digest::<Fr>(&regex4_defs(), &BOUNDS).unwrap();
+        let prover = MockProver::run(K as u32, &circuit, vec![vec![digest]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The regex that does not accept the digit 9 has a different digest.
+        let mut other_defs = regex4_defs();
+        other_defs[0]
+            .allstr
+            .state_lookup
+            .retain(|(char, _), _| *char != b'9');
+        let other_digest = universal_table_digest::<Fr>(&other_defs, &BOUNDS).unwrap();
+        let prover = MockProver::run(K as u32, &circuit, vec![vec![other_digest]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_universal_run_length_limit() {
+        // The substring "1000" is longer than the committed limit of 3 characters.
+        let mut defs = regex4_defs();
+        defs[0].substrs[0].max_length = 3;
+        let circuit = TestUniversalCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            regex_defs: defs.clone(),
+            _marker: PhantomData,
+        };
+        let digest = universal_table_digest::<Fr>(&defs, &BOUNDS).unwrap();
+        let prover = MockProver::run(K as u32, &circuit, vec![vec![digest]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_universal_keys_serve_two_regexes() {
+        let params = ParamsKZG::<Bn256>::setup(K as u32, OsRng);
+        let emp_circuit = TestUniversalCircuit::<Fr>::default().without_witnesses();
+        let vk = keygen_vk(&params, &emp_circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &emp_circuit).unwrap();
+        // The regexes differ in their transitions and in the maximum lengths of their substrings.
+        for (characters, regex_defs) in [
+            (b"amount=1000;".to_vec(), regex4_defs()),
+            (b"abba".to_vec(), regex5_defs()),
+        ] {
+            let digest = universal_table_digest::<Fr>(&regex_defs, &BOUNDS).unwrap();
+            let circuit = TestUniversalCircuit::<Fr> {
+                characters,
+                regex_defs,
+                _marker: PhantomData,
+            };
+            let proof = {
+                let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+                create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
+                    &params,
+                    &pk,
+                    &[circuit],
+                    &[&[&[digest]]],
+                    OsRng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            };
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            let verifier_params = params.verifier_params();
+            let strategy = SingleStrategy::new(&verifier_params);
+            verify_proof::<_, VerifierGWC<_>, _, _, _>(
+                verifier_params,
+                &vk,
+                strategy,
+                &[&[&[digest]]],
+                &mut transcript,
+            )
+            .unwrap();
+        }
+    }
+}
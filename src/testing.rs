@@ -0,0 +1,326 @@
+use crate::helpers::MaskedOutput;
+use crate::{new_region_context, RegexDefs, RegexVerifyConfig};
+use halo2_base::gates::flex_gate::{FlexGateConfig, GateStrategy};
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    dev::MockProver,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2_base::{utils::PrimeField, AssignedValue, SKIP_FIRST_PASS};
+use lazy_static::lazy_static;
+use std::marker::PhantomData;
+use std::sync::{Mutex, PoisonError};
+
+/// The smallest degree of the number of rows with which [`assert_regex_match`] and [`assert_regex_no_match`] run [`MockProver`].
+pub const MIN_K: u32 = 10;
+/// The largest degree of the number of rows with which [`assert_regex_match`] and [`assert_regex_no_match`] run [`MockProver`].
+pub const MAX_K: u32 = 18;
+/// An upper bound of the advice cells of [`FlexGateConfig`] assigned for each character and each regex or substring definition.
+const CELLS_PER_CHAR: usize = 256;
+
+lazy_static! {
+    static ref testCircuitParams: Mutex<TestCircuitParams> =
+        Mutex::new(TestCircuitParams::default());
+    static ref testOutput: Mutex<Option<MaskedOutput>> = Mutex::new(None);
+    // The circuit is configured by the global parameters, so the runs of the helpers do not overlap.
+    static ref testRunLock: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, Default)]
+struct TestCircuitParams {
+    k: usize,
+    num_advice: usize,
+    max_chars_size: usize,
+    regex_defs: Vec<RegexDefs>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TestCircuit<F: PrimeField> {
+    characters: Vec<u8>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> Circuit<F> for TestCircuit<F> {
+    type Config = RegexVerifyConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            characters: vec![],
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let params = testCircuitParams.lock().unwrap().clone();
+        let gate = FlexGateConfig::<F>::configure(
+            meta,
+            GateStrategy::Vertical,
+            &[params.num_advice],
+            1,
+            0,
+            params.k,
+        );
+        RegexVerifyConfig::configure(meta, params.max_chars_size, gate, params.regex_defs)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let gate = config.gate().clone();
+        layouter.assign_region(
+            || "regex",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = new_region_context(region, &gate);
+                let ctx = &mut aux;
+                let result = config.match_substrs(ctx, &self.characters)?;
+                let to_u64 = |assigned: &AssignedValue<F>| {
+                    let mut val = 0;
+                    assigned.value().map(|v| val = v.get_lower_128() as u64);
+                    val
+                };
+                *testOutput.lock().unwrap() = Some(MaskedOutput {
+                    masked_characters: result
+                        .masked_characters
+                        .iter()
+                        .map(|char| to_u64(char) as u8)
+                        .collect(),
+                    substr_ids: result
+                        .all_substr_ids
+                        .iter()
+                        .map(|id| to_u64(id) as usize)
+                        .collect(),
+                });
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Assert that the circuit of `defs` accepts `input` and extracts `expected_parts` from it, with [`MockProver`] at the smallest `k` fitting them.
+///
+/// The maximum length of the input string is `input.len() + 1`, i.e., the circuit checks one padded character.
+/// It panics with the off-circuit trace of the failing character, the unsatisfied constraints or the mismatched substrings.
+///
+/// # Arguments
+/// * `defs` - the regex definitions applied to the input string.
+/// * `input` - the characters of the input string.
+/// * `expected_parts` - the start position and the string of each substring in the order of the substring ids over all `defs`.
+pub fn assert_regex_match(defs: &[RegexDefs], input: &[u8], expected_parts: &[(usize, &str)]) {
+    if let Err(message) = check_regex_match(defs, input, expected_parts) {
+        panic!("{}", message);
+    }
+}
+
+/// Assert that the circuit of `defs` rejects `input`, i.e., [`MockProver`] reports an unsatisfied constraint with the witnesses derived from `input`.
+///
+/// An input string with a character that has no transition from its state is rejected without [`MockProver`], since no witness is derived for it.
+///
+/// # Arguments
+/// * `defs` - the regex definitions applied to the input string.
+/// * `input` - the characters of the input string.
+pub fn assert_regex_no_match(defs: &[RegexDefs], input: &[u8]) {
+    if let Err(message) = check_regex_no_match(defs, input) {
+        panic!("{}", message);
+    }
+}
+
+fn check_regex_match(
+    defs: &[RegexDefs],
+    input: &[u8],
+    expected_parts: &[(usize, &str)],
+) -> Result<(), String> {
+    let input_str = String::from_utf8_lossy(input);
+    let num_substrs = defs.iter().map(|defs| defs.substrs.len()).sum::<usize>();
+    if expected_parts.len() != num_substrs {
+        return Err(format!(
+            "{} expected parts are given for {} substring definitions.",
+            expected_parts.len(),
+            num_substrs
+        ));
+    }
+    for (d_idx, defs) in defs.iter().enumerate() {
+        let trace = defs.trace(input);
+        if let Some(position) = trace.failed_position {
+            return Err(format!(
+                "{:?} is rejected by regex #{}: the character at index {} (byte {:#04x}) has no transition from state {}.",
+                input_str, d_idx, position, input[position], trace.states[position]
+            ));
+        }
+        if !trace.is_accepted {
+            return Err(format!(
+                "{:?} is rejected by regex #{}: it ends in state {} instead of the accepted state {}.",
+                input_str,
+                d_idx,
+                trace.states[input.len()],
+                defs.allstr.accepted_state_val
+            ));
+        }
+    }
+    let (verify_result, output) = run_mock_prover(defs, input)?;
+    if let Err(failures) = verify_result {
+        return Err(format!(
+            "the circuit rejects {:?}:\n{}",
+            input_str,
+            failures.join("\n")
+        ));
+    }
+    let mut extracted_parts = vec![(0, String::new()); num_substrs];
+    for substr in output.substrs().into_iter() {
+        extracted_parts[substr.substr_id - 1] = (substr.pos, substr.string);
+    }
+    let mismatches = expected_parts
+        .iter()
+        .zip(extracted_parts.iter())
+        .enumerate()
+        .filter(|(_, ((expected_pos, expected_str), (pos, string)))| {
+            expected_pos != pos || *expected_str != string.as_str()
+        })
+        .map(|(idx, (expected, extracted))| {
+            format!(
+                "substring #{}: expected {:?} but extracted {:?}",
+                idx + 1,
+                expected,
+                extracted
+            )
+        })
+        .collect::<Vec<String>>();
+    if !mismatches.is_empty() {
+        return Err(format!(
+            "the circuit extracts the wrong substrings from {:?}:\n{}",
+            input_str,
+            mismatches.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+fn check_regex_no_match(defs: &[RegexDefs], input: &[u8]) -> Result<(), String> {
+    if defs
+        .iter()
+        .any(|defs| defs.trace(input).failed_position.is_some())
+    {
+        return Ok(());
+    }
+    let (verify_result, output) = run_mock_prover(defs, input)?;
+    if verify_result.is_ok() {
+        let extracted_parts = output
+            .substrs()
+            .into_iter()
+            .map(|substr| {
+                format!(
+                    "substring #{}: {:?}",
+                    substr.substr_id,
+                    (substr.pos, substr.string)
+                )
+            })
+            .collect::<Vec<String>>();
+        return Err(format!(
+            "the circuit accepts {:?}, from which it extracts:\n{}",
+            String::from_utf8_lossy(input),
+            extracted_parts.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+/// Run [`MockProver`] over `input` from [`MIN_K`] until the rows are enough, and return the failures and the masked output.
+fn run_mock_prover(
+    defs: &[RegexDefs],
+    input: &[u8],
+) -> Result<(Result<(), Vec<String>>, MaskedOutput), String> {
+    let _guard = testRunLock.lock().unwrap_or_else(PoisonError::into_inner);
+    let max_chars_size = input.len() + 1;
+    let num_cells = CELLS_PER_CHAR
+        * (max_chars_size + 1)
+        * defs
+            .iter()
+            .map(|defs| 1 + defs.substrs.len())
+            .sum::<usize>();
+    let circuit = TestCircuit::<Fr> {
+        characters: input.to_vec(),
+        _marker: PhantomData,
+    };
+    for k in MIN_K..=MAX_K {
+        *testCircuitParams.lock().unwrap() = TestCircuitParams {
+            k: k as usize,
+            // At least half of the rows are usable.
+            num_advice: (num_cells >> (k - 1)) + 1,
+            max_chars_size,
+            regex_defs: defs.to_vec(),
+        };
+        *testOutput.lock().unwrap() = None;
+        match MockProver::run(k, &circuit, vec![]) {
+            Ok(prover) => {
+                let verify_result = prover.verify().map_err(|failures| {
+                    failures
+                        .iter()
+                        .map(|failure| failure.to_string())
+                        .collect::<Vec<String>>()
+                });
+                let output = testOutput.lock().unwrap().take().unwrap_or_default();
+                return Ok((verify_result, output));
+            }
+            Err(Error::NotEnoughRowsAvailable { .. }) => continue,
+            Err(e) => {
+                return Err(format!(
+                    "the circuit fails to synthesize {:?}: {:?}",
+                    String::from_utf8_lossy(input),
+                    e
+                ))
+            }
+        }
+    }
+    Err(format!(
+        "the circuit of {:?} does not fit in 2^{} rows.",
+        String::from_utf8_lossy(input),
+        MAX_K
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AllstrRegexDef, SubstrRegexDef};
+
+    fn regex_defs() -> Vec<RegexDefs> {
+        vec![RegexDefs {
+            allstr: AllstrRegexDef::read_from_text("./test_regexes/regex4_test_lookup.txt"),
+            substrs: vec![SubstrRegexDef::read_from_text(
+                "./test_regexes/substr4_test_lookup.txt",
+            )],
+        }]
+    }
+
+    #[test]
+    fn test_assert_regex_match() {
+        assert_regex_match(&regex_defs(), b"amount=1000;", &[(7, "1000")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "substring #1: expected (7, \"100\") but extracted (7, \"1000\")")]
+    fn test_assert_regex_match_wrong_part() {
+        assert_regex_match(&regex_defs(), b"amount=1000;", &[(7, "100")]);
+    }
+
+    #[test]
+    fn test_assert_regex_no_match() {
+        assert_regex_no_match(&regex_defs(), b"amount=10a0;");
+        assert_regex_no_match(&regex_defs(), b"amount=1000");
+    }
+
+    #[test]
+    #[should_panic(expected = "the circuit accepts")]
+    fn test_assert_regex_no_match_accepted() {
+        assert_regex_no_match(&regex_defs(), b"amount=1000;");
+    }
+}
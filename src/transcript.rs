@@ -0,0 +1,232 @@
+use crate::helpers::{
+    open_key_file, read_params, read_proof_metadata, read_verifying_key, write_proof_metadata,
+    KeySerdeFormat, ProofMetadata, ProveError, VerificationError,
+};
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_base::halo2_proofs::plonk::{
+    create_proof, verify_proof, Circuit, ProvingKey, VerifyingKey,
+};
+use halo2_base::halo2_proofs::poly::commitment::{Params, ParamsProver};
+use halo2_base::halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_base::halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_base::halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_base::halo2_proofs::transcript::TranscriptWriterBuffer;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use snark_verifier::loader::native::NativeLoader;
+use snark_verifier::system::halo2::{compile, Config};
+use snark_verifier_sdk::halo2::{PoseidonTranscript, POSEIDON_SPEC};
+use snark_verifier_sdk::Snark;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+/// The name of the transcript of the proofs generated by [`prove_poseidon`] recorded in [`ProofMetadata`].
+pub const POSEIDON_TRANSCRIPT: &str = "poseidon";
+
+/// Generate a proof whose transcript is Poseidon instead of Blake2b and whose multiopen scheme is SHPLONK, which is cheap to verify inside another halo2 circuit, e.g., the aggregation circuit of snark-verifier-sdk.
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `pk` - the proving key, which is the same as that of the proofs with the Blake2b transcript.
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+/// * `circuit` - a regex verification circuit.
+/// * `instances` - the instances of the circuit.
+///
+/// # Return values
+/// Return the bytes of the proof.
+pub fn prove_poseidon_with_keys<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    seed: Option<[u8; 32]>,
+    circuit: C,
+    instances: &[Fr],
+) -> Result<Vec<u8>, ProveError> {
+    let rng = ChaCha20Rng::from_seed(seed.unwrap_or_else(|| thread_rng().gen()));
+    let mut transcript =
+        PoseidonTranscript::<NativeLoader, Vec<u8>>::from_spec(vec![], POSEIDON_SPEC.clone());
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[instances]],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Generate a proof with the Poseidon transcript and write it with its metadata, as [`prove`](crate::helpers::prove) does.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `pk_path` - a file path of the proving key.
+/// * `serde_format` - the serialization format of the proving key.
+/// * `seed` - the seed of the RNG used to blind the proof, or `None` to use a random seed.
+/// * `proof_path` - a file path of the output proof.
+/// * `circuit` - a regex verification circuit.
+/// * `instances` - the instances of the circuit.
+pub fn prove_poseidon<C: Circuit<Fr>>(
+    params_path: &str,
+    pk_path: &str,
+    serde_format: KeySerdeFormat,
+    seed: Option<[u8; 32]>,
+    proof_path: &str,
+    circuit: C,
+    instances: &[Fr],
+) -> Result<(), ProveError> {
+    let params = read_params::<Bn256>(params_path)?;
+    let pk = {
+        let mut reader = open_key_file(pk_path)?;
+        ProvingKey::<G1Affine>::read::<_, C>(&mut reader, serde_format.into())
+            .map_err(ProveError::InvalidProvingKey)?
+    };
+    let proof = prove_poseidon_with_keys(&params, &pk, seed, circuit, instances)?;
+    let mut writer = BufWriter::new(File::create(proof_path)?);
+    writer.write_all(&proof)?;
+    writer.flush()?;
+    write_proof_metadata(proof_path, &poseidon_metadata(params.k(), instances))?;
+    Ok(())
+}
+
+/// Verify a proof generated by [`prove_poseidon_with_keys`] natively.
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `vk` - the verifying key.
+/// * `proof` - the bytes of the proof.
+/// * `instances` - the instances of the circuit.
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not.
+pub fn verify_poseidon_with_keys(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Fr],
+) -> Result<(), VerificationError> {
+    let mut transcript = PoseidonTranscript::<NativeLoader, &[u8]>::new(proof);
+    let verifier_params = params.verifier_params();
+    let strategy = SingleStrategy::new(verifier_params);
+    verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+        verifier_params,
+        vk,
+        strategy,
+        &[&[instances]],
+        &mut transcript,
+    )
+    .map_err(|e| {
+        tracing::debug!("the proof is rejected: {:?}", e);
+        VerificationError::from(e)
+    })
+}
+
+/// Verify a proof written by [`prove_poseidon`], as [`verify`](crate::helpers::verify) does.
+///
+/// # Arguments
+/// * `params_path` - a file path of the SRS parameters.
+/// * `vk_path` - a file path of the verifying key.
+/// * `serde_format` - the serialization format of the verifying key.
+/// * `proof_path` - a file path of the proof.
+/// * `instances` - the instances of the circuit `C`.
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not, e.g., [`VerificationError::MetadataMismatch`] if the proof was generated with another transcript.
+pub fn verify_poseidon<C: Circuit<Fr>>(
+    params_path: &str,
+    vk_path: &str,
+    serde_format: KeySerdeFormat,
+    proof_path: &str,
+    instances: &[Fr],
+) -> Result<(), VerificationError> {
+    let params = read_params::<Bn256>(params_path)?;
+    check_poseidon_metadata(proof_path, params.k(), instances)?;
+    let vk = read_verifying_key::<Bn256, C>(vk_path, serde_format)?;
+    let proof = fs::read(proof_path)?;
+    verify_poseidon_with_keys(&params, &vk, &proof, instances)
+}
+
+/// Return the proof generated by [`prove_poseidon_with_keys`] as a [`Snark`] of snark-verifier, which is verified inside another halo2 circuit by the SHPLONK accumulation scheme, e.g., accumulated by `PublicAggregationCircuit` of snark-verifier-sdk as the snarks of `gen_snark_shplonk`.
+///
+/// # Arguments
+/// * `params` - the SRS parameters.
+/// * `vk` - the verifying key.
+/// * `proof` - the bytes of the proof.
+/// * `instances` - the instances of the circuit.
+pub fn poseidon_snark(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: Vec<u8>,
+    instances: &[Fr],
+) -> Snark {
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg().with_num_instance(vec![instances.len()]),
+    );
+    Snark::new(protocol, vec![instances.to_vec()], proof)
+}
+
+/// Return [`ProofMetadata`] of a proof with the Poseidon transcript.
+fn poseidon_metadata(k: u32, instances: &[Fr]) -> ProofMetadata {
+    let mut metadata = ProofMetadata::new(k, instances);
+    metadata.multiopen = "shplonk".to_string();
+    metadata.transcript = POSEIDON_TRANSCRIPT.to_string();
+    metadata
+}
+
+/// Check the metadata of the proof at `proof_path` as [`check_proof_metadata`](crate::helpers::check_proof_metadata) does, except that the transcript is Poseidon.
+fn check_poseidon_metadata(
+    proof_path: &str,
+    k: u32,
+    instances: &[Fr],
+) -> Result<(), VerificationError> {
+    let metadata = match read_proof_metadata(proof_path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(()),
+    };
+    let mismatches = poseidon_metadata(k, instances).mismatches(&metadata);
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(VerificationError::MetadataMismatch(mismatches))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helpers::{
+        set_config_k, set_config_max_chars_size, set_config_params, RegexCircuit,
+    };
+    use halo2_base::halo2_proofs::plonk::{keygen_pk, keygen_vk};
+
+    #[test]
+    fn test_prove_and_verify_poseidon() {
+        set_config_params(
+            "./test_regexes/regex4_test_lookup.txt".to_string(),
+            "./test_regexes/substr4_test_lookup.txt".to_string(),
+        );
+        set_config_max_chars_size(64);
+        set_config_k(13);
+        let circuit = RegexCircuit::<Fr> {
+            characters: b"amount=1000;".to_vec(),
+            correct_substrs: vec![(7, "1000".to_string())],
+            is_success: true,
+            ..Default::default()
+        };
+        let instances = circuit.instances();
+        let params = ParamsKZG::<Bn256>::setup(13, thread_rng());
+        let vk = keygen_vk(&params, &circuit.without_witnesses()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit.without_witnesses()).unwrap();
+        let proof =
+            prove_poseidon_with_keys(&params, &pk, Some([0; 32]), circuit, &instances).unwrap();
+        assert!(verify_poseidon_with_keys(&params, &vk, &proof, &instances).is_ok());
+        let mut wrong_instances = instances.clone();
+        wrong_instances[0] += Fr::from(1);
+        assert!(verify_poseidon_with_keys(&params, &vk, &proof, &wrong_instances).is_err());
+        assert_eq!(
+            poseidon_snark(&params, &vk, proof, &instances).instances,
+            vec![instances]
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use crate::helpers::{
+    check_num_instances, check_table_rows, decode_instances, read_params_from,
+    read_verifying_key_from, verify_with_keys, KeySerdeFormat, RegexCircuit, VerificationError,
+};
+use halo2_base::halo2_proofs::halo2curves::bn256::{Bn256, Fr};
+use halo2_base::halo2_proofs::poly::commitment::Params;
+
+/// Verify a proof of the regex verification circuit configured by the current parameters from the bytes of the setup parameters, the verifying key, the proof and the instances.
+///
+/// No file is read if the regex definitions of the configured input strings are registered by [`set_config_regex_text`](crate::helpers::set_config_regex_text), which is necessary where no file system is available, e.g., on wasm32.
+///
+/// # Arguments
+/// * `params` - the bytes of the SRS parameters.
+/// * `vk` - the bytes of the verifying key.
+/// * `serde_format` - the serialization format of the verifying key.
+/// * `proof` - the bytes of the proof.
+/// * `instances` - the bytes of the instances in either format of [`InstancesFormat`](crate::helpers::InstancesFormat).
+///
+/// # Return values
+/// Return `Ok(())` iff the proof is valid, or [`VerificationError`] telling why it is not, e.g., [`VerificationError::Config`] if the configuration is invalid or does not fit in the rows of the parameters.
+pub fn verify_proof_bytes(
+    params: &[u8],
+    vk: &[u8],
+    serde_format: KeySerdeFormat,
+    proof: &[u8],
+    instances: &[u8],
+) -> Result<(), VerificationError> {
+    let params = read_params_from::<Bn256, _>(&mut &params[..])?;
+    check_table_rows(params.k() as usize)?;
+    let vk = read_verifying_key_from::<Bn256, RegexCircuit<Fr>, _>(&mut &vk[..], serde_format)?;
+    let instances = decode_instances(instances)?;
+    check_num_instances(&instances)?;
+    verify_with_keys(&params, &vk, proof, &instances)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verify_proof_bytes_invalid_params() {
+        let result = verify_proof_bytes(&[0; 3], &[], KeySerdeFormat::Processed, &[], b"[]");
+        assert!(matches!(result, Err(VerificationError::Io(_))));
+    }
+}
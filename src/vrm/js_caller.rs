@@ -13,6 +13,8 @@ pub enum JsCallerError {
     InvalidNodeValue(Value),
     #[error("No accepted state")]
     NoAcceptedState,
+    #[error("Edge key {0:?} is not one character")]
+    InvalidEdgeKey(String),
     #[error(transparent)]
     JsError(#[from] JsError),
     #[error(transparent)]
@@ -115,7 +117,9 @@ pub fn add_graph_nodes(
             let key_list: Vec<String> = serde_json::from_str(&key)?;
             let mut key_str = String::new();
             for key_char in key_list.iter() {
-                assert!(key_char.len() == 1);
+                if key_char.len() != 1 {
+                    return Err(JsCallerError::InvalidEdgeKey(key_char.clone()));
+                }
                 key_str += key_char;
             }
             graph.add_edge(NodeIndex::from(next_node), NodeIndex::from(i), key_str);
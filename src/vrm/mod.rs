@@ -2,7 +2,7 @@ use std::{collections::HashMap, fs::File};
 pub mod circom;
 pub mod js_caller;
 use crate::vrm::js_caller::*;
-use crate::{AllstrRegexDef, SubstrRegexDef};
+use crate::{AllstrRegexDef, SubstrRegexDef, DEFAULT_MAX_SUBSTR_ID};
 use fancy_regex::Regex;
 use itertools::Itertools;
 use petgraph::prelude::*;
@@ -25,6 +25,19 @@ pub enum VrmError {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     RegexError(#[from] fancy_regex::Error),
+    #[error("The public parts {first} and {second} tag the same transitions {transitions:?}, whose substring id is ambiguous")]
+    OverlappingSubstrs {
+        first: usize,
+        second: usize,
+        transitions: Vec<(usize, usize)>,
+    },
+    #[error("The {num_public_parts} public parts exceed the maximum substring id {max_substr_id}")]
+    TooManyPublicParts {
+        num_public_parts: usize,
+        max_substr_id: usize,
+    },
+    #[error("The string {0:?} of a path to the accepted state is not matched by the regexes of the parts")]
+    UnmatchedPath(String),
 }
 
 /// A configuration of decomposed regexes.
@@ -34,6 +47,13 @@ pub struct DecomposedRegexConfig {
     pub max_byte_size: usize,
     /// A vector of decomposed regexes.
     pub parts: Vec<RegexPartConfig>,
+    /// The maximum substring id, i.e., the maximum number of the public parts, which is [`DEFAULT_MAX_SUBSTR_ID`] by default.
+    #[serde(default = "default_max_substr_id")]
+    pub max_substr_id: usize,
+}
+
+fn default_max_substr_id() -> usize {
+    DEFAULT_MAX_SUBSTR_ID
 }
 
 /// Decomposed regex part.
@@ -331,6 +351,21 @@ impl DecomposedRegexConfig {
         //     solidity: None,
         // };
         let part_configs = &self.parts;
+        // A match-only regex has no substring to extract, so its paths are not searched.
+        let num_public_parts = part_configs
+            .iter()
+            .filter(|config| config.is_public)
+            .count();
+        if num_public_parts == 0 {
+            return Ok((vec![], vec![], vec![]));
+        }
+        // The substring ids are assigned to the public parts from 1 in order.
+        if num_public_parts > self.max_substr_id {
+            return Err(VrmError::TooManyPublicParts {
+                num_public_parts,
+                max_substr_id: self.max_substr_id,
+            });
+        }
 
         let mut graph = Graph::<bool, String, Directed, usize>::with_capacity(0, 0);
         let max_state = get_max_state(dfa_val)?;
@@ -351,12 +386,18 @@ impl DecomposedRegexConfig {
         let mut pathes = Vec::<Vec<NodeIndex<usize>>>::new();
         let mut stack = Vec::<(NodeIndex<usize>, Vec<NodeIndex<usize>>)>::new();
         stack.push((accepted_state_index, vec![accepted_state_index]));
+        // If the first state is accepted, the empty string is matched by the trivial path of no transition.
+        if accepted_state == 0 {
+            pathes.push(vec![accepted_state_index]);
+        }
         let mut self_nodes = HashSet::new();
         let mut self_nodes_char = HashMap::new();
         for state in 0..=max_state {
             let node = NodeIndex::from(state);
-            if let Some(edge) = graph.find_edge(node, node) {
-                let str = graph.edge_weight(edge).unwrap().as_str();
+            if let Some(str) = graph
+                .find_edge(node, node)
+                .and_then(|edge| graph.edge_weight(edge))
+            {
                 let bytes = str.as_bytes();
                 // println!("byte {} {}", bytes[0], "^".as_bytes()[0]);
                 // let char = if bytes[0] == b"^"[0] || bytes[0] == b"$"[0] {
@@ -369,20 +410,20 @@ impl DecomposedRegexConfig {
             }
         }
 
-        while stack.len() != 0 {
-            let (node, path) = stack.pop().unwrap();
+        while let Some((node, path)) = stack.pop() {
             let mut parents = graph.neighbors(node).detach();
             while let Some((edge, parent)) = parents.next(&graph) {
                 if parent.index() == node.index() {
                     self_nodes.insert(node.index());
-                    graph.remove_edge(edge).unwrap();
+                    graph.remove_edge(edge);
+                    continue;
+                }
+                // The path ends at the first state even if it is the accepted state already in the path.
+                if parent.index() == 0 {
+                    pathes.push(path.to_vec());
                     continue;
                 }
                 if !path.contains(&parent) {
-                    if parent.index() == 0 {
-                        pathes.push(path.to_vec());
-                        continue;
-                    }
                     stack.push((parent, vec![path.clone(), vec![parent]].concat()));
                 }
             }
@@ -412,19 +453,19 @@ impl DecomposedRegexConfig {
             .map(|_| (HashSet::<usize>::new(), HashSet::<usize>::new()))
             .collect_vec();
         for path in pathes.iter_mut() {
-            let n = path.len();
-            path.append(&mut vec![NodeIndex::from(0)]);
-            let edges = (0..n)
+            if path.len() > 1 || path[0].index() != 0 {
+                path.append(&mut vec![NodeIndex::from(0)]);
+            }
+            let n = path.len() - 1;
+            let string_vec = (0..n)
                 .map(|idx| {
                     graph
                         .find_edge(path[idx], path[idx + 1])
+                        .and_then(|edge| graph.edge_weight(edge))
+                        .map(|str| str.as_str())
                         .ok_or(VrmError::NoEdge(path[idx], path[idx + 1]))
                 })
-                .collect::<Result<Vec<EdgeIndex<usize>>, VrmError>>()?;
-            let string_vec = edges
-                .iter()
-                .map(|edge| graph.edge_weight(*edge).unwrap().as_str())
-                .collect::<Vec<&str>>();
+                .collect::<Result<Vec<&str>, VrmError>>()?;
             let path_states = path
                 .into_iter()
                 .rev()
@@ -487,7 +528,7 @@ impl DecomposedRegexConfig {
                     let part_regex = &part_regexes[part_index];
                     let byte = self_nodes_char[&path_states[path_states.len() - 1]];
                     let substr = substr + &(byte as char).to_string();
-                    if part_regex.is_match(&substr).unwrap() {
+                    if part_regex.is_match(&substr)? {
                         defs.insert((
                             path_states[path_states.len() - 1],
                             path_states[path_states.len() - 1],
@@ -529,6 +570,22 @@ impl DecomposedRegexConfig {
         //         writer.write_fmt(format_args!("{} {}\n", cur, next))?;
         //     }
         // }
+        for (first, first_defs) in substr_defs_array.iter().enumerate() {
+            for (second, second_defs) in substr_defs_array.iter().enumerate().skip(first + 1) {
+                let transitions = first_defs
+                    .intersection(second_defs)
+                    .copied()
+                    .sorted()
+                    .collect_vec();
+                if !transitions.is_empty() {
+                    return Err(VrmError::OverlappingSubstrs {
+                        first: public_config_indexes[first],
+                        second: public_config_indexes[second],
+                        transitions,
+                    });
+                }
+            }
+        }
         Ok((
             substr_defs_array,
             substr_endpoints_array,
@@ -544,6 +601,13 @@ impl DecomposedRegexConfig {
         public_config_indexes: &[usize],
     ) -> Result<Vec<(Vec<usize>, String)>, VrmError> {
         debug_assert_eq!(path_states.len(), path_strs.len() + 1);
+        // Every public part matches the empty string of the trivial path.
+        if path_strs.is_empty() {
+            return Ok(public_config_indexes
+                .iter()
+                .map(|_| (path_states.to_vec(), String::new()))
+                .collect_vec());
+        }
         let mut concat_str = String::new();
         for str in path_strs.into_iter() {
             let first_chars = str.as_bytes();
@@ -574,14 +638,16 @@ impl DecomposedRegexConfig {
                 //         .as_bytes(),
                 //     regex.find(&concat_str).unwrap().unwrap().end()
                 // );
-                let found = regex.find(&concat_str).unwrap().unwrap();
+                let found = regex
+                    .find(&concat_str)?
+                    .ok_or_else(|| VrmError::UnmatchedPath(concat_str.clone()))?;
                 if found.start() == found.end() {
-                    found.end() + 1
+                    Ok(found.end() + 1)
                 } else {
-                    found.end()
+                    Ok(found.end())
                 }
             })
-            .collect_vec();
+            .collect::<Result<Vec<usize>, VrmError>>()?;
         let mut substr_results = vec![];
         for index in public_config_indexes.iter() {
             let start = if *index == 0 {
@@ -599,3 +665,50 @@ impl DecomposedRegexConfig {
         Ok(substr_results)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "circuit")]
+    #[test]
+    fn test_match_only() {
+        let regex_decomposed: DecomposedRegexConfig = serde_json::from_str(
+            r#"{"max_byte_size": 64, "parts": [{"is_public": false, "regex_def": "amount=(0|1|2|3|4|5|6|7|8|9)+;", "max_size": 64}]}"#,
+        )
+        .unwrap();
+        let allstr_file_path = std::env::temp_dir().join("halo2_regex_test_match_only_allstr.txt");
+        regex_decomposed
+            .gen_regex_files(&allstr_file_path, &[])
+            .unwrap();
+        let regex_defs = vec![crate::RegexDefs {
+            allstr: AllstrRegexDef::read_from_text(allstr_file_path.to_str().unwrap()),
+            substrs: vec![],
+        }];
+        crate::testing::assert_regex_match(&regex_defs, b"amount=1000;", &[]);
+        crate::testing::assert_regex_no_match(&regex_defs, b"amount=1000");
+    }
+
+    #[test]
+    fn test_too_many_public_parts() {
+        let regex_decomposed: DecomposedRegexConfig = serde_json::from_str(
+            r#"{"max_byte_size": 64, "max_substr_id": 1, "parts": [{"is_public": true, "regex_def": "a", "max_size": 1}, {"is_public": true, "regex_def": "b", "max_size": 1}]}"#,
+        )
+        .unwrap();
+        let dir = std::env::temp_dir();
+        let result = regex_decomposed.gen_regex_files(
+            &dir.join("halo2_regex_test_too_many_allstr.txt"),
+            &[
+                dir.join("halo2_regex_test_too_many_substr0.txt"),
+                dir.join("halo2_regex_test_too_many_substr1.txt"),
+            ],
+        );
+        match result {
+            Err(VrmError::TooManyPublicParts {
+                num_public_parts,
+                max_substr_id,
+            }) => assert_eq!((num_public_parts, max_substr_id), (2, 1)),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}